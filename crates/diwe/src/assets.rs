@@ -0,0 +1,131 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use serde::Serialize;
+
+use liwe::graph::assets::{asset_references, AssetReference};
+use liwe::graph::Graph;
+use liwe::model::config::Format;
+use liwe::model::Key;
+
+use crate::fs::asset_paths;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetStatus {
+    /// On disk and referenced by at least one document.
+    Referenced,
+    /// Referenced but not on disk.
+    Missing,
+    /// On disk but referenced by no document.
+    Unreferenced,
+}
+
+impl AssetStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AssetStatus::Referenced => "referenced",
+            AssetStatus::Missing => "missing",
+            AssetStatus::Unreferenced => "unreferenced",
+        }
+    }
+}
+
+/// One asset path with its status and the documents that reference it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetEntry {
+    pub path: String,
+    pub status: AssetStatus,
+    pub referenced_by: Vec<Key>,
+}
+
+/// The asset files in the library set against the asset references in the graph.
+#[derive(Debug, Clone, Default)]
+pub struct AssetInventory {
+    /// Library-relative paths of the asset files, sorted.
+    pub files: Vec<String>,
+    pub references: Vec<AssetReference>,
+    present: HashSet<String>,
+}
+
+impl AssetInventory {
+    /// Inventory of the library at `library_path`. A referenced file counts as present when it
+    /// exists on disk, even inside a hidden or ignored directory the file listing skips.
+    pub fn scan(graph: &Graph, library_path: &Path, format: Format) -> Self {
+        let mut inventory = AssetInventory::new(graph, asset_paths(library_path, format));
+        let found: Vec<String> = inventory
+            .references
+            .iter()
+            .filter(|reference| library_path.join(&reference.path).is_file())
+            .map(|reference| reference.path.clone())
+            .collect();
+        inventory.present.extend(found);
+        inventory
+    }
+
+    /// Inventory where `files` are the only asset files that exist.
+    pub fn new(graph: &Graph, mut files: Vec<String>) -> Self {
+        files.sort();
+        files.dedup();
+        AssetInventory {
+            present: files.iter().cloned().collect(),
+            references: asset_references(graph),
+            files,
+        }
+    }
+
+    pub fn is_missing(&self, path: &str) -> bool {
+        !self.present.contains(path)
+    }
+
+    /// References to files that do not exist, ordered by key, then path.
+    pub fn missing(&self) -> Vec<&AssetReference> {
+        self.references
+            .iter()
+            .filter(|reference| self.is_missing(&reference.path))
+            .collect()
+    }
+
+    /// Asset files no document references, sorted.
+    pub fn unreferenced(&self) -> Vec<&String> {
+        let referenced: HashSet<&str> = self
+            .references
+            .iter()
+            .map(|reference| reference.path.as_str())
+            .collect();
+        self.files
+            .iter()
+            .filter(|file| !referenced.contains(file.as_str()))
+            .collect()
+    }
+
+    /// Every asset path, whether on disk or only referenced, in path order.
+    pub fn entries(&self) -> Vec<AssetEntry> {
+        let mut by_path: BTreeMap<&str, Vec<Key>> = self
+            .files
+            .iter()
+            .map(|file| (file.as_str(), Vec::new()))
+            .collect();
+        for reference in &self.references {
+            let keys = by_path.entry(reference.path.as_str()).or_default();
+            if !keys.contains(&reference.key) {
+                keys.push(reference.key.clone());
+            }
+        }
+        by_path
+            .into_iter()
+            .map(|(path, referenced_by)| AssetEntry {
+                status: if self.is_missing(path) {
+                    AssetStatus::Missing
+                } else if referenced_by.is_empty() {
+                    AssetStatus::Unreferenced
+                } else {
+                    AssetStatus::Referenced
+                },
+                path: path.to_string(),
+                referenced_by,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use liwe::graph::Graph;
+use liwe::model::Key;
+use liwe::operations::{delete as op_delete, Changes, OperationError};
+use liwe::query::{parse_operation, strict_guard_violations, Operation, OperationKind, Outcome};
+
+use crate::search::Language;
+use crate::search_query::{build_index, execute};
+
+/// One entry of a batch document.
+#[derive(Debug, Clone)]
+pub enum BatchStep {
+    Create { key: Key, content: String },
+    Query(Operation),
+}
+
+impl BatchStep {
+    fn name(&self) -> &'static str {
+        match self {
+            BatchStep::Create { .. } => "create",
+            BatchStep::Query(Operation::Find(_)) => "find",
+            BatchStep::Query(Operation::Count(_)) => "count",
+            BatchStep::Query(Operation::Update(_)) => "update",
+            BatchStep::Query(Operation::Delete(_)) => "delete",
+        }
+    }
+}
+
+/// Result of one step, in batch order.
+#[derive(Debug, Serialize)]
+#[serde(tag = "operation", rename_all = "lowercase")]
+pub enum StepResult {
+    Find { documents: Vec<Mapping> },
+    Count { count: usize },
+    Create { key: String },
+    Update { changed: Vec<String> },
+    Delete { removed: Vec<String> },
+}
+
+#[derive(Debug)]
+pub struct BatchOutcome {
+    pub steps: Vec<StepResult>,
+    /// Net effect of the whole batch against the graph it ran on.
+    pub changes: Changes,
+}
+
+/// Parse a batch document: an `operations` list whose entries are single-key mappings naming the
+/// kind (`find`, `count`, `update`, `delete`, `create`) over that operation's document.
+pub fn parse_batch(yaml: &str) -> Result<Vec<BatchStep>, String> {
+    let root: Value = serde_yaml::from_str(yaml).map_err(|e| e.to_string())?;
+    let operations = root
+        .as_mapping()
+        .filter(|m| m.len() == 1)
+        .and_then(|m| m.get("operations"))
+        .and_then(Value::as_sequence)
+        .ok_or("batch document must be a mapping with a single 'operations' list")?;
+    operations
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| parse_step(entry).map_err(|e| format!("operation {}: {}", i + 1, e)))
+        .collect()
+}
+
+fn parse_step(entry: &Value) -> Result<BatchStep, String> {
+    let (kind, body) = entry
+        .as_mapping()
+        .filter(|m| m.len() == 1)
+        .and_then(|m| m.iter().next())
+        .and_then(|(k, v)| k.as_str().map(|k| (k, v)))
+        .ok_or("expected a single-key mapping such as `update: { ... }`")?;
+    let kind = match kind {
+        "find" => OperationKind::Find,
+        "count" => OperationKind::Count,
+        "update" => OperationKind::Update,
+        "delete" => OperationKind::Delete,
+        "create" => return parse_create(body),
+        other => {
+            return Err(format!(
+                "unknown operation '{}' (expected find, count, update, delete, create)",
+                other
+            ))
+        }
+    };
+    let document = serde_yaml::to_string(body).map_err(|e| e.to_string())?;
+    parse_operation(&document, kind)
+        .map(BatchStep::Query)
+        .map_err(|e| e.to_string())
+}
+
+fn parse_create(body: &Value) -> Result<BatchStep, String> {
+    let field = |name: &str| {
+        body.get(name)
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or(format!("create requires a string '{}'", name))
+    };
+    Ok(BatchStep::Create {
+        key: Key::name(&field("key")?),
+        content: field("content")?,
+    })
+}
+
+/// Missing `expect` guards across all steps, each prefixed with its position in the batch.
+pub fn batch_guard_violations(steps: &[BatchStep]) -> Vec<String> {
+    steps
+        .iter()
+        .enumerate()
+        .flat_map(|(i, step)| match step {
+            BatchStep::Query(op) => strict_guard_violations(op)
+                .into_iter()
+                .map(|missing| format!("operation {} {}", i + 1, missing))
+                .collect(),
+            BatchStep::Create { .. } => Vec::new(),
+        })
+        .collect()
+}
+
+/// Run `steps` in order against a working copy of `graph`; each step sees the effects of the
+/// steps before it. `graph` itself is left untouched. The first failing step aborts the batch.
+pub fn run_batch(
+    graph: &Graph,
+    steps: &[BatchStep],
+    language: &Language,
+) -> Result<BatchOutcome, String> {
+    let mut working = graph.clone();
+    let mut ledger = Ledger::default();
+    let mut results = Vec::with_capacity(steps.len());
+    for (i, step) in steps.iter().enumerate() {
+        let result = run_step(&mut working, &mut ledger, step, language)
+            .map_err(|e| format!("operation {} ({}): {}", i + 1, step.name(), e))?;
+        results.push(result);
+    }
+    Ok(BatchOutcome {
+        steps: results,
+        changes: ledger.into_changes(graph),
+    })
+}
+
+fn run_step(
+    working: &mut Graph,
+    ledger: &mut Ledger,
+    step: &BatchStep,
+    language: &Language,
+) -> Result<StepResult, String> {
+    let op = match step {
+        BatchStep::Create { key, content } => {
+            if working.maybe_key(key).is_some() {
+                return Err(OperationError::AlreadyExists(key.clone()).to_string());
+            }
+            working.insert_document(key.clone(), content.clone());
+            ledger.record(key, Some(content.clone()));
+            return Ok(StepResult::Create {
+                key: key.to_string(),
+            });
+        }
+        BatchStep::Query(op) => op,
+    };
+    let index = match op {
+        Operation::Find(find) if find.search.is_some() => {
+            Some(build_index(working, language.clone()))
+        }
+        _ => None,
+    };
+    let outcome = execute(op, working, index.as_ref()).map_err(|e| e.to_string())?;
+    Ok(match outcome {
+        Outcome::Find { matches } => StepResult::Find {
+            documents: matches.into_iter().map(|m| m.document).collect(),
+        },
+        Outcome::Count(count) => StepResult::Count { count },
+        Outcome::Update { changes } => {
+            let mut changed = Vec::with_capacity(changes.len());
+            for (key, content) in changes {
+                working.update_document(key.clone(), content.clone());
+                ledger.record(&key, Some(content));
+                changed.push(key.to_string());
+            }
+            StepResult::Update { changed }
+        }
+        Outcome::Delete { removed } => {
+            for key in &removed {
+                let changes = op_delete(working, key).map_err(|e| e.to_string())?;
+                apply(working, ledger, changes);
+            }
+            StepResult::Delete {
+                removed: removed.iter().map(|key| key.to_string()).collect(),
+            }
+        }
+    })
+}
+
+fn apply(working: &mut Graph, ledger: &mut Ledger, changes: Changes) {
+    for key in changes.removes {
+        working.remove_document(key.clone());
+        ledger.record(&key, None);
+    }
+    for (key, content) in changes.creates {
+        working.insert_document(key.clone(), content.clone());
+        ledger.record(&key, Some(content));
+    }
+    for (key, content) in changes.updates {
+        working.update_document(key.clone(), content.clone());
+        ledger.record(&key, Some(content));
+    }
+}
+
+/// Final content of every document a batch touched, in first-touch order; `None` is removed.
+#[derive(Default)]
+struct Ledger {
+    order: Vec<Key>,
+    state: HashMap<Key, Option<String>>,
+}
+
+impl Ledger {
+    fn record(&mut self, key: &Key, content: Option<String>) {
+        if self.state.insert(key.clone(), content).is_none() {
+            self.order.push(key.clone());
+        }
+    }
+
+    fn into_changes(mut self, original: &Graph) -> Changes {
+        let mut changes = Changes::new();
+        for key in self.order {
+            let existed = original.maybe_key(&key).is_some();
+            match (existed, self.state.remove(&key).flatten()) {
+                (true, Some(content)) => changes.add_update(key, content),
+                (true, None) => changes.add_remove(key),
+                (false, Some(content)) => changes.add_create(key, content),
+                (false, None) => {}
+            }
+        }
+        changes
+    }
+}
@@ -1,7 +1,7 @@
 use indoc::indoc;
 use log::debug;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env,
     fs::read_to_string,
     path::{Path, PathBuf},
@@ -10,12 +10,16 @@ use toml_edit::{value, DocumentMut, Item};
 
 use serde::{Deserialize, Serialize};
 
+use crate::lint::Severity;
 use crate::search::{parse_language, Language};
+use crate::tokens::count_tokens;
 pub use liwe::model::config::{
     DjotOptions, Format, FormatOptions, FormattingOptions, InlineType, LineBreakStyle, LinkType,
     MarkdownOptions, Operation, RefsPath, RefsText, TargetType, WikiLinkPath,
     DEFAULT_KEY_DATE_FORMAT,
 };
+use liwe::query::block::parse_block_predicate;
+use liwe::query::{ComputedField, ComputedFields, EdgeDirection, FieldPath};
 
 const CONFIG_FILE_NAME: &str = "config.toml";
 const IWE_MARKER: &str = ".iwe";
@@ -29,6 +33,10 @@ pub struct LibraryOptions {
     pub default_template: Option<String>,
     pub frontmatter_document_title: Option<String>,
     pub locale: Option<String>,
+    /// Other libraries linked into this one: links whose key starts with `prefix/` resolve to
+    /// the library at the mapped path, relative to this library.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub mounts: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
@@ -65,6 +73,7 @@ impl Default for LibraryOptions {
             default_template: None,
             frontmatter_document_title: None,
             locale: None,
+            mounts: HashMap::new(),
         }
     }
 }
@@ -92,6 +101,13 @@ pub struct Configuration {
     pub templates: HashMap<String, NoteTemplate>,
     #[serde(default)]
     pub schemas: HashMap<String, SchemaBinding>,
+    /// Computed field definitions, read one at a time so an invalid entry leaves the others.
+    #[serde(default)]
+    pub fields: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    pub lint: LintOptions,
+    #[serde(default)]
+    pub journal: JournalOptions,
 }
 
 #[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
@@ -198,6 +214,125 @@ pub struct SchemaBinding {
     pub r#match: Patterns,
 }
 
+/// `[lint]`: severity per rule, per-path overrides, and the thresholds rules read.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct LintOptions {
+    #[serde(default)]
+    pub rules: HashMap<String, Severity>,
+    /// Token budget above which `oversized-document` fires.
+    pub max_tokens: Option<usize>,
+    /// Frontmatter keys `missing-frontmatter` requires on every document.
+    #[serde(default)]
+    pub required_frontmatter: Vec<String>,
+    #[serde(default)]
+    pub overrides: Vec<LintOverride>,
+    /// Attach action `lint --fix` links orphans from.
+    pub attach_orphans: Option<String>,
+}
+
+/// Rule severities for documents whose key matches `match`; later overrides win.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct LintOverride {
+    pub r#match: Patterns,
+    #[serde(default)]
+    pub rules: HashMap<String, Severity>,
+}
+
+/// `[journal]`: the periodic notes `iwe journal` opens; a period is enabled by its table.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct JournalOptions {
+    pub daily: Option<PeriodOptions>,
+    pub weekly: Option<PeriodOptions>,
+    pub monthly: Option<PeriodOptions>,
+}
+
+/// `[journal.daily]`, `[journal.weekly]` or `[journal.monthly]`.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+pub struct PeriodOptions {
+    /// strftime pattern of the note key, e.g. `journal/%G-W%V`.
+    pub key_format: Option<String>,
+    /// strftime pattern of the note title, rendered in the library locale.
+    pub title_format: Option<String>,
+    /// Template of a new note; see `diwe::journal` for the variables.
+    pub document_template: Option<String>,
+    /// Key of the document that includes the notes when no longer period is configured.
+    pub hub: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum FieldDefinition {
+    #[serde(rename = "tokens")]
+    Tokens,
+    #[serde(rename = "words")]
+    Words,
+    #[serde(rename = "count")]
+    Count(CountField),
+    #[serde(rename = "edges")]
+    Edges(EdgesField),
+    #[serde(rename = "days")]
+    Days(DaysField),
+    #[serde(rename = "coalesce")]
+    Coalesce(CoalesceField),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CountField {
+    /// Block predicate in the query language, written as YAML.
+    pub blocks: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct EdgesField {
+    pub edge: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DaysField {
+    pub from: String,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CoalesceField {
+    pub paths: Vec<String>,
+}
+
+impl FieldDefinition {
+    pub fn to_computed(&self) -> Result<ComputedField, String> {
+        Ok(match self {
+            FieldDefinition::Tokens => ComputedField::Tokens,
+            FieldDefinition::Words => ComputedField::Words,
+            FieldDefinition::Count(count) => {
+                let value: serde_yaml::Value = serde_yaml::from_str(&count.blocks)
+                    .map_err(|e| format!("invalid blocks: {}", e))?;
+                ComputedField::Count(
+                    parse_block_predicate(&value, "blocks").map_err(|e| e.to_string())?,
+                )
+            }
+            FieldDefinition::Edges(edges) => ComputedField::Edges(
+                EdgeDirection::from_name(&edges.edge).ok_or_else(|| {
+                    format!(
+                        "unknown edge '{}', expected includes, includedBy, references or referencedBy",
+                        edges.edge
+                    )
+                })?,
+            ),
+            FieldDefinition::Days(days) => ComputedField::Days {
+                from: FieldPath::from_dotted(&days.from),
+                to: days.to.as_deref().map(FieldPath::from_dotted),
+            },
+            FieldDefinition::Coalesce(coalesce) => ComputedField::Coalesce(
+                coalesce
+                    .paths
+                    .iter()
+                    .map(|path| FieldPath::from_dotted(path))
+                    .collect(),
+            ),
+        })
+    }
+}
+
 impl Default for Configuration {
     fn default() -> Self {
         Self {
@@ -212,6 +347,9 @@ impl Default for Configuration {
             actions: Default::default(),
             templates: Default::default(),
             schemas: Default::default(),
+            fields: Default::default(),
+            lint: Default::default(),
+            journal: Default::default(),
         }
     }
 }
@@ -224,6 +362,42 @@ impl Configuration {
         }
     }
 
+    /// The valid `[fields]` definitions, and one message per definition that was left out.
+    pub fn computed_fields(&self) -> (ComputedFields, Vec<String>) {
+        let mut computed = ComputedFields::new().token_counter(count_tokens);
+        let mut errors = Vec::new();
+        for (name, definition) in &self.fields {
+            let field = definition
+                .clone()
+                .try_into::<FieldDefinition>()
+                .map_err(|e| e.message().to_string())
+                .and_then(|definition| definition.to_computed());
+            match field {
+                Ok(field) => computed = computed.field(name.clone(), field),
+                Err(e) => errors.push(format!("field '{}': {}", name, e)),
+            }
+        }
+        (computed, errors)
+    }
+
+    /// The attach action named by `[lint] attach_orphans`, if set.
+    pub fn orphan_attach_action(&self) -> Result<Option<&Attach>, String> {
+        let Some(name) = &self.lint.attach_orphans else {
+            return Ok(None);
+        };
+        match self.actions.get(name) {
+            Some(ActionDefinition::Attach(attach)) => Ok(Some(attach)),
+            Some(_) => Err(format!(
+                "[lint] attach_orphans: action '{}' is not an attach action",
+                name
+            )),
+            None => Err(format!(
+                "[lint] attach_orphans: action '{}' not found",
+                name
+            )),
+        }
+    }
+
     pub fn search_language(&self) -> Language {
         parse_language(&self.search.language)
     }
@@ -435,12 +609,19 @@ pub fn schemas_dir_in(base: &Path) -> PathBuf {
     base.join(IWE_MARKER).join("schemas")
 }
 
+pub fn config_path_in(base: &Path) -> PathBuf {
+    base.join(IWE_MARKER).join(CONFIG_FILE_NAME)
+}
+
 pub fn load_config() -> Result<Configuration, String> {
     let current_dir =
         env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
-    let mut config_path = current_dir.clone();
-    config_path.push(IWE_MARKER);
-    config_path.push(CONFIG_FILE_NAME);
+    load_config_in(&current_dir)
+}
+
+/// Configuration of the library rooted at `base`, read from its `.iwe/config.toml`.
+pub fn load_config_in(base: &Path) -> Result<Configuration, String> {
+    let config_path = config_path_in(base);
 
     if config_path.exists() {
         debug!("reading config from path: {:?}", config_path);
@@ -452,7 +633,7 @@ pub fn load_config() -> Result<Configuration, String> {
                 e
             )
         })?;
-        let configuration = migrate(&raw)?;
+        let configuration = migrate(&raw, &config_path)?;
 
         let mut config = toml::from_str::<Configuration>(&configuration).map_err(|e| {
             format!(
@@ -469,7 +650,7 @@ pub fn load_config() -> Result<Configuration, String> {
     }
 }
 
-fn migrate(config: &str) -> Result<String, String> {
+fn migrate(config: &str, config_path: &Path) -> Result<String, String> {
     let doc = config
         .parse::<DocumentMut>()
         .map_err(|e| format!("Config file is not valid TOML: {}", e))?;
@@ -510,14 +691,9 @@ fn migrate(config: &str) -> Result<String, String> {
 
     if needs_update {
         debug!("configuration file migration applied");
-        let current_dir =
-            env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
-        let mut config_path = current_dir.clone();
-        config_path.push(IWE_MARKER);
-        config_path.push(CONFIG_FILE_NAME);
 
         debug!("updating configuration file");
-        std::fs::write(&config_path, &updated).map_err(|e| {
+        std::fs::write(config_path, &updated).map_err(|e| {
             format!(
                 "Failed to write config file '{}': {}",
                 config_path.display(),
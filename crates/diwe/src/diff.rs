@@ -0,0 +1,132 @@
+use difference::{Changeset, Difference};
+
+use liwe::graph::Graph;
+use liwe::model::config::Format;
+use liwe::operations::Changes;
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Same,
+    Remove,
+    Add,
+}
+
+/// Unified diff of every document `changes` would write, against the graph's current content.
+pub fn changes_diff(graph: &Graph, changes: &Changes, format: Format) -> String {
+    let path = |key: &liwe::model::Key| key.to_path(format);
+    let mut out = String::new();
+    for (key, markdown) in &changes.creates {
+        out.push_str(&unified_diff("/dev/null", &path(key), "", markdown));
+    }
+    for (key, markdown) in &changes.updates {
+        let current = graph.get_document(key).unwrap_or_default();
+        out.push_str(&unified_diff(&path(key), &path(key), &current, markdown));
+    }
+    for key in &changes.removes {
+        let current = graph.get_document(key).unwrap_or_default();
+        out.push_str(&unified_diff(&path(key), "/dev/null", &current, ""));
+    }
+    out
+}
+
+/// Line diff of `old` against `new` in unified format, empty when they are equal.
+pub fn unified_diff(old_path: &str, new_path: &str, old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+    let ops = line_ops(old, new);
+    let mut out = format!(
+        "--- {}\n+++ {}\n",
+        prefixed("a", old_path),
+        prefixed("b", new_path)
+    );
+
+    let changed: Vec<usize> = (0..ops.len()).filter(|i| ops[*i].0 != Op::Same).collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in changed {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let old_start = ops[..start].iter().filter(|(op, _)| *op != Op::Add).count();
+        let new_start = ops[..start]
+            .iter()
+            .filter(|(op, _)| *op != Op::Remove)
+            .count();
+        let old_len = ops[start..end]
+            .iter()
+            .filter(|(op, _)| *op != Op::Add)
+            .count();
+        let new_len = ops[start..end]
+            .iter()
+            .filter(|(op, _)| *op != Op::Remove)
+            .count();
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_len),
+            range(new_start, new_len)
+        ));
+        for (op, line) in &ops[start..end] {
+            let marker = match op {
+                Op::Same => ' ',
+                Op::Remove => '-',
+                Op::Add => '+',
+            };
+            out.push(marker);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn prefixed(side: &str, path: &str) -> String {
+    if path == "/dev/null" {
+        path.to_string()
+    } else {
+        format!("{}/{}", side, path)
+    }
+}
+
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{}", start + 1, len),
+    }
+}
+
+fn line_ops(old: &str, new: &str) -> Vec<(Op, String)> {
+    let old = old.strip_suffix('\n').unwrap_or(old);
+    let new = new.strip_suffix('\n').unwrap_or(new);
+    if old.is_empty() || new.is_empty() {
+        let (op, text) = if old.is_empty() {
+            (Op::Add, new)
+        } else {
+            (Op::Remove, old)
+        };
+        return text
+            .split('\n')
+            .map(|line| (op, line.to_string()))
+            .collect();
+    }
+    let changeset = Changeset::new(old, new, "\n");
+    let mut ops = Vec::new();
+    for difference in changeset.diffs {
+        let (op, text) = match difference {
+            Difference::Same(text) => (Op::Same, text),
+            Difference::Rem(text) => (Op::Remove, text),
+            Difference::Add(text) => (Op::Add, text),
+        };
+        ops.extend(text.split('\n').map(|line| (op, line.to_string())));
+    }
+    ops
+}
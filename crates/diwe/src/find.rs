@@ -3,13 +3,18 @@ use crate::search_query;
 use crate::tokens::{
     apply_budget, count_tokens, truncate_to_tokens, truncation_marker, Budget, Truncation,
 };
+use liwe::graph::analytics::MetricCache;
 use liwe::graph::Graph;
 use liwe::model::Key;
+use liwe::query::computed::sort_rows;
 use liwe::query::project::{apply_projection, ProjectionContext};
-use liwe::query::sort::sort_in_place;
-use liwe::query::{self, Filter, InclusionAnchor, Projection, ReferenceAnchor, SearchSpec, Sort};
+use liwe::query::{
+    self, evaluate_explained_with, Explain, Filter, InclusionAnchor, Projection, ReferenceAnchor,
+    SearchSpec, Sort,
+};
 use serde::Serialize;
 use serde_yaml::{Mapping, Value};
+use std::time::Instant;
 
 pub type FindResult = Mapping;
 
@@ -26,6 +31,8 @@ pub struct FindOutput {
     pub titles: Vec<String>,
     #[serde(skip)]
     pub truncation: Truncation,
+    #[serde(skip)]
+    pub explain: Option<Explain>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -40,6 +47,7 @@ pub struct FindOptions {
     pub project: Option<Projection>,
     pub max_tokens: Option<usize>,
     pub max_document_tokens: Option<usize>,
+    pub explain: bool,
 }
 
 struct FindRow {
@@ -51,28 +59,43 @@ struct FindRow {
 pub struct DocumentFinder<'a> {
     graph: &'a Graph,
     index: Option<&'a Bm25Index>,
+    metrics: MetricCache<'a>,
 }
 
 impl<'a> DocumentFinder<'a> {
     pub fn new(graph: &'a Graph) -> Self {
-        Self { graph, index: None }
+        Self {
+            graph,
+            index: None,
+            metrics: MetricCache::new(graph),
+        }
     }
 
     pub fn with_index(graph: &'a Graph, index: &'a Bm25Index) -> Self {
         Self {
             graph,
             index: Some(index),
+            metrics: MetricCache::new(graph),
         }
     }
 
     pub fn find(&self, options: &FindOptions) -> FindOutput {
-        let candidates = self.candidates(options);
+        let start = Instant::now();
+        let mut explain = options.explain.then(Explain::default);
+        let candidates = self.candidates(options, explain.as_mut());
         let spec = SearchSpec::new(options.lexical.clone(), options.fuzzy.clone());
         let searching = !spec.is_empty();
 
         let candidates = if searching && options.sort.is_some() {
             match self.index {
-                Some(index) => search_query::matched(self.graph, index, candidates, &spec),
+                Some(index) => {
+                    let (kept, trace) =
+                        search_query::matched_traced(self.graph, index, candidates, &spec);
+                    if let Some(explain) = explain.as_mut() {
+                        explain.search = Some(trace);
+                    }
+                    kept
+                }
                 None => Vec::new(),
             }
         } else {
@@ -83,7 +106,14 @@ impl<'a> DocumentFinder<'a> {
             self.sort_by_frontmatter(candidates, s)
         } else if searching {
             match self.index {
-                Some(index) => search_query::ranked(self.graph, index, &candidates, &spec),
+                Some(index) => {
+                    let (ranked, trace) =
+                        search_query::ranked_traced(self.graph, index, &candidates, &spec);
+                    if let Some(explain) = explain.as_mut() {
+                        explain.search = Some(trace);
+                    }
+                    ranked
+                }
                 None => Vec::new(),
             }
         } else {
@@ -127,6 +157,11 @@ impl<'a> DocumentFinder<'a> {
         let keys: Vec<Key> = rows.iter().map(|r| r.key.clone()).collect();
         let results: Vec<FindResult> = rows.into_iter().map(|r| r.result).collect();
 
+        if let Some(explain) = explain.as_mut() {
+            explain.matched = results.len();
+            explain.elapsed_us = start.elapsed().as_micros() as u64;
+        }
+
         FindOutput {
             query: options.fuzzy.clone().or_else(|| options.lexical.clone()),
             limit,
@@ -135,6 +170,7 @@ impl<'a> DocumentFinder<'a> {
             keys,
             titles,
             truncation,
+            explain,
         }
     }
 
@@ -146,15 +182,28 @@ impl<'a> DocumentFinder<'a> {
                 (k, m)
             })
             .collect();
-        sort_in_place(&mut rows, sort);
+        sort_rows(self.graph, &self.metrics, &mut rows, sort);
         rows.into_iter().map(|(k, _)| k).collect()
     }
 
-    fn candidates(&self, options: &FindOptions) -> Vec<Key> {
-        match build_filter(options) {
+    fn candidates(&self, options: &FindOptions, explain: Option<&mut Explain>) -> Vec<Key> {
+        let filter = build_filter(options);
+        let Some(explain) = explain else {
+            return match filter {
+                None => self.graph.keys(),
+                Some(f) => query::evaluate_with(&f, self.graph, &self.metrics),
+            };
+        };
+        let keys = match filter {
             None => self.graph.keys(),
-            Some(f) => query::evaluate(&f, self.graph),
-        }
+            Some(f) => {
+                let (keys, trace) = evaluate_explained_with(&f, self.graph, &self.metrics);
+                explain.set_filter(trace);
+                keys
+            }
+        };
+        explain.candidates = keys.len();
+        keys
     }
 
     fn order_by_rank(&self, candidates: Vec<Key>) -> Vec<Key> {
@@ -170,7 +219,7 @@ impl<'a> DocumentFinder<'a> {
     }
 
     fn build_result(&self, key: &Key, project: &Projection) -> FindResult {
-        let ctx = ProjectionContext::new(self.graph, key);
+        let ctx = ProjectionContext::new(self.graph, &self.metrics, key);
         apply_projection(&ctx, project)
     }
 
@@ -7,8 +7,9 @@ use ignore::{Match, WalkBuilder};
 use log::error;
 use rayon::prelude::*;
 
+use liwe::model::asset::is_asset_url;
 use liwe::model::config::Format;
-use liwe::model::{Content, State};
+use liwe::model::{Content, Key, State};
 use liwe::operations::Changes;
 
 pub fn write_file(
@@ -23,6 +24,26 @@ pub fn write_file(
     )
 }
 
+/// Key of the document file at `path` under `base_path`; `None` for other files.
+pub fn path_to_key(path: &Path, base_path: &Path, format: Format) -> Option<Key> {
+    if path.extension().is_none_or(|ext| ext != format.extension()) {
+        return None;
+    }
+
+    let relative = path.strip_prefix(base_path).ok()?;
+    let key_str = relative
+        .with_extension("")
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(os) => Some(os.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+
+    Some(Key::from_stripped(&key_str))
+}
+
 pub fn new_for_path(base_path: &PathBuf, format: Format) -> State {
     if !base_path.exists() {
         error!("path doesn't exist");
@@ -76,6 +97,55 @@ pub fn walk_md_paths(base_path: &Path, format: Format) -> Vec<(String, PathBuf)>
         .collect()
 }
 
+/// Library-relative paths of the asset files under `base_path`: files with a known asset
+/// extension, skipping hidden and ignored paths like the document walk does.
+pub fn asset_paths(base_path: &Path, format: Format) -> Vec<String> {
+    if !base_path.exists() {
+        return Vec::new();
+    }
+
+    let extension = format.extension();
+
+    let mut paths: Vec<String> = WalkBuilder::new(base_path)
+        .follow_links(false)
+        .hidden(true)
+        .require_git(false)
+        .build()
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+
+            if !path.is_file() || path.extension().is_some_and(|ext| ext == extension) {
+                return None;
+            }
+
+            let relative_path = path.strip_prefix(base_path).ok()?;
+            let relative = relative_path
+                .components()
+                .filter_map(|c| match c {
+                    std::path::Component::Normal(os) => Some(os.to_string_lossy().to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            is_asset_url(&relative).then_some(relative)
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Deletes the library-relative `path` under `base_path`, pruning directories left empty.
+pub fn remove_path(path: &str, base_path: &Path) -> std::io::Result<()> {
+    let file_path = base_path.join(path);
+    if file_path.exists() {
+        fs::remove_file(&file_path)?;
+    }
+    prune_empty_dirs(file_path.parent(), base_path);
+    Ok(())
+}
+
 pub fn read_md_file(path: &Path) -> Option<String> {
     fs::read_to_string(path).ok().map(sanitize_content)
 }
@@ -0,0 +1,567 @@
+use std::sync::Arc;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{Datelike, Locale, Months, NaiveDate, TimeDelta};
+use minijinja::{context, Environment};
+use serde::Serialize;
+
+use liwe::graph::Graph;
+use liwe::locale::get_locale;
+use liwe::model::Key;
+use liwe::operations::{attach_references, AttachTarget, Changes};
+
+use crate::config::{Configuration, PeriodOptions};
+use crate::template::TemplateEngine;
+
+pub const DEFAULT_DOCUMENT_TEMPLATE: &str = "# {{ title }}\n{% if previous or next %}\n\
+     {% if previous %}[← {{ previous.title }}]({{ previous.url }}){% endif %}\
+     {% if previous and next %} · {% endif %}\
+     {% if next %}[{{ next.title }} →]({{ next.url }}){% endif %}\n{% endif %}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Period::Day => "day",
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Period::Day => "daily",
+            Period::Week => "weekly",
+            Period::Month => "monthly",
+        }
+    }
+
+    fn default_key_format(&self) -> &'static str {
+        match self {
+            Period::Day => "journal/%Y-%m-%d",
+            Period::Week => "journal/%G-W%V",
+            Period::Month => "journal/%Y-%m",
+        }
+    }
+
+    fn default_title_format(&self) -> &'static str {
+        match self {
+            Period::Day => "%A, %B %-d, %Y",
+            Period::Week => "Week %V, %G",
+            Period::Month => "%B %Y",
+        }
+    }
+
+    /// The first day of the period `date` falls in; weeks start on Monday.
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Day => date,
+            Period::Week => date - TimeDelta::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// The start of the period `by` periods away from the one `date` falls in.
+    pub fn shift(&self, date: NaiveDate, by: i32) -> NaiveDate {
+        let start = self.start(date);
+        let shifted = match self {
+            Period::Day => start.checked_add_signed(TimeDelta::days(by as i64)),
+            Period::Week => start.checked_add_signed(TimeDelta::weeks(by as i64)),
+            Period::Month if by < 0 => start.checked_sub_months(Months::new(by.unsigned_abs())),
+            Period::Month => start.checked_add_months(Months::new(by as u32)),
+        };
+        shifted.unwrap_or(start)
+    }
+}
+
+/// The document a periodic note is included from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parent {
+    Period(Period, NaiveDate),
+    Hub(Key),
+}
+
+#[derive(Clone)]
+struct Settings {
+    key_format: String,
+    title_format: String,
+    document_template: String,
+    hub: Option<Key>,
+}
+
+/// The configured `[journal]` periods.
+#[derive(Clone)]
+pub struct Journal {
+    periods: Vec<(Period, Settings)>,
+    locale: Locale,
+    engine: TemplateEngine,
+}
+
+#[derive(Serialize)]
+struct Link {
+    key: String,
+    title: String,
+    url: String,
+}
+
+impl Journal {
+    pub fn new(configuration: &Configuration) -> Result<Self, String> {
+        let journal = &configuration.journal;
+        let mut periods = Vec::new();
+
+        for (period, options) in [
+            (Period::Day, &journal.daily),
+            (Period::Week, &journal.weekly),
+            (Period::Month, &journal.monthly),
+        ] {
+            if let Some(options) = options {
+                periods.push((period, settings(period, options)?));
+            }
+        }
+
+        Ok(Journal {
+            periods,
+            locale: get_locale(configuration.library.locale.as_deref()),
+            engine: TemplateEngine::new(configuration),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.periods.is_empty()
+    }
+
+    pub fn has(&self, period: Period) -> bool {
+        self.settings(period).is_some()
+    }
+
+    fn settings(&self, period: Period) -> Option<&Settings> {
+        self.periods
+            .iter()
+            .find(|(configured, _)| *configured == period)
+            .map(|(_, settings)| settings)
+    }
+
+    fn require(&self, period: Period) -> Result<&Settings, String> {
+        self.settings(period)
+            .ok_or_else(|| format!("[journal.{}] is not configured", period.table()))
+    }
+
+    /// Key of the note of the period `date` falls in.
+    pub fn key(&self, period: Period, date: NaiveDate) -> Option<Key> {
+        let settings = self.settings(period)?;
+        Some(Key::name(
+            &period.start(date).format(&settings.key_format).to_string(),
+        ))
+    }
+
+    pub fn title(&self, period: Period, date: NaiveDate) -> Option<String> {
+        let settings = self.settings(period)?;
+        Some(
+            period
+                .start(date)
+                .format_localized(&settings.title_format, self.locale)
+                .to_string(),
+        )
+    }
+
+    /// The period and start date of a periodic note key.
+    pub fn parse(&self, key: &Key) -> Option<(Period, NaiveDate)> {
+        self.periods.iter().find_map(|(period, settings)| {
+            let date = match period {
+                Period::Day => NaiveDate::parse_from_str(key.as_str(), &settings.key_format),
+                Period::Week => NaiveDate::parse_from_str(
+                    &format!("{key}|1"),
+                    &format!("{}|%u", settings.key_format),
+                ),
+                Period::Month => NaiveDate::parse_from_str(
+                    &format!("{key}|1"),
+                    &format!("{}|%d", settings.key_format),
+                ),
+            }
+            .ok()?;
+            let start = period.start(date);
+            (self.key(*period, start).as_ref() == Some(key)).then_some((*period, start))
+        })
+    }
+
+    /// The next longer configured period, else the period's hub. A week belongs to the month
+    /// its Thursday falls in.
+    pub fn parent(&self, period: Period, date: NaiveDate) -> Option<Parent> {
+        let start = period.start(date);
+        let longer: &[Period] = match period {
+            Period::Day => &[Period::Week, Period::Month],
+            Period::Week => &[Period::Month],
+            Period::Month => &[],
+        };
+        if let Some(parent) = longer.iter().find(|parent| self.has(**parent)) {
+            let anchor = match period {
+                Period::Week => start + TimeDelta::days(3),
+                _ => start,
+            };
+            return Some(Parent::Period(*parent, parent.start(anchor)));
+        }
+        self.settings(period)?.hub.clone().map(Parent::Hub)
+    }
+
+    /// A new note of the period rendered from its document template.
+    pub fn document(
+        &self,
+        graph: &Arc<Graph>,
+        period: Period,
+        date: NaiveDate,
+    ) -> Result<String, String> {
+        let settings = self.require(period)?;
+        let start = period.start(date);
+        let key = self.key(period, start).expect("configured period");
+        let relative_to = key.parent();
+        let link = |target: Key, title: String| Link {
+            url: link_url(graph, &target, &relative_to),
+            key: target.to_string(),
+            title,
+        };
+        let period_link = |period: Period, date: NaiveDate| {
+            link(
+                self.key(period, date).expect("configured period"),
+                self.title(period, date).expect("configured period"),
+            )
+        };
+        let neighbour = |by: i32| {
+            let date = period.shift(start, by);
+            self.key(period, date)
+                .filter(|key| graph.has_key(key))
+                .map(|_| period_link(period, date))
+        };
+        let parent = self.parent(period, start).map(|parent| match parent {
+            Parent::Period(period, date) => period_link(period, date),
+            Parent::Hub(hub) => {
+                let title = graph.get_key_title(&hub).unwrap_or_else(|| hub.source());
+                link(hub, title)
+            }
+        });
+
+        let mut environment = self
+            .engine
+            .clone()
+            .with_graph(graph.clone())
+            .relative_to(&key)
+            .environment();
+        environment.set_keep_trailing_newline(true);
+        environment
+            .template_from_str(&settings.document_template)
+            .and_then(|template| {
+                template.render(context! {
+                    title => self.title(period, start),
+                    date => start.format("%Y-%m-%d").to_string(),
+                    period => period.name(),
+                    key => key.to_string(),
+                    previous => neighbour(-1),
+                    next => neighbour(1),
+                    parent => parent,
+                })
+            })
+            .map_err(|error| format!("[journal.{}] document_template: {}", period.table(), error))
+    }
+
+    /// The note of the period `date` falls in, and the changes that create it when it is
+    /// missing: the note itself, included from its parent, which is created the same way.
+    pub fn open(
+        &self,
+        graph: &Arc<Graph>,
+        period: Period,
+        date: NaiveDate,
+    ) -> Result<(Key, Changes), String> {
+        self.require(period)?;
+        let key = self.key(period, date).expect("configured period");
+        let mut changes = Changes::new();
+        if !graph.has_key(&key) {
+            let text = self.document(graph, period, date)?;
+            self.include(graph, key.clone(), text, period, date, &mut changes)?;
+        }
+        Ok((key, changes))
+    }
+
+    fn include(
+        &self,
+        graph: &Arc<Graph>,
+        key: Key,
+        text: String,
+        period: Period,
+        date: NaiveDate,
+        changes: &mut Changes,
+    ) -> Result<(), String> {
+        let reference = [(key.clone(), self.title(period, date).unwrap_or_default())];
+        changes.add_create(key, text);
+
+        let Some(parent) = self.parent(period, date) else {
+            return Ok(());
+        };
+        let parent_key = match &parent {
+            Parent::Period(period, date) => self.key(*period, *date).expect("configured period"),
+            Parent::Hub(hub) => hub.clone(),
+        };
+
+        if graph.has_key(&parent_key) {
+            if let AttachTarget::Update(text) = attach_references(graph, &parent_key, &reference) {
+                changes.add_update(parent_key, text);
+            }
+            return Ok(());
+        }
+
+        let parent_text = match &parent {
+            Parent::Period(period, date) => self.document(graph, *period, *date)?,
+            Parent::Hub(hub) => format!("# {}\n", hub.source()),
+        };
+        let mut patch = graph.new_patch();
+        patch.update_key(parent_key.clone(), &parent_text);
+        let text = match attach_references(&patch, &parent_key, &reference) {
+            AttachTarget::Update(text) | AttachTarget::Create(text) => text,
+            AttachTarget::AlreadyAttached => parent_text,
+        };
+
+        match parent {
+            Parent::Period(period, date) => {
+                self.include(graph, parent_key, text, period, date, changes)
+            }
+            Parent::Hub(_) => {
+                changes.add_create(parent_key, text);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The period and date a `iwe journal` argument names: `today`, `yesterday`, `tomorrow`,
+/// `[last-|this-|next-]week`, `[last-|this-|next-]month`, or a `YYYY-MM-DD`, `YYYY-Www` or
+/// `YYYY-MM` date.
+pub fn resolve(spec: &str, today: NaiveDate) -> Result<(Period, NaiveDate), String> {
+    let relative = match spec {
+        "today" => Some((Period::Day, 0)),
+        "yesterday" => Some((Period::Day, -1)),
+        "tomorrow" => Some((Period::Day, 1)),
+        "week" | "this-week" => Some((Period::Week, 0)),
+        "last-week" => Some((Period::Week, -1)),
+        "next-week" => Some((Period::Week, 1)),
+        "month" | "this-month" => Some((Period::Month, 0)),
+        "last-month" => Some((Period::Month, -1)),
+        "next-month" => Some((Period::Month, 1)),
+        _ => None,
+    };
+    if let Some((period, by)) = relative {
+        return Ok((period, period.shift(today, by)));
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(spec, "%Y-%m-%d") {
+        return Ok((Period::Day, date));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{spec}-1"), "%G-W%V-%u") {
+        return Ok((Period::Week, date));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{spec}-01"), "%Y-%m-%d") {
+        return Ok((Period::Month, date));
+    }
+
+    Err(format!(
+        "unknown period '{spec}': expected today, yesterday, tomorrow, [last-|next-]week, [last-|next-]month, YYYY-MM-DD, YYYY-Www or YYYY-MM"
+    ))
+}
+
+fn settings(period: Period, options: &PeriodOptions) -> Result<Settings, String> {
+    let table = period.table();
+    let key_format = options
+        .key_format
+        .clone()
+        .unwrap_or_else(|| period.default_key_format().to_string());
+    let title_format = options
+        .title_format
+        .clone()
+        .unwrap_or_else(|| period.default_title_format().to_string());
+    let document_template = options
+        .document_template
+        .clone()
+        .unwrap_or_else(|| DEFAULT_DOCUMENT_TEMPLATE.to_string());
+
+    for (name, format) in [("key_format", &key_format), ("title_format", &title_format)] {
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            return Err(format!(
+                "[journal.{table}] {name}: invalid format '{format}'"
+            ));
+        }
+    }
+    Environment::new()
+        .template_from_str(&document_template)
+        .map_err(|error| format!("[journal.{table}] document_template: {error}"))?;
+
+    let settings = Settings {
+        key_format,
+        title_format,
+        document_template,
+        hub: options.hub.as_deref().map(Key::name),
+    };
+
+    let sample = period.start(NaiveDate::from_ymd_opt(2026, 1, 14).expect("valid date"));
+    let next = period.shift(sample, 1);
+    if sample.format(&settings.key_format).to_string()
+        == next.format(&settings.key_format).to_string()
+    {
+        return Err(format!(
+            "[journal.{table}] key_format '{}' gives consecutive {}s the same key",
+            settings.key_format,
+            period.name()
+        ));
+    }
+
+    Ok(settings)
+}
+
+fn link_url(graph: &Graph, key: &Key, relative_to: &str) -> String {
+    let options = graph.format_options();
+    format!(
+        "{}{}",
+        key.link_url(relative_to, options.refs_path()),
+        options.refs_extension()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::JournalOptions;
+    use indoc::indoc;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn journal(options: JournalOptions) -> Journal {
+        let mut configuration = Configuration {
+            journal: options,
+            ..Default::default()
+        };
+        configuration.library.locale = Some("en_US".to_string());
+        Journal::new(&configuration).unwrap()
+    }
+
+    fn all_periods() -> Journal {
+        journal(JournalOptions {
+            daily: Some(Default::default()),
+            weekly: Some(Default::default()),
+            monthly: Some(PeriodOptions {
+                hub: Some("journal".to_string()),
+                ..Default::default()
+            }),
+        })
+    }
+
+    #[test]
+    fn resolves_relative_and_absolute_periods() {
+        let today = date("2026-10-18");
+
+        assert_eq!(
+            resolve("yesterday", today),
+            Ok((Period::Day, date("2026-10-17")))
+        );
+        assert_eq!(
+            resolve("next-week", today),
+            Ok((Period::Week, date("2026-10-19")))
+        );
+        assert_eq!(
+            resolve("last-month", today),
+            Ok((Period::Month, date("2026-09-01")))
+        );
+        assert_eq!(
+            resolve("2026-W01", today),
+            Ok((Period::Week, date("2025-12-29")))
+        );
+        assert_eq!(
+            resolve("2026-02", today),
+            Ok((Period::Month, date("2026-02-01")))
+        );
+        assert!(resolve("someday", today).is_err());
+    }
+
+    #[test]
+    fn keys_parse_back_to_their_period() {
+        let journal = all_periods();
+
+        for (period, key, start) in [
+            (Period::Day, "journal/2026-10-17", "2026-10-17"),
+            (Period::Week, "journal/2026-W42", "2026-10-12"),
+            (Period::Month, "journal/2026-10", "2026-10-01"),
+        ] {
+            assert_eq!(journal.key(period, date(start)), Some(Key::name(key)));
+            assert_eq!(journal.parse(&Key::name(key)), Some((period, date(start))));
+        }
+        assert_eq!(journal.parse(&Key::name("journal/notes")), None);
+    }
+
+    #[test]
+    fn weeks_belong_to_the_month_of_their_thursday() {
+        let journal = all_periods();
+
+        assert_eq!(
+            journal.parent(Period::Week, date("2026-09-30")),
+            Some(Parent::Period(Period::Month, date("2026-10-01")))
+        );
+        assert_eq!(
+            journal.parent(Period::Month, date("2026-10-01")),
+            Some(Parent::Hub(Key::name("journal")))
+        );
+    }
+
+    #[test]
+    fn opening_a_missing_day_creates_its_week_and_month() {
+        let journal = all_periods();
+        let mut graph = Graph::new();
+        graph.update_key(Key::name("journal"), "# Journal\n");
+
+        let (key, changes) = journal
+            .open(&Arc::new(graph), Period::Day, date("2026-10-17"))
+            .unwrap();
+
+        assert_eq!(key, Key::name("journal/2026-10-17"));
+        assert_eq!(
+            changes.creates,
+            vec![
+                (
+                    Key::name("journal/2026-10-17"),
+                    indoc! {"
+                        # Saturday, October 17, 2026
+                    "}
+                    .to_string()
+                ),
+                (
+                    Key::name("journal/2026-W42"),
+                    indoc! {"
+                        # Week 42, 2026
+
+                        [Saturday, October 17, 2026](2026-10-17)
+                    "}
+                    .to_string()
+                ),
+                (
+                    Key::name("journal/2026-10"),
+                    indoc! {"
+                        # October 2026
+
+                        [Week 42, 2026](2026-W42)
+                    "}
+                    .to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            changes.updates,
+            vec![(
+                Key::name("journal"),
+                "# Journal\n\n[October 2026](journal/2026-10)\n".to_string()
+            )]
+        );
+    }
+}
@@ -1,14 +1,22 @@
+pub mod assets;
+pub mod batch;
 pub mod config;
+pub mod diff;
 pub mod file;
 pub mod find;
 pub mod fs;
+pub mod journal;
+pub mod lint;
 pub mod loader;
+pub mod mentions;
 
 pub use loader::graph_from_path;
 pub mod retrieve;
+pub mod scaffold;
 pub mod schema;
 pub mod search;
 pub mod search_query;
 pub mod stats;
+pub mod template;
 pub mod tokens;
 pub mod watcher;
@@ -0,0 +1,949 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use globset::GlobMatcher;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use liwe::graph::assets::is_asset_link;
+use liwe::graph::walk::ancestors_inclusion;
+use liwe::graph::Graph;
+use liwe::model::asset::{is_local_url, resolve_asset};
+use liwe::model::document::{DocumentBlock, DocumentInline};
+use liwe::model::reference::ReferenceType;
+use liwe::model::{InlineRange, Key};
+use liwe::parser::Parser;
+
+use crate::assets::AssetInventory;
+use crate::config::LintOptions;
+use crate::schema::{compile_patterns, matches_patterns};
+use crate::search::Language;
+use crate::stats::{
+    broken_links, broken_links_from, is_orphan, orphan_keys, Finding, Rule, SimilarityIndex,
+};
+use crate::tokens::count_tokens;
+
+pub mod fix;
+
+/// Token budget `oversized-document` applies when `[lint] max_tokens` is unset.
+pub const DEFAULT_MAX_TOKENS: usize = 4000;
+
+const DISABLE: &str = "iwe-disable";
+const ENABLE: &str = "iwe-enable";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Off => "off",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+fn default_severity(rule: Rule) -> Severity {
+    match rule {
+        Rule::DanglingLink | Rule::MissingAsset => Severity::Error,
+        Rule::SimilarPage => Severity::Off,
+        _ => Severity::Warning,
+    }
+}
+
+/// A rule finding with its configured severity and, when the rule can place it, the 1-based line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    #[serde(flatten)]
+    pub finding: Finding,
+    pub severity: Severity,
+    pub line: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn render(&self) -> String {
+        let location = match self.line {
+            Some(line) => format!("{}:{}", self.finding.key, line),
+            None => self.finding.key.to_string(),
+        };
+        format!(
+            "{} {} › {}: {}",
+            location,
+            self.severity.label(),
+            self.finding.rule.label(),
+            self.finding.message
+        )
+    }
+}
+
+/// Path patterns of a `[[lint.overrides]]` entry and the severities it sets.
+type Override = (Vec<(GlobMatcher, bool)>, HashMap<Rule, Severity>);
+
+/// `[lint]` compiled: rule severities resolved per key, plus the rule thresholds.
+#[derive(Debug)]
+pub struct LintRules {
+    base: HashMap<Rule, Severity>,
+    overrides: Vec<Override>,
+    max_tokens: usize,
+    required_frontmatter: Vec<String>,
+}
+
+impl Default for LintRules {
+    fn default() -> Self {
+        LintRules::compile(&LintOptions::default()).expect("default lint options compile")
+    }
+}
+
+impl LintRules {
+    pub fn compile(options: &LintOptions) -> Result<Self, Vec<String>> {
+        let mut errors = Vec::new();
+        let mut base: HashMap<Rule, Severity> = Rule::ALL
+            .into_iter()
+            .map(|rule| (rule, default_severity(rule)))
+            .collect();
+        base.extend(resolve_rules(&options.rules, "[lint.rules]", &mut errors));
+
+        let overrides = options
+            .overrides
+            .iter()
+            .enumerate()
+            .map(|(i, o)| {
+                let owner = format!("lint override {}", i + 1);
+                let patterns = compile_patterns(&owner, o.r#match.as_slice(), &mut errors);
+                (patterns, resolve_rules(&o.rules, &owner, &mut errors))
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(LintRules {
+                base,
+                overrides,
+                max_tokens: options.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+                required_frontmatter: options.required_frontmatter.clone(),
+            })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn severity(&self, rule: Rule, key: &Key) -> Severity {
+        self.overrides
+            .iter()
+            .filter(|(patterns, _)| matches_patterns(patterns, key.as_str()))
+            .filter_map(|(_, rules)| rules.get(&rule))
+            .next_back()
+            .or_else(|| self.base.get(&rule))
+            .copied()
+            .unwrap_or(Severity::Off)
+    }
+
+    fn enabled_anywhere(&self, rule: Rule) -> bool {
+        self.base.get(&rule) != Some(&Severity::Off)
+            || self
+                .overrides
+                .iter()
+                .any(|(_, rules)| rules.get(&rule).is_some_and(|s| *s != Severity::Off))
+    }
+}
+
+fn resolve_rules(
+    rules: &HashMap<String, Severity>,
+    owner: &str,
+    errors: &mut Vec<String>,
+) -> HashMap<Rule, Severity> {
+    let mut resolved = HashMap::new();
+    for (label, severity) in rules {
+        match Rule::from_label(label) {
+            Some(rule) => {
+                resolved.insert(rule, *severity);
+            }
+            None => errors.push(format!("{owner}: unknown lint rule '{label}'")),
+        }
+    }
+    resolved
+}
+
+/// Runs every enabled rule over the whole graph and reports the findings on `keys`, in key then
+/// line order. Findings switched off for their key, or suppressed by an `iwe-disable` comment in
+/// the document, are dropped.
+///
+/// The asset rules run only with an `assets` inventory. Unreferenced files, keyed by their path,
+/// are reported only when `keys` cover the whole library.
+pub fn lint(
+    graph: &Graph,
+    rules: &LintRules,
+    language: Language,
+    keys: &[Key],
+    assets: Option<&AssetInventory>,
+) -> Vec<Diagnostic> {
+    let mut selected: HashSet<Key> = keys.iter().cloned().collect();
+    let mut raw: Vec<(Finding, Option<usize>)> = Vec::new();
+    let mut sources: HashMap<Key, Source> = HashMap::new();
+    for key in keys {
+        let text = graph.get_document(key).unwrap_or_default();
+        sources.insert(key.clone(), Source::scan(graph, key, &text));
+    }
+
+    for key in orphan_keys(graph) {
+        raw.push((
+            finding(Rule::Orphan, &key, None, "no page links here".to_string()),
+            None,
+        ));
+    }
+    for link in broken_links(graph) {
+        let line = sources
+            .get(&link.source_key)
+            .and_then(|source| source.line_of(&link.target_key));
+        raw.push((
+            finding(
+                Rule::DanglingLink,
+                &link.source_key,
+                Some(link.target_key.clone()),
+                format!("links to missing '{}'", link.target_key),
+            ),
+            line,
+        ));
+    }
+    if rules.enabled_anywhere(Rule::SimilarPage) {
+        for (a, b) in SimilarityIndex::build(graph, language).pairs() {
+            for (key, other) in [(&a, &b), (&b, &a)] {
+                raw.push((
+                    finding(
+                        Rule::SimilarPage,
+                        key,
+                        Some(other.clone()),
+                        format!("closely matches '{}'", other),
+                    ),
+                    None,
+                ));
+            }
+        }
+    }
+    raw.extend(duplicate_titles(graph, &sources));
+    if let Some(inventory) = assets {
+        for reference in inventory.missing() {
+            let line = sources
+                .get(&reference.key)
+                .and_then(|source| source.asset_line(&reference.path));
+            raw.push((
+                finding(
+                    Rule::MissingAsset,
+                    &reference.key,
+                    None,
+                    format!("links to missing asset '{}'", reference.path),
+                ),
+                line,
+            ));
+        }
+        if graph.keys().iter().all(|key| selected.contains(key)) {
+            for path in inventory.unreferenced() {
+                let key = Key::from_stripped(path);
+                raw.push((
+                    finding(
+                        Rule::UnreferencedAsset,
+                        &key,
+                        None,
+                        "no page links to this file".to_string(),
+                    ),
+                    None,
+                ));
+                selected.insert(key);
+            }
+        }
+    }
+
+    for key in keys {
+        let source = &sources[key];
+        raw.extend(source.findings(graph, key, rules));
+    }
+
+    diagnose(raw, &selected, &sources, rules)
+}
+
+/// [`lint`] of one document, limited to the rules that only read that document and the links
+/// into it: `similar-page`, `duplicate-title` and the asset rules are skipped, so the cost does
+/// not grow with the library.
+pub fn lint_document(graph: &Graph, rules: &LintRules, key: &Key) -> Vec<Diagnostic> {
+    let text = graph.get_document(key).unwrap_or_default();
+    let source = Source::scan(graph, key, &text);
+    let mut raw: Vec<(Finding, Option<usize>)> = Vec::new();
+    if is_orphan(graph, key) {
+        raw.push((
+            finding(Rule::Orphan, key, None, "no page links here".to_string()),
+            None,
+        ));
+    }
+    for link in broken_links_from(graph, key) {
+        raw.push((
+            finding(
+                Rule::DanglingLink,
+                key,
+                Some(link.target_key.clone()),
+                format!("links to missing '{}'", link.target_key),
+            ),
+            source.line_of(&link.target_key),
+        ));
+    }
+    raw.extend(source.findings(graph, key, rules));
+
+    let selected = HashSet::from([key.clone()]);
+    let sources = HashMap::from([(key.clone(), source)]);
+    diagnose(raw, &selected, &sources, rules)
+}
+
+/// Findings on `selected` keys that are switched on and not suppressed, in key then line order.
+fn diagnose(
+    raw: Vec<(Finding, Option<usize>)>,
+    selected: &HashSet<Key>,
+    sources: &HashMap<Key, Source>,
+    rules: &LintRules,
+) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = raw
+        .into_iter()
+        .filter(|(finding, _)| selected.contains(&finding.key))
+        .filter_map(|(finding, line)| {
+            let severity = rules.severity(finding.rule, &finding.key);
+            let suppressed = sources
+                .get(&finding.key)
+                .is_some_and(|source| source.suppresses(finding.rule, line));
+            (severity != Severity::Off && !suppressed).then_some(Diagnostic {
+                finding,
+                severity,
+                line,
+            })
+        })
+        .collect();
+    diagnostics.sort_by(|a, b| {
+        (
+            &a.finding.key,
+            a.line,
+            a.finding.rule.label(),
+            &a.finding.message,
+        )
+            .cmp(&(
+                &b.finding.key,
+                b.line,
+                b.finding.rule.label(),
+                &b.finding.message,
+            ))
+    });
+    diagnostics
+}
+
+fn finding(rule: Rule, key: &Key, other: Option<Key>, message: String) -> Finding {
+    Finding {
+        rule,
+        key: key.clone(),
+        other,
+        message,
+    }
+}
+
+fn duplicate_titles(
+    graph: &Graph,
+    sources: &HashMap<Key, Source>,
+) -> Vec<(Finding, Option<usize>)> {
+    let mut by_title: HashMap<String, Vec<Key>> = HashMap::new();
+    for key in graph.keys() {
+        if let Some(title) = graph.get_key_title(&key) {
+            let normalized = title.trim().to_lowercase();
+            if !normalized.is_empty() {
+                by_title.entry(normalized).or_default().push(key);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    for mut group in by_title.into_values().filter(|group| group.len() > 1) {
+        group.sort();
+        for key in &group {
+            let other = group.iter().find(|other| *other != key).cloned();
+            let title = graph.get_key_title(key).unwrap_or_default();
+            let line = sources
+                .get(key)
+                .and_then(|source| source.headings.first())
+                .map(|heading| heading.line);
+            out.push((
+                finding(
+                    Rule::DuplicateTitle,
+                    key,
+                    other.clone(),
+                    format!(
+                        "title '{}' is also used by '{}'",
+                        title,
+                        other.map(|k| k.to_string()).unwrap_or_default()
+                    ),
+                ),
+                line,
+            ));
+        }
+    }
+    out
+}
+
+struct Heading {
+    line: usize,
+    level: usize,
+    text: String,
+    /// Whether the heading is written with leading `#`s rather than underlined.
+    atx: bool,
+}
+
+/// A local image or asset link, with the library path it resolves to.
+struct AssetLink {
+    line: usize,
+    path: String,
+}
+
+enum LinkKind {
+    Regular,
+    Wiki,
+}
+
+struct Link {
+    line: usize,
+    /// Byte range of `raw` within the line, when the target is written inline.
+    span: Option<Range<usize>>,
+    kind: LinkKind,
+    raw: String,
+    target: Key,
+}
+
+/// `iwe-disable` / `iwe-enable` comment; an empty rule list applies to every rule.
+struct Directive {
+    line: usize,
+    disable: bool,
+    rules: Vec<Rule>,
+}
+
+/// What the line-based rules read from one parsed document: headings, links and suppression
+/// comments outside code, each with its 1-based line.
+struct Source {
+    headings: Vec<Heading>,
+    /// Whether each heading is followed by content before the next heading.
+    heading_has_body: Vec<bool>,
+    links: Vec<Link>,
+    assets: Vec<AssetLink>,
+    directives: Vec<Directive>,
+    tokens: usize,
+}
+
+impl Source {
+    fn scan(graph: &Graph, key: &Key, text: &str) -> Source {
+        let mut source = Source {
+            headings: Vec::new(),
+            heading_has_body: Vec::new(),
+            links: Vec::new(),
+            assets: Vec::new(),
+            directives: Vec::new(),
+            tokens: count_tokens(text),
+        };
+        let relative_to = key.parent();
+        let lines: Vec<&str> = text.lines().collect();
+        let parser = Parser::new(text, &graph.format_options());
+        let document = parser.document();
+
+        for block in &document.blocks {
+            match block {
+                DocumentBlock::Header(header) => {
+                    let line = header.line_range.start;
+                    source.headings.push(Heading {
+                        line: line + 1,
+                        level: header.level as usize,
+                        text: header
+                            .inlines
+                            .iter()
+                            .map(DocumentInline::to_plain_text)
+                            .collect::<String>()
+                            .trim()
+                            .to_string(),
+                        atx: lines
+                            .get(line)
+                            .is_some_and(|text| text.trim_start().starts_with('#')),
+                    });
+                    source.heading_has_body.push(false);
+                }
+                _ => source.mark_body(),
+            }
+        }
+
+        let mut code_lines = HashSet::new();
+        let mut code_spans = Vec::new();
+        for block in document.all_blocks() {
+            if let DocumentBlock::CodeBlock(code) = block {
+                code_lines.extend(code.line_range.clone());
+            }
+            for inline in block.child_inlines() {
+                source.collect_links(graph, &lines, &relative_to, &inline, &mut code_spans);
+            }
+        }
+
+        for (index, line) in lines.iter().enumerate().skip(frontmatter_lines(&lines)) {
+            if code_lines.contains(&index) || !line.contains("<!--") {
+                continue;
+            }
+            let mut masked = line.to_string();
+            for span in &code_spans {
+                if let Some(range) = line_span(&lines, index, span) {
+                    masked.replace_range(range.clone(), &" ".repeat(range.len()));
+                }
+            }
+            source.directives.extend(directives(&masked, index + 1));
+        }
+        source
+    }
+
+    fn collect_links(
+        &mut self,
+        graph: &Graph,
+        lines: &[&str],
+        relative_to: &str,
+        inline: &DocumentInline,
+        code_spans: &mut Vec<InlineRange>,
+    ) {
+        match inline {
+            DocumentInline::Link(link) => {
+                let range = &link.inline_range;
+                if link.link_type.to_ref_type() == ReferenceType::Regular {
+                    let written = inline_url(lines, range);
+                    let (line, span, raw) = match written {
+                        Some((line, span)) => {
+                            (line, Some(span.clone()), lines[line][span].to_string())
+                        }
+                        None => (range.start.line, None, link.target.url.clone()),
+                    };
+                    if is_asset_link(graph, &raw, relative_to) {
+                        self.assets.push(AssetLink {
+                            line: line + 1,
+                            path: resolve_asset(&raw, relative_to),
+                        });
+                    } else if !raw.is_empty() && !raw.starts_with('#') && !raw.contains(':') {
+                        self.links.push(Link {
+                            line: line + 1,
+                            span,
+                            kind: LinkKind::Regular,
+                            target: Key::from_rel_link_url(&raw, relative_to),
+                            raw,
+                        });
+                    }
+                } else if let Some(span) = wiki_target(lines, range) {
+                    let raw = lines[range.start.line][span.clone()].to_string();
+                    self.links.push(Link {
+                        line: range.start.line + 1,
+                        span: Some(span),
+                        kind: LinkKind::Wiki,
+                        target: graph.key_index().resolve_wiki(&raw),
+                        raw,
+                    });
+                }
+            }
+            DocumentInline::Image(image) if is_local_url(&image.target.url) => {
+                self.assets.push(AssetLink {
+                    line: image.inline_range.start.line + 1,
+                    path: resolve_asset(&image.target.url, relative_to),
+                });
+            }
+            DocumentInline::Code(_) | DocumentInline::Math(_) | DocumentInline::RawInline(_) => {
+                code_spans.push(inline.inline_range());
+                return;
+            }
+            _ => {}
+        }
+        for child in inline.child_inlines() {
+            self.collect_links(graph, lines, relative_to, child, code_spans);
+        }
+    }
+
+    fn mark_body(&mut self) {
+        if let Some(last) = self.heading_has_body.last_mut() {
+            *last = true;
+        }
+    }
+
+    fn line_of(&self, target: &Key) -> Option<usize> {
+        self.links
+            .iter()
+            .find(|link| link.target == *target)
+            .map(|link| link.line)
+    }
+
+    fn asset_line(&self, path: &str) -> Option<usize> {
+        self.assets
+            .iter()
+            .find(|asset| asset.path == path)
+            .map(|asset| asset.line)
+    }
+
+    /// Findings about the document as a whole are suppressed by a disable comment anywhere in it;
+    /// line findings by the disable and enable comments above or on their line.
+    fn suppresses(&self, rule: Rule, line: Option<usize>) -> bool {
+        let applies = |d: &Directive| d.rules.is_empty() || d.rules.contains(&rule);
+        match line.filter(|_| !is_document_rule(rule)) {
+            None => self.directives.iter().any(|d| d.disable && applies(d)),
+            Some(line) => self
+                .directives
+                .iter()
+                .rfind(|d| d.line <= line && applies(d))
+                .is_some_and(|d| d.disable),
+        }
+    }
+
+    fn findings(
+        &self,
+        graph: &Graph,
+        key: &Key,
+        rules: &LintRules,
+    ) -> Vec<(Finding, Option<usize>)> {
+        let mut out = Vec::new();
+
+        for pair in self.headings.windows(2) {
+            if pair[1].level > pair[0].level + 1 {
+                out.push((
+                    finding(
+                        Rule::HeadingJump,
+                        key,
+                        None,
+                        format!(
+                            "heading '{}' jumps from level {} to {}",
+                            pair[1].text, pair[0].level, pair[1].level
+                        ),
+                    ),
+                    Some(pair[1].line),
+                ));
+            }
+        }
+
+        for (i, heading) in self.headings.iter().enumerate() {
+            let has_subsection = self
+                .headings
+                .get(i + 1)
+                .is_some_and(|next| next.level > heading.level);
+            if !self.heading_has_body[i] && !has_subsection {
+                out.push((
+                    finding(
+                        Rule::EmptySection,
+                        key,
+                        None,
+                        format!("section '{}' has no content", heading.text),
+                    ),
+                    Some(heading.line),
+                ));
+            }
+        }
+
+        let ancestors = ancestors_inclusion(graph, key, u32::MAX);
+        for link in &self.links {
+            if link.target == *key {
+                out.push((
+                    finding(Rule::SelfLink, key, None, "links to itself".to_string()),
+                    Some(link.line),
+                ));
+            } else if ancestors.contains_key(&link.target) {
+                out.push((
+                    finding(
+                        Rule::AncestorLink,
+                        key,
+                        Some(link.target.clone()),
+                        format!("links to '{}', which includes it", link.target),
+                    ),
+                    Some(link.line),
+                ));
+            }
+            if let LinkKind::Wiki = link.kind {
+                let candidates = graph.key_index().wiki_candidates(&link.raw);
+                if candidates.len() > 1 {
+                    let names: Vec<String> = candidates.iter().map(Key::to_string).collect();
+                    out.push((
+                        finding(
+                            Rule::AmbiguousWikilink,
+                            key,
+                            Some(link.target.clone()),
+                            format!(
+                                "[[{}]] matches {}; resolves to '{}'",
+                                link.raw,
+                                names.join(", "),
+                                link.target
+                            ),
+                        ),
+                        Some(link.line),
+                    ));
+                }
+            }
+        }
+
+        if self.tokens > rules.max_tokens {
+            out.push((
+                finding(
+                    Rule::OversizedDocument,
+                    key,
+                    None,
+                    format!(
+                        "{} tokens, over the {} budget",
+                        self.tokens, rules.max_tokens
+                    ),
+                ),
+                None,
+            ));
+        }
+
+        let frontmatter = graph.frontmatter(key);
+        for field in &rules.required_frontmatter {
+            let present = frontmatter
+                .and_then(|m| m.get(Value::String(field.clone())))
+                .is_some_and(|v| !v.is_null());
+            if !present {
+                out.push((
+                    finding(
+                        Rule::MissingFrontmatter,
+                        key,
+                        None,
+                        format!("missing required frontmatter '{}'", field),
+                    ),
+                    None,
+                ));
+            }
+        }
+
+        out
+    }
+}
+
+fn is_document_rule(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::Orphan
+            | Rule::SimilarPage
+            | Rule::DuplicateTitle
+            | Rule::OversizedDocument
+            | Rule::MissingFrontmatter
+    )
+}
+
+/// `iwe-disable` / `iwe-enable` comments on a line, whether the comment stands alone or trails
+/// other content. Normalization drops standalone HTML blocks, so trailing comments are the form
+/// that survives formatting.
+fn directives(line: &str, number: usize) -> Vec<Directive> {
+    let mut out = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("<!--") {
+        let after = &rest[start + 4..];
+        let Some(end) = after.find("-->") else { break };
+        let body = after[..end].trim();
+        rest = &after[end + 3..];
+        let (disable, names) = if let Some(names) = body.strip_prefix(DISABLE) {
+            (true, names)
+        } else if let Some(names) = body.strip_prefix(ENABLE) {
+            (false, names)
+        } else {
+            continue;
+        };
+        if !names.is_empty() && !names.starts_with([' ', '\t']) {
+            continue;
+        }
+        out.push(Directive {
+            line: number,
+            disable,
+            rules: names
+                .split([' ', ','])
+                .filter_map(Rule::from_label)
+                .collect(),
+        });
+    }
+    out
+}
+
+/// Lines taken by the frontmatter block at the top of the document.
+fn frontmatter_lines(lines: &[&str]) -> usize {
+    if lines.first().is_none_or(|line| line.trim_end() != "---") {
+        return 0;
+    }
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| matches!(line.trim_end(), "---" | "..."))
+        .map_or(0, |end| end + 2)
+}
+
+/// Byte offset of the UTF-16 `column` the parser reports within `line`.
+fn byte_offset(line: &str, column: usize) -> usize {
+    let mut units = 0;
+    for (offset, c) in line.char_indices() {
+        if units >= column {
+            return offset;
+        }
+        units += c.len_utf16();
+    }
+    line.len()
+}
+
+/// The bytes of line `index` that `range` covers.
+fn line_span(lines: &[&str], index: usize, range: &InlineRange) -> Option<Range<usize>> {
+    if index < range.start.line || index > range.end.line {
+        return None;
+    }
+    let line = lines.get(index)?;
+    let start = if index == range.start.line {
+        byte_offset(line, range.start.character)
+    } else {
+        0
+    };
+    let end = if index == range.end.line {
+        byte_offset(line, range.end.character)
+    } else {
+        line.len()
+    };
+    Some(start..end.max(start))
+}
+
+/// Line and byte range of the url of an inline `[text](url)` link, or `None` when the link
+/// takes its url from a reference definition.
+fn inline_url(lines: &[&str], range: &InlineRange) -> Option<(usize, Range<usize>)> {
+    let index = range.end.line;
+    let span = line_span(lines, index, range)?;
+    let line = lines[index];
+    let open = span.start + line[span.clone()].rfind("](")? + 2;
+    let start = open + usize::from(line[open..].starts_with('<'));
+    let len = line[start..span.end]
+        .find(|c: char| c == ')' || c == '>' || c.is_whitespace())
+        .unwrap_or(span.end - start);
+    Some((index, start..start + len))
+}
+
+/// Byte range of the key of a `[[key#section|text]]` link on its line.
+fn wiki_target(lines: &[&str], range: &InlineRange) -> Option<Range<usize>> {
+    let span = line_span(lines, range.start.line, range)?;
+    let line = lines[range.start.line];
+    let inner = span.start + line[span.clone()].find("[[")? + 2;
+    let target = line[inner..span.end]
+        .split(['|', '#', ']'])
+        .next()
+        .unwrap_or("");
+    let start = inner + target.len() - target.trim_start().len();
+    let target = target.trim();
+    (!target.is_empty()).then(|| start..start + target.len())
+}
+
+/// SARIF 2.1.0 log for code-scanning tools. `extension` is the document file extension used to
+/// turn keys back into paths.
+pub fn to_sarif(diagnostics: &[Diagnostic], extension: &str) -> SarifLog {
+    SarifLog {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: vec![SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: "iwe",
+                    information_uri: "https://github.com/iwe-org/iwe",
+                    rules: Rule::ALL
+                        .iter()
+                        .map(|rule| SarifRule { id: rule.label() })
+                        .collect(),
+                },
+            },
+            results: diagnostics
+                .iter()
+                .map(|d| SarifResult {
+                    rule_id: d.finding.rule.label(),
+                    level: match d.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                        Severity::Info | Severity::Off => "note",
+                    },
+                    message: SarifMessage {
+                        text: d.finding.message.clone(),
+                    },
+                    locations: vec![SarifLocation {
+                        physical_location: SarifPhysicalLocation {
+                            artifact_location: SarifArtifactLocation {
+                                uri: match d.finding.rule {
+                                    Rule::UnreferencedAsset => d.finding.key.to_string(),
+                                    _ => format!("{}.{}", d.finding.key, extension),
+                                },
+                            },
+                            region: d.line.map(|line| SarifRegion { start_line: line }),
+                        },
+                    }],
+                })
+                .collect(),
+        }],
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifRule {
+    id: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    level: &'static str,
+    message: SarifMessage,
+    locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: usize,
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use liwe::graph::Graph;
+use liwe::model::{strip_doc_extension, Key};
+use liwe::operations::{attach_references, AttachTarget, Changes};
+
+use super::{Diagnostic, LinkKind, Source};
+use crate::stats::Rule;
+
+/// Where `--fix` links orphans from: the document an attach action targets, and how that action
+/// wraps the references into a new document when the target does not exist yet.
+pub struct OrphanHub<'a> {
+    pub key: Key,
+    pub document: &'a dyn Fn(&str) -> String,
+}
+
+/// Rewrites that resolve some of the diagnostics, and the diagnostics they resolve.
+#[derive(Debug, Default)]
+pub struct LintFixes {
+    pub changes: Changes,
+    pub fixed: Vec<Diagnostic>,
+}
+
+/// Whether `fix` knows how to resolve findings of `rule`.
+pub fn is_fixable(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::DanglingLink | Rule::AmbiguousWikilink | Rule::HeadingJump | Rule::Orphan
+    )
+}
+
+/// Fixes what it can among `diagnostics`: repoints dangling links to the closest existing key,
+/// lengthens ambiguous wikilinks until they match one document, relevels headings that skip a
+/// level, and links orphans from `hub`. Findings with no confident fix are left alone.
+pub fn fix(graph: &Graph, diagnostics: &[Diagnostic], hub: Option<&OrphanHub>) -> LintFixes {
+    let mut by_key: BTreeMap<&Key, Vec<&Diagnostic>> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        by_key
+            .entry(&diagnostic.finding.key)
+            .or_default()
+            .push(diagnostic);
+    }
+
+    let mut fixes = LintFixes::default();
+    let mut edited: BTreeMap<Key, String> = BTreeMap::new();
+    let mut orphans: Vec<(Key, String)> = Vec::new();
+
+    for (key, diagnostics) in by_key {
+        let text = graph.get_document(key).unwrap_or_default();
+        let source = Source::scan(graph, key, &text);
+        let mut edits: Vec<Edit> = Vec::new();
+        let mut releveled = false;
+
+        for diagnostic in diagnostics {
+            let resolved = match diagnostic.finding.rule {
+                Rule::DanglingLink => fix_dangling(graph, key, &source, diagnostic, &mut edits),
+                Rule::AmbiguousWikilink => fix_ambiguous(graph, &source, diagnostic, &mut edits),
+                Rule::HeadingJump => {
+                    if !releveled {
+                        relevel_headings(&source, &text, &mut edits);
+                        releveled = true;
+                    }
+                    true
+                }
+                Rule::Orphan if hub.is_some_and(|hub| hub.key != *key) => {
+                    let title = graph.get_key_title(key).unwrap_or_else(|| key.to_string());
+                    orphans.push((key.clone(), title));
+                    true
+                }
+                _ => false,
+            };
+            if resolved {
+                fixes.fixed.push(diagnostic.clone());
+            }
+        }
+
+        let rewritten = apply_edits(&text, edits);
+        if rewritten != text {
+            edited.insert(key.clone(), rewritten);
+        }
+    }
+
+    if let (Some(hub), false) = (hub, orphans.is_empty()) {
+        let target = match edited.get(&hub.key) {
+            Some(text) => {
+                let mut patch = graph.new_patch();
+                patch.update_document(hub.key.clone(), text.clone());
+                attach_references(&patch, &hub.key, &orphans)
+            }
+            None => attach_references(graph, &hub.key, &orphans),
+        };
+        match target {
+            AttachTarget::AlreadyAttached => {}
+            AttachTarget::Update(text) => {
+                edited.insert(hub.key.clone(), text);
+            }
+            AttachTarget::Create(body) => {
+                fixes
+                    .changes
+                    .add_create(hub.key.clone(), (hub.document)(&body));
+            }
+        }
+    }
+
+    for (key, text) in edited {
+        fixes.changes.add_update(key, text);
+    }
+    fixes
+}
+
+/// Replacement of a byte range on one 1-based line.
+struct Edit {
+    line: usize,
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+fn fix_dangling(
+    graph: &Graph,
+    key: &Key,
+    source: &Source,
+    diagnostic: &Diagnostic,
+    edits: &mut Vec<Edit>,
+) -> bool {
+    let Some(missing) = &diagnostic.finding.other else {
+        return false;
+    };
+    let Some(closest) = graph.key_index().closest(missing) else {
+        return false;
+    };
+    let mut resolved = false;
+    for link in source
+        .links
+        .iter()
+        .filter(|link| Some(link.line) == diagnostic.line && link.target == *missing)
+    {
+        let Some(span) = &link.span else {
+            continue;
+        };
+        let text = match link.kind {
+            LinkKind::Wiki => graph.key_index().shorten_wiki(&closest),
+            LinkKind::Regular => regular_url(&link.raw, &closest, &key.parent()),
+        };
+        edits.push(Edit {
+            line: link.line,
+            start: span.start,
+            end: span.end,
+            text,
+        });
+        resolved = true;
+    }
+    resolved
+}
+
+fn fix_ambiguous(
+    graph: &Graph,
+    source: &Source,
+    diagnostic: &Diagnostic,
+    edits: &mut Vec<Edit>,
+) -> bool {
+    let Some(target) = &diagnostic.finding.other else {
+        return false;
+    };
+    let unique = graph.key_index().shorten_wiki(target);
+    let mut resolved = false;
+    for link in source.links.iter().filter(|link| {
+        matches!(link.kind, LinkKind::Wiki)
+            && Some(link.line) == diagnostic.line
+            && link.target == *target
+            && link.raw != unique
+    }) {
+        let Some(span) = &link.span else {
+            continue;
+        };
+        edits.push(Edit {
+            line: link.line,
+            start: span.start,
+            end: span.end,
+            text: unique.clone(),
+        });
+        resolved = true;
+    }
+    resolved
+}
+
+/// Relevels every heading so none sits more than one level below the heading it belongs to,
+/// moving the subsections of a jumped heading along with it.
+fn relevel_headings(source: &Source, text: &str, edits: &mut Vec<Edit>) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for heading_line in &source.headings {
+        while stack
+            .last()
+            .is_some_and(|(original, _)| *original >= heading_line.level)
+        {
+            stack.pop();
+        }
+        let level = match stack.last() {
+            Some((_, parent)) => (parent + 1).min(heading_line.level),
+            None => heading_line.level,
+        };
+        stack.push((heading_line.level, level));
+        if level == heading_line.level || !heading_line.atx {
+            continue;
+        }
+        let line = lines[heading_line.line - 1];
+        let start = line.len() - line.trim_start_matches(' ').len();
+        edits.push(Edit {
+            line: heading_line.line,
+            start,
+            end: start + heading_line.level,
+            text: "#".repeat(level),
+        });
+    }
+}
+
+/// The url of a regular link repointed at `target`, keeping the original's absolute or relative
+/// form, file extension and fragment.
+fn regular_url(raw: &str, target: &Key, relative_to: &str) -> String {
+    let (path, fragment) = match raw.split_once('#') {
+        Some((path, fragment)) => (path, format!("#{}", fragment)),
+        None => (raw, String::new()),
+    };
+    let extension = &path[strip_doc_extension(path).len()..];
+    let base = if path.starts_with('/') {
+        format!("/{}", target)
+    } else {
+        target.to_rel_link_url(relative_to)
+    };
+    format!("{}{}{}", base, extension, fragment)
+}
+
+fn apply_edits(text: &str, mut edits: Vec<Edit>) -> String {
+    if edits.is_empty() {
+        return text.to_string();
+    }
+    edits.sort_by_key(|edit| (edit.line, edit.start));
+    edits.dedup_by(|a, b| a.line == b.line && a.start == b.start);
+
+    let mut out = String::with_capacity(text.len());
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let mut line = line.to_string();
+        for edit in edits.iter().rev().filter(|edit| edit.line == index + 1) {
+            line.replace_range(edit.start..edit.end, &edit.text);
+        }
+        out.push_str(&line);
+    }
+    out
+}
@@ -0,0 +1,369 @@
+use std::collections::BTreeMap;
+
+use bm25::{DefaultTokenizer, Tokenizer};
+use itertools::Itertools;
+use liwe::graph::{DatabaseContext, Graph};
+use liwe::model::document::{DocumentBlock, DocumentInline};
+use liwe::model::{InlineRange, Key, Position};
+use liwe::operations::Changes;
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::config::LinkType;
+use crate::search::Language;
+use crate::stats::serialize_key;
+
+/// Frontmatter field with alternative names of a document, a string or a list of strings.
+pub const ALIASES_FIELD: &str = "aliases";
+
+/// A plain-text occurrence of a document's title or alias in another document. Positions are
+/// zero-based, with `start` and `end` counted in UTF-16 code units as LSP does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mention {
+    #[serde(serialize_with = "serialize_key")]
+    pub key: Key,
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub context: String,
+}
+
+impl Mention {
+    pub fn render(&self) -> String {
+        format!(
+            "{}:{}:{}: {}",
+            self.key,
+            self.line + 1,
+            self.start + 1,
+            self.context
+        )
+    }
+}
+
+/// Finds unlinked mentions of a document. Names and prose are split into words and each word is
+/// stemmed with the search tokenizer, so "Gardens" mentions a note titled "Garden". Only paragraph,
+/// list and table text is searched; headings, code, links and images are skipped.
+pub struct MentionFinder<'a> {
+    graph: &'a Graph,
+    tokenizer: DefaultTokenizer,
+}
+
+#[derive(Clone)]
+struct Word {
+    start_byte: usize,
+    end_byte: usize,
+    start: usize,
+    end: usize,
+    token: String,
+}
+
+impl<'a> MentionFinder<'a> {
+    pub fn new(graph: &'a Graph, language: Language) -> Self {
+        let tokenizer = DefaultTokenizer::builder()
+            .language_mode(language)
+            .normalization(true)
+            .stemming(true)
+            .stopwords(false)
+            .build();
+        Self { graph, tokenizer }
+    }
+
+    /// Title and frontmatter aliases of `key`.
+    pub fn names(&self, key: &Key) -> Vec<String> {
+        let aliases = self
+            .graph
+            .frontmatter(key)
+            .and_then(|frontmatter| frontmatter.get(Value::String(ALIASES_FIELD.to_string())))
+            .map(|value| match value {
+                Value::String(alias) => vec![alias.clone()],
+                Value::Sequence(aliases) => aliases
+                    .iter()
+                    .filter_map(|alias| alias.as_str().map(str::to_string))
+                    .collect(),
+                _ => vec![],
+            })
+            .unwrap_or_default();
+
+        self.graph
+            .get_key_title(key)
+            .into_iter()
+            .chain(aliases)
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unique()
+            .collect()
+    }
+
+    /// Mentions of `key` in every other document, ordered by document and position.
+    pub fn find(&self, key: &Key) -> Vec<Mention> {
+        let phrases = self.phrases(key);
+        if phrases.is_empty() {
+            return vec![];
+        }
+
+        self.graph
+            .keys()
+            .into_iter()
+            .filter(|source| source != key)
+            .sorted()
+            .flat_map(|source| self.find_in(&source, &phrases))
+            .collect()
+    }
+
+    /// Mentions of `key` in `source` alone, ordered by position; none when `source` is `key`.
+    pub fn find_in_document(&self, key: &Key, source: &Key) -> Vec<Mention> {
+        let phrases = self.phrases(key);
+        if phrases.is_empty() || source == key {
+            return vec![];
+        }
+        self.find_in(source, &phrases)
+    }
+
+    /// Token sequences of the names, longest first so "Garden Tools" wins over "Garden".
+    fn phrases(&self, key: &Key) -> Vec<Vec<String>> {
+        self.names(key)
+            .iter()
+            .map(|name| {
+                self.words(name)
+                    .into_iter()
+                    .map(|word| word.token)
+                    .collect_vec()
+            })
+            .filter(|tokens| !tokens.is_empty())
+            .unique()
+            .sorted_by(|a, b| b.len().cmp(&a.len()))
+            .collect()
+    }
+
+    fn find_in(&self, source: &Key, phrases: &[Vec<String>]) -> Vec<Mention> {
+        let Some(parser) = (&self.graph).parser(source) else {
+            return vec![];
+        };
+        let lines = parser.content().lines().collect_vec();
+
+        let mut mentions = vec![];
+        for block in parser.document().all_blocks() {
+            if !matches!(
+                block,
+                DocumentBlock::Para(_) | DocumentBlock::Plain(_) | DocumentBlock::Table(_)
+            ) {
+                continue;
+            }
+
+            let mut masked = vec![];
+            for inline in block.child_inlines() {
+                collect_masked(&inline, &mut masked);
+            }
+
+            for line in block.line_range() {
+                let Some(text) = lines.get(line) else {
+                    continue;
+                };
+                mentions.extend(
+                    self.find_in_line(text, phrases)
+                        .into_iter()
+                        .filter(|(first, last)| {
+                            !masked
+                                .iter()
+                                .any(|range| overlaps(range, line, first.start, last.end))
+                        })
+                        .map(|(first, last)| Mention {
+                            key: source.clone(),
+                            line,
+                            start: first.start,
+                            end: last.end,
+                            text: text[first.start_byte..last.end_byte].to_string(),
+                            context: text.trim().to_string(),
+                        }),
+                );
+            }
+        }
+
+        mentions.sort_by_key(|mention| (mention.line, mention.start));
+        mentions.dedup();
+        mentions
+    }
+
+    /// First and last word of every non-overlapping phrase occurrence in `text`.
+    fn find_in_line(&self, text: &str, phrases: &[Vec<String>]) -> Vec<(Word, Word)> {
+        let words = self.words(text);
+        let mut found = vec![];
+
+        let mut index = 0;
+        while index < words.len() {
+            let matched = phrases.iter().find(|phrase| {
+                let Some(candidate) = words.get(index..index + phrase.len()) else {
+                    return false;
+                };
+                candidate
+                    .iter()
+                    .zip(phrase.iter())
+                    .all(|(word, token)| &word.token == token)
+                    && candidate.iter().tuple_windows().all(|(a, b)| {
+                        text[a.end_byte..b.start_byte]
+                            .chars()
+                            .all(char::is_whitespace)
+                    })
+            });
+
+            match matched {
+                Some(phrase) => {
+                    let last = index + phrase.len() - 1;
+                    found.push((words[index].clone(), words[last].clone()));
+                    index += phrase.len();
+                }
+                None => index += 1,
+            }
+        }
+
+        found
+    }
+
+    fn words(&self, text: &str) -> Vec<Word> {
+        let mut words = vec![];
+        let mut current: Option<(usize, usize)> = None;
+        let mut column = 0;
+
+        for (byte, c) in text.char_indices() {
+            if c.is_alphanumeric() || c == '_' {
+                current.get_or_insert((byte, column));
+            } else if let Some((start_byte, start)) = current.take() {
+                words.extend(self.word(text, start_byte, byte, start, column));
+            }
+            column += c.len_utf16();
+        }
+        if let Some((start_byte, start)) = current {
+            words.extend(self.word(text, start_byte, text.len(), start, column));
+        }
+
+        words
+    }
+
+    fn word(
+        &self,
+        text: &str,
+        start_byte: usize,
+        end_byte: usize,
+        start: usize,
+        end: usize,
+    ) -> Option<Word> {
+        let token = self
+            .tokenizer
+            .tokenize(&text[start_byte..end_byte])
+            .join("");
+        (!token.is_empty()).then_some(Word {
+            start_byte,
+            end_byte,
+            start,
+            end,
+            token,
+        })
+    }
+}
+
+/// Inline ranges whose text is not prose: links, images, code and raw markup.
+fn collect_masked(inline: &DocumentInline, masked: &mut Vec<InlineRange>) {
+    match inline {
+        DocumentInline::Link(_)
+        | DocumentInline::Image(_)
+        | DocumentInline::Code(_)
+        | DocumentInline::Math(_)
+        | DocumentInline::RawInline(_) => masked.push(inline.inline_range()),
+        _ => {
+            for child in inline.child_inlines() {
+                collect_masked(child, masked);
+            }
+        }
+    }
+}
+
+fn overlaps(range: &InlineRange, line: usize, start: usize, end: usize) -> bool {
+    range.start
+        < Position {
+            line,
+            character: end,
+        }
+        && Position {
+            line,
+            character: start,
+        } < range.end
+}
+
+/// Updates replacing each mention with a link to `target`, keeping the mentioned text as the link
+/// text.
+pub fn link_mentions(
+    graph: &Graph,
+    target: &Key,
+    mentions: &[Mention],
+    link_type: &LinkType,
+) -> Changes {
+    let options = graph.format_options();
+    let by_key: BTreeMap<&Key, Vec<&Mention>> = mentions
+        .iter()
+        .map(|mention| (&mention.key, mention))
+        .into_group_map()
+        .into_iter()
+        .collect();
+
+    let mut changes = Changes::new();
+    for (key, mentions) in by_key {
+        let Some(content) = graph.get_document(key) else {
+            continue;
+        };
+
+        let mut lines = content
+            .split_inclusive('\n')
+            .map(str::to_string)
+            .collect_vec();
+        for mention in mentions
+            .iter()
+            .sorted_by_key(|mention| (mention.line, std::cmp::Reverse(mention.start)))
+        {
+            let Some(line) = lines.get_mut(mention.line) else {
+                continue;
+            };
+            let (Some(start), Some(end)) = (
+                byte_offset(line, mention.start),
+                byte_offset(line, mention.end),
+            ) else {
+                continue;
+            };
+            if line[start..end] != mention.text {
+                continue;
+            }
+
+            let link = match link_type {
+                LinkType::WikiLink => format!(
+                    "[[{}|{}]]",
+                    graph
+                        .key_index()
+                        .wiki_target(target, options.markdown_options().wiki_link_path),
+                    mention.text
+                ),
+                LinkType::Markdown => format!(
+                    "[{}]({}{})",
+                    mention.text,
+                    target.link_url(&key.parent(), options.refs_path()),
+                    options.refs_extension()
+                ),
+            };
+            line.replace_range(start..end, &link);
+        }
+
+        changes.add_update(key.clone(), lines.concat());
+    }
+    changes
+}
+
+fn byte_offset(line: &str, column: usize) -> Option<usize> {
+    let mut utf16 = 0;
+    for (byte, c) in line.char_indices() {
+        if utf16 == column {
+            return Some(byte);
+        }
+        utf16 += c.len_utf16();
+    }
+    (utf16 == column).then_some(line.len())
+}
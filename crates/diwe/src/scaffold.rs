@@ -0,0 +1,352 @@
+use std::fs::read_to_string;
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use liwe::model::{prepend_frontmatter, Frontmatter};
+use liwe::schema::compile_schema;
+
+/// A required frontmatter field and what its schema says about the value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    /// The `default`, or the `const`, when the schema fixes one.
+    pub default: Option<Value>,
+    /// The `enum` values; empty when any value of the type goes.
+    pub choices: Vec<Value>,
+    /// The JSON Schema `type`, when exactly one is declared.
+    pub kind: Option<String>,
+    pub format: Option<String>,
+    pub description: Option<String>,
+    /// Required fields of an object value.
+    pub fields: Vec<Field>,
+}
+
+impl Field {
+    /// The value the skeleton carries when nobody supplies one: the default, else the first
+    /// enum value, else an empty value of the declared type. `today` fills `format: date`.
+    pub fn placeholder(&self, today: &str) -> Value {
+        if let Some(value) = &self.default {
+            return value.clone();
+        }
+        if let Some(first) = self.choices.first() {
+            return first.clone();
+        }
+        match self.kind.as_deref() {
+            Some("object") => Value::Mapping(
+                self.fields
+                    .iter()
+                    .map(|field| (Value::String(field.name.clone()), field.placeholder(today)))
+                    .collect(),
+            ),
+            Some("array") => Value::Sequence(Vec::new()),
+            Some("boolean") => Value::Bool(false),
+            Some("integer") | Some("number") => Value::Number(0.into()),
+            _ if self.format.as_deref() == Some("date") => Value::String(today.to_string()),
+            _ => Value::String(String::new()),
+        }
+    }
+
+    /// `raw` read as a value of this field: verbatim for strings, parsed as YAML otherwise.
+    pub fn parse(&self, raw: &str) -> Value {
+        let textual = match self.kind.as_deref() {
+            Some("string") => true,
+            Some(_) => false,
+            None => self.choices.iter().all(Value::is_string),
+        };
+        if textual {
+            return Value::String(raw.to_string());
+        }
+        serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+    }
+}
+
+/// A section the skeleton writes, at its heading level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    pub level: usize,
+    /// The header `const`, or the first `enum` value. `None` when only a pattern, or nothing,
+    /// constrains the header; the document title goes there.
+    pub text: Option<String>,
+}
+
+/// What a schema requires of a new document: frontmatter fields and section headings in order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Skeleton {
+    pub fields: Vec<Field>,
+    pub headings: Vec<Heading>,
+}
+
+impl Skeleton {
+    /// Skeleton of the named schemas in `.iwe/schemas` under `dir`. Fields are merged by name,
+    /// the first schema deciding; headings come from the first schema that has any, followed by
+    /// the headings later schemas add.
+    pub fn load(dir: &Path, names: &[&str]) -> Result<Self, Vec<String>> {
+        let mut skeleton = Skeleton::default();
+        let mut errors = Vec::new();
+
+        for name in names {
+            match load_schema(dir, name) {
+                Ok(schema) => skeleton.merge(Skeleton::from_schema(&schema)),
+                Err(schema_errors) => errors.extend(schema_errors),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(skeleton)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Skeleton of a schema document that already compiled.
+    pub fn from_schema(schema: &Value) -> Self {
+        let fields = schema
+            .get("frontmatter")
+            .map(required_fields)
+            .unwrap_or_default();
+
+        let mut headings = Vec::new();
+        if let Some(entries) = schema.get("sections").and_then(Value::as_sequence) {
+            collect_headings(entries, 1, &mut headings);
+        }
+
+        Skeleton { fields, headings }
+    }
+
+    fn merge(&mut self, other: Skeleton) {
+        for field in other.fields {
+            if !self.fields.iter().any(|known| known.name == field.name) {
+                self.fields.push(field);
+            }
+        }
+        for heading in other.headings {
+            if !self.headings.contains(&heading) {
+                self.headings.push(heading);
+            }
+        }
+    }
+
+    /// The document: `frontmatter`, then each heading with `body` below the first one. Without
+    /// headings the document is the title heading and the body.
+    pub fn render(
+        &self,
+        frontmatter: Frontmatter,
+        title: &str,
+        body: &str,
+    ) -> Result<String, String> {
+        let mut parts: Vec<String> = Vec::new();
+        if self.headings.is_empty() {
+            parts.push(format!("# {title}"));
+            push_body(&mut parts, body);
+        }
+        for (index, heading) in self.headings.iter().enumerate() {
+            parts.push(format!(
+                "{} {}",
+                "#".repeat(heading.level),
+                heading.text.as_deref().unwrap_or(title)
+            ));
+            if index == 0 {
+                push_body(&mut parts, body);
+            }
+        }
+
+        let rendered = format!("{}\n", parts.join("\n\n"));
+        prepend_frontmatter(Some(frontmatter), &rendered)
+    }
+}
+
+fn push_body(parts: &mut Vec<String>, body: &str) {
+    let body = body.trim();
+    if !body.is_empty() {
+        parts.push(body.to_string());
+    }
+}
+
+fn load_schema(dir: &Path, name: &str) -> Result<Value, Vec<String>> {
+    let source = read_to_string(dir.join(format!("{name}.yaml"))).map_err(|_| {
+        vec![format!(
+            "schema '{name}': .iwe/schemas/{name}.yaml not found"
+        )]
+    })?;
+
+    compile_schema(&source).map_err(|schema_errors| {
+        schema_errors
+            .into_iter()
+            .map(|error| {
+                if error.pointer.is_empty() {
+                    format!("schema '{name}': {}", error.message)
+                } else {
+                    format!("schema '{name}' {}: {}", error.pointer, error.message)
+                }
+            })
+            .collect::<Vec<_>>()
+    })?;
+
+    serde_yaml::from_str(&source).map_err(|error| vec![format!("schema '{name}': {error}")])
+}
+
+fn required_fields(schema: &Value) -> Vec<Field> {
+    let properties = schema.get("properties");
+    schema
+        .get("required")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(|name| {
+            let property = properties.and_then(|properties| properties.get(name));
+            field(name, property)
+        })
+        .collect()
+}
+
+fn field(name: &str, property: Option<&Value>) -> Field {
+    let text = |keyword: &str| {
+        property
+            .and_then(|property| property.get(keyword))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    Field {
+        name: name.to_string(),
+        default: property.and_then(|property| {
+            property
+                .get("default")
+                .or_else(|| property.get("const"))
+                .cloned()
+        }),
+        choices: property
+            .and_then(|property| property.get("enum"))
+            .and_then(Value::as_sequence)
+            .cloned()
+            .unwrap_or_default(),
+        kind: text("type"),
+        format: text("format"),
+        description: text("description"),
+        fields: property.map(required_fields).unwrap_or_default(),
+    }
+}
+
+fn collect_headings(entries: &[Value], level: usize, headings: &mut Vec<Heading>) {
+    for entry in entries {
+        let occurrences = entry
+            .get("minContains")
+            .and_then(Value::as_u64)
+            .unwrap_or(1);
+        let header = entry.get("header");
+        let text = header
+            .and_then(|header| header.get("const"))
+            .or_else(|| {
+                header
+                    .and_then(|header| header.get("enum"))
+                    .and_then(Value::as_sequence)
+                    .and_then(|values| values.first())
+            })
+            .and_then(Value::as_str)
+            .map(str::to_string);
+
+        for _ in 0..occurrences {
+            headings.push(Heading {
+                level,
+                text: text.clone(),
+            });
+            if let Some(nested) = entry.get("sections").and_then(Value::as_sequence) {
+                collect_headings(nested, level + 1, headings);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    fn skeleton(source: &str) -> Skeleton {
+        Skeleton::from_schema(&serde_yaml::from_str(source).unwrap())
+    }
+
+    #[test]
+    fn required_sections_become_headings_in_order() {
+        let skeleton = skeleton(indoc! {r#"
+            sections:
+              - header: { pattern: "^[A-Z]" }
+                maxContains: 1
+                sections:
+                  - header: { const: Summary }
+                  - header: { enum: [Tasks, Todo] }
+                  - header: { const: Notes }
+                    minContains: 0
+        "#});
+
+        assert_eq!(
+            skeleton.render(Frontmatter::new(), "Weekly", "").unwrap(),
+            indoc! {"
+                # Weekly
+
+                ## Summary
+
+                ## Tasks
+            "}
+        );
+    }
+
+    #[test]
+    fn required_fields_take_defaults_consts_and_enum_placeholders() {
+        let skeleton = skeleton(indoc! {"
+            frontmatter:
+              type: object
+              required: [type, status, priority, date, tags]
+              properties:
+                type: { const: note }
+                status: { enum: [draft, published] }
+                priority: { type: integer, default: 3 }
+                date: { type: string, format: date }
+                tags: { type: array }
+                extra: { type: string }
+        "});
+
+        let values: Vec<Value> = skeleton
+            .fields
+            .iter()
+            .map(|field| field.placeholder("2026-10-18"))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                Value::String("note".to_string()),
+                Value::String("draft".to_string()),
+                Value::Number(3.into()),
+                Value::String("2026-10-18".to_string()),
+                Value::Sequence(Vec::new()),
+            ]
+        );
+        assert_eq!(skeleton.fields[2].parse("5"), Value::Number(5.into()));
+        assert_eq!(
+            skeleton.fields[1].parse("true"),
+            Value::String("true".to_string())
+        );
+    }
+
+    #[test]
+    fn repeated_sections_are_written_min_contains_times() {
+        let skeleton = skeleton(indoc! {"
+            sections:
+              - header: { const: Entry }
+                minContains: 2
+        "});
+
+        assert_eq!(
+            skeleton.render(Frontmatter::new(), "Log", "body").unwrap(),
+            indoc! {"
+                # Entry
+
+                body
+
+                # Entry
+            "}
+        );
+    }
+}
@@ -9,7 +9,9 @@ use liwe::graph::Graph;
 use liwe::markdown::MarkdownReader;
 use liwe::model::Key;
 use liwe::operations::Changes;
-use liwe::schema::{build_document, compile_schema, CompiledSchema, Violation};
+use liwe::schema::{
+    build_document, compile_schema, derive_schema, CompiledSchema, DeriveOptions, Violation,
+};
 
 use crate::config::{schemas_dir, Configuration, SchemaBinding};
 use crate::tokens::count_tokens;
@@ -28,7 +30,11 @@ impl SchemaBindings {
         let mut errors = Vec::new();
 
         for name in names {
-            let patterns = compile_patterns(name, schemas[name].r#match.as_slice(), &mut errors);
+            let patterns = compile_patterns(
+                &format!("schema '{name}'"),
+                schemas[name].r#match.as_slice(),
+                &mut errors,
+            );
             rules.push((name.clone(), patterns));
         }
 
@@ -42,22 +48,27 @@ impl SchemaBindings {
     pub fn schemas_for(&self, key: &str) -> Vec<&str> {
         self.rules
             .iter()
-            .filter(|(_, patterns)| {
-                patterns.iter().fold(false, |bound, (matcher, negated)| {
-                    if matcher.is_match(key) {
-                        !negated
-                    } else {
-                        bound
-                    }
-                })
-            })
+            .filter(|(_, patterns)| matches_patterns(patterns, key))
             .map(|(name, _)| name.as_str())
             .collect()
     }
 }
 
-fn compile_patterns(
-    name: &str,
+/// Whether `key` is selected by `patterns`, gitignore style: the last pattern that matches wins,
+/// and a `!` pattern deselects.
+pub(crate) fn matches_patterns(patterns: &[(GlobMatcher, bool)], key: &str) -> bool {
+    patterns.iter().fold(false, |bound, (matcher, negated)| {
+        if matcher.is_match(key) {
+            !negated
+        } else {
+            bound
+        }
+    })
+}
+
+/// Compiles `match` patterns; `owner` names the config entry in error messages.
+pub(crate) fn compile_patterns(
+    owner: &str,
     patterns: &[String],
     errors: &mut Vec<String>,
 ) -> Vec<(GlobMatcher, bool)> {
@@ -73,9 +84,7 @@ fn compile_patterns(
         let anchored = body.strip_prefix('/').unwrap_or(body);
         match GlobBuilder::new(anchored).literal_separator(true).build() {
             Ok(glob) => matchers.push((glob.compile_matcher(), negated)),
-            Err(error) => errors.push(format!(
-                "schema '{name}': invalid pattern '{pattern}': {error}"
-            )),
+            Err(error) => errors.push(format!("{owner}: invalid pattern '{pattern}': {error}")),
         }
     }
     matchers
@@ -211,6 +220,49 @@ pub fn explain_documents_against_file(
     Ok(out)
 }
 
+/// The YAML of a schema inferred from the documents at `keys`, checked to compile.
+pub fn infer_schema_yaml(
+    graph: &Graph,
+    keys: &[Key],
+    options: &DeriveOptions,
+) -> Result<String, Vec<String>> {
+    let schema = derive_schema(graph, keys, count_tokens, options);
+    let yaml = serde_yaml::to_string(&schema).map_err(|error| vec![error.to_string()])?;
+    compile_schema(&yaml).map_err(|schema_errors| {
+        schema_errors
+            .into_iter()
+            .map(|error| format!("inferred schema {}: {}", error.pointer, error.message))
+            .collect::<Vec<_>>()
+    })?;
+    Ok(yaml)
+}
+
+/// A `match` pattern for a binding that covers `keys`: everything below their deepest shared
+/// directory, or every document when they share none.
+pub fn propose_pattern(keys: &[Key]) -> String {
+    let mut directories = keys.iter().map(|key| {
+        let key = key.to_string();
+        let segments: Vec<String> = key.split('/').map(str::to_string).collect();
+        segments[..segments.len() - 1].to_vec()
+    });
+    let Some(mut shared) = directories.next() else {
+        return "**".to_string();
+    };
+    for directory in directories {
+        let common = shared
+            .iter()
+            .zip(directory.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        shared.truncate(common);
+    }
+    if shared.is_empty() {
+        "**".to_string()
+    } else {
+        format!("{}/**", shared.join("/"))
+    }
+}
+
 pub fn render_reports_text(reports: &[KeyReport]) -> String {
     let mut out = String::new();
     for report in reports {
@@ -365,6 +417,21 @@ mod tests {
         SchemaBindings::compile(&schemas).expect("compiles")
     }
 
+    #[test]
+    fn proposed_pattern_covers_the_deepest_shared_directory() {
+        let keys = |names: &[&str]| names.iter().map(|name| Key::name(name)).collect::<Vec<_>>();
+        assert_eq!(
+            propose_pattern(&keys(&["notes/2026/a", "notes/2026/b"])),
+            "notes/2026/**"
+        );
+        assert_eq!(
+            propose_pattern(&keys(&["notes/2026/a", "notes/b"])),
+            "notes/**"
+        );
+        assert_eq!(propose_pattern(&keys(&["notes/a", "index"])), "**");
+        assert_eq!(propose_pattern(&[]), "**");
+    }
+
     #[test]
     fn negated_patterns_unbind_keys_earlier_patterns_matched() {
         let bindings = bindings(&[(
@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
@@ -7,7 +8,9 @@ use rayon::prelude::*;
 use liwe::graph::Graph;
 use liwe::model::Key;
 use liwe::query::block_update::EvalError;
-use liwe::query::{self, Operation, Outcome, QueryScores, SearchSpec};
+use liwe::query::{
+    self, Explain, FindOp, Operation, Outcome, QueryScores, SearchSpec, SearchTrace,
+};
 
 use crate::search::{rrf_weight, Bm25Index, Language};
 
@@ -67,16 +70,39 @@ fn search_lists(
     index: &Bm25Index,
     candidates: &[Key],
     spec: &SearchSpec,
-) -> Vec<Vec<Key>> {
+) -> (Vec<Vec<Key>>, SearchTrace) {
+    let start = Instant::now();
     let candidate_set: HashSet<&Key> = candidates.iter().collect();
     let mut lists: Vec<Vec<Key>> = Vec::new();
+    let mut trace = SearchTrace {
+        candidates: candidates.len(),
+        ..SearchTrace::default()
+    };
     if let Some(q) = spec.fuzzy.as_deref() {
-        lists.push(fuzzy_ranked(graph, candidates, q));
+        let list = fuzzy_ranked(graph, candidates, q);
+        trace.fuzzy = Some(list.len());
+        lists.push(list);
     }
     if let Some(q) = spec.lexical.as_deref() {
-        lists.push(lexical_ranked(index, &candidate_set, q));
+        let list = lexical_ranked(index, &candidate_set, q);
+        trace.lexical = Some(list.len());
+        lists.push(list);
     }
-    lists
+    trace.elapsed_us = start.elapsed().as_micros() as u64;
+    (lists, trace)
+}
+
+/// Fused RRF scores over every ranker in `spec`, with the per-ranker counts for `explain`.
+fn fused_scores(
+    graph: &Graph,
+    index: &Bm25Index,
+    candidates: &[Key],
+    spec: &SearchSpec,
+) -> (HashMap<Key, f64>, SearchTrace) {
+    let (lists, mut trace) = search_lists(graph, index, candidates, spec);
+    let scores = rrf_scores(&lists);
+    trace.fused = scores.len();
+    (scores, trace)
 }
 
 fn rrf_scores(lists: &[Vec<Key>]) -> HashMap<Key, f64> {
@@ -105,7 +131,18 @@ fn order_by_scores(scores: HashMap<Key, f64>) -> Vec<Key> {
 /// RRF. Candidates with no BM25 hit / no skim score are dropped, so the result is the joint set of
 /// search matches within the candidate set, relevance-ordered, ties broken by key ascending.
 pub fn ranked(graph: &Graph, index: &Bm25Index, candidates: &[Key], spec: &SearchSpec) -> Vec<Key> {
-    order_by_scores(rrf_scores(&search_lists(graph, index, candidates, spec)))
+    ranked_traced(graph, index, candidates, spec).0
+}
+
+/// [`ranked`], also reporting the search stage for `explain`.
+pub fn ranked_traced(
+    graph: &Graph,
+    index: &Bm25Index,
+    candidates: &[Key],
+    spec: &SearchSpec,
+) -> (Vec<Key>, SearchTrace) {
+    let (scores, trace) = fused_scores(graph, index, candidates, spec);
+    (order_by_scores(scores), trace)
 }
 
 /// Restrict `candidates` to the documents matching `spec`, preserving the incoming candidate order.
@@ -117,13 +154,22 @@ pub fn matched(
     candidates: Vec<Key>,
     spec: &SearchSpec,
 ) -> Vec<Key> {
-    let matches: HashSet<Key> = rrf_scores(&search_lists(graph, index, &candidates, spec))
-        .into_keys()
-        .collect();
-    candidates
+    matched_traced(graph, index, candidates, spec).0
+}
+
+/// [`matched`], also reporting the search stage for `explain`.
+pub fn matched_traced(
+    graph: &Graph,
+    index: &Bm25Index,
+    candidates: Vec<Key>,
+    spec: &SearchSpec,
+) -> (Vec<Key>, SearchTrace) {
+    let (scores, trace) = fused_scores(graph, index, &candidates, spec);
+    let kept = candidates
         .into_iter()
-        .filter(|k| matches.contains(k))
-        .collect()
+        .filter(|k| scores.contains_key(k))
+        .collect();
+    (kept, trace)
 }
 
 /// Resolve `spec` over `candidates` into a [`QueryScores`] for the query engine.
@@ -133,7 +179,7 @@ pub fn resolve_scores(
     candidates: &[Key],
     spec: &SearchSpec,
 ) -> QueryScores {
-    QueryScores::from_fused(rrf_scores(&search_lists(graph, index, candidates, spec)))
+    QueryScores::from_fused(fused_scores(graph, index, candidates, spec).0)
 }
 
 /// True when a `lexical` query is present but reduces to no searchable terms after stop-word
@@ -164,30 +210,76 @@ pub fn execute(
     graph: &Graph,
     index: Option<&Bm25Index>,
 ) -> Result<Outcome, EvalError> {
-    let spec = match op {
-        Operation::Find(find) => find.search.as_ref(),
-        _ => None,
-    };
-    match spec {
+    match search_scores(op, graph, index)? {
         None => query::execute(op, graph),
-        Some(spec) => {
-            let Some(index) = index else {
-                return Err(EvalError::SearchIndexMissing);
-            };
-            let filter = match op {
-                Operation::Find(find) => find.filter.as_ref(),
-                _ => None,
-            };
-            let candidates: Vec<Key> = match filter {
-                None => {
-                    let mut k = graph.keys();
-                    k.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
-                    k
-                }
-                Some(f) => query::evaluate(f, graph),
-            };
-            let scores = resolve_scores(graph, index, &candidates, spec);
-            query::execute_with_scores(op, graph, &scores)
-        }
+        Some(scores) => query::execute_with_scores(op, graph, &scores),
     }
 }
+
+/// Execute `op` like [`execute`], returning the [`Explain`] profile with its search stage filled.
+pub fn explain(
+    op: &Operation,
+    graph: &Graph,
+    index: Option<&Bm25Index>,
+) -> Result<(Outcome, Explain), EvalError> {
+    let start = Instant::now();
+    let Operation::Find(FindOp {
+        filter,
+        search: Some(spec),
+        ..
+    }) = op
+    else {
+        let (outcome, mut explain) =
+            query::explain_with_scores(op, graph, &QueryScores::default())?;
+        explain.elapsed_us = start.elapsed().as_micros() as u64;
+        return Ok((outcome, explain));
+    };
+    let Some(index) = index else {
+        return Err(EvalError::SearchIndexMissing);
+    };
+    let (candidates, trace) = match filter {
+        None => (all_keys_sorted(graph), None),
+        Some(f) => {
+            let (keys, trace) = query::evaluate_explained(f, graph);
+            (keys, Some(trace))
+        }
+    };
+    let (scores, search) = fused_scores(graph, index, &candidates, spec);
+    let (outcome, mut explain) = query::explain_with_candidates(
+        op,
+        graph,
+        &QueryScores::from_fused(scores),
+        candidates,
+        trace,
+    )?;
+    explain.search = Some(search);
+    explain.elapsed_us = start.elapsed().as_micros() as u64;
+    Ok((outcome, explain))
+}
+
+fn search_scores(
+    op: &Operation,
+    graph: &Graph,
+    index: Option<&Bm25Index>,
+) -> Result<Option<QueryScores>, EvalError> {
+    let Operation::Find(find) = op else {
+        return Ok(None);
+    };
+    let Some(spec) = find.search.as_ref() else {
+        return Ok(None);
+    };
+    let Some(index) = index else {
+        return Err(EvalError::SearchIndexMissing);
+    };
+    let candidates: Vec<Key> = match find.filter.as_ref() {
+        None => all_keys_sorted(graph),
+        Some(f) => query::evaluate(f, graph),
+    };
+    Ok(Some(resolve_scores(graph, index, &candidates, spec)))
+}
+
+fn all_keys_sorted(graph: &Graph) -> Vec<Key> {
+    let mut keys = graph.keys();
+    keys.sort_by_key(|k| k.to_string());
+    keys
+}
@@ -5,12 +5,14 @@ use itertools::Itertools;
 use rayon::prelude::*;
 use serde::Serialize;
 
+use liwe::graph::analytics::GraphAnalytics;
 use liwe::graph::basic_iter::GraphNodePointer;
 use liwe::graph::{Graph, GraphContext};
 use liwe::model::is_ref_url;
 use liwe::model::node::{Node, NodeIter, NodePointer};
 use liwe::model::Key;
 
+use crate::assets::AssetInventory;
 use crate::search::{Bm25Index, Language};
 use crate::search_query::corpus_text;
 use crate::tokens::count_tokens;
@@ -31,7 +33,10 @@ pub struct BrokenLink {
     pub target_key: Key,
 }
 
-fn serialize_key<S: serde::Serializer>(key: &Key, serializer: S) -> Result<S::Ok, S::Error> {
+pub(crate) fn serialize_key<S: serde::Serializer>(
+    key: &Key,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
     serializer.serialize_str(&key.to_string())
 }
 
@@ -81,22 +86,96 @@ pub fn broken_links(graph: &Graph) -> Vec<BrokenLink> {
     broken
 }
 
+/// [`broken_links`] whose source is `key`, without walking the rest of the graph.
+pub fn broken_links_from(graph: &Graph, key: &Key) -> Vec<BrokenLink> {
+    let inclusions = graph
+        .get_inclusion_edges_in(key)
+        .into_iter()
+        .filter_map(|id| graph.graph_node(id).ref_key());
+    let references = graph
+        .get_reference_edges_in(key)
+        .into_iter()
+        .filter(|target| is_ref_url(&target.to_string()));
+    let mut targets: Vec<Key> = inclusions
+        .chain(references)
+        .filter(|target| graph.maybe_key(target).is_none())
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+        .into_iter()
+        .map(|target_key| BrokenLink {
+            source_key: key.clone(),
+            target_key,
+        })
+        .collect()
+}
+
+/// A document reference to an asset file that does not exist.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingAsset {
+    #[serde(serialize_with = "serialize_key")]
+    pub source_key: Key,
+    pub path: String,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Rule {
     DanglingLink,
     Orphan,
     SimilarPage,
+    DuplicateTitle,
+    AmbiguousWikilink,
+    HeadingJump,
+    EmptySection,
+    SelfLink,
+    AncestorLink,
+    OversizedDocument,
+    MissingFrontmatter,
+    MissingAsset,
+    UnreferencedAsset,
 }
 
 impl Rule {
+    pub const ALL: [Rule; 13] = [
+        Rule::DanglingLink,
+        Rule::Orphan,
+        Rule::SimilarPage,
+        Rule::DuplicateTitle,
+        Rule::AmbiguousWikilink,
+        Rule::HeadingJump,
+        Rule::EmptySection,
+        Rule::SelfLink,
+        Rule::AncestorLink,
+        Rule::OversizedDocument,
+        Rule::MissingFrontmatter,
+        Rule::MissingAsset,
+        Rule::UnreferencedAsset,
+    ];
+
     pub fn label(&self) -> &'static str {
         match self {
             Rule::DanglingLink => "dangling-link",
             Rule::Orphan => "orphan",
             Rule::SimilarPage => "similar-page",
+            Rule::DuplicateTitle => "duplicate-title",
+            Rule::AmbiguousWikilink => "ambiguous-wikilink",
+            Rule::HeadingJump => "heading-jump",
+            Rule::EmptySection => "empty-section",
+            Rule::SelfLink => "self-link",
+            Rule::AncestorLink => "ancestor-link",
+            Rule::OversizedDocument => "oversized-document",
+            Rule::MissingFrontmatter => "missing-frontmatter",
+            Rule::MissingAsset => "missing-asset",
+            Rule::UnreferencedAsset => "unreferenced-asset",
         }
     }
+
+    pub fn from_label(label: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.label() == label)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
@@ -142,6 +221,96 @@ pub struct KeyStatisticsReport {
     pub similar_pages: Vec<SimilarPage>,
 }
 
+/// Graph-wide metrics of one document, as reported by [`GraphAnalyticsReport`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeMetrics {
+    pub key: String,
+    pub title: String,
+    pub pagerank: f64,
+    pub betweenness: f64,
+    pub component: usize,
+    pub community: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentGroup {
+    pub id: usize,
+    pub size: usize,
+    pub keys: Vec<String>,
+}
+
+/// Centrality, connectivity and inclusion cycles of the whole graph. Documents are ordered by
+/// PageRank, highest first.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphAnalyticsReport {
+    pub documents: usize,
+    pub edges: usize,
+    pub components: Vec<DocumentGroup>,
+    pub communities: Vec<DocumentGroup>,
+    pub inclusion_cycles: Vec<Vec<String>>,
+    pub nodes: Vec<NodeMetrics>,
+}
+
+impl GraphAnalyticsReport {
+    pub fn from_graph(graph: &Graph) -> Self {
+        let analytics = GraphAnalytics::compute(graph);
+        let mut nodes: Vec<NodeMetrics> = analytics
+            .keys()
+            .iter()
+            .map(|key| NodeMetrics {
+                key: key.to_string(),
+                title: graph.get_key_title(key).unwrap_or_else(|| key.to_string()),
+                pagerank: analytics.pagerank(key).unwrap_or_default(),
+                betweenness: analytics.betweenness(key).unwrap_or_default(),
+                component: analytics.component(key).unwrap_or_default(),
+                community: analytics.community(key).unwrap_or_default(),
+            })
+            .collect();
+        nodes.sort_by(|a, b| {
+            b.pagerank
+                .total_cmp(&a.pagerank)
+                .then_with(|| a.key.cmp(&b.key))
+        });
+        GraphAnalyticsReport {
+            documents: analytics.keys().len(),
+            edges: analytics.edge_count(),
+            components: document_groups(analytics.components()),
+            communities: document_groups(analytics.communities()),
+            inclusion_cycles: analytics
+                .inclusion_cycles()
+                .iter()
+                .map(|cycle| cycle.iter().map(Key::to_string).collect())
+                .collect(),
+            nodes,
+        }
+    }
+
+    /// One CSV row per document with its metrics.
+    pub fn export_csv<W: Write>(&self, writer: W) -> Result<(), csv::Error> {
+        let mut csv_writer = csv::Writer::from_writer(writer);
+        for node in &self.nodes {
+            csv_writer.serialize(node)?;
+        }
+        csv_writer.flush()?;
+        Ok(())
+    }
+}
+
+fn document_groups(groups: Vec<Vec<Key>>) -> Vec<DocumentGroup> {
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(id, keys)| DocumentGroup {
+            id,
+            size: keys.len(),
+            keys: keys.iter().map(Key::to_string).collect(),
+        })
+        .collect()
+}
+
 /// An `index` page (root `index` or any `<dir>/index`) is an intentional entry point, so it is never
 /// reported as an orphan even when nothing links to it.
 fn is_index_key(key: &str) -> bool {
@@ -152,16 +321,18 @@ pub fn orphan_keys(graph: &Graph) -> Vec<Key> {
     let mut keys: Vec<Key> = graph
         .keys()
         .into_iter()
-        .filter(|key| {
-            !is_index_key(key.as_str())
-                && graph.get_inclusion_edges_to(key).is_empty()
-                && graph.get_reference_edges_to(key).is_empty()
-        })
+        .filter(|key| is_orphan(graph, key))
         .collect();
     keys.sort();
     keys
 }
 
+pub fn is_orphan(graph: &Graph, key: &Key) -> bool {
+    !is_index_key(key.as_str())
+        && graph.get_inclusion_edges_to(key).is_empty()
+        && graph.get_reference_edges_to(key).is_empty()
+}
+
 /// A search index plus per-key token counts, built once and reused for every similarity query in a
 /// run. Building walks each page's corpus text (title + plain body) a single time to feed both the
 /// BM25 index and the token map that drives the size gate.
@@ -528,6 +699,11 @@ pub struct GraphStatistics {
 
     pub broken_link_count: usize,
     pub broken_links: Vec<BrokenLink>,
+
+    pub asset_files: usize,
+    pub asset_references: usize,
+    pub missing_assets: Vec<MissingAsset>,
+    pub unreferenced_assets: Vec<String>,
 }
 
 impl GraphStatistics {
@@ -571,6 +747,22 @@ impl GraphStatistics {
         stats
     }
 
+    /// Fills in the asset counts, missing assets and unreferenced files from `inventory`.
+    pub fn with_assets(mut self, inventory: &AssetInventory) -> Self {
+        self.asset_files = inventory.files.len();
+        self.asset_references = inventory.references.len();
+        self.missing_assets = inventory
+            .missing()
+            .into_iter()
+            .map(|reference| MissingAsset {
+                source_key: reference.key.clone(),
+                path: reference.path.clone(),
+            })
+            .collect();
+        self.unreferenced_assets = inventory.unreferenced().into_iter().cloned().collect();
+        self
+    }
+
     fn aggregate_statistics(
         key_stats: Vec<KeyStatistics>,
         total_nodes: usize,
@@ -718,6 +910,10 @@ impl GraphStatistics {
             most_connected,
             broken_link_count: broken_links.len(),
             broken_links,
+            asset_files: 0,
+            asset_references: 0,
+            missing_assets: Vec::new(),
+            unreferenced_assets: Vec::new(),
         }
     }
 }
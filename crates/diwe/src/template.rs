@@ -0,0 +1,435 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use chrono::{Months, NaiveDate, TimeDelta};
+use log::warn;
+use minijinja::value::{Kwargs, Value};
+use minijinja::{Environment, Error, ErrorKind};
+use serde::Serialize;
+
+use liwe::graph::Graph;
+use liwe::model::Key;
+use liwe::query::computed::frontmatter_for;
+use liwe::query::edges::{included_by, referenced_by};
+use liwe::query::filter::{resolve_path, Resolution};
+use liwe::query::{
+    evaluate, execute, parse_filter_expression, parse_operation, FieldPath, OperationKind, Outcome,
+};
+
+use crate::config::{Configuration, LinkType, DEFAULT_KEY_DATE_FORMAT};
+use crate::graph_from_path;
+
+type GraphLoader = dyn Fn() -> Graph + Send + Sync;
+
+/// A library loaded the first time one of the template functions runs.
+struct Library {
+    graph: OnceLock<Graph>,
+    load: Box<GraphLoader>,
+}
+
+/// Where the template functions find the graph.
+#[derive(Clone)]
+enum Source {
+    Graph(Arc<Graph>),
+    Library(Arc<Library>),
+}
+
+impl Source {
+    fn graph(&self) -> &Graph {
+        match self {
+            Source::Graph(graph) => graph,
+            Source::Library(library) => library.graph.get_or_init(|| (library.load)()),
+        }
+    }
+}
+
+/// The minijinja setup shared by note, attach, transform and journal templates: the
+/// graph-aware functions `query`, `backlinks`, `frontmatter` and `link`, and `date_add`.
+#[derive(Clone)]
+pub struct TemplateEngine {
+    library: Option<Source>,
+    link_type: LinkType,
+    date_formats: Vec<String>,
+    relative_to: String,
+}
+
+#[derive(Serialize)]
+struct Document {
+    key: String,
+    title: String,
+    frontmatter: serde_yaml::Mapping,
+}
+
+impl TemplateEngine {
+    pub fn new(configuration: &Configuration) -> Self {
+        let format_options = configuration.format_options();
+        let mut date_formats: Vec<String> = [
+            configuration.library.date_format.as_deref(),
+            format_options.date_format(),
+            Some(DEFAULT_KEY_DATE_FORMAT),
+            Some("%b %d, %Y"),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+        date_formats.dedup();
+
+        TemplateEngine {
+            library: None,
+            link_type: configuration
+                .completion
+                .link_format
+                .clone()
+                .unwrap_or(LinkType::Markdown),
+            date_formats,
+            relative_to: String::new(),
+        }
+    }
+
+    /// The graph functions read `graph`, shared with the caller.
+    pub fn with_graph(mut self, graph: Arc<Graph>) -> Self {
+        self.library = Some(Source::Graph(graph));
+        self
+    }
+
+    /// The graph functions read the library at `path`, loaded on first use.
+    pub fn with_library(mut self, configuration: &Configuration, path: PathBuf) -> Self {
+        let format_options = configuration.format_options();
+        let title = configuration.library.frontmatter_document_title.clone();
+        let (computed_fields, errors) = configuration.computed_fields();
+        for error in errors {
+            warn!("ignoring {}", error);
+        }
+        self.library = Some(Source::Library(Arc::new(Library {
+            graph: OnceLock::new(),
+            load: Box::new(move || {
+                let mut graph =
+                    graph_from_path(&path, false, format_options.clone(), title.clone());
+                graph.set_computed_fields(computed_fields.clone());
+                graph
+            }),
+        })));
+        self
+    }
+
+    /// `link` writes URLs relative to the directory of `key`, the document being rendered.
+    pub fn relative_to(mut self, key: &Key) -> Self {
+        self.relative_to = key.parent();
+        self
+    }
+
+    pub fn environment<'source>(&self) -> Environment<'source> {
+        let mut environment = Environment::new();
+
+        let date_formats = self.date_formats.clone();
+        environment.add_function("date_add", move |date: String, kwargs: Kwargs| {
+            date_add(&date, &date_formats, &kwargs)
+        });
+
+        let library = self.library.clone();
+        environment.add_function("query", move |yaml: String| {
+            read_graph(&library, "query", |graph| query(graph, &yaml))
+        });
+
+        let library = self.library.clone();
+        environment.add_function("backlinks", move |key: String| {
+            read_graph(&library, "backlinks", |graph| {
+                Ok(backlinks(graph, &Key::name(&key)))
+            })
+        });
+
+        let library = self.library.clone();
+        environment.add_function("frontmatter", move |key: String, path: String| {
+            read_graph(&library, "frontmatter", |graph| {
+                Ok(frontmatter(graph, &Key::name(&key), &path))
+            })
+        });
+
+        let library = self.library.clone();
+        let link_type = self.link_type.clone();
+        let relative_to = self.relative_to.clone();
+        environment.add_function("link", move |key: String, text: Option<String>| {
+            read_graph(&library, "link", |graph| {
+                Ok(link(
+                    graph,
+                    &Key::name(&key),
+                    text,
+                    &link_type,
+                    &relative_to,
+                ))
+            })
+        });
+
+        environment
+    }
+
+    pub fn render(&self, template: &str, context: impl Serialize) -> Result<String, Error> {
+        self.environment()
+            .template_from_str(template)?
+            .render(context)
+    }
+}
+
+/// Calls `read` with the graph behind `function`, or fails when the template has none.
+fn read_graph<R>(
+    library: &Option<Source>,
+    function: &str,
+    read: impl FnOnce(&Graph) -> Result<R, Error>,
+) -> Result<R, Error> {
+    match library {
+        Some(library) => read(library.graph()),
+        None => Err(Error::new(
+            ErrorKind::InvalidOperation,
+            format!("{function}() needs the library, which this template has no access to"),
+        )),
+    }
+}
+
+/// Documents a find operation or a bare filter selects, with their titles and frontmatter. A
+/// filter's matches are ordered by key; an operation keeps its `sort`.
+fn query(graph: &Graph, yaml: &str) -> Result<Value, Error> {
+    let invalid =
+        |error: String| Error::new(ErrorKind::InvalidOperation, format!("query: {error}"));
+
+    let keys = match parse_operation(yaml, OperationKind::Find) {
+        Ok(operation) => match execute(&operation, graph).map_err(|e| invalid(e.to_string()))? {
+            Outcome::Find { matches } => matches.into_iter().map(|found| found.key).collect(),
+            _ => Vec::new(),
+        },
+        Err(_) => {
+            let filter = parse_filter_expression(yaml).map_err(|e| invalid(e.to_string()))?;
+            evaluate(&filter, graph)
+        }
+    };
+
+    Ok(Value::from_serialize(
+        keys.iter()
+            .map(|key| document(graph, key))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn document(graph: &Graph, key: &Key) -> Document {
+    Document {
+        key: key.to_string(),
+        title: graph.get_key_title(key).unwrap_or_else(|| key.to_string()),
+        frontmatter: graph.frontmatter(key).cloned().unwrap_or_default(),
+    }
+}
+
+/// Documents that include or reference `key`, ordered by key.
+fn backlinks(graph: &Graph, key: &Key) -> Value {
+    let mut linking: BTreeMap<String, String> = BTreeMap::new();
+    for edge in included_by(graph, key)
+        .into_iter()
+        .chain(referenced_by(graph, key))
+    {
+        linking.entry(edge.key).or_insert(edge.title);
+    }
+
+    Value::from_serialize(
+        linking
+            .into_iter()
+            .map(|(key, title)| BTreeMap::from([("key", key), ("title", title)]))
+            .collect::<Vec<_>>(),
+    )
+}
+
+fn frontmatter(graph: &Graph, key: &Key, path: &str) -> Value {
+    let path = FieldPath::from_dotted(path);
+    match resolve_path(&frontmatter_for(graph, key, &path), &path) {
+        Resolution::Present(value) => Value::from_serialize(value),
+        Resolution::Missing => Value::from(()),
+    }
+}
+
+fn link(
+    graph: &Graph,
+    key: &Key,
+    text: Option<String>,
+    link_type: &LinkType,
+    relative_to: &str,
+) -> String {
+    let options = graph.format_options();
+    match link_type {
+        LinkType::WikiLink => {
+            let target = graph
+                .key_index()
+                .wiki_target(key, options.markdown_options().wiki_link_path);
+            match text {
+                Some(text) => format!("[[{target}|{text}]]"),
+                None => format!("[[{target}]]"),
+            }
+        }
+        LinkType::Markdown => format!(
+            "[{}]({}{})",
+            text.or_else(|| graph.get_key_title(key))
+                .unwrap_or_else(|| key.to_string()),
+            key.link_url(relative_to, options.refs_path()),
+            options.refs_extension()
+        ),
+    }
+}
+
+/// `date` moved by the `days`, `weeks` and `months` arguments, in the format it was written in.
+fn date_add(date: &str, formats: &[String], kwargs: &Kwargs) -> Result<String, Error> {
+    let days: i64 = kwargs.get::<Option<i64>>("days")?.unwrap_or(0);
+    let weeks: i64 = kwargs.get::<Option<i64>>("weeks")?.unwrap_or(0);
+    let months: i32 = kwargs.get::<Option<i32>>("months")?.unwrap_or(0);
+    kwargs.assert_all_used()?;
+
+    let (parsed, format) = std::iter::once("%Y-%m-%d")
+        .chain(formats.iter().map(String::as_str))
+        .find_map(|format| {
+            NaiveDate::parse_from_str(date, format)
+                .ok()
+                .map(|parsed| (parsed, format))
+        })
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidOperation,
+                format!("date_add: '{date}' is not a date"),
+            )
+        })?;
+
+    let shifted = if months < 0 {
+        parsed.checked_sub_months(Months::new(months.unsigned_abs()))
+    } else {
+        parsed.checked_add_months(Months::new(months as u32))
+    }
+    .and_then(|moved| moved.checked_add_signed(TimeDelta::days(days + weeks * 7)))
+    .ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidOperation,
+            "date_add: date out of range".to_string(),
+        )
+    })?;
+
+    Ok(shifted.format(format).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+    use minijinja::context;
+
+    fn graph() -> Graph {
+        let mut graph = Graph::new();
+        graph.update_key(
+            Key::name("projects/alpha"),
+            indoc! {"
+                ---
+                status: active
+                owner:
+                  name: Ada
+                ---
+                # Alpha
+
+                [Beta](beta)
+            "},
+        );
+        graph.update_key(
+            Key::name("projects/beta"),
+            "---\nstatus: done\n---\n# Beta\n",
+        );
+        graph.update_key(Key::name("index"), "# Index\n\n[Alpha](projects/alpha)\n");
+        graph
+    }
+
+    fn engine() -> TemplateEngine {
+        TemplateEngine::new(&Configuration::default()).with_graph(Arc::new(graph()))
+    }
+
+    #[test]
+    fn query_lists_matching_documents() {
+        let rendered = engine()
+            .render(
+                "{% for doc in query('status: active') %}{{ doc.key }}={{ doc.title }}{% endfor %}",
+                context! {},
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "projects/alpha=Alpha");
+    }
+
+    #[test]
+    fn query_operations_keep_their_sort() {
+        let rendered = engine()
+            .render(
+                "{{ query('filter: { status: { $exists: true } }\nsort: { status: -1 }') | map(attribute='title') | join(',') }}",
+                context! {},
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "Beta,Alpha");
+    }
+
+    #[test]
+    fn backlinks_frontmatter_and_links_read_the_graph() {
+        let rendered = engine()
+            .relative_to(&Key::name("reviews/weekly"))
+            .render(
+                indoc! {"
+                    {{ backlinks('projects/beta') | map(attribute='key') | join(',') }}
+                    {{ frontmatter('projects/alpha', 'owner.name') }}
+                    {{ link('projects/alpha') }}"},
+                context! {},
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "projects/alpha\nAda\n[Alpha](../projects/alpha)");
+    }
+
+    #[test]
+    fn clones_keep_reading_the_shared_graph() {
+        let graph = Arc::new(graph());
+        let engine = TemplateEngine::new(&Configuration::default()).with_graph(graph.clone());
+        let clone = engine.clone();
+        drop(engine);
+
+        assert_eq!(
+            clone
+                .render("{{ link('projects/alpha') }}", context! {})
+                .unwrap(),
+            "[Alpha](projects/alpha)"
+        );
+        assert_eq!(Arc::strong_count(&graph), 2);
+    }
+
+    #[test]
+    fn date_add_keeps_the_date_format() {
+        let engine = TemplateEngine::new(&Configuration::default());
+
+        assert_eq!(
+            engine
+                .render(
+                    "{{ date_add(today, days=7) }}",
+                    context! { today => "2026-10-18" }
+                )
+                .unwrap(),
+            "2026-10-25"
+        );
+        assert_eq!(
+            engine
+                .render(
+                    "{{ date_add(today, months=-1, weeks=1) }}",
+                    context! { today => "Oct 18, 2026" }
+                )
+                .unwrap(),
+            "Sep 25, 2026"
+        );
+    }
+
+    #[test]
+    fn graph_functions_without_a_library_fail() {
+        let error = TemplateEngine::new(&Configuration::default())
+            .render("{{ query('status: active') }}", context! {})
+            .unwrap_err();
+
+        assert!(error.to_string().contains("query() needs the library"));
+    }
+}
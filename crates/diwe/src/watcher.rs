@@ -4,33 +4,14 @@ use std::time::Duration;
 use liwe::model::Key;
 use notify::{Config, Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
 
-use crate::config::Format;
-use crate::fs::{read_md_file, PathFilter};
+use crate::config::{config_path_in, schemas_dir_in, Format};
+use crate::fs::{path_to_key, read_md_file, PathFilter};
 
 pub enum FsChange {
     Update(Key, String),
     Remove(Key),
 }
 
-fn path_to_key(path: &Path, base_path: &Path, format: Format) -> Option<Key> {
-    if path.extension().is_none_or(|ext| ext != format.extension()) {
-        return None;
-    }
-
-    let relative = path.strip_prefix(base_path).ok()?;
-    let key_str = relative
-        .with_extension("")
-        .components()
-        .filter_map(|c| match c {
-            std::path::Component::Normal(os) => Some(os.to_string_lossy().to_string()),
-            _ => None,
-        })
-        .collect::<Vec<_>>()
-        .join("/");
-
-    Some(Key::from_stripped(&key_str))
-}
-
 fn dispatch<H: Fn(FsChange)>(
     base_path: &Path,
     format: Format,
@@ -109,6 +90,92 @@ pub fn start_poll_watcher(
     Some(watcher)
 }
 
+/// Watches the `.iwe` directory of the library rooted at `base_path`, or the root until it
+/// exists, and calls `handler` when
+/// `config.toml` or a schema changes.
+pub fn start_config_watcher(
+    base_path: PathBuf,
+    handler: impl Fn() + Send + 'static,
+) -> Option<impl Watcher + Send> {
+    let base_path = base_path.canonicalize().unwrap_or(base_path);
+    let handler_base = base_path.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                dispatch_config(&handler_base, event, &handler);
+            }
+        },
+        Config::default(),
+    )
+    .ok()?;
+
+    watcher
+        .watch(&config_watch_path(&base_path)?, RecursiveMode::Recursive)
+        .ok()?;
+    Some(watcher)
+}
+
+pub fn start_config_poll_watcher(
+    base_path: PathBuf,
+    interval: Duration,
+    handler: impl Fn() + Send + 'static,
+) -> Option<impl Watcher + Send> {
+    let base_path = base_path.canonicalize().unwrap_or(base_path);
+    let handler_base = base_path.clone();
+    let config = Config::default()
+        .with_poll_interval(interval)
+        .with_compare_contents(true);
+    let mut watcher = PollWatcher::new(
+        move |res: notify::Result<Event>| {
+            if let Ok(event) = res {
+                dispatch_config(&handler_base, event, &handler);
+            }
+        },
+        config,
+    )
+    .ok()?;
+
+    watcher
+        .watch(&config_watch_path(&base_path)?, RecursiveMode::Recursive)
+        .ok()?;
+    Some(watcher)
+}
+
+/// The `.iwe` directory, or the library root while it does not exist so that a configuration
+/// created later is still seen.
+fn config_watch_path(base_path: &Path) -> Option<PathBuf> {
+    let config_dir = config_path_in(base_path).parent()?.to_path_buf();
+    Some(if config_dir.is_dir() {
+        config_dir
+    } else {
+        base_path.to_path_buf()
+    })
+}
+
+fn dispatch_config<H: Fn()>(base_path: &Path, event: Event, handler: &H) {
+    let changed = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    if changed
+        && event
+            .paths
+            .iter()
+            .any(|path| is_config_path(base_path, path))
+    {
+        handler();
+    }
+}
+
+/// Whether `path` is the configuration file or a schema of the library rooted at `base_path`.
+fn is_config_path(base_path: &Path, path: &Path) -> bool {
+    path == config_path_in(base_path)
+        || (path.parent() == Some(schemas_dir_in(base_path).as_path())
+            && path
+                .extension()
+                .is_some_and(|extension| extension == "yaml"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,4 +440,21 @@ mod tests {
 
         assert_eq!(key, Key::from_stripped("note"));
     }
+
+    #[test]
+    fn config_paths_are_the_config_file_and_schemas() {
+        let base = PathBuf::from("base");
+        let iwe = base.join(".iwe");
+
+        assert!(is_config_path(&base, &iwe.join("config.toml")));
+        assert!(is_config_path(
+            &base,
+            &iwe.join("schemas").join("task.yaml")
+        ));
+        assert!(!is_config_path(
+            &base,
+            &iwe.join("schemas").join("notes.txt")
+        ));
+        assert!(!is_config_path(&base, &base.join("config.toml")));
+    }
 }
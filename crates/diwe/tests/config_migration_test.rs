@@ -1,5 +1,6 @@
 use diwe::config::{
-    migrate_v2_to_v3, ActionDefinition, Configuration, DjotOptions, Format, FormatOptions,
+    load_config_in, migrate_v2_to_v3, ActionDefinition, Configuration, DjotOptions, Format,
+    FormatOptions,
 };
 use indoc::indoc;
 
@@ -327,3 +328,22 @@ fn test_migrated_v2_config_parses_correctly() {
         panic!("extract should be Extract type");
     }
 }
+
+#[test]
+fn migration_rewrites_the_config_of_the_loaded_folder() {
+    let folder = tempfile::tempdir().unwrap();
+    let config_path = folder.path().join(".iwe").join("config.toml");
+    std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+    std::fs::write(&config_path, "version = 2\n").unwrap();
+
+    let config = load_config_in(folder.path()).unwrap();
+
+    assert_eq!(config.version, Some(3));
+    let migrated = std::fs::read_to_string(&config_path).unwrap();
+    assert!(migrated.contains("version = 3"), "{}", migrated);
+    assert!(!std::env::current_dir()
+        .unwrap()
+        .join(".iwe")
+        .join("config.toml")
+        .exists());
+}
@@ -0,0 +1,508 @@
+use std::collections::HashMap;
+
+use diwe::assets::AssetInventory;
+use diwe::config::{LintOptions, LintOverride, Patterns};
+use diwe::diff::unified_diff;
+use diwe::lint::fix::{fix, OrphanHub};
+use diwe::lint::{lint, lint_document, to_sarif, Diagnostic, LintRules, Severity};
+use diwe::search::Language;
+use diwe::stats::Rule;
+use indoc::indoc;
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::model::Key;
+use pretty_assertions::assert_eq;
+
+fn graph_with(docs: &[(&str, &str)]) -> Graph {
+    let state = docs
+        .iter()
+        .map(|(key, content)| (key.to_string(), content.to_string()))
+        .collect();
+    Graph::import(&state, MarkdownOptions::default(), None)
+}
+
+fn run(graph: &Graph, options: &LintOptions) -> Vec<String> {
+    let rules = LintRules::compile(options).expect("lint options compile");
+    let mut keys = graph.keys();
+    keys.sort();
+    lint(graph, &rules, Language::English, &keys, None)
+        .iter()
+        .map(Diagnostic::render)
+        .collect()
+}
+
+fn quiet() -> LintOptions {
+    LintOptions {
+        rules: HashMap::from([("orphan".to_string(), Severity::Off)]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn structural_rules_report_lines() {
+    let graph = graph_with(&[
+        (
+            "index",
+            indoc! {"
+                # Index
+
+                [Child](child)
+            "},
+        ),
+        (
+            "child",
+            indoc! {"
+                # Child
+
+                Back to [Index](index) and [here](child).
+
+                ### Deep
+
+                ## Empty
+
+                ## Full
+
+                Text.
+            "},
+        ),
+    ]);
+    assert_eq!(
+        run(&graph, &quiet()),
+        vec![
+            "child:3 warning › ancestor-link: links to 'index', which includes it",
+            "child:3 warning › self-link: links to itself",
+            "child:5 warning › empty-section: section 'Deep' has no content",
+            "child:5 warning › heading-jump: heading 'Deep' jumps from level 1 to 3",
+            "child:7 warning › empty-section: section 'Empty' has no content",
+        ]
+    );
+}
+
+#[test]
+fn code_fences_and_frontmatter_are_skipped() {
+    let graph = graph_with(&[(
+        "doc",
+        indoc! {"
+            ---
+            title: x
+            ---
+            # Doc
+
+            ```
+            # not a heading
+            [x](doc)
+            ```
+        "},
+    )]);
+    assert_eq!(run(&graph, &quiet()), Vec::<String>::new());
+}
+
+#[test]
+fn code_spans_and_indented_code_are_skipped() {
+    let graph = graph_with(&[(
+        "doc",
+        indoc! {"
+            # Doc
+
+            Write `[x](doc)` or ``a `[[doc]]` b`` to link.
+
+                [x](doc)
+                <!-- iwe-disable self-link -->
+
+            - Item
+
+                Nested [here](doc).
+        "},
+    )]);
+    assert_eq!(
+        run(&graph, &quiet()),
+        vec!["doc:10 warning › self-link: links to itself"]
+    );
+}
+
+#[test]
+fn setext_headings_reference_links_and_tilde_fences_are_parsed() {
+    let graph = graph_with(&[(
+        "doc",
+        indoc! {"
+            Doc
+            ===
+
+            ~~~
+            [x](doc)
+            ~~~
+
+            See [me][self] and <https://example.com>.
+
+            Deep
+            ----
+
+            [self]: doc
+        "},
+    )]);
+    assert_eq!(
+        run(&graph, &quiet()),
+        vec![
+            "doc:8 warning › self-link: links to itself",
+            "doc:10 warning › empty-section: section 'Deep' has no content",
+        ]
+    );
+}
+
+#[test]
+fn fix_leaves_code_untouched() {
+    let graph = graph_with(&[
+        ("notes/project", "# Project\n\nText.\n"),
+        (
+            "index",
+            indoc! {"
+                # Index
+
+                See `[P](notes/projct)` and [P](notes/projct).
+
+                    [P](notes/projct)
+            "},
+        ),
+    ]);
+    assert_eq!(
+        fixed(&graph, &quiet(), None),
+        vec![(
+            "index".to_string(),
+            indoc! {"
+                # Index
+
+                See `[P](notes/projct)` and [P](notes/project).
+
+                    [P](notes/projct)
+            "}
+            .to_string()
+        )]
+    );
+}
+
+#[test]
+fn document_lint_matches_library_lint_for_its_rules() {
+    let graph = graph_with(&[
+        ("index", "# Index\n\n[A](a)\n"),
+        (
+            "a",
+            indoc! {"
+                # A
+
+                [Gone](gone) and [[a]].
+
+                ### Deep
+            "},
+        ),
+        ("lonely", "# Lonely\n\nText.\n"),
+    ]);
+    let rules = LintRules::compile(&LintOptions::default()).unwrap();
+    for key in ["a", "lonely"] {
+        let key = Key::name(key);
+        assert_eq!(
+            lint_document(&graph, &rules, &key),
+            lint(
+                &graph,
+                &rules,
+                Language::English,
+                std::slice::from_ref(&key),
+                None
+            )
+        );
+    }
+    let rendered: Vec<String> = lint_document(&graph, &rules, &Key::name("a"))
+        .iter()
+        .map(Diagnostic::render)
+        .collect();
+    assert_eq!(
+        rendered,
+        vec![
+            "a:3 error › dangling-link: links to missing 'gone'",
+            "a:3 warning › self-link: links to itself",
+            "a:5 warning › empty-section: section 'Deep' has no content",
+            "a:5 warning › heading-jump: heading 'Deep' jumps from level 1 to 3",
+        ]
+    );
+}
+
+#[test]
+fn duplicate_titles_and_ambiguous_wikilinks() {
+    let graph = graph_with(&[
+        ("a/note", "# Note\n\nA\n"),
+        ("b/note", "# Note\n\nB\n"),
+        ("index", "# Index\n\nSee [[note]].\n"),
+    ]);
+    assert_eq!(
+        run(&graph, &quiet()),
+        vec![
+            "a/note:1 warning › duplicate-title: title 'Note' is also used by 'b/note'",
+            "b/note:1 warning › duplicate-title: title 'Note' is also used by 'a/note'",
+            "index:3 warning › ambiguous-wikilink: [[note]] matches a/note, b/note; resolves to 'a/note'",
+        ]
+    );
+}
+
+#[test]
+fn thresholds_and_required_frontmatter() {
+    let graph = graph_with(&[
+        (
+            "long",
+            "# Long\n\none two three four five six seven eight\n",
+        ),
+        ("tagged", "---\nstatus: done\n---\n# Tagged\n"),
+    ]);
+    let options = LintOptions {
+        max_tokens: Some(5),
+        required_frontmatter: vec!["status".to_string()],
+        ..quiet()
+    };
+    assert_eq!(
+        run(&graph, &options),
+        vec![
+            "long warning › missing-frontmatter: missing required frontmatter 'status'",
+            "long warning › oversized-document: 12 tokens, over the 5 budget",
+            "tagged warning › oversized-document: 9 tokens, over the 5 budget",
+            "tagged:4 warning › empty-section: section 'Tagged' has no content",
+        ]
+    );
+}
+
+#[test]
+fn severities_and_path_overrides() {
+    let graph = graph_with(&[
+        ("home", "# Home\n\n[gone](missing)\n"),
+        ("journal/day", "# Day\n\nNotes.\n"),
+    ]);
+    let options = LintOptions {
+        rules: HashMap::from([("dangling-link".to_string(), Severity::Warning)]),
+        overrides: vec![LintOverride {
+            r#match: Patterns::One("journal/**".to_string()),
+            rules: HashMap::from([("orphan".to_string(), Severity::Off)]),
+        }],
+        ..Default::default()
+    };
+    assert_eq!(
+        run(&graph, &options),
+        vec![
+            "home warning › orphan: no page links here",
+            "home:3 warning › dangling-link: links to missing 'missing'",
+        ]
+    );
+}
+
+#[test]
+fn unknown_rules_are_config_errors() {
+    let options = LintOptions {
+        rules: HashMap::from([("no-such-rule".to_string(), Severity::Error)]),
+        ..Default::default()
+    };
+    let errors = LintRules::compile(&options).unwrap_err();
+    assert_eq!(
+        errors,
+        vec!["[lint.rules]: unknown lint rule 'no-such-rule'".to_string()]
+    );
+}
+
+#[test]
+fn inline_comments_suppress_rules() {
+    let graph = graph_with(&[(
+        "doc",
+        indoc! {"
+            # Doc
+
+            A [first](doc) link.
+
+            Then [second](doc) link. <!-- iwe-disable self-link -->
+
+            And [third](doc). <!-- iwe-enable self-link -->
+
+            <!-- iwe-disable -->
+
+            ## Empty
+        "},
+    )]);
+    assert_eq!(
+        run(&graph, &quiet()),
+        vec![
+            "doc:3 warning › self-link: links to itself",
+            "doc:7 warning › self-link: links to itself",
+        ]
+    );
+}
+
+#[test]
+fn sarif_carries_levels_and_regions() {
+    let graph = graph_with(&[("index", "# Index\n\n[gone](missing)\n")]);
+    let rules = LintRules::compile(&quiet()).unwrap();
+    let diagnostics = lint(
+        &graph,
+        &rules,
+        Language::English,
+        &[Key::name("index")],
+        None,
+    );
+    let sarif = serde_json::to_value(to_sarif(&diagnostics, "md")).unwrap();
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(result["ruleId"], "dangling-link");
+    assert_eq!(result["level"], "error");
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+        "index.md"
+    );
+    assert_eq!(
+        result["locations"][0]["physicalLocation"]["region"]["startLine"],
+        3
+    );
+    assert!(diagnostics
+        .iter()
+        .all(|d| d.finding.rule == Rule::DanglingLink));
+}
+
+#[test]
+fn reports_missing_and_unreferenced_assets() {
+    let graph = graph_with(&[
+        (
+            "index",
+            "# Index\n\n![chart](img/chart.png)\n\nSee [spec](docs/spec.pdf).\n",
+        ),
+        (
+            "docs/guide",
+            "# Guide\n\n![shot](../img/shot.png) and [index](../index)\n",
+        ),
+    ]);
+    let inventory = AssetInventory::new(&graph, vec!["img/chart.png".into(), "img/old.png".into()]);
+    let rules = LintRules::compile(&quiet()).unwrap();
+    let mut keys = graph.keys();
+    keys.sort();
+
+    let render = |keys: &[Key]| -> Vec<String> {
+        lint(&graph, &rules, Language::English, keys, Some(&inventory))
+            .iter()
+            .map(Diagnostic::render)
+            .collect()
+    };
+
+    assert_eq!(
+        render(&keys),
+        vec![
+            "docs/guide:3 error › missing-asset: links to missing asset 'img/shot.png'",
+            "img/old.png warning › unreferenced-asset: no page links to this file",
+            "index:5 error › missing-asset: links to missing asset 'docs/spec.pdf'",
+        ]
+    );
+    assert_eq!(
+        render(&[Key::name("index")]),
+        vec!["index:5 error › missing-asset: links to missing asset 'docs/spec.pdf'"]
+    );
+}
+
+fn fixed(graph: &Graph, options: &LintOptions, hub: Option<&OrphanHub>) -> Vec<(String, String)> {
+    let rules = LintRules::compile(options).unwrap();
+    let mut keys = graph.keys();
+    keys.sort();
+    let diagnostics = lint(graph, &rules, Language::English, &keys, None);
+    let changes = fix(graph, &diagnostics, hub).changes;
+    changes
+        .creates
+        .into_iter()
+        .chain(changes.updates)
+        .map(|(key, text)| (key.to_string(), text))
+        .collect()
+}
+
+#[test]
+fn fix_repoints_links_and_relevels_headings() {
+    let graph = graph_with(&[
+        ("notes/project", "# Project\n\nText.\n"),
+        ("a/note", "# A\n\nA\n"),
+        ("b/note", "# B\n\nB\n"),
+        (
+            "index",
+            indoc! {"
+                # Index
+
+                [P](notes/projct.md#top), [[note]] and [[nowhere-near]].
+
+                ### Deep
+
+                #### Deeper
+
+                Text.
+
+                ## Next
+
+                Text.
+            "},
+        ),
+    ]);
+    assert_eq!(
+        fixed(&graph, &quiet(), None),
+        vec![(
+            "index".to_string(),
+            indoc! {"
+                # Index
+
+                [P](notes/project.md#top), [[a/note]] and [[nowhere-near]].
+
+                ## Deep
+
+                ### Deeper
+
+                Text.
+
+                ## Next
+
+                Text.
+            "}
+            .to_string()
+        )]
+    );
+}
+
+#[test]
+fn fix_attaches_orphans_to_hub() {
+    let graph = graph_with(&[("one", "# One\n\nText.\n"), ("two", "# Two\n\nText.\n")]);
+    let options = LintOptions {
+        rules: HashMap::from([("empty-section".to_string(), Severity::Off)]),
+        ..Default::default()
+    };
+    let document = |body: &str| format!("# Hub\n\n{}", body);
+    let hub = OrphanHub {
+        key: Key::name("hub"),
+        document: &document,
+    };
+    assert_eq!(
+        fixed(&graph, &options, Some(&hub)),
+        vec![(
+            "hub".to_string(),
+            "# Hub\n\n[One](one)\n\n[Two](two)\n".to_string()
+        )]
+    );
+}
+
+#[test]
+fn unified_diff_shows_hunks_with_context() {
+    let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nJ\n";
+    assert_eq!(
+        unified_diff("x.md", "x.md", old, new),
+        indoc! {"
+            --- a/x.md
+            +++ b/x.md
+            @@ -1,5 +1,5 @@
+             a
+            -b
+            +B
+             c
+             d
+             e
+            @@ -7,4 +7,4 @@
+             g
+             h
+             i
+            -j
+            +J
+        "}
+    );
+    assert_eq!(unified_diff("x.md", "x.md", old, old), "");
+}
@@ -0,0 +1,164 @@
+use diwe::config::LinkType;
+use diwe::mentions::{link_mentions, MentionFinder};
+use diwe::search::Language;
+use indoc::indoc;
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::model::Key;
+use pretty_assertions::assert_eq;
+
+fn graph_with(docs: &[(&str, &str)]) -> Graph {
+    let state = docs
+        .iter()
+        .map(|(key, content)| (key.to_string(), content.to_string()))
+        .collect();
+    Graph::import(&state, MarkdownOptions::default(), None)
+}
+
+fn mentions(graph: &Graph, key: &str) -> Vec<String> {
+    MentionFinder::new(graph, Language::English)
+        .find(&Key::name(key))
+        .iter()
+        .map(|mention| {
+            format!(
+                "{}:{}:{}-{} {}",
+                mention.key, mention.line, mention.start, mention.end, mention.text
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn finds_title_as_whole_words() {
+    let graph = graph_with(&[
+        ("garden", "# Garden\n"),
+        (
+            "notes",
+            indoc! {"
+                # Notes
+
+                Work in the garden today. Gardening is fun.
+
+                - back to the Garden
+            "},
+        ),
+    ]);
+
+    assert_eq!(
+        mentions(&graph, "garden"),
+        vec![
+            "notes:2:12-18 garden".to_string(),
+            "notes:2:26-35 Gardening".to_string(),
+            "notes:4:14-20 Garden".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn stems_multi_word_titles() {
+    let graph = graph_with(&[
+        ("tools", "# Garden Tools\n"),
+        (
+            "notes",
+            "# Notes\n\nSharpen the garden tool, then the tools.\n",
+        ),
+    ]);
+
+    assert_eq!(
+        mentions(&graph, "tools"),
+        vec!["notes:2:12-23 garden tool".to_string()]
+    );
+}
+
+#[test]
+fn finds_frontmatter_aliases() {
+    let graph = graph_with(&[
+        (
+            "kubernetes",
+            indoc! {"
+                ---
+                aliases:
+                  - k8s
+                  - kube
+                ---
+
+                # Kubernetes
+            "},
+        ),
+        ("notes", "# Notes\n\nDeploy to k8s with kube tooling.\n"),
+    ]);
+
+    assert_eq!(
+        mentions(&graph, "kubernetes"),
+        vec![
+            "notes:2:10-13 k8s".to_string(),
+            "notes:2:19-23 kube".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn skips_links_code_and_headings() {
+    let graph = graph_with(&[
+        ("garden", "# Garden\n"),
+        (
+            "notes",
+            indoc! {"
+                # Garden notes
+
+                See [garden](garden) and [[garden]] or `garden`.
+
+                ```
+                garden
+                ```
+            "},
+        ),
+    ]);
+
+    assert_eq!(mentions(&graph, "garden"), Vec::<String>::new());
+}
+
+#[test]
+fn skips_the_document_itself() {
+    let graph = graph_with(&[("garden", "# Garden\n\nThe garden grows.\n")]);
+
+    assert_eq!(mentions(&graph, "garden"), Vec::<String>::new());
+}
+
+#[test]
+fn links_mentions_with_markdown_links() {
+    let graph = graph_with(&[
+        ("garden", "# Garden\n"),
+        ("notes", "# Notes\n\nThe garden and the Garden.\n"),
+    ]);
+    let found = MentionFinder::new(&graph, Language::English).find(&Key::name("garden"));
+
+    let changes = link_mentions(&graph, &Key::name("garden"), &found, &LinkType::Markdown);
+
+    assert_eq!(
+        changes.updates,
+        vec![(
+            Key::name("notes"),
+            "# Notes\n\nThe [garden](garden) and the [Garden](garden).\n".to_string()
+        )]
+    );
+}
+
+#[test]
+fn links_mentions_with_wiki_links() {
+    let graph = graph_with(&[
+        ("garden", "# Garden\n"),
+        ("notes", "# Notes\n\nThe gardens.\n"),
+    ]);
+    let found = MentionFinder::new(&graph, Language::English).find(&Key::name("garden"));
+
+    let changes = link_mentions(&graph, &Key::name("garden"), &found, &LinkType::WikiLink);
+
+    assert_eq!(
+        changes.updates,
+        vec![(
+            Key::name("notes"),
+            "# Notes\n\nThe [[garden|gardens]].\n".to_string()
+        )]
+    );
+}
@@ -16,6 +16,12 @@ pub mod new {
     pub const AFTER_HELP: &str = include_str!("../help/new/after_help.txt");
 }
 
+pub mod journal {
+    pub const ABOUT: &str = include_str!("../help/journal/about.txt");
+    pub const LONG_ABOUT: &str = include_str!("../help/journal/long_about.txt");
+    pub const AFTER_HELP: &str = include_str!("../help/journal/after_help.txt");
+}
+
 pub mod retrieve {
     pub const ABOUT: &str = include_str!("../help/retrieve/about.txt");
     pub const LONG_ABOUT: &str = include_str!("../help/retrieve/long_about.txt");
@@ -70,6 +76,24 @@ pub mod stats {
     pub const AFTER_HELP: &str = include_str!("../help/stats/after_help.txt");
 }
 
+pub mod lint {
+    pub const ABOUT: &str = include_str!("../help/lint/about.txt");
+    pub const LONG_ABOUT: &str = include_str!("../help/lint/long_about.txt");
+    pub const AFTER_HELP: &str = include_str!("../help/lint/after_help.txt");
+}
+
+pub mod mentions {
+    pub const ABOUT: &str = include_str!("../help/mentions/about.txt");
+    pub const LONG_ABOUT: &str = include_str!("../help/mentions/long_about.txt");
+    pub const AFTER_HELP: &str = include_str!("../help/mentions/after_help.txt");
+}
+
+pub mod assets {
+    pub const ABOUT: &str = include_str!("../help/assets/about.txt");
+    pub const LONG_ABOUT: &str = include_str!("../help/assets/long_about.txt");
+    pub const AFTER_HELP: &str = include_str!("../help/assets/after_help.txt");
+}
+
 pub mod rename {
     pub const ABOUT: &str = include_str!("../help/rename/about.txt");
     pub const LONG_ABOUT: &str = include_str!("../help/rename/long_about.txt");
@@ -1,6 +1,7 @@
 use std::env;
 use std::path::PathBuf;
 use std::process::Command as ProcessCommand;
+use std::sync::Arc;
 
 use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::generate;
@@ -9,8 +10,13 @@ use clap_complete_nushell::Nushell;
 mod help;
 use itertools::Itertools;
 
+use diwe::assets::{AssetEntry, AssetInventory, AssetStatus};
 use diwe::config::{load_config, ActionDefinition, Configuration, InlineType, LinkType};
+use diwe::diff::{changes_diff, unified_diff};
 use diwe::graph_from_path;
+use diwe::lint::fix::{fix, OrphanHub};
+use diwe::lint::{lint, to_sarif, LintRules, Severity};
+use diwe::mentions::{link_mentions, MentionFinder};
 use diwe::schema::{
     explain_documents, explain_documents_against_file, pending_from_changes, render_reports_text,
     validate_pending_documents,
@@ -20,6 +26,7 @@ use diwe::stats::{
     graph_findings, mutation_findings, KeyStatisticsReport, SimilarityIndex,
     DEFAULT_SIMILARITY_THRESHOLD,
 };
+use diwe::template::TemplateEngine;
 use diwe::tokens::Truncation;
 use iwe::export::{dot_details_exporter, dot_exporter, graph_data};
 use iwe::filter_args::FilterArgs;
@@ -27,13 +34,14 @@ use iwe::find::{DocumentFinder, FindOptions};
 use iwe::init::{current_root, init_library, InitOptions, Overrides};
 use iwe::new::{
     read_stdin, read_stdin_if_available, write_document, ContentOptions, CreateOptions,
-    DocumentCreator, IfExists, PreparedDocument, Variables, BODY_VARIABLE, LEGACY_BODY_VARIABLE,
-    RESERVED_VARIABLES, TITLE_VARIABLE,
+    DocumentCreator, IfExists, PreparedDocument, Scaffold, Variables, BODY_VARIABLE,
+    LEGACY_BODY_VARIABLE, RESERVED_VARIABLES, TITLE_VARIABLE,
 };
 use iwe::projection_args::{parse_projection_extend, parse_projection_replace};
 use iwe::render::{FindBlockRenderer, RetrieveRenderer};
 use iwe::retrieve::{DocumentReader, RetrieveOptions};
-use iwe::stats::{render_stats, GraphStatistics};
+use iwe::stats::{render_graph_stats, render_stats, GraphAnalyticsReport, GraphStatistics};
+use liwe::graph::analytics::MetricCache;
 use liwe::graph::{Graph, GraphContext};
 use liwe::locale::get_locale;
 use liwe::model::node::NodePointer;
@@ -47,6 +55,7 @@ use liwe::operations::{
 use liwe::query::block::{
     parse_block_predicate, BlockOp, BlockPredicate, BlockRegex, MatchesSource,
 };
+use liwe::query::live::refresh;
 use liwe::query::{
     FieldPath, Filter, Projection as QueryProjection, ProjectionField, ProjectionSource,
     Sort as QuerySort, SortDir,
@@ -74,6 +83,7 @@ enum Command {
     Init(Init),
     Create(Create),
     New(New),
+    Journal(Journal),
     Retrieve(Retrieve),
     Find(Find),
     Count(Count),
@@ -83,6 +93,9 @@ enum Command {
     Export(Export),
     Schema(Schema),
     Stats(Stats),
+    Lint(Lint),
+    Mentions(Mentions),
+    Assets(Assets),
     Rename(Rename),
     Delete(Delete),
     Extract(Extract),
@@ -327,6 +340,12 @@ struct Find {
     #[clap(long, short = 'f', value_enum, default_value = "markdown")]
     format: FindFormat,
 
+    #[clap(
+        long,
+        help = "Print the evaluation profile (filter tree, candidate counts, timings) instead of the results. YAML unless -f json."
+    )]
+    explain: bool,
+
     #[clap(flatten)]
     selector: FilterArgs,
 }
@@ -363,7 +382,31 @@ struct Count {
     long_about = help::normalize::LONG_ABOUT,
     after_help = help::normalize::AFTER_HELP
 )]
-struct Normalize {}
+struct Normalize {
+    #[clap(help = "Files or directories to normalize. Defaults to the whole library.")]
+    paths: Vec<PathBuf>,
+
+    #[clap(
+        long,
+        help = "List the files whose normalized form differs and exit with status 1, without writing"
+    )]
+    check: bool,
+
+    #[clap(
+        long,
+        help = "Print the changes normalize would make as a unified diff, without writing"
+    )]
+    diff: bool,
+
+    #[clap(long, help = "Preview without writing")]
+    dry_run: bool,
+
+    #[clap(long, help = "Suppress progress output")]
+    quiet: bool,
+
+    #[clap(flatten)]
+    selector: FilterArgs,
+}
 
 #[derive(Debug, Args)]
 #[clap(
@@ -475,6 +518,14 @@ struct Create {
     )]
     set: Vec<String>,
 
+    #[clap(
+        long,
+        value_name = "NAME",
+        requires = "template",
+        help = "Write the skeleton of the named schema in .iwe/schemas instead of the template's document: its required frontmatter and sections. The template still derives the key. Requires --template."
+    )]
+    schema: Option<String>,
+
     #[clap(
         long,
         short = 'i',
@@ -509,6 +560,28 @@ struct New {
     #[clap(long, short = 'c', help = "Content for the new document")]
     content: Option<String>,
 
+    #[clap(
+        long,
+        value_name = "NAME",
+        help = "Write the skeleton of the named schema in .iwe/schemas: its required frontmatter and sections. Without --template, the schemas bound to the new key are used automatically."
+    )]
+    schema: Option<String>,
+
+    #[clap(
+        long,
+        conflicts_with = "schema",
+        help = "Use the template even when the new key matches a [schemas] binding"
+    )]
+    no_schema: bool,
+
+    #[clap(
+        long,
+        value_name = "NAME=VALUE",
+        allow_hyphen_values = true,
+        help = "Set a template variable or a required frontmatter field of the schema, NAME=VALUE. Repeatable."
+    )]
+    var: Vec<String>,
+
     #[clap(
         long,
         short = 'k',
@@ -528,6 +601,23 @@ struct New {
     edit: bool,
 }
 
+#[derive(Debug, Args)]
+#[clap(
+    about = help::journal::ABOUT,
+    long_about = help::journal::LONG_ABOUT,
+    after_help = help::journal::AFTER_HELP
+)]
+struct Journal {
+    #[clap(
+        default_value = "today",
+        help = "The period to open: today, yesterday, tomorrow, [last-|next-]week, [last-|next-]month, or a YYYY-MM-DD, YYYY-Www or YYYY-MM date"
+    )]
+    when: String,
+
+    #[clap(long, short = 'e', help = "Open the note in $EDITOR")]
+    edit: bool,
+}
+
 #[derive(Debug, Args)]
 #[clap(
     about = help::tree::ABOUT,
@@ -596,6 +686,7 @@ struct Schema {
 #[derive(Debug, Subcommand)]
 enum SchemaCommand {
     Validate(SchemaValidate),
+    Infer(SchemaInfer),
 }
 
 #[derive(Debug, Args)]
@@ -616,6 +707,60 @@ struct SchemaFields {
     selector: FilterArgs,
 }
 
+#[derive(Debug, Args)]
+#[clap(
+    about = "Infer the frontmatter schema, or write a document schema with --emit",
+    long_about = "Infer the frontmatter schema of the selected documents, as `iwe schema` does. With --emit NAME, write a document schema to .iwe/schemas/NAME.yaml instead: frontmatter fields required by coverage, common section headers in their usual order, and token budgets at a percentile of the observed sizes. A [schemas] binding for it is proposed."
+)]
+struct SchemaInfer {
+    #[clap(
+        long,
+        value_name = "NAME",
+        help = "Write the inferred document schema to .iwe/schemas/NAME.yaml"
+    )]
+    emit: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "PCT",
+        default_value = "90",
+        requires = "emit",
+        help = "Coverage in percent at which a frontmatter field or section becomes required"
+    )]
+    required: f64,
+
+    #[clap(
+        long,
+        value_name = "PCT",
+        default_value = "50",
+        requires = "emit",
+        help = "Coverage in percent at which a section header is listed"
+    )]
+    common: f64,
+
+    #[clap(
+        long,
+        value_name = "PCT",
+        default_value = "95",
+        requires = "emit",
+        help = "Percentile of the observed token counts used for maxTokens budgets"
+    )]
+    percentile: f64,
+
+    #[clap(long, requires = "emit", help = "Overwrite an existing schema file")]
+    force: bool,
+
+    #[clap(
+        long,
+        requires = "emit",
+        help = "Print the schema instead of writing it"
+    )]
+    dry_run: bool,
+
+    #[clap(flatten)]
+    fields: SchemaFields,
+}
+
 #[derive(Debug, Args)]
 #[clap(about = "Validate documents against their configured schemas")]
 struct SchemaValidate {
@@ -644,6 +789,134 @@ struct SchemaValidate {
     selector: FilterArgs,
 }
 
+#[derive(Debug, Args)]
+#[clap(
+    about = help::lint::ABOUT,
+    long_about = help::lint::LONG_ABOUT,
+    after_help = help::lint::AFTER_HELP
+)]
+struct Lint {
+    #[clap(
+        long,
+        short = 'f',
+        value_enum,
+        default_value = "text",
+        help = "Output format for findings"
+    )]
+    format: LintFormat,
+
+    #[clap(long, help = "Exit with status 1 on warnings as well as errors")]
+    deny_warnings: bool,
+
+    #[clap(
+        long,
+        help = "Rewrite documents to fix what can be fixed, then report what is left"
+    )]
+    fix: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "fix",
+        help = "Print the fixes --fix would make as a unified diff, without writing"
+    )]
+    diff: bool,
+
+    #[clap(flatten)]
+    selector: FilterArgs,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum LintFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+#[derive(Debug, Args)]
+#[clap(
+    about = help::mentions::ABOUT,
+    long_about = help::mentions::LONG_ABOUT,
+    after_help = help::mentions::AFTER_HELP
+)]
+struct Mentions {
+    #[clap(long, short = 'k', help = "Key of the document whose mentions to find")]
+    key: String,
+
+    #[clap(
+        long,
+        short = 'f',
+        value_enum,
+        default_value = "text",
+        help = "Output format for mentions"
+    )]
+    format: MentionsFormat,
+
+    #[clap(long, help = "Rewrite every mention as a link to the document")]
+    link: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "link",
+        help = "Print the rewrites --link would make as a unified diff, without writing"
+    )]
+    diff: bool,
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum MentionsFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Args)]
+#[clap(
+    about = help::assets::ABOUT,
+    long_about = help::assets::LONG_ABOUT,
+    after_help = help::assets::AFTER_HELP
+)]
+struct Assets {
+    #[command(subcommand)]
+    command: Option<AssetsCommand>,
+
+    #[clap(
+        long,
+        short = 'f',
+        value_enum,
+        default_value = "text",
+        help = "Output format for the asset list"
+    )]
+    format: AssetsFormat,
+
+    #[clap(
+        long,
+        conflicts_with = "unreferenced",
+        help = "List only referenced files that do not exist"
+    )]
+    missing: bool,
+
+    #[clap(long, help = "List only files no document references")]
+    unreferenced: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum AssetsCommand {
+    #[clap(about = "List, and with --yes delete, the asset files no document references")]
+    Gc {
+        #[clap(
+            long,
+            visible_alias = "apply",
+            help = "Delete the listed files. Without it gc only prints them"
+        )]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum AssetsFormat {
+    Text,
+    Json,
+}
+
 #[derive(Debug, Clone, clap::ValueEnum)]
 enum SchemaFormat {
     Markdown,
@@ -699,6 +972,26 @@ enum StatsCommand {
         )]
         threshold: f32,
     },
+    #[clap(
+        about = "Rank documents by PageRank and betweenness, and list components, communities and inclusion cycles"
+    )]
+    Graph {
+        #[clap(
+            long,
+            short = 'f',
+            value_enum,
+            default_value = "markdown",
+            help = "Output format. csv prints one row of metrics per document."
+        )]
+        format: StatsFormat,
+
+        #[clap(
+            long,
+            default_value_t = 10,
+            help = "Entries listed per ranking in markdown output"
+        )]
+        top: usize,
+    },
 }
 
 fn parse_similarity_threshold(value: &str) -> Result<f32, String> {
@@ -1116,12 +1409,16 @@ fn main() {
         Command::Init(init) => init_command(init),
         Command::Create(create) => create_command(create),
         Command::New(new) => new_command(new),
+        Command::Journal(journal) => journal_command(journal),
         Command::Retrieve(retrieve) => retrieve_command(retrieve),
         Command::Find(find) => find_command(find),
         Command::Count(count) => count_command(count),
         Command::Export(export) => export_command(export),
         Command::Schema(schema) => schema_command(schema),
         Command::Stats(stats) => stats_command(stats),
+        Command::Lint(lint) => lint_command(lint),
+        Command::Mentions(mentions) => mentions_command(mentions),
+        Command::Assets(assets) => assets_command(assets),
         Command::Rename(rename) => rename_command(rename),
         Command::Delete(delete) => delete_command(delete),
         Command::Extract(extract) => extract_command(extract),
@@ -1433,6 +1730,7 @@ fn find_command(args: Find) {
         project: project.clone(),
         max_tokens: args.max_tokens,
         max_document_tokens: args.max_document_tokens,
+        explain: args.explain,
     };
 
     let output = finder.find(&options);
@@ -1446,6 +1744,21 @@ fn find_command(args: Find) {
         }
     }
 
+    if let Some(explain) = &output.explain {
+        match args.format {
+            FindFormat::Json => {
+                let json =
+                    serde_json::to_string_pretty(explain).expect("Failed to serialize to JSON");
+                println!("{}", json);
+            }
+            _ => {
+                let yaml = serde_yaml::to_string(explain).expect("Failed to serialize to YAML");
+                print!("{}", yaml);
+            }
+        }
+        return;
+    }
+
     match args.format {
         FindFormat::Json => {
             let json =
@@ -1488,6 +1801,15 @@ fn find_command(args: Find) {
                     .collect(),
                 None => Vec::new(),
             };
+            let lookup_output_names: Vec<String> = match &project {
+                Some(p) => p
+                    .fields
+                    .iter()
+                    .filter(|f| matches!(f.source, ProjectionSource::Lookup(_)))
+                    .map(|f| f.output.clone())
+                    .collect(),
+                None => Vec::new(),
+            };
             let md_options = graph.format_options().markdown_options();
             let renderer = FindBlockRenderer::new(
                 &md_options,
@@ -1502,7 +1824,8 @@ fn find_command(args: Find) {
                     &output.results,
                     &content_output_names,
                     narrowed_content,
-                    &grep_output_names
+                    &grep_output_names,
+                    &lookup_output_names
                 )
             );
         }
@@ -1572,15 +1895,23 @@ fn new_command(args: New) {
         IfExists::Suffix
     });
 
-    let mut variables = Variables::new();
+    let scaffold = match (args.schema, args.no_schema || args.template.is_some()) {
+        (Some(name), _) => Scaffold::Schema(name),
+        (None, true) => Scaffold::Template,
+        (None, false) => Scaffold::Bound,
+    };
+
+    let mut variables = parse_variables(None, None, &args.var);
     variables.insert(
         TITLE_VARIABLE.to_string(),
         serde_yaml::Value::String(args.title),
     );
-    variables.insert(
-        BODY_VARIABLE.to_string(),
-        serde_yaml::Value::String(content),
-    );
+    if !content.is_empty() || !variables.contains_key(BODY_VARIABLE) {
+        variables.insert(
+            BODY_VARIABLE.to_string(),
+            serde_yaml::Value::String(content),
+        );
+    }
 
     let creator = DocumentCreator::new(&config, library_path);
     let options = CreateOptions {
@@ -1589,6 +1920,7 @@ fn new_command(args: New) {
         key: args.key,
         if_exists,
         frontmatter: None,
+        scaffold,
         empty_key_error: "Generated key is empty. Give the document a title, or pass --key."
             .to_string(),
     };
@@ -1739,6 +2071,11 @@ fn prepare_from_template(
         key: args.key.clone(),
         if_exists,
         frontmatter: parse_document_frontmatter(&args.set),
+        scaffold: args
+            .schema
+            .clone()
+            .map(Scaffold::Schema)
+            .unwrap_or_default(),
         empty_key_error:
             "Generated key is empty. Set the title with --var title=VALUE, or pass an explicit key."
                 .to_string(),
@@ -1920,6 +2257,40 @@ fn open_in_editor(path: &std::path::Path) {
     }
 }
 
+#[tracing::instrument(level = "debug")]
+fn journal_command(args: Journal) {
+    let config = get_configuration();
+    let journal = diwe::journal::Journal::new(&config).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(2);
+    });
+    if journal.is_empty() {
+        eprintln!("error: no [journal] periods configured");
+        std::process::exit(2);
+    }
+
+    let today = chrono::Local::now().date_naive();
+    let (period, date) = diwe::journal::resolve(&args.when, today).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(2);
+    });
+
+    let graph = Arc::new(load_graph(&config));
+    let (key, changes) = journal.open(&graph, period, date).unwrap_or_else(|e| {
+        eprintln!("error: {}", e);
+        std::process::exit(2);
+    });
+    apply_changes(&changes, &config);
+
+    let path = get_library_path(&config).join(key.to_path(config.format));
+    let path = path.canonicalize().unwrap_or(path);
+    println!("{}", path.display());
+
+    if args.edit {
+        open_in_editor(&path);
+    }
+}
+
 #[tracing::instrument(level = "debug")]
 fn tree_command(args: TreeArgs) {
     let config = get_configuration();
@@ -2071,7 +2442,8 @@ fn build_tree_node(
     );
 
     if let Some(p) = project {
-        let ctx = ProjectionContext::new(graph, key);
+        let metrics = MetricCache::new(graph);
+        let ctx = ProjectionContext::new(graph, &metrics, key);
         let projected = apply_projection(&ctx, p);
         for (k, v) in projected {
             if let Some(s) = k.as_str() {
@@ -2151,8 +2523,132 @@ fn build_tree_lines(
 #[tracing::instrument(level = "debug")]
 fn normalize_command(args: Normalize) {
     let configuration = get_configuration();
-    let graph = load_graph(&configuration);
-    write_graph(graph, &configuration);
+    let mut graph = load_graph(&configuration);
+    let library_path = get_library_path(&configuration);
+
+    let mut keys: Vec<Key> = match resolve_filter(&args.selector, &graph) {
+        Some(filter) => liwe::query::evaluate(&filter, &graph),
+        None => graph.keys(),
+    };
+    if !args.paths.is_empty() {
+        let named = keys_for_paths(&args.paths, &graph, &library_path, configuration.format);
+        keys.retain(|key| named.contains(key));
+    }
+    keys.sort();
+
+    refresh_query_blocks(&mut graph, &configuration, &keys);
+
+    let docs: Vec<(Key, String)> = keys
+        .into_iter()
+        .map(|key| {
+            let content = graph.to_markdown(&key);
+            (key, content)
+        })
+        .collect();
+
+    if args.check || args.diff {
+        let display = |key: &Key| {
+            std::path::Path::new(&configuration.library.path)
+                .join(key.to_path(configuration.format))
+                .to_string_lossy()
+                .to_string()
+        };
+        let mut unformatted = 0;
+        for (key, content) in &docs {
+            let existing =
+                std::fs::read_to_string(library_path.join(key.to_path(configuration.format)))
+                    .unwrap_or_default();
+            if *content == existing {
+                continue;
+            }
+            unformatted += 1;
+            if args.diff {
+                let path = display(key);
+                print!("{}", unified_diff(&path, &path, &existing, content));
+            } else {
+                println!("{}", display(key));
+            }
+        }
+        if args.check && unformatted > 0 {
+            if !args.quiet {
+                eprintln!("{} document(s) not normalized", unformatted);
+            }
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let (matched, changed) =
+        write_changed_documents(&library_path, configuration.format, &docs, args.dry_run);
+    report_mutation(args.quiet, args.dry_run, matched, changed);
+}
+
+/// Keys of the documents named by `paths`: a file names its own document, a directory every
+/// document below it. Paths outside the library or naming no document are usage errors.
+fn keys_for_paths(
+    paths: &[PathBuf],
+    graph: &Graph,
+    library_path: &std::path::Path,
+    format: liwe::model::config::Format,
+) -> std::collections::HashSet<Key> {
+    let library = library_path
+        .canonicalize()
+        .unwrap_or_else(|_| library_path.to_path_buf());
+    let mut keys = std::collections::HashSet::new();
+    for path in paths {
+        let fail = || -> ! {
+            eprintln!(
+                "error: {}: not a document or directory in the library",
+                path.display()
+            );
+            std::process::exit(2);
+        };
+        let Ok(resolved) = path.canonicalize() else {
+            fail()
+        };
+        if resolved.is_dir() {
+            let Ok(relative) = resolved.strip_prefix(&library) else {
+                fail()
+            };
+            let prefix = relative.to_string_lossy().replace('\\', "/");
+            keys.extend(graph.keys().into_iter().filter(|key| {
+                prefix.is_empty() || key.to_string().starts_with(&format!("{}/", prefix))
+            }));
+        } else {
+            match diwe::fs::path_to_key(&resolved, &library, format) {
+                Some(key) if graph.maybe_key(&key).is_some() => {
+                    keys.insert(key);
+                }
+                _ => fail(),
+            }
+        }
+    }
+    keys
+}
+
+/// Rewrites the results of every `iwe-query` block. Blocks that do not evaluate keep their
+/// results and are reported.
+fn refresh_query_blocks(graph: &mut Graph, configuration: &Configuration, keys: &[Key]) {
+    let link_type = configuration
+        .completion
+        .link_format
+        .clone()
+        .unwrap_or(LinkType::Markdown);
+
+    let mut refreshed = Vec::new();
+    for key in keys.iter().cloned() {
+        let outcome = refresh(graph, &key, &link_type);
+        for error in &outcome.errors {
+            eprintln!("warning: {}: iwe-query block: {}", key, error);
+        }
+        if let Some(content) = outcome.content {
+            refreshed.push((key, content));
+        }
+    }
+
+    for (key, content) in refreshed {
+        graph.update_key(key, &content);
+    }
 }
 
 #[tracing::instrument(level = "debug")]
@@ -2172,15 +2668,6 @@ fn squash_command(args: Squash) {
     print!("{}", patch.export_key(&args.key.into()).unwrap_or_default())
 }
 
-fn write_graph(graph: Graph, configuration: &Configuration) {
-    diwe::fs::write_store_at_path(
-        &graph.export(),
-        &get_library_path(configuration),
-        configuration.format,
-    )
-    .expect("Failed to write graph")
-}
-
 fn apply_changes(changes: &Changes, configuration: &Configuration) {
     diwe::fs::apply_changes(
         changes,
@@ -2191,12 +2678,18 @@ fn apply_changes(changes: &Changes, configuration: &Configuration) {
 }
 
 fn load_graph(configuration: &Configuration) -> Graph {
-    graph_from_path(
+    let mut graph = graph_from_path(
         &get_library_path(configuration),
         false,
         configuration.format_options(),
         configuration.library.frontmatter_document_title.clone(),
-    )
+    );
+    let (fields, errors) = configuration.computed_fields();
+    for error in errors {
+        eprintln!("warning: ignoring {}", error);
+    }
+    graph.set_computed_fields(fields);
+    graph
 }
 
 fn load_search_graph(configuration: &Configuration) -> (Graph, diwe::search::Bm25Index) {
@@ -2280,6 +2773,10 @@ fn get_configuration() -> Configuration {
 fn schema_command(args: Schema) {
     match args.command {
         Some(SchemaCommand::Validate(validate)) => schema_validate_command(validate),
+        Some(SchemaCommand::Infer(infer)) => match infer.emit.clone() {
+            Some(name) => schema_emit_command(&name, infer),
+            None => schema_infer_command(infer.fields),
+        },
         None => schema_infer_command(args.fields),
     }
 }
@@ -2319,6 +2816,83 @@ fn schema_infer_command(args: SchemaFields) {
     }
 }
 
+fn schema_emit_command(name: &str, args: SchemaInfer) {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        eprintln!(
+            "error: --emit needs a schema name such as 'note', got '{}'",
+            name
+        );
+        std::process::exit(2);
+    }
+
+    let config = get_configuration();
+    let graph = load_graph(&config);
+
+    let keys: Vec<Key> = match resolve_filter(&args.fields.selector, &graph) {
+        Some(filter) => liwe::query::evaluate(&filter, &graph),
+        None => {
+            let mut k = graph.keys();
+            k.sort();
+            k
+        }
+    };
+    if keys.is_empty() {
+        eprintln!("error: no documents selected to infer a schema from");
+        std::process::exit(1);
+    }
+
+    let options = liwe::schema::DeriveOptions {
+        required: args.required,
+        common: args.common,
+        percentile: args.percentile,
+        ..Default::default()
+    };
+    let yaml = diwe::schema::infer_schema_yaml(&graph, &keys, &options).unwrap_or_else(|errors| {
+        for error in errors {
+            eprintln!("error: {}", error);
+        }
+        std::process::exit(2);
+    });
+
+    if args.dry_run {
+        print!("{}", yaml);
+        return;
+    }
+
+    let dir = diwe::config::schemas_dir().unwrap_or_else(|error| {
+        eprintln!("error: {}", error);
+        std::process::exit(2);
+    });
+    let path = dir.join(format!("{}.yaml", name));
+    if path.exists() && !args.force {
+        eprintln!(
+            "error: .iwe/schemas/{}.yaml already exists; pass --force to overwrite it",
+            name
+        );
+        std::process::exit(2);
+    }
+    if let Err(error) = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(&path, &yaml)) {
+        eprintln!("error: failed to write {}: {}", path.display(), error);
+        std::process::exit(1);
+    }
+
+    println!(
+        "wrote .iwe/schemas/{}.yaml from {} document{}",
+        name,
+        keys.len(),
+        if keys.len() == 1 { "" } else { "s" }
+    );
+    if config.schemas.contains_key(name) {
+        println!("already bound by [schemas.{}] in .iwe/config.toml", name);
+    } else {
+        println!();
+        println!("bind it in .iwe/config.toml:");
+        println!();
+        println!("[schemas.{}]", name);
+        println!("match = \"{}\"", diwe::schema::propose_pattern(&keys));
+    }
+}
+
 fn schema_validate_command(args: SchemaValidate) {
     let config = get_configuration();
     let graph = load_graph(&config);
@@ -2379,6 +2953,117 @@ fn schema_validate_command(args: SchemaValidate) {
     std::process::exit(1);
 }
 
+fn lint_command(args: Lint) {
+    let config = get_configuration();
+    let mut graph = Arc::new(load_graph(&config));
+
+    let rules = LintRules::compile(&config.lint).unwrap_or_else(|errors| {
+        for error in errors {
+            eprintln!("error: {}", error);
+        }
+        std::process::exit(2);
+    });
+
+    let keys: Vec<Key> = match resolve_filter(&args.selector, &graph) {
+        Some(filter) => liwe::query::evaluate(&filter, &graph),
+        None => {
+            let mut k = graph.keys();
+            k.sort();
+            k
+        }
+    };
+
+    let assets = AssetInventory::scan(&graph, &get_library_path(&config), config.format);
+    let mut diagnostics = lint(
+        &graph,
+        &rules,
+        config.search_language(),
+        &keys,
+        Some(&assets),
+    );
+
+    if args.fix || args.diff {
+        let attach = config.orphan_attach_action().unwrap_or_else(|e| {
+            eprintln!("error: {}", e);
+            std::process::exit(2);
+        });
+        let fixes = {
+            let engine = TemplateEngine::new(&config).with_graph(graph.clone());
+            let hub_key = attach.map(|a| {
+                Key::name(
+                    &render_key_template(&engine, &a.key_template).unwrap_or_else(|e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    }),
+                )
+            });
+            let document_engine = match &hub_key {
+                Some(key) => engine.clone().relative_to(key),
+                None => engine,
+            };
+            let document = |body: &str| {
+                let template = attach.map(|a| a.document_template.as_str()).unwrap_or("");
+                render_document_template(&document_engine, template, body, &config).unwrap_or_else(
+                    |e| {
+                        eprintln!("Error: {}", e);
+                        std::process::exit(1);
+                    },
+                )
+            };
+            let hub = hub_key.map(|key| OrphanHub {
+                key,
+                document: &document,
+            });
+            fix(&graph, &diagnostics, hub.as_ref())
+        };
+
+        if args.diff {
+            print!("{}", changes_diff(&graph, &fixes.changes, config.format));
+            return;
+        }
+
+        apply_changes(&fixes.changes, &config);
+        apply_changes_to_graph(Arc::make_mut(&mut graph), &fixes.changes);
+        if !fixes.fixed.is_empty() {
+            eprintln!("Fixed {} finding(s)", fixes.fixed.len());
+        }
+        diagnostics = lint(
+            &graph,
+            &rules,
+            config.search_language(),
+            &keys,
+            Some(&assets),
+        );
+    }
+
+    match args.format {
+        LintFormat::Text => {
+            for diagnostic in &diagnostics {
+                println!("{}", diagnostic.render());
+            }
+        }
+        LintFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&diagnostics).expect("Failed to serialize findings");
+            println!("{}", json);
+        }
+        LintFormat::Sarif => {
+            let sarif = to_sarif(&diagnostics, config.format.extension());
+            let json = serde_json::to_string_pretty(&sarif).expect("Failed to serialize findings");
+            println!("{}", json);
+        }
+    }
+
+    let threshold = if args.deny_warnings {
+        Severity::Warning
+    } else {
+        Severity::Error
+    };
+    if diagnostics.iter().any(|d| d.severity >= threshold) {
+        std::process::exit(1);
+    }
+}
+
 fn gate_pending(config: &Configuration, docs: &[(Key, String)]) {
     match validate_pending_documents(config, docs) {
         Ok(reports) if reports.is_empty() => {}
@@ -2421,19 +3106,74 @@ fn warn_stats(config: &Configuration, graph: &Graph, targets: &[Key]) {
 }
 
 #[tracing::instrument(level = "debug")]
-fn stats_command(args: Stats) {
+fn mentions_command(args: Mentions) {
     let config = get_configuration();
     let graph = load_graph(&config);
 
-    if let Some(StatsCommand::Similarity { threshold }) = args.command {
-        let similarity =
-            SimilarityIndex::build(&graph, config.search_language()).with_threshold(threshold);
-        for (a, b) in similarity.pairs() {
-            println!("{}\t{}", a, b);
+    let key = Key::name(&args.key);
+    if graph.maybe_key(&key).is_none() {
+        eprintln!("Error: Document '{}' not found", args.key);
+        std::process::exit(1);
+    }
+
+    let mentions = MentionFinder::new(&graph, config.search_language()).find(&key);
+
+    if args.link || args.diff {
+        let link_type = config
+            .completion
+            .link_format
+            .clone()
+            .unwrap_or(LinkType::Markdown);
+        let changes = link_mentions(&graph, &key, &mentions, &link_type);
+
+        if args.diff {
+            print!("{}", changes_diff(&graph, &changes, config.format));
+            return;
         }
+
+        apply_changes(&changes, &config);
+        eprintln!(
+            "Linked {} mention(s) in {} document(s)",
+            mentions.len(),
+            changes.updates.len()
+        );
         return;
     }
 
+    match args.format {
+        MentionsFormat::Text => {
+            for mention in &mentions {
+                println!("{}", mention.render());
+            }
+        }
+        MentionsFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&mentions).expect("Failed to serialize mentions");
+            println!("{}", json);
+        }
+    }
+}
+
+fn stats_command(args: Stats) {
+    let config = get_configuration();
+    let graph = load_graph(&config);
+
+    match args.command {
+        Some(StatsCommand::Similarity { threshold }) => {
+            let similarity =
+                SimilarityIndex::build(&graph, config.search_language()).with_threshold(threshold);
+            for (a, b) in similarity.pairs() {
+                println!("{}\t{}", a, b);
+            }
+            return;
+        }
+        Some(StatsCommand::Graph { format, top }) => {
+            graph_stats_command(&graph, format, top);
+            return;
+        }
+        None => {}
+    }
+
     if let Some(key_str) = args.key {
         let normalized_key = Key::name(&key_str).to_string();
         let key_stats = diwe::stats::KeyStatistics::from_graph(&graph);
@@ -2503,9 +3243,10 @@ fn stats_command(args: Stats) {
         return;
     }
 
+    let assets = AssetInventory::scan(&graph, &get_library_path(&config), config.format);
     match args.format {
         StatsFormat::Markdown => {
-            let stats = GraphStatistics::from_graph(&graph);
+            let stats = GraphStatistics::from_graph(&graph).with_assets(&assets);
             let output = render_stats(&stats);
             print!("{}", output);
         }
@@ -2517,18 +3258,104 @@ fn stats_command(args: Stats) {
             }
         }
         StatsFormat::Json => {
-            let stats = GraphStatistics::from_graph(&graph);
+            let stats = GraphStatistics::from_graph(&graph).with_assets(&assets);
             let json = serde_json::to_string_pretty(&stats).expect("Failed to serialize stats");
             println!("{}", json);
         }
         StatsFormat::Yaml => {
-            let stats = GraphStatistics::from_graph(&graph);
+            let stats = GraphStatistics::from_graph(&graph).with_assets(&assets);
             let yaml = serde_yaml::to_string(&stats).expect("Failed to serialize stats");
             print!("{}", yaml);
         }
     }
 }
 
+fn assets_command(args: Assets) {
+    let config = get_configuration();
+    let graph = load_graph(&config);
+    let library_path = get_library_path(&config);
+    let inventory = AssetInventory::scan(&graph, &library_path, config.format);
+
+    if let Some(AssetsCommand::Gc { yes }) = args.command {
+        let unreferenced: Vec<String> = inventory.unreferenced().into_iter().cloned().collect();
+        for path in &unreferenced {
+            println!("{}", path);
+        }
+        if !yes {
+            if !unreferenced.is_empty() {
+                eprintln!(
+                    "{} unreferenced asset(s). Run with --yes to delete them",
+                    unreferenced.len()
+                );
+            }
+            return;
+        }
+
+        let mut removed = 0;
+        let mut failed = false;
+        for path in &unreferenced {
+            match diwe::fs::remove_path(path, &library_path) {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    eprintln!("Error: failed to remove '{}': {}", path, e);
+                    failed = true;
+                }
+            }
+        }
+        eprintln!("Removed {} unreferenced asset(s)", removed);
+        if failed {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let entries: Vec<AssetEntry> = inventory
+        .entries()
+        .into_iter()
+        .filter(|entry| !args.missing || entry.status == AssetStatus::Missing)
+        .filter(|entry| !args.unreferenced || entry.status == AssetStatus::Unreferenced)
+        .collect();
+
+    match args.format {
+        AssetsFormat::Text => {
+            for entry in &entries {
+                let mut line = format!("{}\t{}", entry.status.label(), entry.path);
+                if !entry.referenced_by.is_empty() {
+                    line.push('\t');
+                    line.push_str(&entry.referenced_by.iter().join(", "));
+                }
+                println!("{}", line);
+            }
+        }
+        AssetsFormat::Json => {
+            let json = serde_json::to_string_pretty(&entries).expect("Failed to serialize assets");
+            println!("{}", json);
+        }
+    }
+}
+
+fn graph_stats_command(graph: &Graph, format: StatsFormat, top: usize) {
+    let report = GraphAnalyticsReport::from_graph(graph);
+    match format {
+        StatsFormat::Markdown => print!("{}", render_graph_stats(&report, top)),
+        StatsFormat::Csv => {
+            let stdout = std::io::stdout();
+            if let Err(e) = report.export_csv(stdout.lock()) {
+                error!("Failed to export CSV: {}", e);
+                std::process::exit(1);
+            }
+        }
+        StatsFormat::Json => {
+            let json = serde_json::to_string_pretty(&report).expect("Failed to serialize stats");
+            println!("{}", json);
+        }
+        StatsFormat::Yaml => {
+            let yaml = serde_yaml::to_string(&report).expect("Failed to serialize stats");
+            print!("{}", yaml);
+        }
+    }
+}
+
 #[tracing::instrument]
 fn export_command(args: Export) {
     let config = get_configuration();
@@ -3215,7 +4042,6 @@ fn update_mutation(args: Update) {
     }
 
     let library_path = get_library_path(&config);
-    let ext = config.format.extension();
 
     let docs: Vec<(Key, String)> = if update_doc.block_ops.is_empty() {
         let find_op = FindOp::new().filter(filter);
@@ -3233,7 +4059,7 @@ fn update_mutation(args: Update) {
         }
         keys.into_iter()
             .filter_map(|key| {
-                let file_path = library_path.join(format!("{}.{}", key, ext));
+                let file_path = library_path.join(key.to_path(config.format));
                 let raw_content = std::fs::read_to_string(&file_path).ok()?;
                 let (_, body) = split_raw_frontmatter(&raw_content);
                 let mut mapping = graph.frontmatter(&key).cloned().unwrap_or_default();
@@ -3269,7 +4095,8 @@ fn update_mutation(args: Update) {
         gate_pending(&config, &docs);
     }
 
-    let (matched, changed) = write_changed_documents(&library_path, ext, &docs, args.dry_run);
+    let (matched, changed) =
+        write_changed_documents(&library_path, config.format, &docs, args.dry_run);
 
     if args.strict && !args.dry_run {
         let targets: Vec<Key> = docs.iter().map(|(key, _)| key.clone()).collect();
@@ -3326,13 +4153,13 @@ fn enforce_strict_update(has_doc_expect: bool, update_doc: &liwe::query::Update)
 
 fn write_changed_documents(
     library_path: &std::path::Path,
-    ext: &str,
+    format: liwe::model::config::Format,
     docs: &[(Key, String)],
     dry_run: bool,
 ) -> (usize, usize) {
     let mut changed = 0;
     for (key, content) in docs {
-        let file_path = library_path.join(format!("{}.{}", key, ext));
+        let file_path = library_path.join(key.to_path(format));
         let existing = std::fs::read_to_string(&file_path).unwrap_or_default();
         if *content == existing {
             continue;
@@ -3409,9 +4236,10 @@ fn attach_command(args: Attach) {
     let config = get_configuration();
 
     if args.list {
+        let engine = TemplateEngine::new(&config).with_library(&config, get_library_path(&config));
         for (name, action) in &config.actions {
             if let ActionDefinition::Attach(a) = action {
-                let target = match render_key_template(&a.key_template) {
+                let target = match render_key_template(&engine, &a.key_template) {
                     Ok(target) => target,
                     Err(e) => {
                         eprintln!("Error: action '{}': {}", name, e);
@@ -3434,13 +4262,13 @@ fn attach_command(args: Attach) {
     });
     let source_key = Key::name(&source_key_str);
 
-    let graph = load_graph(&config);
-    if (&graph).get_node_id(&source_key).is_none() {
+    let graph = Arc::new(load_graph(&config));
+    if (&*graph).get_node_id(&source_key).is_none() {
         eprintln!("Error: Source document '{}' not found", source_key_str);
         std::process::exit(1);
     }
 
-    let reference_text = (&graph)
+    let reference_text = (&*graph)
         .get_key_title(&source_key)
         .unwrap_or_else(|| source_key_str.clone());
 
@@ -3459,7 +4287,9 @@ fn attach_command(args: Attach) {
             }
         };
 
-        let target_key_str = match render_key_template(&attach.key_template) {
+        let engine = TemplateEngine::new(&config).with_graph(graph.clone());
+
+        let target_key_str = match render_key_template(&engine, &attach.key_template) {
             Ok(target) => target,
             Err(e) => {
                 eprintln!("Error: action '{}': {}", action_name, e);
@@ -3473,7 +4303,12 @@ fn attach_command(args: Attach) {
             AttachTarget::AlreadyAttached => continue,
             AttachTarget::Update(content) => content,
             AttachTarget::Create(body) => {
-                match render_document_template(&attach.document_template, &body, &config) {
+                match render_document_template(
+                    &engine.relative_to(&target_key),
+                    &attach.document_template,
+                    &body,
+                    &config,
+                ) {
                     Ok(content) => content,
                     Err(e) => {
                         eprintln!("Error: action '{}': {}", action_name, e);
@@ -3506,12 +4341,13 @@ fn attach_command(args: Attach) {
     }
 }
 
-fn render_key_template(template: &str) -> Result<String, String> {
+fn render_key_template(engine: &TemplateEngine, template: &str) -> Result<String, String> {
     use chrono::Local;
-    use minijinja::{context, Environment};
+    use minijinja::context;
     let now = Local::now();
     let formatted = now.format("%Y-%m-%d").to_string();
-    Environment::new()
+    engine
+        .environment()
         .template_from_str(template)
         .map_err(|e| format!("invalid key template: {}", e))?
         .render(context! {
@@ -3522,12 +4358,13 @@ fn render_key_template(template: &str) -> Result<String, String> {
 }
 
 fn render_document_template(
+    engine: &TemplateEngine,
     template: &str,
     content: &str,
     config: &Configuration,
 ) -> Result<String, String> {
     use chrono::Local;
-    use minijinja::{context, Environment};
+    use minijinja::context;
     let now = Local::now();
     let date_format = config
         .markdown
@@ -3535,7 +4372,8 @@ fn render_document_template(
         .as_deref()
         .unwrap_or("%b %d, %Y");
     let formatted = now.format(date_format).to_string();
-    Environment::new()
+    engine
+        .environment()
         .template_from_str(template)
         .map_err(|e| format!("invalid document template: {}", e))?
         .render(context! {
@@ -1,14 +1,16 @@
 use std::collections::BTreeMap;
-use std::io::{self, Read};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
 use std::path::PathBuf;
 
 use chrono::Local;
 use minijinja::value::Value as TemplateValue;
-use minijinja::Environment;
 use rand::distr::Alphanumeric;
 use rand::Rng;
 
-use diwe::config::{Configuration, NoteTemplate, DEFAULT_KEY_DATE_FORMAT};
+use diwe::config::{schemas_dir, Configuration, NoteTemplate, DEFAULT_KEY_DATE_FORMAT};
+use diwe::scaffold::{Field, Skeleton};
+use diwe::schema::SchemaBindings;
+use diwe::template::TemplateEngine;
 use liwe::locale::get_locale;
 use liwe::model::{prepend_frontmatter, strip_doc_extension, Frontmatter, Key};
 
@@ -19,6 +21,18 @@ pub const RESERVED_VARIABLES: [&str; 4] = ["slug", "today", "now", "id"];
 
 pub type Variables = BTreeMap<String, serde_yaml::Value>;
 
+/// Where the document body comes from.
+#[derive(Debug, Clone, Default)]
+pub enum Scaffold {
+    /// The template's document_template.
+    #[default]
+    Template,
+    /// The skeleton of the named schema.
+    Schema(String),
+    /// The skeleton of the schemas bound to the new key, or the template when none is.
+    Bound,
+}
+
 #[derive(Debug, Clone, Default, clap::ValueEnum)]
 pub enum IfExists {
     #[default]
@@ -46,6 +60,7 @@ pub struct CreateOptions {
     pub key: Option<String>,
     pub if_exists: IfExists,
     pub frontmatter: Option<Frontmatter>,
+    pub scaffold: Scaffold,
     pub empty_key_error: String,
 }
 
@@ -216,6 +231,9 @@ impl<'a> DocumentCreator<'a> {
         let slug = string_to_slug(&scalar_text(options.variables.get(TITLE_VARIABLE)));
         let id = generate_random_id();
 
+        let engine =
+            TemplateEngine::new(self.config).with_library(self.config, self.library_path.clone());
+
         let relative_key = match &options.key {
             Some(key) => {
                 if strip_doc_extension(key) != key.as_str() {
@@ -224,17 +242,37 @@ impl<'a> DocumentCreator<'a> {
                 key.clone()
             }
             None => render_template(
+                &engine,
                 &template.key_template,
                 &template_context(&options.variables, &slug, &key_today, &key_now, &id),
             )?,
         };
 
-        let document_content = render_template(
-            &template.document_template,
-            &template_context(&options.variables, &slug, &content_today, &content_now, &id),
-        )?;
-
-        let document_content = prepend_frontmatter(options.frontmatter, &document_content)?;
+        let document_content = match self.skeleton(&options.scaffold, &relative_key)? {
+            Some(skeleton) => {
+                let mut frontmatter = fill_fields(
+                    &skeleton.fields,
+                    &options.variables,
+                    &now.format("%Y-%m-%d").to_string(),
+                );
+                for (field, value) in options.frontmatter.unwrap_or_default() {
+                    frontmatter.insert(field, value);
+                }
+                skeleton.render(
+                    frontmatter,
+                    &scalar_text(options.variables.get(TITLE_VARIABLE)),
+                    &scalar_text(options.variables.get(BODY_VARIABLE)),
+                )?
+            }
+            None => {
+                let rendered = render_template(
+                    &engine.relative_to(&Key::name(&relative_key)),
+                    &template.document_template,
+                    &template_context(&options.variables, &slug, &content_today, &content_now, &id),
+                )?;
+                prepend_frontmatter(options.frontmatter, &rendered)?
+            }
+        };
 
         let empty_key_error = if options.key.is_some() {
             "Provided key is empty."
@@ -251,6 +289,84 @@ impl<'a> DocumentCreator<'a> {
             None => Ok(None),
         }
     }
+
+    fn skeleton(&self, scaffold: &Scaffold, key: &str) -> Result<Option<Skeleton>, String> {
+        let names = match scaffold {
+            Scaffold::Template => return Ok(None),
+            Scaffold::Schema(name) => vec![name.clone()],
+            Scaffold::Bound => SchemaBindings::compile(&self.config.schemas)
+                .map_err(|errors| errors.join("\n"))?
+                .schemas_for(Key::name(key).as_str())
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        };
+        if names.is_empty() {
+            return Ok(None);
+        }
+
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        Skeleton::load(&schemas_dir()?, &names)
+            .map(Some)
+            .map_err(|errors| errors.join("\n"))
+    }
+}
+
+/// Frontmatter for the required `fields`: a variable of the same name when one is set, else an
+/// answer typed at the terminal, else the field's placeholder.
+fn fill_fields(fields: &[Field], variables: &Variables, today: &str) -> Frontmatter {
+    let interactive = io::stdin().is_terminal() && io::stderr().is_terminal();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+
+    let mut frontmatter = Frontmatter::new();
+    for field in fields {
+        let value = match variables.get(&field.name) {
+            Some(serde_yaml::Value::String(raw)) => field.parse(raw),
+            Some(value) => value.clone(),
+            None if interactive && field.default.is_none() => {
+                prompt(field, today, &mut input, &mut io::stderr())
+            }
+            None => field.placeholder(today),
+        };
+        frontmatter.insert(serde_yaml::Value::String(field.name.clone()), value);
+    }
+    frontmatter
+}
+
+fn prompt(
+    field: &Field,
+    today: &str,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> serde_yaml::Value {
+    let placeholder = field.placeholder(today);
+    let mut question = field.name.clone();
+    if let Some(description) = &field.description {
+        question.push_str(&format!(" ({description})"));
+    }
+    if !field.choices.is_empty() {
+        let choices: Vec<String> = field.choices.iter().map(yaml_text).collect();
+        question.push_str(&format!(" [{}]", choices.join("|")));
+    }
+    let _ = write!(output, "{question} [{}]: ", yaml_text(&placeholder));
+    let _ = output.flush();
+
+    let mut answer = String::new();
+    match input.read_line(&mut answer) {
+        Ok(_) if !answer.trim().is_empty() => field.parse(answer.trim()),
+        _ => placeholder,
+    }
+}
+
+fn yaml_text(value: &serde_yaml::Value) -> String {
+    match value {
+        serde_yaml::Value::String(text) => text.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    }
 }
 
 pub fn write_document(prepared: &PreparedDocument) -> Result<CreatedDocument, String> {
@@ -273,8 +389,6 @@ pub fn write_document(prepared: &PreparedDocument) -> Result<CreatedDocument, St
 }
 
 pub fn read_stdin_if_available() -> String {
-    use std::io::IsTerminal;
-
     if std::io::stdin().is_terminal() {
         return String::new();
     }
@@ -312,10 +426,12 @@ fn template_context(
 }
 
 fn render_template(
+    engine: &TemplateEngine,
     template_str: &str,
     context: &BTreeMap<String, TemplateValue>,
 ) -> Result<String, String> {
-    Environment::new()
+    engine
+        .environment()
         .template_from_str(template_str)
         .map_err(|e| format!("Invalid template syntax: {}", e))?
         .render(context)
@@ -100,12 +100,14 @@ impl<'a> FindBlockRenderer<'a> {
         content_output_names: &[String],
         narrowed_content: bool,
         grep_output_names: &[String],
+        lookup_output_names: &[String],
     ) -> String {
         if content_output_names.is_empty() {
             keys.iter()
                 .zip(results.iter())
                 .map(|(key, fm)| {
-                    let mut line = render_index_line(key, fm, grep_output_names);
+                    let mut line =
+                        render_index_line(key, fm, grep_output_names, lookup_output_names);
                     line.push_str(&render_grep_lines(key, fm, grep_output_names));
                     line
                 })
@@ -161,7 +163,7 @@ fn truncate_rendered_body(
     }
 }
 
-fn render_index_line(key: &Key, fm: &Mapping, skip: &[String]) -> String {
+fn render_index_line(key: &Key, fm: &Mapping, skip: &[String], lookups: &[String]) -> String {
     let title = fm
         .get(Value::String("title".to_string()))
         .and_then(|v| v.as_str())
@@ -193,7 +195,11 @@ fn render_index_line(key: &Key, fm: &Mapping, skip: &[String]) -> String {
                 annotations.push_str(" · ");
                 annotations.push_str(name);
                 annotations.push_str(": ");
-                annotations.push_str(&render_annotation_value(v));
+                if lookups.iter().any(|s| s == name) {
+                    annotations.push_str(&render_lookup_value(v));
+                } else {
+                    annotations.push_str(&render_annotation_value(v));
+                }
             }
         }
     }
@@ -276,6 +282,44 @@ fn is_scalar(v: &Value) -> bool {
     )
 }
 
+/// `$lookup` entries, each as a link to the related document followed by its other fields.
+fn render_lookup_value(v: &Value) -> String {
+    let Some(items) = v.as_sequence() else {
+        return inline_value(v);
+    };
+    items
+        .iter()
+        .map(|item| {
+            let Some(m) = item.as_mapping() else {
+                return inline_value(item);
+            };
+            let field = |name: &str| {
+                m.get(Value::String(name.to_string()))
+                    .and_then(|v| v.as_str())
+            };
+            let rest: Vec<String> = m
+                .iter()
+                .filter_map(|(k, val)| {
+                    let name = k.as_str()?;
+                    (name != "key" && name != "title")
+                        .then(|| format!("{}: {}", name, inline_value(val)))
+                })
+                .collect();
+            let head = match (field("key"), field("title")) {
+                (Some(key), title) => format!("[{}]({})", title.unwrap_or(key), key),
+                (None, Some(title)) => title.to_string(),
+                (None, None) => String::new(),
+            };
+            match (head.is_empty(), rest.is_empty()) {
+                (_, true) => head,
+                (true, false) => format!("{{{}}}", rest.join(", ")),
+                (false, false) => format!("{} {{{}}}", head, rest.join(", ")),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
 fn render_annotation_value(v: &Value) -> String {
     match v {
         Value::Sequence(items) if items.iter().all(is_scalar) => items
@@ -1,8 +1,11 @@
-pub use diwe::stats::{BrokenLink, GraphStatistics, KeyStatistics};
+pub use diwe::stats::{BrokenLink, GraphAnalyticsReport, GraphStatistics, KeyStatistics};
 
+use diwe::stats::{DocumentGroup, NodeMetrics};
 use minijinja::Environment;
+use serde::Serialize;
 
 const STATS_TEMPLATE: &str = include_str!("../templates/stats.md.jinja");
+const GRAPH_STATS_TEMPLATE: &str = include_str!("../templates/graph_stats.md.jinja");
 
 pub fn render_stats(stats: &GraphStatistics) -> String {
     let mut env = Environment::new();
@@ -12,3 +15,52 @@ pub fn render_stats(stats: &GraphStatistics) -> String {
     let template = env.get_template("stats").expect("Failed to get template");
     template.render(stats).expect("Failed to render template")
 }
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphStatsView<'a> {
+    documents: usize,
+    edges: usize,
+    components: usize,
+    communities: Vec<&'a DocumentGroup>,
+    inclusion_cycles: &'a [Vec<String>],
+    top_pagerank: Vec<&'a NodeMetrics>,
+    top_betweenness: Vec<&'a NodeMetrics>,
+}
+
+/// Markdown summary of `report`, listing at most `top` entries per ranking.
+pub fn render_graph_stats(report: &GraphAnalyticsReport, top: usize) -> String {
+    let mut by_betweenness: Vec<&NodeMetrics> = report
+        .nodes
+        .iter()
+        .filter(|node| node.betweenness > 0.0)
+        .collect();
+    by_betweenness.sort_by(|a, b| {
+        b.betweenness
+            .total_cmp(&a.betweenness)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    let view = GraphStatsView {
+        documents: report.documents,
+        edges: report.edges,
+        components: report.components.len(),
+        communities: report
+            .communities
+            .iter()
+            .filter(|group| group.size > 1)
+            .take(top)
+            .collect(),
+        inclusion_cycles: &report.inclusion_cycles,
+        top_pagerank: report.nodes.iter().take(top).collect(),
+        top_betweenness: by_betweenness.into_iter().take(top).collect(),
+    };
+
+    let mut env = Environment::new();
+    env.add_template("graph_stats", GRAPH_STATS_TEMPLATE)
+        .expect("Failed to add template");
+
+    let template = env
+        .get_template("graph_stats")
+        .expect("Failed to get template");
+    template.render(&view).expect("Failed to render template")
+}
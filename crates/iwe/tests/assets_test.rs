@@ -0,0 +1,153 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+#[test]
+fn assets_lists_status_and_referencing_documents() {
+    let temp = library();
+
+    let output = run(temp.path(), &["assets"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "referenced\timg/chart.png\tindex, notes/note\n\
+         missing\timg/gone.png\tnotes/note\n\
+         unreferenced\timg/old.jpg\n\
+         referenced\tpapers/spec.pdf\tnotes/note\n"
+    );
+}
+
+#[test]
+fn assets_filters_missing_as_json() {
+    let temp = library();
+
+    let output = run(temp.path(), &["assets", "--missing", "-f", "json"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([{
+            "path": "img/gone.png",
+            "status": "missing",
+            "referencedBy": ["notes/note"],
+        }])
+    );
+}
+
+#[test]
+fn assets_gc_removes_unreferenced_files() {
+    let temp = library();
+
+    let preview = run(temp.path(), &["assets", "gc"]);
+    assert_eq!(String::from_utf8(preview.stdout).unwrap(), "img/old.jpg\n");
+    assert!(temp.path().join("img/old.jpg").exists());
+
+    let gc = run(temp.path(), &["assets", "gc", "--yes"]);
+    assert_eq!(gc.status.code(), Some(0));
+    assert!(!temp.path().join("img/old.jpg").exists());
+    assert!(temp.path().join("img/chart.png").exists());
+    assert!(temp.path().join("build.sh").exists());
+    assert!(temp.path().join("package.json").exists());
+}
+
+#[test]
+fn rename_rewrites_asset_paths_for_new_location() {
+    let temp = library();
+
+    let output = run(temp.path(), &["rename", "notes/note", "archive/2024/note"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read_to_string(temp.path().join("archive/2024/note.md")).unwrap(),
+        "# Note\n\n![chart](../../img/chart.png) and ![gone](../../img/gone.png)\n\n[Spec](/papers/spec.pdf)\n"
+    );
+}
+
+#[test]
+fn lint_and_stats_report_assets() {
+    let temp = library();
+
+    let lint = run(temp.path(), &["lint"]);
+    assert_eq!(lint.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(lint.stdout).unwrap(),
+        "img/old.jpg warning › unreferenced-asset: no page links to this file\n\
+         notes/note:3 error › missing-asset: links to missing asset 'img/gone.png'\n"
+    );
+
+    let stats = run(temp.path(), &["stats", "-f", "json"]);
+    let json: serde_json::Value = serde_json::from_slice(&stats.stdout).unwrap();
+    assert_eq!(json["assetFiles"], 3);
+    assert_eq!(json["assetReferences"], 4);
+    assert_eq!(json["missingAssets"][0]["path"], "img/gone.png");
+    assert_eq!(
+        json["unreferencedAssets"],
+        serde_json::json!(["img/old.jpg"])
+    );
+}
+
+#[test]
+fn dotted_note_keys_are_links_not_assets() {
+    let temp = library();
+    write(
+        temp.path().join("index.md"),
+        "# Index\n\n![chart](img/chart.png)\n\n[Note](notes/note)\n\n[John](notes/john.doe)\n",
+    )
+    .unwrap();
+    write(
+        temp.path().join("notes/john.doe.md"),
+        "# John\n\nEngineer.\n",
+    )
+    .unwrap();
+
+    let lint = run(temp.path(), &["lint"]);
+    assert_eq!(
+        String::from_utf8(lint.stdout).unwrap(),
+        "img/old.jpg warning › unreferenced-asset: no page links to this file\n\
+         notes/note:3 error › missing-asset: links to missing asset 'img/gone.png'\n"
+    );
+
+    let assets = run(temp.path(), &["assets"]);
+    assert!(!String::from_utf8(assets.stdout).unwrap().contains("john"));
+}
+
+fn library() -> TempDir {
+    let temp = TempDir::new().expect("tempdir");
+    let path = temp.path();
+    create_dir_all(path.join(".iwe")).unwrap();
+    write(
+        path.join(".iwe/config.toml"),
+        "library.path = \"\"\nmarkdown.refs_extension = \"\"\n",
+    )
+    .unwrap();
+    create_dir_all(path.join("notes")).unwrap();
+    create_dir_all(path.join("img")).unwrap();
+    create_dir_all(path.join("papers")).unwrap();
+    write(
+        path.join("index.md"),
+        "# Index\n\n![chart](img/chart.png)\n\n[Note](notes/note)\n",
+    )
+    .unwrap();
+    write(
+        path.join("notes/note.md"),
+        "# Note\n\n![chart](../img/chart.png) and ![gone](../img/gone.png)\n\n[Spec](/papers/spec.pdf)\n",
+    )
+    .unwrap();
+    for file in [
+        "img/chart.png",
+        "img/old.jpg",
+        "papers/spec.pdf",
+        "build.sh",
+        "package.json",
+    ] {
+        write(path.join(file), "").unwrap();
+    }
+    temp
+}
+
+fn run(work_dir: &Path, args: &[&str]) -> Output {
+    Command::new(crate::common::get_iwe_binary_path())
+        .args(args)
+        .current_dir(work_dir)
+        .output()
+        .expect("run iwe command")
+}
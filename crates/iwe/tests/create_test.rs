@@ -405,6 +405,52 @@ fn setup_with_schema() -> TempDir {
     temp
 }
 
+#[test]
+fn template_mode_writes_the_skeleton_of_a_named_schema() {
+    let temp = setup_with_schema();
+    let output = run(
+        temp.path(),
+        &[
+            "docs/ada",
+            "--template",
+            "default",
+            "--schema",
+            "person",
+            "--var",
+            "title=Ada",
+        ],
+    );
+
+    assert!(output.status.success(), "{}", stderr_of(&output));
+    assert_eq!(
+        read_to_string(temp.path().join("docs/ada.md")).unwrap(),
+        indoc! {"
+            # Summary
+
+            # Tasks
+        "}
+    );
+}
+
+#[test]
+fn schema_requires_a_template() {
+    let temp = setup_with_schema();
+    let output = run(temp.path(), &["docs/ada", "--schema", "person"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        stderr_of(&output),
+        indoc! {"
+            error: the following required arguments were not provided:
+              --template <NAME>
+
+            Usage: iwe create --template <NAME> --schema <NAME> <KEY>
+
+            For more information, try '--help'.
+        "}
+    );
+}
+
 #[test]
 fn template_mode_renders_the_stock_template() {
     let temp = setup();
@@ -1143,6 +1143,51 @@ fn test_find_add_fields_extends_default() {
     assert_eq!(stdout, expected);
 }
 
+#[test]
+fn test_find_projects_table_rows_as_records() {
+    let dir = setup_workspace();
+
+    write(
+        dir.path().join("tasks.md"),
+        indoc! {"
+            # Tasks
+
+            | Task  | Status |
+            | ----- | ------ |
+            | Draft | open   |
+            | Print | done   |
+        "},
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_iwe(
+        dir.path(),
+        &[
+            "--project",
+            "open: { $rows: { $row: { Status: open } } }",
+            "-f",
+            "json",
+        ],
+    );
+
+    assert!(success, "stderr: {}", stderr);
+
+    let expected = indoc! {r#"
+        [
+          {
+            "open": [
+              {
+                "Task": "Draft",
+                "Status": "open"
+              }
+            ]
+          }
+        ]
+    "#};
+
+    assert_eq!(stdout, expected);
+}
+
 #[test]
 fn test_find_project_and_add_fields_conflict() {
     let dir = setup_workspace();
@@ -2145,3 +2190,210 @@ fn test_find_content_filter_membership() {
     assert!(success, "stderr: {}", stderr);
     assert_eq!(stdout, "doc1\n");
 }
+
+#[test]
+fn test_find_explain_reports_filter_and_search_stages() {
+    let dir = setup_workspace();
+
+    write(
+        dir.path().join("draft-auth.md"),
+        "---\nstatus: draft\n---\n# User Authentication\n\nAuth content.",
+    )
+    .unwrap();
+    write(
+        dir.path().join("draft-db.md"),
+        "---\nstatus: draft\n---\n# Database Config\n\nDB content.",
+    )
+    .unwrap();
+    write(
+        dir.path().join("published.md"),
+        "---\nstatus: published\n---\n# Authentication Guide\n\nMore auth.",
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_iwe(
+        dir.path(),
+        &[
+            "--lexical",
+            "authentication",
+            "--filter",
+            "status: draft",
+            "--explain",
+            "-f",
+            "json",
+        ],
+    );
+
+    assert!(success, "stderr: {}", stderr);
+
+    let report: serde_json::Value = serde_json::from_str(&stdout).expect("report is valid JSON");
+    assert_eq!(report["candidates"], 2);
+    assert_eq!(report["matched"], 1);
+    assert_eq!(report["filter"]["clause"], "$and");
+    assert_eq!(report["filter"]["input"], 3);
+    assert_eq!(report["filter"]["output"], 2);
+    assert_eq!(report["search"]["candidates"], 2);
+    assert_eq!(report["search"]["lexical"], 1);
+    assert_eq!(report["search"]["fused"], 1);
+}
+
+#[test]
+fn test_find_project_lookup_in_markdown_and_json() {
+    let dir = setup_workspace();
+
+    write(
+        dir.path().join("apollo.md"),
+        indoc! {"
+            ---
+            owner: ann
+            ---
+            # Apollo
+
+            [Write spec](spec)
+        "},
+    )
+    .unwrap();
+    write(dir.path().join("spec.md"), "# Write spec\n").unwrap();
+
+    let projection = "{title: $title, project: {$lookup: {from: $includedBy, project: {key: $key, title: $title, owner: 1}}}}";
+
+    let (stdout, stderr, success) = run_iwe(
+        dir.path(),
+        &["--filter", "$key: spec", "--project", projection],
+    );
+    assert!(success, "stderr: {}", stderr);
+    assert_eq!(
+        stdout,
+        "- [Write spec](spec) · project: [Apollo](apollo) {owner: ann}\n"
+    );
+
+    let (stdout, stderr, success) = run_iwe(
+        dir.path(),
+        &[
+            "--filter",
+            "$key: spec",
+            "--project",
+            projection,
+            "-f",
+            "json",
+        ],
+    );
+    assert!(success, "stderr: {}", stderr);
+    let expected = indoc! {r#"
+        [
+          {
+            "title": "Write spec",
+            "project": [
+              {
+                "key": "apollo",
+                "title": "Apollo",
+                "owner": "ann"
+              }
+            ]
+          }
+        ]
+    "#};
+    assert_eq!(stdout, expected);
+}
+
+#[test]
+fn test_find_uses_computed_fields_from_config() {
+    let dir = setup_workspace();
+    let config_path = dir.path().join(".iwe").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).unwrap();
+    config.push_str(indoc! {r#"
+
+        [fields.words]
+        type = "words"
+
+        [fields.backlinks]
+        type = "edges"
+        edge = "referencedBy"
+    "#});
+    write(&config_path, config).unwrap();
+
+    write(
+        dir.path().join("hub.md"),
+        "# Hub\n\nA longer body with several words.\n",
+    )
+    .unwrap();
+    write(dir.path().join("leaf.md"), "# Leaf\n\nSee [Hub](hub).\n").unwrap();
+
+    let (stdout, stderr, success) = run_iwe(
+        dir.path(),
+        &[
+            "--filter",
+            "words: { $gt: 3 }",
+            "--sort",
+            "backlinks:-1",
+            "--project",
+            "{key: $key, backlinks: 1}",
+            "-f",
+            "json",
+        ],
+    );
+    assert!(success, "stderr: {}", stderr);
+    let expected = indoc! {r#"
+        [
+          {
+            "key": "hub",
+            "backlinks": 1
+          },
+          {
+            "key": "leaf",
+            "backlinks": 0
+          }
+        ]
+    "#};
+    assert_eq!(stdout, expected);
+}
+
+#[test]
+fn test_find_ignores_invalid_computed_fields() {
+    let dir = setup_workspace();
+    let config_path = dir.path().join(".iwe").join("config.toml");
+    let mut config = std::fs::read_to_string(&config_path).unwrap();
+    config.push_str(indoc! {r#"
+
+        [fields.words]
+        type = "words"
+
+        [fields.broken]
+        type = "edges"
+        edge = "sideways"
+
+        [fields.unknown]
+        type = "sideways"
+    "#});
+    write(&config_path, config).unwrap();
+
+    write(
+        dir.path().join("hub.md"),
+        "# Hub\n\nA longer body with several words.\n",
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_iwe(
+        dir.path(),
+        &[
+            "--filter",
+            "words: { $gt: 3 }",
+            "--project",
+            "{key: $key, words: 1}",
+            "-f",
+            "json",
+        ],
+    );
+    assert!(success, "stderr: {}", stderr);
+    assert!(stderr.contains("field 'broken'"), "stderr: {}", stderr);
+    assert!(stderr.contains("field 'unknown'"), "stderr: {}", stderr);
+    let expected = indoc! {r#"
+        [
+          {
+            "key": "hub",
+            "words": 8
+          }
+        ]
+    "#};
+    assert_eq!(stdout, expected);
+}
@@ -0,0 +1,192 @@
+use std::fs::read_to_string;
+use std::process::Command;
+use tempfile::TempDir;
+
+use diwe::config::{Configuration, JournalOptions, PeriodOptions};
+use indoc::indoc;
+
+fn setup_journal(journal: JournalOptions) -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+
+    Command::new(crate::common::get_iwe_binary_path())
+        .arg("init")
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to initialize IWE");
+
+    let config_path = temp_dir.path().join(".iwe").join("config.toml");
+    let mut config: Configuration =
+        toml::from_str(&read_to_string(&config_path).expect("Read config")).expect("Parse config");
+    config.library.locale = Some("en_US".to_string());
+    config.journal = journal;
+    std::fs::write(
+        &config_path,
+        toml::to_string(&config).expect("Serialize config"),
+    )
+    .expect("Write config");
+
+    temp_dir
+}
+
+fn all_periods() -> JournalOptions {
+    JournalOptions {
+        daily: Some(PeriodOptions::default()),
+        weekly: Some(PeriodOptions::default()),
+        monthly: Some(PeriodOptions {
+            hub: Some("journal".to_string()),
+            ..Default::default()
+        }),
+    }
+}
+
+fn run_journal(temp_dir: &TempDir, args: &[&str]) -> std::process::Output {
+    Command::new(crate::common::get_iwe_binary_path())
+        .arg("journal")
+        .args(args)
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute iwe journal")
+}
+
+fn read_doc(temp_dir: &TempDir, key: &str) -> String {
+    read_to_string(temp_dir.path().join(format!("{key}.md"))).expect("Read document")
+}
+
+#[test]
+fn journal_creates_the_day_with_its_week_month_and_hub() {
+    let temp_dir = setup_journal(all_periods());
+
+    let output = run_journal(&temp_dir, &["2026-10-17"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.trim().ends_with("journal/2026-10-17.md"), "{stdout}");
+
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-10-17"),
+        indoc! {"
+            # Saturday, October 17, 2026
+        "}
+    );
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-W42"),
+        indoc! {"
+            # Week 42, 2026
+
+            [Saturday, October 17, 2026](2026-10-17)
+        "}
+    );
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-10"),
+        indoc! {"
+            # October 2026
+
+            [Week 42, 2026](2026-W42)
+        "}
+    );
+    assert_eq!(
+        read_doc(&temp_dir, "journal"),
+        indoc! {"
+            # journal
+
+            [October 2026](journal/2026-10)
+        "}
+    );
+}
+
+#[test]
+fn journal_links_new_days_into_an_existing_week() {
+    let temp_dir = setup_journal(all_periods());
+
+    run_journal(&temp_dir, &["2026-10-17"]);
+    let output = run_journal(&temp_dir, &["2026-10-18"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-W42"),
+        indoc! {"
+            # Week 42, 2026
+
+            [Saturday, October 17, 2026](2026-10-17)
+
+            [Sunday, October 18, 2026](2026-10-18)
+        "}
+    );
+}
+
+#[test]
+fn journal_links_only_to_existing_neighbours() {
+    let temp_dir = setup_journal(all_periods());
+
+    run_journal(&temp_dir, &["2026-10-17"]);
+    run_journal(&temp_dir, &["2026-10-19"]);
+    let output = run_journal(&temp_dir, &["2026-10-18"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-10-19"),
+        indoc! {"
+            # Monday, October 19, 2026
+        "}
+    );
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-10-18"),
+        indoc! {"
+            # Sunday, October 18, 2026
+
+            [← Saturday, October 17, 2026](2026-10-17) · [Monday, October 19, 2026 →](2026-10-19)
+        "}
+    );
+}
+
+#[test]
+fn journal_leaves_an_existing_note_alone() {
+    let temp_dir = setup_journal(all_periods());
+    std::fs::create_dir_all(temp_dir.path().join("journal")).unwrap();
+    std::fs::write(
+        temp_dir.path().join("journal/2026-10.md"),
+        "# October\n\nWritten by hand.\n",
+    )
+    .unwrap();
+
+    let output = run_journal(&temp_dir, &["2026-10"]);
+
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .trim()
+        .ends_with("journal/2026-10.md"));
+    assert_eq!(
+        read_doc(&temp_dir, "journal/2026-10"),
+        "# October\n\nWritten by hand.\n"
+    );
+    assert!(!temp_dir.path().join("journal.md").exists());
+}
+
+#[test]
+fn journal_rejects_an_unconfigured_period() {
+    let temp_dir = setup_journal(JournalOptions {
+        daily: Some(PeriodOptions::default()),
+        ..Default::default()
+    });
+
+    let output = run_journal(&temp_dir, &["next-week"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: [journal.weekly] is not configured\n"
+    );
+}
+
+#[test]
+fn journal_rejects_an_unknown_period() {
+    let temp_dir = setup_journal(all_periods());
+
+    let output = run_journal(&temp_dir, &["someday"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: unknown period 'someday'"));
+}
@@ -0,0 +1,169 @@
+use indoc::indoc;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+#[test]
+fn lint_passes_on_clean_library() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(temp.path(), "");
+    write(temp.path().join("index.md"), "# Index\n\n[Note](note)\n").unwrap();
+    write(temp.path().join("note.md"), "# Note\n\nText.\n").unwrap();
+
+    let output = run(temp.path(), &[]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "");
+}
+
+#[test]
+fn lint_fails_on_errors() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(temp.path(), "");
+    write(temp.path().join("index.md"), "# Index\n\n[Gone](missing)\n").unwrap();
+
+    let output = run(temp.path(), &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "index:3 error › dangling-link: links to missing 'missing'\n"
+    );
+}
+
+#[test]
+fn lint_warnings_fail_only_when_denied() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(temp.path(), "");
+    write(temp.path().join("index.md"), "# Index\n\n[Note](note)\n").unwrap();
+    write(temp.path().join("note.md"), "# Note\n\n### Deep\n\nText.\n").unwrap();
+
+    assert_eq!(run(temp.path(), &[]).status.code(), Some(0));
+    assert_eq!(
+        run(temp.path(), &["--deny-warnings"]).status.code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn lint_json_output_and_config_severities() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(
+        temp.path(),
+        indoc! {r#"
+            [lint.rules]
+            dangling-link = "info"
+        "#},
+    );
+    write(temp.path().join("index.md"), "# Index\n\n[Gone](missing)\n").unwrap();
+
+    let output = run(temp.path(), &["--format", "json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json[0]["key"], "index");
+    assert_eq!(json[0]["rule"], "dangling-link");
+    assert_eq!(json[0]["severity"], "info");
+    assert_eq!(json[0]["line"], 3);
+}
+
+#[test]
+fn lint_rejects_unknown_rules() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(
+        temp.path(),
+        indoc! {r#"
+            [lint.rules]
+            no-such-rule = "error"
+        "#},
+    );
+    write(temp.path().join("index.md"), "# Index\n").unwrap();
+
+    let output = run(temp.path(), &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("unknown lint rule 'no-such-rule'"));
+}
+
+#[test]
+fn lint_diff_previews_fixes_without_writing() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(temp.path(), "");
+    write(temp.path().join("index.md"), "# Index\n\n[Note](nte)\n").unwrap();
+    write(temp.path().join("note.md"), "# Note\n\nText.\n").unwrap();
+
+    let output = run(temp.path(), &["--diff"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        indoc! {"
+            --- a/index.md
+            +++ b/index.md
+            @@ -1,3 +1,3 @@
+             # Index
+             
+            -[Note](nte)
+            +[Note](note)
+        "}
+    );
+    assert_eq!(
+        read_to_string(temp.path().join("index.md")).unwrap(),
+        "# Index\n\n[Note](nte)\n"
+    );
+}
+
+#[test]
+fn lint_fix_writes_fixes_and_reports_the_rest() {
+    let temp = TempDir::new().expect("tempdir");
+    write_config(
+        temp.path(),
+        indoc! {r##"
+            [lint]
+            attach_orphans = "hub"
+
+            [actions.hub]
+            type = "attach"
+            title = "Hub"
+            key_template = "index"
+            document_template = "# Index\n\n{{content}}\n"
+        "##},
+    );
+    write(
+        temp.path().join("index.md"),
+        "# Index\n\n[Note](nte)\n\n[Gone](zzzz)\n",
+    )
+    .unwrap();
+    write(temp.path().join("note.md"), "# Note\n\n### Deep\n\nText.\n").unwrap();
+    write(temp.path().join("lonely.md"), "# Lonely\n\nText.\n").unwrap();
+
+    let output = run(temp.path(), &["--fix"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "index:5 error › dangling-link: links to missing 'zzzz'\n"
+    );
+    assert_eq!(
+        read_to_string(temp.path().join("index.md")).unwrap(),
+        "# Index\n\n[Note](note)\n\n[Gone](zzzz)\n\n[Lonely](lonely)\n"
+    );
+    assert_eq!(
+        read_to_string(temp.path().join("note.md")).unwrap(),
+        "# Note\n\n## Deep\n\nText.\n"
+    );
+}
+
+fn write_config(path: &Path, extra: &str) {
+    create_dir_all(path.join(".iwe")).unwrap();
+    write(
+        path.join(".iwe/config.toml"),
+        format!("library.path = \"\"\nmarkdown.refs_extension = \"\"\n{extra}"),
+    )
+    .unwrap();
+}
+
+fn run(work_dir: &Path, args: &[&str]) -> Output {
+    let mut cmd = Command::new(crate::common::get_iwe_binary_path());
+    cmd.arg("lint").current_dir(work_dir);
+    for arg in args {
+        cmd.arg(arg);
+    }
+    cmd.output().expect("run iwe command")
+}
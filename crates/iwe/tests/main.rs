@@ -1,5 +1,6 @@
 mod common;
 
+mod assets_test;
 mod attach_test;
 mod cli_filter_test;
 mod completions_test;
@@ -11,6 +12,9 @@ mod extract_test;
 mod find_test;
 mod init_test;
 mod inline_test;
+mod journal_test;
+mod lint_test;
+mod mentions_test;
 mod new_test;
 mod normalize_test;
 mod rename_test;
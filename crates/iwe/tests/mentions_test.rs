@@ -0,0 +1,95 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+#[test]
+fn mentions_lists_unlinked_mentions() {
+    let temp = library();
+
+    let output = run(temp.path(), &["-k", "garden"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "notes:3:13: Work in the gardens, see [Garden](garden).\n"
+    );
+}
+
+#[test]
+fn mentions_json_output() {
+    let temp = library();
+
+    let output = run(temp.path(), &["-k", "garden", "-f", "json"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([{
+            "key": "notes",
+            "line": 2,
+            "start": 12,
+            "end": 19,
+            "text": "gardens",
+            "context": "Work in the gardens, see [Garden](garden).",
+        }])
+    );
+}
+
+#[test]
+fn mentions_link_rewrites_documents() {
+    let temp = library();
+
+    let output = run(temp.path(), &["-k", "garden", "--link"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        read_to_string(temp.path().join("notes.md")).unwrap(),
+        "# Notes\n\nWork in the [gardens](garden), see [Garden](garden).\n"
+    );
+}
+
+#[test]
+fn mentions_diff_does_not_write() {
+    let temp = library();
+
+    let output = run(temp.path(), &["-k", "garden", "--diff"]);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("+Work in the [gardens](garden), see [Garden](garden)."));
+    assert_eq!(
+        read_to_string(temp.path().join("notes.md")).unwrap(),
+        "# Notes\n\nWork in the gardens, see [Garden](garden).\n"
+    );
+}
+
+#[test]
+fn mentions_of_missing_document() {
+    let temp = library();
+
+    let output = run(temp.path(), &["-k", "missing"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+fn library() -> TempDir {
+    let temp = TempDir::new().expect("tempdir");
+    create_dir_all(temp.path().join(".iwe")).unwrap();
+    write(
+        temp.path().join(".iwe/config.toml"),
+        "library.path = \"\"\nmarkdown.refs_extension = \"\"\n",
+    )
+    .unwrap();
+    write(temp.path().join("garden.md"), "# Garden\n").unwrap();
+    write(
+        temp.path().join("notes.md"),
+        "# Notes\n\nWork in the gardens, see [Garden](garden).\n",
+    )
+    .unwrap();
+    temp
+}
+
+fn run(work_dir: &Path, args: &[&str]) -> Output {
+    let mut cmd = Command::new(crate::common::get_iwe_binary_path());
+    cmd.arg("mentions").current_dir(work_dir);
+    for arg in args {
+        cmd.arg(arg);
+    }
+    cmd.output().expect("run iwe command")
+}
@@ -3,7 +3,8 @@ use std::io::Write;
 use std::process::{Command, Stdio};
 use tempfile::TempDir;
 
-use diwe::config::{Configuration, NoteTemplate};
+use diwe::config::{Configuration, NoteTemplate, Patterns, SchemaBinding};
+use indoc::indoc;
 
 fn setup_iwe_project() -> TempDir {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
@@ -29,6 +30,42 @@ fn add_template(temp_dir: &TempDir, name: &str, template: NoteTemplate) {
     std::fs::write(&config_path, updated_config).expect("Write config");
 }
 
+fn add_schema(temp_dir: &TempDir, name: &str, pattern: &str, source: &str) {
+    let config_path = temp_dir.path().join(".iwe").join("config.toml");
+    let config_content = read_to_string(&config_path).expect("Read config");
+    let mut config: Configuration = toml::from_str(&config_content).expect("Parse config");
+
+    config.schemas.insert(
+        name.to_string(),
+        SchemaBinding {
+            r#match: Patterns::One(pattern.to_string()),
+        },
+    );
+
+    let updated_config = toml::to_string(&config).expect("Serialize config");
+    std::fs::write(&config_path, updated_config).expect("Write config");
+
+    let schemas = temp_dir.path().join(".iwe").join("schemas");
+    std::fs::create_dir_all(&schemas).expect("Create schemas dir");
+    std::fs::write(schemas.join(format!("{name}.yaml")), source).expect("Write schema");
+}
+
+const NOTE_SCHEMA: &str = indoc! {r#"
+    frontmatter:
+      type: object
+      required: [type, status, priority]
+      properties:
+        type: { const: note }
+        status: { enum: [draft, published] }
+        priority: { type: integer }
+    sections:
+      - header: { pattern: "^[A-Z]" }
+        maxContains: 1
+        sections:
+          - header: { const: Summary }
+          - header: { const: Tasks }
+"#};
+
 #[test]
 fn test_new_creates_file_with_default_template() {
     let temp_dir = setup_iwe_project();
@@ -775,3 +812,172 @@ fn test_new_key_with_template_renders_body() {
         "Should bypass the template's key derivation"
     );
 }
+
+#[test]
+fn test_new_writes_skeleton_of_bound_schema() {
+    let temp_dir = setup_iwe_project();
+    let temp_path = temp_dir.path();
+    add_schema(&temp_dir, "note", "notes/**", NOTE_SCHEMA);
+
+    let output = Command::new(crate::common::get_iwe_binary_path())
+        .args(["new", "Weekly Review", "--key", "notes/weekly"])
+        .args(["--content", "Looking back."])
+        .current_dir(temp_path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute iwe new");
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let content = read_to_string(temp_path.join("notes/weekly.md")).expect("Read created file");
+    assert_eq!(
+        content,
+        indoc! {"
+            ---
+            type: note
+            status: draft
+            priority: 0
+            ---
+
+            # Weekly Review
+
+            Looking back.
+
+            ## Summary
+
+            ## Tasks
+        "}
+    );
+}
+
+#[test]
+fn test_new_schema_fields_take_var_values() {
+    let temp_dir = setup_iwe_project();
+    let temp_path = temp_dir.path();
+    add_schema(&temp_dir, "note", "notes/**", NOTE_SCHEMA);
+
+    let output = Command::new(crate::common::get_iwe_binary_path())
+        .args(["new", "Plan", "--schema", "note"])
+        .args(["--var", "status=published", "--var", "priority=2"])
+        .current_dir(temp_path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute iwe new");
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let content = read_to_string(temp_path.join("plan.md")).expect("Read created file");
+    assert_eq!(
+        content,
+        indoc! {"
+            ---
+            type: note
+            status: published
+            priority: 2
+            ---
+
+            # Plan
+
+            ## Summary
+
+            ## Tasks
+        "}
+    );
+}
+
+#[test]
+fn test_new_no_schema_keeps_the_template() {
+    let temp_dir = setup_iwe_project();
+    let temp_path = temp_dir.path();
+    add_schema(&temp_dir, "note", "notes/**", NOTE_SCHEMA);
+
+    let output = Command::new(crate::common::get_iwe_binary_path())
+        .args([
+            "new",
+            "Weekly Review",
+            "--key",
+            "notes/weekly",
+            "--no-schema",
+        ])
+        .current_dir(temp_path)
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute iwe new");
+
+    assert!(output.status.success(), "Command should succeed");
+
+    let content = read_to_string(temp_path.join("notes/weekly.md")).expect("Read created file");
+    assert_eq!(content, "# Weekly Review\n\n");
+}
+
+#[test]
+fn test_new_unknown_schema_fails() {
+    let temp_dir = setup_iwe_project();
+
+    let output = Command::new(crate::common::get_iwe_binary_path())
+        .args(["new", "Plan", "--schema", "missing"])
+        .current_dir(temp_dir.path())
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute iwe new");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).expect("Valid UTF-8"),
+        "Error: schema 'missing': .iwe/schemas/missing.yaml not found\n"
+    );
+}
+
+#[test]
+fn test_new_template_lists_query_results_as_links() {
+    let temp_dir = setup_iwe_project();
+    let temp_path = temp_dir.path();
+
+    std::fs::create_dir_all(temp_path.join("projects")).expect("Create projects dir");
+    std::fs::write(
+        temp_path.join("projects/alpha.md"),
+        "---\nstatus: active\n---\n\n# Alpha\n",
+    )
+    .expect("Write alpha");
+    std::fs::write(
+        temp_path.join("projects/beta.md"),
+        "---\nstatus: done\n---\n\n# Beta\n",
+    )
+    .expect("Write beta");
+
+    add_template(
+        &temp_dir,
+        "review",
+        NoteTemplate {
+            key_template: "reviews/{{slug}}".to_string(),
+            document_template: indoc! {"
+                # {{title}}
+
+                {% for doc in query('status: active') -%}
+                - {{ link(doc.key) }} ({{ frontmatter(doc.key, 'status') }})
+                {% endfor %}"}
+            .to_string(),
+        },
+    );
+
+    let output = Command::new(crate::common::get_iwe_binary_path())
+        .arg("new")
+        .arg("Weekly")
+        .arg("--template")
+        .arg("review")
+        .current_dir(temp_path)
+        .output()
+        .expect("Failed to execute iwe new");
+
+    assert!(
+        output.status.success(),
+        "Command should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let content = read_to_string(temp_path.join("reviews/weekly.md")).expect("Read note");
+    assert_eq!(
+        content,
+        "# Weekly\n\n- [Alpha](../projects/alpha) (active)\n"
+    );
+}
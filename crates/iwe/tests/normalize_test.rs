@@ -720,3 +720,244 @@ fn test_normalize_keeps_link_to_grandparent_hub() {
         "},
     );
 }
+
+#[test]
+fn test_normalize_refreshes_query_blocks() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    setup_iwe_config(temp_path);
+
+    write(
+        temp_path.join("index.md"),
+        indoc! {"
+            # Projects
+
+            ```iwe-query
+            filter:
+              status: active
+            render: inclusion
+            ```
+        "},
+    )
+    .expect("Should write index");
+    write(
+        temp_path.join("beta.md"),
+        "---\nstatus: active\n---\n\n# Beta\n",
+    )
+    .expect("Should write beta");
+    write(
+        temp_path.join("alpha.md"),
+        "---\nstatus: active\n---\n\n# Alpha\n",
+    )
+    .expect("Should write alpha");
+
+    let output = run_normalize_command(temp_path);
+    assert!(output.status.success(), "Normalize command should succeed");
+
+    let expected = indoc! {"
+        # Projects
+
+        ``` iwe-query
+        filter:
+          status: active
+        render: inclusion
+        ```
+
+        <!-- iwe-query:begin -->
+
+        [Alpha](alpha)
+
+        [Beta](beta)
+
+        <!-- iwe-query:end -->
+    "};
+    assert_eq!(
+        read_to_string(temp_path.join("index.md")).expect("Should read index"),
+        expected
+    );
+
+    let output = run_normalize_command(temp_path);
+    assert!(output.status.success(), "Second normalize should succeed");
+    assert_eq!(
+        read_to_string(temp_path.join("index.md")).expect("Should read index"),
+        expected
+    );
+}
+
+#[test]
+fn test_normalize_reports_invalid_query_blocks() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    setup_iwe_config(temp_path);
+
+    let index = "# Projects\n\n``` iwe-query\nfilter:\n  $bogus: 1\n```\n";
+    write(temp_path.join("index.md"), index).expect("Should write index");
+
+    let output = run_normalize_command(temp_path);
+    assert!(output.status.success(), "Normalize command should succeed");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("warning: index: iwe-query block:"),
+        "{stderr}"
+    );
+    assert_eq!(
+        read_to_string(temp_path.join("index.md")).expect("Should read index"),
+        index
+    );
+}
+
+fn setup_check_workspace() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+
+    setup_iwe_config(temp_path);
+
+    write(
+        temp_path.join("clean.md"),
+        "# Clean\n\nAlready normalized.\n",
+    )
+    .unwrap();
+    write(
+        temp_path.join("messy.md"),
+        "---\nstatus: draft\n---\n# Messy\n* one\n* two\n",
+    )
+    .unwrap();
+    create_dir_all(temp_path.join("notes")).unwrap();
+    write(temp_path.join("notes/inner.md"), "# Inner\n* item\n").unwrap();
+
+    temp_dir
+}
+
+fn run_normalize_with(work_dir: &std::path::Path, args: &[&str]) -> std::process::Output {
+    Command::new(crate::common::get_iwe_binary_path())
+        .arg("normalize")
+        .args(args)
+        .current_dir(work_dir)
+        .output()
+        .expect("Failed to execute iwe normalize")
+}
+
+#[test]
+fn test_normalize_check_lists_unnormalized_files_without_writing() {
+    let temp_dir = setup_check_workspace();
+    let temp_path = temp_dir.path();
+
+    let output = run_normalize_with(temp_path, &["--check"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "messy.md\nnotes/inner.md\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "2 document(s) not normalized\n"
+    );
+    assert_eq!(
+        read_to_string(temp_path.join("messy.md")).unwrap(),
+        "---\nstatus: draft\n---\n# Messy\n* one\n* two\n"
+    );
+}
+
+#[test]
+fn test_normalize_check_passes_once_normalized() {
+    let temp_dir = setup_check_workspace();
+    let temp_path = temp_dir.path();
+
+    let output = run_normalize_command(temp_path);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Matched 3 document(s), 2 changed\n"
+    );
+
+    let output = run_normalize_with(temp_path, &["--check"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_normalize_diff_prints_unified_diff() {
+    let temp_dir = setup_check_workspace();
+    let temp_path = temp_dir.path();
+
+    let output = run_normalize_with(temp_path, &["--diff", "messy.md"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        indoc! {"
+            --- a/messy.md
+            +++ b/messy.md
+            @@ -1,6 +1,8 @@
+             ---
+             status: draft
+             ---
+            +
+             # Messy
+            -* one
+            -* two
+            +
+            +- one
+            +- two
+        "}
+    );
+    assert_eq!(
+        read_to_string(temp_path.join("messy.md")).unwrap(),
+        "---\nstatus: draft\n---\n# Messy\n* one\n* two\n"
+    );
+}
+
+#[test]
+fn test_normalize_dry_run_reports_without_writing() {
+    let temp_dir = setup_check_workspace();
+    let temp_path = temp_dir.path();
+
+    let output = run_normalize_with(temp_path, &["--dry-run", "notes"]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Would update 1 document(s)\n"
+    );
+    assert_eq!(
+        read_to_string(temp_path.join("notes/inner.md")).unwrap(),
+        "# Inner\n* item\n"
+    );
+}
+
+#[test]
+fn test_normalize_selector_limits_documents() {
+    let temp_dir = setup_check_workspace();
+    let temp_path = temp_dir.path();
+
+    let output = run_normalize_with(temp_path, &["--filter", "status: draft", "--quiet"]);
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        read_to_string(temp_path.join("messy.md")).unwrap(),
+        "---\nstatus: draft\n---\n\n# Messy\n\n- one\n- two\n"
+    );
+    assert_eq!(
+        read_to_string(temp_path.join("notes/inner.md")).unwrap(),
+        "# Inner\n* item\n"
+    );
+}
+
+#[test]
+fn test_normalize_rejects_paths_outside_the_library() {
+    let temp_dir = setup_check_workspace();
+    let temp_path = temp_dir.path();
+
+    let output = run_normalize_with(temp_path, &["--check", "missing.md"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: missing.md: not a document or directory in the library\n"
+    );
+}
@@ -1,6 +1,6 @@
 use diwe::config::{Configuration, LibraryOptions, MarkdownOptions};
 use indoc::indoc;
-use std::fs::{create_dir_all, write};
+use std::fs::{create_dir_all, read_to_string, write};
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -149,6 +149,115 @@ fn test_schema_empty_workspace() {
     assert_eq!(fields.len(), 0);
 }
 
+#[test]
+fn test_schema_infer_without_emit_matches_bare_schema() {
+    let temp_dir = setup_workspace();
+    let bare = run_schema(&temp_dir, &[]);
+    let infer = run_schema(&temp_dir, &["infer"]);
+
+    assert!(infer.status.success(), "Command should succeed");
+    assert_eq!(infer.stdout, bare.stdout);
+}
+
+#[test]
+fn test_schema_infer_emit_writes_schema_and_proposes_binding() {
+    let temp_dir = setup_weekly_workspace();
+    let output = run_schema(
+        &temp_dir,
+        &["infer", "--emit", "weekly", "--filter", "type: note"],
+    );
+
+    assert!(output.status.success(), "Command should succeed");
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("Valid UTF-8 output"),
+        indoc! {r#"
+            wrote .iwe/schemas/weekly.yaml from 3 documents
+
+            bind it in .iwe/config.toml:
+
+            [schemas.weekly]
+            match = "notes/**"
+        "#}
+    );
+
+    let source = read_to_string(temp_dir.path().join(".iwe/schemas/weekly.yaml")).unwrap();
+    let schema: serde_yaml::Value = serde_yaml::from_str(&source).expect("Valid YAML");
+    assert_eq!(
+        schema["frontmatter"]["required"],
+        serde_yaml::from_str::<serde_yaml::Value>("[status, type]").unwrap()
+    );
+    assert_eq!(
+        schema["frontmatter"]["properties"]["status"]["enum"],
+        serde_yaml::from_str::<serde_yaml::Value>("[draft, published]").unwrap()
+    );
+    let headers: Vec<&str> = schema["sections"][0]["sections"]
+        .as_sequence()
+        .expect("nested sections")
+        .iter()
+        .map(|section| section["header"]["const"].as_str().unwrap())
+        .collect();
+    assert_eq!(headers, vec!["Summary", "Tasks"]);
+
+    let validate = run_schema(
+        &temp_dir,
+        &[
+            "validate",
+            "--filter",
+            "type: note",
+            "--schema-file",
+            ".iwe/schemas/weekly.yaml",
+        ],
+    );
+    assert!(
+        validate.status.success(),
+        "Sources satisfy the inferred schema"
+    );
+}
+
+#[test]
+fn test_schema_infer_emit_keeps_an_existing_schema() {
+    let temp_dir = setup_weekly_workspace();
+    create_dir_all(temp_dir.path().join(".iwe/schemas")).unwrap();
+    write(temp_dir.path().join(".iwe/schemas/weekly.yaml"), "{}\n").unwrap();
+
+    let output = run_schema(&temp_dir, &["infer", "--emit", "weekly"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(output.stderr).expect("Valid UTF-8 output"),
+        "error: .iwe/schemas/weekly.yaml already exists; pass --force to overwrite it\n"
+    );
+    assert_eq!(
+        read_to_string(temp_dir.path().join(".iwe/schemas/weekly.yaml")).unwrap(),
+        "{}\n"
+    );
+}
+
+fn setup_weekly_workspace() -> TempDir {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let temp_path = temp_dir.path();
+    create_dir_all(temp_path.join(".iwe")).unwrap();
+    create_dir_all(temp_path.join("notes/2026")).unwrap();
+    write_config(temp_path);
+
+    for (key, status) in [
+        ("notes/one", "draft"),
+        ("notes/two", "draft"),
+        ("notes/2026/three", "published"),
+    ] {
+        write(
+            temp_path.join(format!("{key}.md")),
+            format!(
+                "---\ntype: note\nstatus: {status}\n---\n# {key}\n\n## Summary\n\nA summary.\n\n## Tasks\n\n- a task\n"
+            ),
+        )
+        .unwrap();
+    }
+    write(temp_path.join("index.md"), "# Index\n").unwrap();
+
+    temp_dir
+}
+
 fn setup_workspace() -> TempDir {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
     let temp_path = temp_dir.path();
@@ -458,6 +458,38 @@ fn test_stats_per_doc_csv_format() {
     assert_eq!(stdout, expected);
 }
 
+#[test]
+fn test_stats_graph_csv_format() {
+    let temp_dir = setup_test_workspace();
+    let output = run_stats_command(&temp_dir, &["graph", "-f", "csv"]);
+
+    assert!(output.status.success(), "Should succeed with csv format");
+    let stdout = String::from_utf8(output.stdout).expect("Valid UTF-8 output");
+    let expected = indoc! {"
+        key,title,pagerank,betweenness,component,community
+        related,Related Document,0.5,0.0,0,0
+        test,Test Document,0.5,0.0,0,0
+    "};
+    assert_eq!(stdout, expected);
+}
+
+#[test]
+fn test_stats_graph_json_format() {
+    let temp_dir = setup_test_workspace_with_broken_links();
+    let output = run_stats_command(&temp_dir, &["graph", "-f", "json"]);
+
+    assert!(output.status.success(), "Should succeed with json format");
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Valid JSON output");
+    assert_eq!(
+        report["documents"],
+        report["nodes"].as_array().unwrap().len()
+    );
+    assert!(report["components"].is_array());
+    assert!(report["communities"].is_array());
+    assert_eq!(report["inclusionCycles"], serde_json::json!([]));
+}
+
 fn run_stats_command(temp_dir: &TempDir, args: &[&str]) -> std::process::Output {
     let binary_path = crate::common::get_iwe_binary_path();
 
@@ -1,17 +1,20 @@
 pub mod watcher;
 
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 
 use chrono::Local;
+use diwe::batch::{batch_guard_violations, parse_batch, run_batch, StepResult};
 use diwe::config::{
-    schemas_dir_in, ActionDefinition, CompletionOptions, Configuration, MarkdownOptions,
-    NoteTemplate, DEFAULT_KEY_DATE_FORMAT,
+    load_config_in, schemas_dir_in, ActionDefinition, CompletionOptions, Configuration, LinkType,
+    MarkdownOptions, NoteTemplate, DEFAULT_KEY_DATE_FORMAT,
 };
 use diwe::find::{DocumentFinder, FindOptions, FindOutput};
 use diwe::fs::{new_for_path, new_from_hashmap};
+use diwe::mentions::{link_mentions, MentionFinder};
 use diwe::retrieve::{DocumentReader, RetrieveOptions, RetrieveOutput};
 use diwe::schema::{
     pending_from_changes, render_reports_text, validate_pending_documents,
@@ -20,9 +23,10 @@ use diwe::schema::{
 use diwe::search::Bm25Index;
 use diwe::search_query::{build_index, corpus_text};
 use diwe::stats::{
-    mutation_findings, Finding, GraphStatistics, KeyStatistics, KeyStatisticsReport,
-    SimilarityIndex,
+    mutation_findings, Finding, GraphAnalyticsReport, GraphStatistics, KeyStatistics,
+    KeyStatisticsReport, SimilarityIndex,
 };
+use diwe::template::TemplateEngine;
 use diwe::tokens::Truncation;
 use liwe::graph::{Graph, GraphContext};
 use liwe::model::node::NodePointer;
@@ -35,16 +39,16 @@ use liwe::operations::{
 };
 use liwe::query::cli::parse_projection;
 use liwe::query::{
-    self, execute, parse_operation, strict_guard_violations, Filter, InclusionAnchor, Operation,
+    self, parse_operation, strict_guard_violations, Explain, Filter, InclusionAnchor, Operation,
     OperationKind, Outcome, ProjectionBase,
 };
-use minijinja::{context, Environment};
+use minijinja::context;
 use rmcp::handler::server::router::prompt::PromptRouter;
 use rmcp::handler::server::router::tool::ToolRouter;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::*;
 use rmcp::schemars::JsonSchema;
-use rmcp::service::RequestContext;
+use rmcp::service::{NotificationContext, Peer, RequestContext};
 use rmcp::{prompt, prompt_handler, prompt_router, tool, tool_router, RoleServer};
 use rmcp::{tool_handler, ErrorData as McpError, ServerHandler};
 use serde::{Deserialize, Serialize};
@@ -267,6 +271,7 @@ impl TryFrom<FindParams> for FindOptions {
             project,
             max_tokens: p.max_tokens,
             max_document_tokens: p.max_document_tokens,
+            explain: false,
         })
     }
 }
@@ -423,6 +428,30 @@ pub struct StatsParams {
         description = "Document key for per-document stats. Omit for aggregate graph statistics"
     )]
     pub key: Option<String>,
+    #[schemars(
+        description = "\"summary\" (default) returns counts and rankings. \"graph\" returns PageRank and betweenness per document, connected components, communities and inclusion cycles; with key, only that document's metrics"
+    )]
+    pub mode: Option<StatsMode>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct MentionsParams {
+    #[schemars(description = "Document key whose title and aliases to look for")]
+    pub key: String,
+    #[schemars(
+        description = "Rewrite every mention as a link to the document and return the changes. Default: false"
+    )]
+    pub link: Option<bool>,
+    #[schemars(description = "With link, preview changes without applying. Default: false")]
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsMode {
+    #[default]
+    Summary,
+    Graph,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -487,15 +516,18 @@ pub enum QueryKind {
     Count,
     Update,
     Delete,
+    Batch,
 }
 
-impl From<QueryKind> for OperationKind {
-    fn from(kind: QueryKind) -> Self {
-        match kind {
-            QueryKind::Find => OperationKind::Find,
-            QueryKind::Count => OperationKind::Count,
-            QueryKind::Update => OperationKind::Update,
-            QueryKind::Delete => OperationKind::Delete,
+impl QueryKind {
+    /// The single-operation kind, or `None` for a batch.
+    fn operation_kind(self) -> Option<OperationKind> {
+        match self {
+            QueryKind::Find => Some(OperationKind::Find),
+            QueryKind::Count => Some(OperationKind::Count),
+            QueryKind::Update => Some(OperationKind::Update),
+            QueryKind::Delete => Some(OperationKind::Delete),
+            QueryKind::Batch => None,
         }
     }
 }
@@ -503,11 +535,11 @@ impl From<QueryKind> for OperationKind {
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct QueryParams {
     #[schemars(
-        description = "Operation kind: find (read documents), count (count documents), update (mutate frontmatter and/or blocks), or delete (remove documents)."
+        description = "Operation kind: find (read documents), count (count documents), update (mutate frontmatter and/or blocks), delete (remove documents), or batch (run several operations all-or-nothing)."
     )]
     pub operation: QueryKind,
     #[schemars(
-        description = "The operation document as YAML. Uses the IWE query + block-selection language: `filter` (with $content block membership), `project`/`addFields` ($content narrowing, $blocks, $matches), `sort`, `limit` for reads; `filter` + `update` (with block operators $replace, $replaceText, $insertBefore, $insertAfter, $append, $delete) for update; `filter` + `expect` for delete. This surface is always strict: every mutating application must carry an `expect` guard (document-level `expect`, and one per block operator)."
+        description = "The operation document as YAML. Uses the IWE query + block-selection language: `filter` (with $content block membership), `project`/`addFields` ($content narrowing, $blocks, $matches), `sort`, `limit` for reads; `filter` + `update` (with block operators $replace, $replaceText, $insertBefore, $insertAfter, $append, $delete) for update; `filter` + `expect` for delete. For batch: an `operations` list of single-key entries (`find`, `count`, `update`, `delete` over an operation document, or `create: { key, content }`) run in order against a working copy; each step sees the previous steps' effects, and nothing is written unless every step succeeds and the combined result passes schema validation. This surface is always strict: every mutating application must carry an `expect` guard (document-level `expect`, and one per block operator)."
     )]
     pub document: String,
     #[schemars(
@@ -515,6 +547,17 @@ pub struct QueryParams {
     )]
     #[serde(default)]
     pub dry_run: Option<bool>,
+    #[schemars(
+        description = "Return an evaluation profile next to the result: the parsed filter tree in evaluation order with candidate-set sizes and time per clause, search candidate counts, and `$content` block evaluations. Explained update/delete run as a dry run. Default: false."
+    )]
+    #[serde(default)]
+    pub explain: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExplainedOutput<T: Serialize> {
+    result: T,
+    explain: Explain,
 }
 
 #[derive(Debug, Serialize)]
@@ -523,6 +566,14 @@ struct QueryUpdateOutput {
     changed: Vec<ChangeEntry>,
 }
 
+#[derive(Debug, Serialize)]
+struct QueryBatchOutput {
+    dry_run: bool,
+    steps: Vec<StepResult>,
+    #[serde(flatten)]
+    changes: ChangesOutput,
+}
+
 #[derive(Debug, Serialize)]
 struct QueryCountOutput {
     count: usize,
@@ -671,7 +722,11 @@ struct ActionResourceView {
 }
 
 impl ConfigResource {
-    fn from_config(config: &Configuration, server: &IweServer) -> Result<Self, String> {
+    fn from_config(
+        config: &Configuration,
+        server: &IweServer,
+        graph: &Arc<Graph>,
+    ) -> Result<Self, String> {
         let actions = config
             .actions
             .iter()
@@ -681,7 +736,7 @@ impl ConfigResource {
                     ActionDefinition::Attach(a) => (
                         "attach",
                         a.title.clone(),
-                        Some(server.render_key_template(&a.key_template)?),
+                        Some(server.render_key_template(&a.key_template, graph)?),
                     ),
                     ActionDefinition::Sort(a) => ("sort", a.title.clone(), None),
                     ActionDefinition::Inline(a) => ("inline", a.title.clone(), None),
@@ -729,11 +784,29 @@ pub struct RefactorPromptArgs {
     pub key: String,
 }
 
+/// The running document watcher and how it was started, so that it can be restarted.
+#[derive(Default)]
+struct DocumentWatch {
+    task: Option<tokio::task::AbortHandle>,
+    poll: Option<Duration>,
+}
+
+/// The library of the configuration rooted at `root`: `[library] path` below it, else `root`.
+pub fn library_path_in(root: &Path, configuration: &Configuration) -> PathBuf {
+    let mut path = root.to_path_buf();
+    if !configuration.library.path.is_empty() {
+        path.push(&configuration.library.path);
+    }
+    path
+}
+
 #[derive(Clone)]
 pub struct IweServer {
-    graph: Arc<Mutex<Graph>>,
-    base_path: Option<PathBuf>,
-    config: Configuration,
+    graph: Arc<Mutex<Arc<Graph>>>,
+    base_path: Arc<RwLock<Option<PathBuf>>>,
+    config: Arc<RwLock<Arc<Configuration>>>,
+    watch: Arc<std::sync::Mutex<DocumentWatch>>,
+    peers: Arc<Mutex<Vec<Peer<RoleServer>>>>,
     index: Arc<Mutex<Option<Bm25Index>>>,
     seen: Arc<Mutex<HashSet<Finding>>>,
     tool_router: ToolRouter<IweServer>,
@@ -752,7 +825,7 @@ impl IweServer {
         let options: FindOptions = params.try_into()?;
         let graph = self.graph.lock().await;
         let index = (options.lexical.is_some() || options.fuzzy.is_some())
-            .then(|| diwe::search_query::build_index(&graph, self.config.search_language()));
+            .then(|| diwe::search_query::build_index(&graph, self.config().search_language()));
         let finder = match &index {
             Some(index) => DocumentFinder::with_index(&graph, index),
             None => DocumentFinder::new(&graph),
@@ -792,7 +865,7 @@ impl IweServer {
                 Some(f) => query::evaluate(f, &graph),
             };
             let spec = query::SearchSpec::new(params.search.clone(), params.fuzzy.clone());
-            let index = diwe::search_query::build_index(&graph, self.config.search_language());
+            let index = diwe::search_query::build_index(&graph, self.config().search_language());
             let seeds = diwe::search_query::ranked(&graph, &index, &candidates, &spec);
             reader.retrieve_many(&seeds, &options)
         } else {
@@ -839,7 +912,7 @@ impl IweServer {
                 .iter()
                 .filter(|n| n.ids().len() == 1)
                 .filter_map(|n| n.first_id())
-                .map(|id| (&*graph).node(id).node_key())
+                .map(|id| (&**graph).node(id).node_key())
                 .collect();
             keys.sort();
             keys.dedup();
@@ -858,13 +931,29 @@ impl IweServer {
     }
 
     #[tool(
-        description = "Get comprehensive statistics about the knowledge graph including document counts, reference patterns, broken links, and most connected documents"
+        description = "Get comprehensive statistics about the knowledge graph including document counts, reference patterns, broken links, and most connected documents. Set mode to \"graph\" for centrality, communities, components and inclusion cycles"
     )]
     async fn iwe_stats(
         &self,
         Parameters(params): Parameters<StatsParams>,
     ) -> Result<CallToolResult, McpError> {
         let graph = self.graph.lock().await;
+        if let StatsMode::Graph = params.mode.unwrap_or_default() {
+            let report = GraphAnalyticsReport::from_graph(&graph);
+            return match params.key {
+                Some(key) => {
+                    let node = report
+                        .nodes
+                        .into_iter()
+                        .find(|node| node.key == key)
+                        .ok_or_else(|| {
+                            McpError::invalid_params(format!("Document '{}' not found", key), None)
+                        })?;
+                    to_json_result(&node)
+                }
+                None => to_json_result(&report),
+            };
+        }
         if let Some(key) = params.key {
             let all_stats = KeyStatistics::from_graph(&graph);
             let stat = all_stats
@@ -873,7 +962,7 @@ impl IweServer {
                 .ok_or_else(|| {
                     McpError::invalid_params(format!("Document '{}' not found", key), None)
                 })?;
-            let similar = SimilarityIndex::build(&graph, self.config.search_language())
+            let similar = SimilarityIndex::build(&graph, self.config().search_language())
                 .similar(&Key::name(&key));
             to_json_result(&KeyStatisticsReport {
                 stats: stat,
@@ -885,6 +974,45 @@ impl IweServer {
         }
     }
 
+    #[tool(
+        description = "Find unlinked mentions of a document: plain-text occurrences of its title or frontmatter aliases in other documents, matched as whole stemmed words. Returns key, zero-based line, UTF-16 start/end, matched text and the line as context. Set link to rewrite the mentions as links"
+    )]
+    async fn iwe_mentions(
+        &self,
+        Parameters(params): Parameters<MentionsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let key = Key::name(&params.key);
+        let mut graph = self.graph.lock().await;
+
+        if graph.maybe_key(&key).is_none() {
+            return Err(McpError::invalid_params(
+                format!("Document '{}' not found", params.key),
+                None,
+            ));
+        }
+
+        let mentions = MentionFinder::new(&graph, self.config().search_language()).find(&key);
+        if !params.link.unwrap_or(false) {
+            return to_json_result(&mentions);
+        }
+
+        let link_type = self
+            .config()
+            .completion
+            .link_format
+            .clone()
+            .unwrap_or(LinkType::Markdown);
+        let changes = link_mentions(&graph, &key, &mentions, &link_type);
+
+        if !params.dry_run.unwrap_or(false) {
+            self.ensure_schema_clean(&pending_from_changes(&changes))?;
+            Self::apply_changes(&mut graph, &changes);
+            self.write_changes(&changes);
+        }
+
+        to_json_result(&ChangesOutput::from(&changes))
+    }
+
     #[tool(
         description = "Expand all block references into a single flat markdown document. Useful for export or generating a complete view of a document tree"
     )]
@@ -896,14 +1024,14 @@ impl IweServer {
         let key = Key::name(&params.key);
         let depth = params.depth.unwrap_or(2);
 
-        if (&*graph).get_node_id(&key).is_none() {
+        if (&**graph).get_node_id(&key).is_none() {
             return Err(McpError::invalid_params(
                 format!("Document '{}' not found", params.key),
                 None,
             ));
         }
 
-        let squashed: Tree = (&*graph).squash(&key, depth);
+        let squashed: Tree = (&**graph).squash(&key, depth);
         let mut patch = Graph::new();
         patch.build_key_from_iter(&key, TreeIter::new(&squashed));
         let content = patch.export_key(&key).unwrap_or_default();
@@ -969,7 +1097,7 @@ impl IweServer {
         let key = Key::name(&key_name);
         let mut graph = self.graph.lock().await;
 
-        if (&*graph).get_node_id(&key).is_some() || self.document_file_exists(&key) {
+        if (&**graph).get_node_id(&key).is_some() || self.document_file_exists(&key) {
             return match params.if_exists {
                 Some(CreateIfExists::Skip) => to_json_result_with_warnings(
                     &CreateResult {
@@ -987,7 +1115,7 @@ impl IweServer {
 
         self.ensure_schema_clean(&[(key.clone(), markdown.clone())])?;
 
-        graph.insert_document(key.clone(), markdown.clone());
+        Arc::make_mut(&mut graph).insert_document(key.clone(), markdown.clone());
         self.write_file(&key, &markdown);
 
         let warnings = self
@@ -1018,23 +1146,23 @@ impl IweServer {
         let key = Key::name(&params.key);
         let mut graph = self.graph.lock().await;
 
-        if (&*graph).get_node_id(&key).is_none() {
+        if (&**graph).get_node_id(&key).is_none() {
             return Err(McpError::invalid_params(
                 format!("Document '{}' not found", params.key),
                 None,
             ));
         }
 
-        let previous_title = (&*graph)
+        let previous_title = (&**graph)
             .get_key_title(&key)
             .unwrap_or_else(|| params.key.clone());
 
         self.ensure_schema_clean(&[(key.clone(), params.content.clone())])?;
 
-        graph.update_document(key.clone(), params.content.clone());
+        Arc::make_mut(&mut graph).update_document(key.clone(), params.content.clone());
         self.write_file(&key, &params.content);
 
-        let new_title = (&*graph)
+        let new_title = (&**graph)
             .get_key_title(&key)
             .unwrap_or_else(|| params.key.clone());
 
@@ -1092,7 +1220,9 @@ impl IweServer {
         &self,
         Parameters(params): Parameters<QueryParams>,
     ) -> Result<CallToolResult, McpError> {
-        let kind: OperationKind = params.operation.into();
+        let Some(kind) = params.operation.operation_kind() else {
+            return self.query_batch(&params).await;
+        };
         let op = parse_operation(&params.document, kind)
             .map_err(|e| McpError::invalid_params(format!("invalid operation: {}", e), None))?;
 
@@ -1108,50 +1238,46 @@ impl IweServer {
             ));
         }
 
-        let dry_run = params.dry_run.unwrap_or(false);
+        let explain = params.explain.unwrap_or(false);
+        let dry_run = params.dry_run.unwrap_or(false) || explain;
         let mut graph = self.graph.lock().await;
 
         let index = match &op {
             Operation::Find(find) if find.search.is_some() => Some(
-                diwe::search_query::build_index(&graph, self.config.search_language()),
+                diwe::search_query::build_index(&graph, self.config().search_language()),
             ),
             _ => None,
         };
 
-        match &op {
-            Operation::Find(find) => {
-                let outcome = diwe::search_query::execute(&op, &graph, index.as_ref())
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                let Outcome::Find { matches } = outcome else {
-                    unreachable!("find operation yields a find outcome")
-                };
+        let (outcome, report) = if explain {
+            let (outcome, report) = diwe::search_query::explain(&op, &graph, index.as_ref())
+                .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+            (outcome, Some(report))
+        } else {
+            let outcome = diwe::search_query::execute(&op, &graph, index.as_ref())
+                .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+            (outcome, None)
+        };
+
+        match outcome {
+            Outcome::Find { matches } => {
                 let documents: Vec<_> = matches.into_iter().map(|m| m.document).collect();
                 let mut warnings = Vec::new();
-                if let Some(spec) = &find.search {
-                    if index
-                        .as_ref()
-                        .map(|idx| diwe::search_query::lexical_has_no_terms(idx, spec))
-                        .unwrap_or(false)
-                    {
-                        warnings.push(diwe::search_query::no_terms_warning(spec));
+                if let Operation::Find(find) = &op {
+                    if let Some(spec) = &find.search {
+                        if index
+                            .as_ref()
+                            .map(|idx| diwe::search_query::lexical_has_no_terms(idx, spec))
+                            .unwrap_or(false)
+                        {
+                            warnings.push(diwe::search_query::no_terms_warning(spec));
+                        }
                     }
                 }
-                to_json_result_with_warnings(&documents, &warnings)
-            }
-            Operation::Count(_) => {
-                let outcome = execute(&op, &graph)
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                let Outcome::Count(count) = outcome else {
-                    unreachable!("count operation yields a count outcome")
-                };
-                to_json_result(&QueryCountOutput { count })
+                Self::query_result(documents, report, &warnings)
             }
-            Operation::Update(_) => {
-                let outcome = execute(&op, &graph)
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                let Outcome::Update { changes } = outcome else {
-                    unreachable!("update operation yields an update outcome")
-                };
+            Outcome::Count(count) => Self::query_result(QueryCountOutput { count }, report, &[]),
+            Outcome::Update { changes } => {
                 let changed: Vec<ChangeEntry> = changes
                     .iter()
                     .map(|(key, content)| ChangeEntry {
@@ -1163,20 +1289,15 @@ impl IweServer {
                 if !dry_run {
                     self.ensure_schema_clean(&changes)?;
                     for (key, content) in &changes {
-                        graph.update_document(key.clone(), content.clone());
+                        Arc::make_mut(&mut graph).update_document(key.clone(), content.clone());
                         self.write_file(key, content);
                     }
                     let touched: Vec<Key> = changes.iter().map(|(key, _)| key.clone()).collect();
                     warnings = self.stats_warnings(&graph, &touched, &[], &touched).await;
                 }
-                to_json_result_with_warnings(&QueryUpdateOutput { dry_run, changed }, &warnings)
+                Self::query_result(QueryUpdateOutput { dry_run, changed }, report, &warnings)
             }
-            Operation::Delete(_) => {
-                let outcome = execute(&op, &graph)
-                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
-                let Outcome::Delete { removed } = outcome else {
-                    unreachable!("delete operation yields a delete outcome")
-                };
+            Outcome::Delete { removed } => {
                 let mut combined = Changes::default();
                 for key in &removed {
                     let changes = op_delete(&graph, key).map_err(op_error_to_mcp)?;
@@ -1189,7 +1310,7 @@ impl IweServer {
                     self.write_changes(&combined);
                     warnings = self.stats_after_delete(&graph, &combined).await;
                 }
-                to_json_result_with_warnings(&ChangesOutput::from(&combined), &warnings)
+                Self::query_result(ChangesOutput::from(&combined), report, &warnings)
             }
         }
     }
@@ -1225,14 +1346,14 @@ impl IweServer {
         let source_key = Key::name(&params.key);
         let mut graph = self.graph.lock().await;
 
-        if (&*graph).get_node_id(&source_key).is_none() {
+        if (&**graph).get_node_id(&source_key).is_none() {
             return Err(McpError::invalid_params(
                 format!("Document '{}' not found", params.key),
                 None,
             ));
         }
 
-        let tree = (&*graph).collect(&source_key);
+        let tree = (&**graph).collect(&source_key);
 
         if params.list.unwrap_or(false) {
             let sections: Vec<SectionEntry> = sections(&tree)
@@ -1312,14 +1433,14 @@ impl IweServer {
         let source_key = Key::name(&params.key);
         let mut graph = self.graph.lock().await;
 
-        if (&*graph).get_node_id(&source_key).is_none() {
+        if (&**graph).get_node_id(&source_key).is_none() {
             return Err(McpError::invalid_params(
                 format!("Document '{}' not found", params.key),
                 None,
             ));
         }
 
-        let tree = (&*graph).collect(&source_key);
+        let tree = (&**graph).collect(&source_key);
 
         if params.list.unwrap_or(false) {
             let refs: Vec<ReferenceEntry> = references(&tree)
@@ -1402,7 +1523,7 @@ impl IweServer {
         let original_count = state.len();
 
         let mut changed = 0usize;
-        if self.base_path.is_some() {
+        if self.base_path().is_some() {
             for (key_str, normalized_content) in &state {
                 let key = Key::name(key_str);
                 if self.read_file(&key).as_deref() != Some(normalized_content.as_str()) {
@@ -1431,14 +1552,15 @@ impl IweServer {
         Parameters(params): Parameters<AttachParams>,
     ) -> Result<CallToolResult, McpError> {
         if params.list.unwrap_or(false) {
+            let graph = self.graph.lock().await;
             let mut entries: Vec<AttachActionEntry> = Vec::new();
-            for (name, action) in &self.config.actions {
+            for (name, action) in &self.config().actions {
                 if let ActionDefinition::Attach(attach) = action {
-                    let target_key =
-                        self.render_key_template(&attach.key_template)
-                            .map_err(|e| {
-                                McpError::invalid_params(format!("action '{}': {}", name, e), None)
-                            })?;
+                    let target_key = self
+                        .render_key_template(&attach.key_template, &graph)
+                        .map_err(|e| {
+                            McpError::invalid_params(format!("action '{}': {}", name, e), None)
+                        })?;
                     entries.push(AttachActionEntry {
                         name: name.clone(),
                         title: attach.title.clone(),
@@ -1463,21 +1585,22 @@ impl IweServer {
         let mut graph = self.graph.lock().await;
 
         let source_key = Key::name(source_key_str);
-        if (&*graph).get_node_id(&source_key).is_none() {
+        if (&**graph).get_node_id(&source_key).is_none() {
             return Err(McpError::invalid_params(
                 format!("Document '{}' not found", source_key_str),
                 None,
             ));
         }
 
-        let reference_text = (&*graph)
+        let reference_text = (&**graph)
             .get_key_title(&source_key)
             .unwrap_or_else(|| source_key_str.to_string());
 
+        let config = self.config();
         let mut combined = Changes::new();
 
         for action_name in &params.to {
-            let attach = match self.config.actions.get(action_name) {
+            let attach = match config.actions.get(action_name) {
                 Some(ActionDefinition::Attach(a)) => a,
                 Some(_) => {
                     return Err(McpError::invalid_params(
@@ -1493,9 +1616,13 @@ impl IweServer {
                 }
             };
 
-            let target_key = Key::name(&self.render_key_template(&attach.key_template).map_err(
-                |e| McpError::invalid_params(format!("action '{}': {}", action_name, e), None),
-            )?);
+            let target_key = Key::name(
+                &self
+                    .render_key_template(&attach.key_template, &graph)
+                    .map_err(|e| {
+                        McpError::invalid_params(format!("action '{}': {}", action_name, e), None)
+                    })?,
+            );
 
             match attach_reference(&graph, &target_key, &source_key, &reference_text) {
                 AttachTarget::AlreadyAttached => continue,
@@ -1504,7 +1631,12 @@ impl IweServer {
                 }
                 AttachTarget::Create(body) => {
                     let document = self
-                        .render_document_template(&attach.document_template, &body)
+                        .render_document_template(
+                            &attach.document_template,
+                            &target_key,
+                            &body,
+                            &graph,
+                        )
                         .map_err(|e| {
                             McpError::invalid_params(
                                 format!("action '{}': {}", action_name, e),
@@ -1663,17 +1795,25 @@ impl IweServer {
 #[tool_handler]
 #[prompt_handler]
 impl ServerHandler for IweServer {
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        tracing::info!("client initialized");
+        let mut peers = self.peers.lock().await;
+        peers.retain(|peer| !peer.is_transport_closed());
+        peers.push(context.peer);
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo::new(
             ServerCapabilities::builder()
                 .enable_tools()
                 .enable_prompts()
                 .enable_resources()
+                .enable_logging()
                 .build(),
         )
         .with_server_info(Implementation::new("iwe", env!("CARGO_PKG_VERSION")))
         .with_instructions(
-            "IWE knowledge graph server. Tools: iwe_find, iwe_retrieve, iwe_tree, iwe_stats, iwe_squash, iwe_create, iwe_update, iwe_delete, iwe_query, iwe_rename, iwe_extract, iwe_inline, iwe_normalize, iwe_attach. Prompts: explore, review, refactor. Resources: iwe://documents/{key}, iwe://tree, iwe://stats, iwe://config."
+            "IWE knowledge graph server. Tools: iwe_find, iwe_retrieve, iwe_tree, iwe_stats, iwe_mentions, iwe_squash, iwe_create, iwe_update, iwe_delete, iwe_query, iwe_rename, iwe_extract, iwe_inline, iwe_normalize, iwe_attach. Prompts: explore, review, refactor. Resources: iwe://documents/{key}, iwe://tree, iwe://stats, iwe://config."
                 .to_string(),
         )
     }
@@ -1700,7 +1840,7 @@ impl ServerHandler for IweServer {
         ];
 
         for key in graph.keys().iter().take(100) {
-            let title = (&*graph)
+            let title = (&**graph)
                 .get_key_title(key)
                 .unwrap_or_else(|| key.to_string());
             resources.push(
@@ -1731,7 +1871,7 @@ impl ServerHandler for IweServer {
                 .iter()
                 .filter(|n| n.ids().len() == 1)
                 .filter_map(|n| n.first_id())
-                .map(|id| (&*graph).node(id).node_key())
+                .map(|id| (&**graph).node(id).node_key())
                 .collect();
             root_keys.sort();
             root_keys.dedup();
@@ -1762,7 +1902,7 @@ impl ServerHandler for IweServer {
         }
 
         if uri == "iwe://config" {
-            let config_view = ConfigResource::from_config(&self.config, self)
+            let config_view = ConfigResource::from_config(&self.config(), self, &graph)
                 .map_err(|e| McpError::internal_error(e, None))?;
             let json = serde_json::to_string_pretty(&config_view)
                 .map_err(|e| McpError::internal_error(e.to_string(), None))?;
@@ -1814,16 +1954,23 @@ impl IweServer {
     pub fn new(base_path: &str, configuration: &Configuration) -> Self {
         let path = PathBuf::from_str(base_path).expect("valid path");
         let state = new_for_path(&path, configuration.format);
-        let graph = Graph::from_state(
+        let mut graph = Graph::from_state(
             &state,
             false,
             configuration.format_options(),
             configuration.library.frontmatter_document_title.clone(),
         );
+        let (fields, errors) = configuration.computed_fields();
+        for error in errors {
+            tracing::error!("ignoring {}", error);
+        }
+        graph.set_computed_fields(fields);
         Self {
-            graph: Arc::new(Mutex::new(graph)),
-            base_path: Some(path),
-            config: configuration.clone(),
+            graph: Arc::new(Mutex::new(Arc::new(graph))),
+            base_path: Arc::new(RwLock::new(Some(path))),
+            config: Arc::new(RwLock::new(Arc::new(configuration.clone()))),
+            watch: Default::default(),
+            peers: Arc::new(Mutex::new(Vec::new())),
             index: Arc::new(Mutex::new(None)),
             seen: Arc::new(Mutex::new(HashSet::new())),
             tool_router: Self::tool_router(),
@@ -1842,11 +1989,18 @@ impl IweServer {
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect::<HashMap<String, String>>(),
         );
-        let graph = Graph::from_state(&state, true, MarkdownOptions::default(), None);
+        let mut graph = Graph::from_state(&state, true, MarkdownOptions::default(), None);
+        let (fields, errors) = config.computed_fields();
+        for error in errors {
+            tracing::error!("ignoring {}", error);
+        }
+        graph.set_computed_fields(fields);
         Self {
-            graph: Arc::new(Mutex::new(graph)),
-            base_path: None,
-            config,
+            graph: Arc::new(Mutex::new(Arc::new(graph))),
+            base_path: Arc::new(RwLock::new(None)),
+            config: Arc::new(RwLock::new(Arc::new(config))),
+            watch: Default::default(),
+            peers: Arc::new(Mutex::new(Vec::new())),
             index: Arc::new(Mutex::new(None)),
             seen: Arc::new(Mutex::new(HashSet::new())),
             tool_router: Self::tool_router(),
@@ -1854,7 +2008,70 @@ impl IweServer {
         }
     }
 
-    fn apply_changes(graph: &mut Graph, changes: &Changes) {
+    fn query_result<T: Serialize>(
+        result: T,
+        explain: Option<Explain>,
+        warnings: &[String],
+    ) -> Result<CallToolResult, McpError> {
+        match explain {
+            Some(explain) => {
+                to_json_result_with_warnings(&ExplainedOutput { result, explain }, warnings)
+            }
+            None => to_json_result_with_warnings(&result, warnings),
+        }
+    }
+
+    async fn query_batch(&self, params: &QueryParams) -> Result<CallToolResult, McpError> {
+        let steps = parse_batch(&params.document)
+            .map_err(|e| McpError::invalid_params(format!("invalid batch: {}", e), None))?;
+        let violations = batch_guard_violations(&steps);
+        if !violations.is_empty() {
+            return Err(McpError::invalid_params(
+                format!(
+                    "MCP block operations run strict: every mutating application must carry an `expect` guard; missing: {}.",
+                    violations.join(", ")
+                ),
+                None,
+            ));
+        }
+        if params.explain.unwrap_or(false) {
+            return Err(McpError::invalid_params(
+                "explain is not supported for batch; explain the operations one at a time",
+                None,
+            ));
+        }
+
+        let dry_run = params.dry_run.unwrap_or(false);
+        let mut graph = self.graph.lock().await;
+        let outcome = run_batch(&graph, &steps, &self.config().search_language())
+            .map_err(|e| McpError::invalid_params(e, None))?;
+        let changes = outcome.changes;
+
+        let mut warnings = Vec::new();
+        if !dry_run && !changes.is_empty() {
+            self.ensure_schema_clean(&pending_from_changes(&changes))?;
+            Self::apply_changes(&mut graph, &changes);
+            self.write_changes(&changes);
+            let upserts: Vec<Key> = pending_from_changes(&changes)
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect();
+            warnings = self
+                .stats_warnings(&graph, &upserts, &changes.removes, &upserts)
+                .await;
+        }
+        to_json_result_with_warnings(
+            &QueryBatchOutput {
+                dry_run,
+                steps: outcome.steps,
+                changes: ChangesOutput::from(&changes),
+            },
+            &warnings,
+        )
+    }
+
+    fn apply_changes(graph: &mut Arc<Graph>, changes: &Changes) {
+        let graph = Arc::make_mut(graph);
         for key in &changes.removes {
             graph.remove_document(key.clone());
         }
@@ -1867,9 +2084,11 @@ impl IweServer {
     }
 
     fn ensure_schema_clean(&self, docs: &[(Key, String)]) -> Result<(), McpError> {
-        let result = match &self.base_path {
-            Some(base) => validate_pending_documents_in(&schemas_dir_in(base), &self.config, docs),
-            None => validate_pending_documents(&self.config, docs),
+        let result = match &self.base_path() {
+            Some(base) => {
+                validate_pending_documents_in(&schemas_dir_in(base), &self.config(), docs)
+            }
+            None => validate_pending_documents(&self.config(), docs),
         };
         match result {
             Ok(reports) if reports.is_empty() => Ok(()),
@@ -1890,7 +2109,7 @@ impl IweServer {
     ) -> Vec<String> {
         let mut index_guard = self.index.lock().await;
         if index_guard.is_none() {
-            *index_guard = Some(build_index(graph, self.config.search_language()));
+            *index_guard = Some(build_index(graph, self.config().search_language()));
         } else {
             let index = index_guard.as_mut().expect("index present");
             for key in removes {
@@ -1919,8 +2138,8 @@ impl IweServer {
     }
 
     fn document_path(&self, key: &Key) -> Option<PathBuf> {
-        let base_path = self.base_path.as_ref()?;
-        let extension = self.config.format.extension();
+        let base_path = self.base_path()?;
+        let extension = self.config().format.extension();
         Some(base_path.join(format!("{}.{}", key, extension)))
     }
 
@@ -1943,27 +2162,138 @@ impl IweServer {
     }
 
     fn write_changes(&self, changes: &Changes) {
-        if let Some(base_path) = &self.base_path {
-            let _ = diwe::fs::apply_changes(changes, base_path, self.config.format);
+        if let Some(base_path) = &self.base_path() {
+            let _ = diwe::fs::apply_changes(changes, base_path, self.config().format);
         }
     }
 
     pub fn start_watching(&self) {
-        if let Some(base_path) = &self.base_path {
-            watcher::start(self.graph.clone(), base_path.clone(), self.config.format);
+        self.watch.lock().expect("watch lock").poll = None;
+        self.restart_watching();
+    }
+
+    /// [`Self::start_watching`] polling the library every `interval` instead of subscribing to
+    /// file system events.
+    pub fn start_polling(&self, interval: Duration) {
+        self.watch.lock().expect("watch lock").poll = Some(interval);
+        self.restart_watching();
+    }
+
+    /// Stops the document watcher, if any, and watches the current library path and format.
+    fn restart_watching(&self) {
+        let Some(base_path) = self.base_path() else {
+            return;
+        };
+        let format = self.config().format;
+        let mut watch = self.watch.lock().expect("watch lock");
+        if let Some(task) = watch.task.take() {
+            task.abort();
         }
+        watch.task = match watch.poll {
+            Some(interval) => {
+                watcher::start_polling(self.graph.clone(), base_path, format, interval)
+            }
+            None => watcher::start(self.graph.clone(), base_path, format),
+        };
+    }
+
+    fn base_path(&self) -> Option<PathBuf> {
+        self.base_path.read().expect("base path lock").clone()
+    }
+
+    /// Reloads the configuration when `.iwe/config.toml` or a schema under `root` changes.
+    pub fn start_watching_config(&self, root: PathBuf) {
+        watcher::start_config(self.clone(), root);
+    }
+
+    fn config(&self) -> Arc<Configuration> {
+        self.config.read().expect("config lock").clone()
     }
 
-    fn render_key_template(&self, template: &str) -> Result<String, String> {
+    /// Re-reads the configuration of the library rooted at `root`, re-parsing documents when
+    /// the format options or the title field changed. A new library path or format reads the
+    /// library again from the disk and restarts the document watcher. Errors are sent to
+    /// clients as log messages and keep the current configuration.
+    pub async fn reload_config(&self, root: &Path) {
+        let config = match load_config_in(root) {
+            Ok(config) => config,
+            Err(e) => {
+                self.log_error(format!("configuration not reloaded: {}", e))
+                    .await;
+                return;
+            }
+        };
+        if let Err(errors) = validate_pending_documents_in(&schemas_dir_in(root), &config, &[]) {
+            self.log_error(format!("schema configuration error: {}", errors.join("; ")))
+                .await;
+        }
+
+        let current = self.config();
+        let library = library_path_in(root, &config);
+        let moved = self
+            .base_path()
+            .is_some_and(|base_path| base_path != library || config.format != current.format);
+        let mut graph = self.graph.lock().await;
+        if moved {
+            tracing::info!("reading the library at {}", library.display());
+            *graph = Arc::new(Graph::from_state(
+                &new_for_path(&library, config.format),
+                false,
+                config.format_options(),
+                config.library.frontmatter_document_title.clone(),
+            ));
+            *self.base_path.write().expect("base path lock") = Some(library);
+        } else if config.format_options() != current.format_options()
+            || config.library.frontmatter_document_title
+                != current.library.frontmatter_document_title
+        {
+            *graph = Arc::new(graph.reparse(
+                config.format_options(),
+                config.library.frontmatter_document_title.clone(),
+            ));
+        }
+        let (fields, errors) = config.computed_fields();
+        for error in errors {
+            tracing::error!("ignoring {}", error);
+        }
+        Arc::make_mut(&mut graph).set_computed_fields(fields);
+        *self.index.lock().await = None;
+        *self.config.write().expect("config lock") = Arc::new(config);
+        drop(graph);
+        if moved {
+            self.restart_watching();
+        }
+        tracing::info!("configuration reloaded");
+    }
+
+    async fn log_error(&self, message: String) {
+        tracing::error!("{}", message);
+        let peers = self.peers.lock().await.clone();
+        for peer in peers {
+            let _ = peer
+                .notify_logging_message(
+                    LoggingMessageNotificationParam::new(
+                        LoggingLevel::Error,
+                        serde_json::json!(message),
+                    )
+                    .with_logger("iwe"),
+                )
+                .await;
+        }
+    }
+
+    fn render_key_template(&self, template: &str, graph: &Arc<Graph>) -> Result<String, String> {
         let now = Local::now();
-        let date_format = self
-            .config
+        let config = self.config();
+        let date_format = config
             .library
             .date_format
             .as_deref()
             .unwrap_or(DEFAULT_KEY_DATE_FORMAT);
         let formatted = now.format(date_format).to_string();
-        Environment::new()
+        TemplateEngine::new(&config)
+            .with_graph(graph.clone())
+            .environment()
             .template_from_str(template)
             .map_err(|e| format!("invalid key template: {}", e))?
             .render(context! {
@@ -1973,16 +2303,25 @@ impl IweServer {
             .map_err(|e| format!("key template rendering failed: {}", e))
     }
 
-    fn render_document_template(&self, template: &str, content: &str) -> Result<String, String> {
+    fn render_document_template(
+        &self,
+        template: &str,
+        key: &Key,
+        content: &str,
+        graph: &Arc<Graph>,
+    ) -> Result<String, String> {
         let now = Local::now();
-        let date_format = self
-            .config
+        let config = self.config();
+        let date_format = config
             .markdown
             .date_format
             .as_deref()
             .unwrap_or("%b %d, %Y");
         let formatted = now.format(date_format).to_string();
-        Environment::new()
+        TemplateEngine::new(&config)
+            .relative_to(key)
+            .with_graph(graph.clone())
+            .environment()
             .template_from_str(template)
             .map_err(|e| format!("invalid document template: {}", e))?
             .render(context! {
@@ -3,7 +3,7 @@ use std::env;
 use anyhow::Result;
 use clap::{Parser, ValueEnum};
 use diwe::config::load_config;
-use iwec::IweServer;
+use iwec::{library_path_in, IweServer};
 use rmcp::transport::stdio;
 use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
 use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, StreamableHttpService};
@@ -60,13 +60,11 @@ async fn main() -> Result<()> {
     });
 
     let current_dir = env::current_dir().expect("current dir");
-    let mut library_path = current_dir.clone();
-    if !configuration.library.path.is_empty() {
-        library_path.push(configuration.library.path.clone());
-    }
+    let library_path = library_path_in(&current_dir, &configuration);
 
     let server = IweServer::new(&library_path.to_string_lossy(), &configuration);
     server.start_watching();
+    server.start_watching_config(current_dir);
 
     match cli.transport {
         Transport::Stdio => {
@@ -3,13 +3,19 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use diwe::config::Format;
-use diwe::watcher::{start_poll_watcher, start_watcher, FsChange};
+use diwe::watcher::{
+    start_config_poll_watcher, start_config_watcher, start_poll_watcher, start_watcher, FsChange,
+};
 use liwe::graph::Graph;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
 
-async fn apply_change(graph: &Arc<Mutex<Graph>>, change: FsChange) {
-    let mut g = graph.lock().await;
+use crate::IweServer;
+
+async fn apply_change(graph: &Arc<Mutex<Arc<Graph>>>, change: FsChange) {
+    let mut graph = graph.lock().await;
+    let g = Arc::make_mut(&mut graph);
     match change {
         FsChange::Update(key, content) => {
             tracing::debug!("file changed: key={}", key);
@@ -22,38 +28,76 @@ async fn apply_change(graph: &Arc<Mutex<Graph>>, change: FsChange) {
     }
 }
 
+/// Applies the watcher's changes to `graph` until the returned task is aborted, which drops
+/// the watcher.
 fn spawn_apply_task<W: Send + 'static>(
-    graph: Arc<Mutex<Graph>>,
+    graph: Arc<Mutex<Arc<Graph>>>,
     watcher: W,
     mut receiver: UnboundedReceiver<FsChange>,
-) {
+) -> AbortHandle {
     tokio::spawn(async move {
         let _watcher = watcher;
         while let Some(change) = receiver.recv().await {
             apply_change(&graph, change).await;
         }
-    });
+    })
+    .abort_handle()
 }
 
-pub fn start(graph: Arc<Mutex<Graph>>, base_path: PathBuf, format: Format) {
+pub fn start(
+    graph: Arc<Mutex<Arc<Graph>>>,
+    base_path: PathBuf,
+    format: Format,
+) -> Option<AbortHandle> {
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<FsChange>();
-    if let Some(watcher) = start_watcher(base_path, format, move |change| {
+    let watcher = start_watcher(base_path, format, move |change| {
         let _ = tx.send(change);
-    }) {
-        spawn_apply_task(graph, watcher, rx);
-    }
+    })?;
+    Some(spawn_apply_task(graph, watcher, rx))
 }
 
 pub fn start_polling(
-    graph: Arc<Mutex<Graph>>,
+    graph: Arc<Mutex<Arc<Graph>>>,
     base_path: PathBuf,
     format: Format,
     interval: Duration,
-) {
+) -> Option<AbortHandle> {
     let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<FsChange>();
-    if let Some(watcher) = start_poll_watcher(base_path, format, interval, move |change| {
+    let watcher = start_poll_watcher(base_path, format, interval, move |change| {
         let _ = tx.send(change);
+    })?;
+    Some(spawn_apply_task(graph, watcher, rx))
+}
+
+fn spawn_reload_task<W: Send + 'static>(
+    server: IweServer,
+    root: PathBuf,
+    watcher: W,
+    mut receiver: UnboundedReceiver<()>,
+) {
+    tokio::spawn(async move {
+        let _watcher = watcher;
+        while receiver.recv().await.is_some() {
+            while receiver.try_recv().is_ok() {}
+            server.reload_config(&root).await;
+        }
+    });
+}
+
+pub fn start_config(server: IweServer, root: PathBuf) {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    if let Some(watcher) = start_config_watcher(root.clone(), move || {
+        let _ = tx.send(());
+    }) {
+        spawn_reload_task(server, root, watcher, rx);
+    }
+}
+
+pub fn start_config_polling(server: IweServer, root: PathBuf, interval: Duration) {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    if let Some(watcher) = start_config_poll_watcher(root.clone(), interval, move || {
+        let _ = tx.send(());
     }) {
-        spawn_apply_task(graph, watcher, rx);
+        spawn_reload_task(server, root, watcher, rx);
     }
 }
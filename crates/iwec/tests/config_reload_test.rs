@@ -0,0 +1,148 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use diwe::config::Configuration;
+use rmcp::model::LoggingLevel;
+use serde_json::json;
+
+use crate::fixture::Fixture;
+
+fn library(root: &Path) {
+    fs::create_dir_all(root.join(".iwe")).unwrap();
+    fs::write(root.join(".iwe/config.toml"), "version = 3\n").unwrap();
+    fs::write(
+        root.join("note.md"),
+        "---\ntitle: Custom Title\n---\n\n# Header Title\n",
+    )
+    .unwrap();
+}
+
+async fn title(f: &Fixture) -> serde_json::Value {
+    let result = f.call_tool("iwe_retrieve", json!({"keys": ["note"]})).await;
+    Fixture::result_json(&result)[0]["title"].clone()
+}
+
+async fn wait_for_title(f: &Fixture, expected: &str) {
+    for _ in 0..200 {
+        if title(f).await == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert_eq!(title(f).await, expected);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reparses_documents_when_the_title_field_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    library(&root);
+
+    let f = Fixture::with_root(&root, Configuration::default()).await;
+    assert_eq!(title(&f).await, "Header Title");
+
+    fs::write(
+        root.join(".iwe/config.toml"),
+        "version = 3\n\n[library]\nfrontmatter_document_title = \"title\"\n",
+    )
+    .unwrap();
+
+    wait_for_title(&f, "Custom Title").await;
+}
+
+async fn keys(f: &Fixture) -> Vec<String> {
+    let result = f.call_tool("iwe_find", json!({})).await;
+    let mut keys: Vec<String> = Fixture::result_json(&result)
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|document| document["key"].as_str().unwrap().to_string())
+        .collect();
+    keys.sort();
+    keys
+}
+
+async fn wait_for_keys(f: &Fixture, expected: &[&str]) {
+    for _ in 0..200 {
+        if keys(f).await == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    assert_eq!(keys(f).await, expected);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn reads_and_watches_the_new_library_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    library(&root);
+    fs::create_dir_all(root.join("notes")).unwrap();
+    fs::write(root.join("notes/moved.md"), "# Moved\n").unwrap();
+
+    let f = Fixture::with_root(&root, Configuration::default()).await;
+    wait_for_keys(&f, &["note", "notes/moved"]).await;
+
+    fs::write(
+        root.join(".iwe/config.toml"),
+        "version = 3\n\n[library]\npath = \"notes\"\n",
+    )
+    .unwrap();
+    wait_for_keys(&f, &["moved"]).await;
+
+    fs::write(root.join("notes/added.md"), "# Added\n").unwrap();
+    wait_for_keys(&f, &["added", "moved"]).await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn picks_up_a_configuration_created_after_startup() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    library(&root);
+    fs::remove_dir_all(root.join(".iwe")).unwrap();
+
+    let f = Fixture::with_root(&root, Configuration::default()).await;
+    assert_eq!(title(&f).await, "Header Title");
+
+    fs::create_dir_all(root.join(".iwe")).unwrap();
+    fs::write(
+        root.join(".iwe/config.toml"),
+        "version = 3\n\n[library]\nfrontmatter_document_title = \"title\"\n",
+    )
+    .unwrap();
+
+    wait_for_title(&f, "Custom Title").await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn logs_invalid_configuration_and_keeps_the_previous_one() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    library(&root);
+
+    let f = Fixture::with_root(&root, Configuration::default()).await;
+    f.list_tools().await;
+
+    fs::write(root.join(".iwe/config.toml"), "version = 3\n\n[library\n").unwrap();
+
+    for _ in 0..200 {
+        if !f.logs().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(25)).await;
+    }
+    let logs = f.logs();
+    assert!(!logs.is_empty(), "expected a log message");
+    assert_eq!(logs[0].level, LoggingLevel::Error);
+    assert!(
+        logs[0]
+            .data
+            .as_str()
+            .unwrap()
+            .starts_with("configuration not reloaded: "),
+        "{:?}",
+        logs[0].data
+    );
+    assert_eq!(title(&f).await, "Header Title");
+}
@@ -1,17 +1,32 @@
 #![allow(dead_code)]
 
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
 use diwe::config::Configuration;
 use iwec::IweServer;
 use rmcp::model::*;
+use rmcp::service::NotificationContext;
 use rmcp::{service::RunningService, ClientHandler, RoleClient, ServiceExt};
 
 #[derive(Default, Clone)]
-struct TestClient;
+struct TestClient {
+    logs: Arc<Mutex<Vec<LoggingMessageNotificationParam>>>,
+}
 
-impl ClientHandler for TestClient {}
+impl ClientHandler for TestClient {
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.logs.lock().unwrap().push(params);
+    }
+}
 
 pub struct Fixture {
     client: RunningService<RoleClient, TestClient>,
+    logs: Arc<Mutex<Vec<LoggingMessageNotificationParam>>>,
     _server_handle: tokio::task::JoinHandle<anyhow::Result<()>>,
 }
 
@@ -33,6 +48,19 @@ impl Fixture {
         Self::serve(server).await
     }
 
+    /// Serves the library at `root`, reloading its configuration on change.
+    pub async fn with_root(root: &std::path::Path, config: Configuration) -> Self {
+        let server = IweServer::new(&root.to_string_lossy(), &config);
+        server.start_polling(Duration::from_millis(10));
+        iwec::watcher::start_config_polling(
+            server.clone(),
+            root.to_path_buf(),
+            Duration::from_millis(10),
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        Self::serve(server).await
+    }
+
     async fn serve(server: IweServer) -> Self {
         let (server_transport, client_transport) = tokio::io::duplex(65536);
 
@@ -42,17 +70,24 @@ impl Fixture {
             anyhow::Ok(())
         });
 
-        let client = TestClient
+        let test_client = TestClient::default();
+        let logs = test_client.logs.clone();
+        let client = test_client
             .serve(client_transport)
             .await
             .expect("client to connect");
 
         Self {
             client,
+            logs,
             _server_handle: server_handle,
         }
     }
 
+    pub fn logs(&self) -> Vec<LoggingMessageNotificationParam> {
+        self.logs.lock().unwrap().clone()
+    }
+
     pub async fn call_tool(&self, name: &str, arguments: serde_json::Value) -> CallToolResult {
         self.try_call_tool(name, arguments)
             .await
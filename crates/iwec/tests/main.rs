@@ -1,10 +1,12 @@
 mod fixture;
 
 mod attach_test;
+mod config_reload_test;
 mod extract_test;
 mod find_test;
 mod http_transport_test;
 mod inline_test;
+mod mentions_test;
 mod normalize_test;
 mod prompt_test;
 mod query_test;
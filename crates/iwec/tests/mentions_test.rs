@@ -0,0 +1,75 @@
+use crate::fixture::Fixture;
+use serde_json::json;
+
+fn library() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("garden", "# Garden\n"),
+        (
+            "notes",
+            "# Notes\n\nWork in the gardens, see [Garden](garden).\n",
+        ),
+    ]
+}
+
+#[tokio::test]
+async fn mentions_lists_unlinked_mentions() {
+    let f = Fixture::with_documents(library()).await;
+
+    let result = f.call_tool("iwe_mentions", json!({"key": "garden"})).await;
+
+    assert_eq!(
+        Fixture::result_json(&result),
+        json!([{
+            "key": "notes",
+            "line": 2,
+            "start": 12,
+            "end": 19,
+            "text": "gardens",
+            "context": "Work in the gardens, see [Garden](garden).",
+        }])
+    );
+}
+
+#[tokio::test]
+async fn mentions_link_rewrites_documents() {
+    let f = Fixture::with_documents(library()).await;
+
+    let result = f
+        .call_tool("iwe_mentions", json!({"key": "garden", "link": true}))
+        .await;
+    let output = Fixture::result_json(&result);
+    assert_eq!(output["updates"][0]["key"], "notes");
+
+    let mentions = f.call_tool("iwe_mentions", json!({"key": "garden"})).await;
+    assert_eq!(Fixture::result_json(&mentions), json!([]));
+}
+
+#[tokio::test]
+async fn mentions_link_dry_run() {
+    let f = Fixture::with_documents(library()).await;
+
+    let result = f
+        .call_tool(
+            "iwe_mentions",
+            json!({"key": "garden", "link": true, "dry_run": true}),
+        )
+        .await;
+    let output = Fixture::result_json(&result);
+    assert!(output["updates"][0]["content"]
+        .as_str()
+        .unwrap()
+        .contains("[gardens]("));
+
+    let mentions = f.call_tool("iwe_mentions", json!({"key": "garden"})).await;
+    assert_eq!(Fixture::result_json(&mentions).as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn mentions_not_found() {
+    let f = Fixture::with_documents(library()).await;
+
+    let result = f
+        .try_call_tool("iwe_mentions", json!({"key": "nonexistent"}))
+        .await;
+    assert!(result.is_err());
+}
@@ -218,3 +218,208 @@ async fn query_delete_strict_requires_expect() {
 
     assert!(result.is_err(), "unguarded delete must be rejected");
 }
+
+#[tokio::test]
+async fn query_find_explain_reports_filter_trace() {
+    let f = Fixture::with_documents(vec![
+        ("1", "# One\n\nalpha TODO beta\n"),
+        ("2", "# Two\n\nnothing here\n"),
+    ])
+    .await;
+
+    let result = f
+        .call_tool(
+            "iwe_query",
+            json!({
+                "operation": "find",
+                "explain": true,
+                "document": "filter: { $content: { $text: TODO } }\nproject: { key: $key }\n",
+            }),
+        )
+        .await;
+
+    let out = Fixture::result_json(&result);
+    assert_eq!(out["result"], json!([{ "key": "1" }]));
+    assert_eq!(out["explain"]["candidates"], 1);
+    assert_eq!(out["explain"]["matched"], 1);
+    assert_eq!(out["explain"]["blockEvaluations"], 2);
+    assert_eq!(out["explain"]["filter"]["clause"], "$content");
+    assert_eq!(out["explain"]["filter"]["input"], 2);
+    assert_eq!(out["explain"]["filter"]["output"], 1);
+}
+
+#[tokio::test]
+async fn query_update_explain_runs_dry() {
+    let f = Fixture::with_documents(vec![("1", "# Roadmap\n\n## Goals\n\nShip it\n")]).await;
+
+    let result = f
+        .call_tool(
+            "iwe_query",
+            json!({
+                "operation": "update",
+                "explain": true,
+                "document": indoc! {"
+                    filter: { $key: '1' }
+                    expect: 1
+                    update:
+                      $replaceText: { $header: Goals, to: Aims, expect: 1 }
+                "},
+            }),
+        )
+        .await;
+
+    let out = Fixture::result_json(&result);
+    assert_eq!(out["result"]["dry_run"], true);
+    assert_eq!(out["explain"]["matched"], 1);
+
+    let retrieve = f
+        .call_tool(
+            "iwe_retrieve",
+            json!({"keys": ["1"], "depth": 0, "backlinks": false}),
+        )
+        .await;
+    let docs = Fixture::result_json(&retrieve);
+    assert_eq!(docs[0]["content"], "# Roadmap\n\n## Goals\n\nShip it\n");
+}
+
+#[tokio::test]
+async fn query_batch_applies_steps_in_order() {
+    let f = Fixture::with_documents(vec![
+        ("1", "---\nstatus: draft\n---\n# One\n\nbody\n"),
+        ("2", "---\nstatus: draft\n---\n# Two\n\nbody\n"),
+        ("3", "# Obsolete\n\nbody\n"),
+    ])
+    .await;
+
+    let result = f
+        .call_tool(
+            "iwe_query",
+            json!({
+                "operation": "batch",
+                "document": indoc! {"
+                    operations:
+                      - create: { key: hub, content: \"# Hub\\n\\n[One](1)\\n\" }
+                      - update:
+                          filter: { status: draft }
+                          expect: 2
+                          update: { $set: { status: done } }
+                      - delete: { filter: { $key: '3' }, expect: 1 }
+                      - count: { filter: { status: done } }
+                "},
+            }),
+        )
+        .await;
+
+    let out = Fixture::result_json(&result);
+    assert_eq!(out["dry_run"], false);
+    assert_eq!(
+        out["steps"],
+        json!([
+            { "operation": "create", "key": "hub" },
+            { "operation": "update", "changed": ["1", "2"] },
+            { "operation": "delete", "removed": ["3"] },
+            { "operation": "count", "count": 2 },
+        ])
+    );
+    assert_eq!(out["creates"][0]["key"], "hub");
+    assert_eq!(out["updates"].as_array().unwrap().len(), 2);
+    assert_eq!(out["removes"], json!(["3"]));
+
+    let count = f
+        .call_tool(
+            "iwe_query",
+            json!({ "operation": "count", "document": "filter: { status: done }\n" }),
+        )
+        .await;
+    assert_eq!(Fixture::result_json(&count)["count"], 2);
+}
+
+#[tokio::test]
+async fn query_batch_failing_step_writes_nothing() {
+    let f = Fixture::with_documents(vec![("1", "---\nstatus: draft\n---\n# One\n\nbody\n")]).await;
+
+    let result = f
+        .try_call_tool(
+            "iwe_query",
+            json!({
+                "operation": "batch",
+                "document": indoc! {"
+                    operations:
+                      - update:
+                          filter: { $key: '1' }
+                          expect: 1
+                          update: { $set: { status: done } }
+                      - delete: { filter: { $key: missing }, expect: 1 }
+                "},
+            }),
+        )
+        .await;
+
+    let err = format!("{:?}", result.expect_err("second step must fail the batch"));
+    assert!(err.contains("operation 2 (delete)"), "{}", err);
+
+    let count = f
+        .call_tool(
+            "iwe_query",
+            json!({ "operation": "count", "document": "filter: { status: draft }\n" }),
+        )
+        .await;
+    assert_eq!(Fixture::result_json(&count)["count"], 1);
+}
+
+#[tokio::test]
+async fn query_batch_dry_run_reports_net_changes() {
+    let f = Fixture::with_documents(vec![("1", "# One\n\nbody\n")]).await;
+
+    let result = f
+        .call_tool(
+            "iwe_query",
+            json!({
+                "operation": "batch",
+                "dry_run": true,
+                "document": indoc! {"
+                    operations:
+                      - create: { key: scratch, content: \"# Scratch\\n\" }
+                      - update:
+                          filter: { $key: scratch }
+                          expect: 1
+                          update: { $set: { status: temp } }
+                      - delete: { filter: { $key: scratch }, expect: 1 }
+                      - find: { filter: { $key: scratch } }
+                "},
+            }),
+        )
+        .await;
+
+    let out = Fixture::result_json(&result);
+    assert_eq!(out["dry_run"], true);
+    assert_eq!(
+        out["steps"][3],
+        json!({ "operation": "find", "documents": [] })
+    );
+    assert_eq!(out["creates"], json!([]));
+    assert_eq!(out["updates"], json!([]));
+    assert_eq!(out["removes"], json!([]));
+}
+
+#[tokio::test]
+async fn query_batch_strict_requires_expect() {
+    let f = Fixture::with_documents(vec![("1", "# One\n\nbody\n")]).await;
+
+    let result = f
+        .try_call_tool(
+            "iwe_query",
+            json!({
+                "operation": "batch",
+                "document": indoc! {"
+                    operations:
+                      - find: { filter: {} }
+                      - delete: { filter: { $key: '1' } }
+                "},
+            }),
+        )
+        .await;
+
+    let err = format!("{:?}", result.expect_err("unguarded step must be rejected"));
+    assert!(err.contains("operation 2 document-level expect"), "{}", err);
+}
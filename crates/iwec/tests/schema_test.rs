@@ -167,3 +167,30 @@ fn mcp_error(err: ServiceError) -> ErrorData {
         other => panic!("expected McpError, got: {other:?}"),
     }
 }
+
+#[tokio::test]
+async fn batch_is_validated_as_a_whole() {
+    let dir = setup(PERSON_SCHEMA);
+    let base = dir.path();
+    let f = Fixture::with_path(base.to_str().unwrap(), config("person", "docs/**")).await;
+
+    let err = f
+        .try_call_tool(
+            "iwe_query",
+            json!({
+                "operation": "batch",
+                "document": "operations:\n  - create: { key: docs/two, content: \"# Summary\\n\\n# Tasks\\n\" }\n  - create: { key: docs/three, content: \"# Summary\\n\" }\n",
+            }),
+        )
+        .await
+        .unwrap_err();
+
+    let error = mcp_error(err);
+    assert!(
+        error.message.contains("docs/three: required section"),
+        "{}",
+        error.message
+    );
+    assert!(!base.join("docs/two.md").exists());
+    assert!(!base.join("docs/three.md").exists());
+}
@@ -50,3 +50,27 @@ async fn stats_broken_links() {
 
     assert!(output["brokenLinkCount"].as_u64().unwrap() >= 1);
 }
+
+#[tokio::test]
+async fn stats_graph_mode() {
+    let f = Fixture::with_documents(vec![
+        ("1", "# Hub\n\n[Child](2)\n"),
+        ("2", "# Child\n\n[Hub](1)\n"),
+        ("3", "# Island\n"),
+    ])
+    .await;
+
+    let result = f.call_tool("iwe_stats", json!({"mode": "graph"})).await;
+    let output = Fixture::result_json(&result);
+
+    assert_eq!(output["documents"], 3);
+    assert_eq!(output["components"][0]["keys"], json!(["1", "2"]));
+    assert_eq!(output["inclusionCycles"], json!([["1", "2"]]));
+
+    let result = f
+        .call_tool("iwe_stats", json!({"mode": "graph", "key": "3"}))
+        .await;
+    let output = Fixture::result_json(&result);
+    assert_eq!(output["key"], "3");
+    assert_eq!(output["component"], 1);
+}
@@ -7,7 +7,7 @@ use liwe::graph::Graph;
 use liwe::model::Key;
 use tokio::sync::Mutex;
 
-async fn start_watcher(graph: Arc<Mutex<Graph>>, base_path: &std::path::Path) {
+async fn start_watcher(graph: Arc<Mutex<Arc<Graph>>>, base_path: &std::path::Path) {
     iwec::watcher::start_polling(
         graph,
         base_path.to_path_buf(),
@@ -39,7 +39,7 @@ async fn watcher_picks_up_new_file() {
     let dir = tempfile::tempdir().unwrap();
     let base_path = dir.path().canonicalize().unwrap();
 
-    let graph = Arc::new(Mutex::new(Graph::new()));
+    let graph = Arc::new(Mutex::new(Arc::new(Graph::new())));
     start_watcher(graph.clone(), &base_path).await;
 
     fs::write(base_path.join("hello.md"), "# Hello\n\nWorld\n").unwrap();
@@ -65,12 +65,12 @@ async fn watcher_picks_up_modification() {
     fs::write(base_path.join("doc.md"), "# Original\n").unwrap();
 
     let state = diwe::fs::new_for_path(&base_path, Format::Markdown);
-    let graph = Arc::new(Mutex::new(Graph::from_state(
+    let graph = Arc::new(Mutex::new(Arc::new(Graph::from_state(
         &state,
         false,
         MarkdownOptions::default(),
         None,
-    )));
+    ))));
     start_watcher(graph.clone(), &base_path).await;
 
     fs::write(base_path.join("doc.md"), "# Updated\n\nNew content\n").unwrap();
@@ -98,12 +98,12 @@ async fn watcher_picks_up_deletion() {
     fs::write(base_path.join("to-delete.md"), "# Delete me\n").unwrap();
 
     let state = diwe::fs::new_for_path(&base_path, Format::Markdown);
-    let graph = Arc::new(Mutex::new(Graph::from_state(
+    let graph = Arc::new(Mutex::new(Arc::new(Graph::from_state(
         &state,
         false,
         MarkdownOptions::default(),
         None,
-    )));
+    ))));
 
     {
         let g = graph.lock().await;
@@ -132,7 +132,7 @@ async fn watcher_ignores_non_md_files() {
     let dir = tempfile::tempdir().unwrap();
     let base_path = dir.path().canonicalize().unwrap();
 
-    let graph = Arc::new(Mutex::new(Graph::new()));
+    let graph = Arc::new(Mutex::new(Arc::new(Graph::new())));
     start_watcher(graph.clone(), &base_path).await;
 
     fs::write(base_path.join("notes.txt"), "not markdown").unwrap();
@@ -1,17 +1,15 @@
-use std::str::FromStr;
+use std::collections::HashMap;
+use std::path::Path;
 use std::time::Duration;
-use std::{collections::HashMap, path::PathBuf};
 
 use anyhow::Result;
 use std::time::SystemTime;
 
-use diwe::config::Configuration;
+use diwe::config::{load_config_in, Configuration};
 use lsp_server::Connection;
+use lsp_types::MessageType;
 
-use crossbeam_channel::unbounded;
-use diwe::fs::{new_for_path, new_from_hashmap};
-use diwe::watcher::{start_poll_watcher, start_watcher, FsChange};
-use router::{LspClient, Router, ServerConfig};
+use router::{LspClient, Router};
 
 pub mod router;
 
@@ -22,12 +20,52 @@ pub struct ServerParams {
     pub client_name: Option<String>,
     pub configuration: Configuration,
     pub base_path: String,
+    /// Folder holding the `.iwe` directory of the library at `base_path`. Its configuration is
+    /// reloaded when `config.toml` or a schema changes.
+    #[serde(default)]
+    pub folder: Option<String>,
+    /// Workspace folders besides the library at `base_path`, each with its own configuration,
+    /// graph and search index.
+    #[serde(default)]
+    pub workspace_folders: Vec<FolderParams>,
+    /// Workspace folders left out because their configuration failed to load, reported to the
+    /// client once the server starts.
+    #[serde(default)]
+    pub folder_errors: Vec<String>,
     #[serde(skip)]
     pub override_now: Option<SystemTime>,
     #[serde(skip)]
     pub watch_poll_interval: Option<Duration>,
 }
 
+/// A library opened as a workspace folder. Without `state`, documents are read from
+/// `base_path` and kept in sync with the disk.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq, Default)]
+pub struct FolderParams {
+    pub state: Option<HashMap<String, String>>,
+    pub configuration: Configuration,
+    pub base_path: String,
+    pub folder: Option<String>,
+}
+
+impl FolderParams {
+    /// Library of a workspace folder, configured by the folder's `.iwe/config.toml`.
+    pub fn load(folder: &Path) -> Result<Self, String> {
+        let configuration = load_config_in(folder)?;
+        let mut base_path = folder.to_path_buf();
+        if !configuration.library.path.is_empty() {
+            base_path.push(&configuration.library.path);
+        }
+
+        Ok(Self {
+            state: None,
+            configuration,
+            base_path: base_path.to_string_lossy().to_string(),
+            folder: Some(folder.to_string_lossy().to_string()),
+        })
+    }
+}
+
 pub fn main_loop(connection: Connection, params: ServerParams) -> Result<()> {
     let client = params
         .clone()
@@ -36,56 +74,25 @@ pub fn main_loop(connection: Connection, params: ServerParams) -> Result<()> {
         .map(|_| LspClient::Helix)
         .unwrap_or(LspClient::Unknown);
 
-    let watch_filesystem = params.state.is_none();
-    let base_path = params.base_path.clone();
-    let format = params.configuration.format;
-    let watch_poll_interval = params.watch_poll_interval;
-
-    let (fs_sender, fs_receiver) = unbounded::<FsChange>();
-
-    let router = if let Some(state) = params.state {
-        Router::new(
-            connection.sender,
-            ServerConfig {
-                base_path: params.base_path.clone(),
-                state: new_from_hashmap(state),
-                sequential_ids: Some(true),
-                lsp_client: client,
-                configuration: params.configuration,
-                override_now: params.override_now,
-            },
-        )
-    } else {
-        Router::new(
-            connection.sender,
-            ServerConfig {
-                base_path: params.base_path.clone(),
-                state: new_for_path(
-                    &PathBuf::from_str(&params.base_path).expect("to work"),
-                    params.configuration.format,
-                ),
-                sequential_ids: None,
-                lsp_client: client,
-                configuration: params.configuration,
-                override_now: params.override_now,
-            },
-        )
-    };
+    let mut router = Router::new(
+        connection.sender,
+        client,
+        params.override_now,
+        params.watch_poll_interval,
+    );
 
-    let handler_sender = fs_sender.clone();
-    let handler = move |change| {
-        let _ = handler_sender.send(change);
-    };
-    let watch_root = PathBuf::from(base_path);
-    let _watcher: Option<Box<dyn Send>> = if !watch_filesystem {
-        None
-    } else if let Some(interval) = watch_poll_interval {
-        start_poll_watcher(watch_root, format, interval, handler)
-            .map(|w| Box::new(w) as Box<dyn Send>)
-    } else {
-        start_watcher(watch_root, format, handler).map(|w| Box::new(w) as Box<dyn Send>)
-    };
-    let _fs_sender = fs_sender;
+    router.open_folder(FolderParams {
+        state: params.state,
+        configuration: params.configuration,
+        base_path: params.base_path,
+        folder: params.folder,
+    });
+    for folder in params.workspace_folders {
+        router.open_folder(folder);
+    }
+    for message in params.folder_errors {
+        router.show_message(MessageType::ERROR, message);
+    }
 
-    router.run(connection.receiver, fs_receiver)
+    router.run(connection.receiver)
 }
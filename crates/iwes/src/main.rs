@@ -2,31 +2,45 @@ use std::env;
 use std::error::Error;
 use std::fs::OpenOptions;
 
-use diwe::config::load_config;
 use iwes::main_loop;
 use iwes::router::server::actions::all_action_types;
 use iwes::router::server::actions::ActionProvider;
-use iwes::ServerParams;
+use iwes::router::server::move_section::MOVE_SECTION_COMMAND;
+use iwes::router::server::semantic_tokens;
+use iwes::router::server::{JOURNAL_COMMAND, MOVE_SECTION_KIND, OPEN_COMMAND};
+use iwes::{FolderParams, ServerParams};
+use lsp_types::CallHierarchyServerCapability;
+use lsp_types::CodeActionKind;
 use lsp_types::CodeActionOptions;
 use lsp_types::CodeActionProviderCapability;
+use lsp_types::CodeLensOptions;
 use lsp_types::CompletionOptions;
+use lsp_types::DocumentLinkOptions;
+use lsp_types::DocumentOnTypeFormattingOptions;
+use lsp_types::ExecuteCommandOptions;
 use lsp_types::FoldingRangeProviderCapability;
 use lsp_types::HoverProviderCapability;
 use lsp_types::InitializeParams;
 use lsp_types::OneOf;
 use lsp_types::RenameOptions;
+use lsp_types::SemanticTokensFullOptions;
+use lsp_types::SemanticTokensOptions;
+use lsp_types::SemanticTokensServerCapabilities;
 use lsp_types::ServerCapabilities;
 use lsp_types::WorkspaceFileOperationsServerCapabilities;
 use lsp_types::WorkspaceServerCapabilities;
 
 use lsp_server::Connection;
 use lsp_types::SaveOptions;
+use lsp_types::SelectionRangeProviderCapability;
 use lsp_types::TextDocumentSyncCapability;
 use lsp_types::TextDocumentSyncKind;
 use lsp_types::TextDocumentSyncOptions;
 use lsp_types::TextDocumentSyncSaveOptions;
+use lsp_types::WorkspaceFoldersServerCapabilities;
+use url::Url;
 
-use log::{debug, info};
+use log::{debug, error, info};
 
 fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
     if env::var("IWE_DEBUG").is_ok() {
@@ -49,16 +63,70 @@ fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
 
     info!("starting IWE LSP server");
 
-    let configuration = load_config().unwrap_or_else(|e| {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
-    });
-
     let (connection, io_threads) = Connection::stdio();
 
-    let server_capabilities = serde_json::to_value(&ServerCapabilities {
+    let (initialize_id, initialization_params_value) = match connection.initialize_start() {
+        Ok(it) => it,
+        Err(e) => {
+            if e.channel_is_disconnected() {
+                io_threads.join()?;
+            }
+            return Err(e.into());
+        }
+    };
+
+    let initialize_params: InitializeParams =
+        serde_json::from_value(initialization_params_value).unwrap();
+
+    let folder_paths = initialize_params
+        .workspace_folders
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|folder| Url::parse(folder.uri.as_str()).ok()?.to_file_path().ok())
+        .collect::<Vec<_>>();
+    let folder_paths = if folder_paths.is_empty() {
+        vec![env::current_dir().expect("to get current dir")]
+    } else {
+        folder_paths
+    };
+
+    let mut folders = vec![];
+    let mut folder_errors = vec![];
+    for path in &folder_paths {
+        match FolderParams::load(path) {
+            Ok(folder) => folders.push(folder),
+            Err(e) => {
+                error!("Failed to open workspace folder {}: {}", path.display(), e);
+                folder_errors.push(format!(
+                    "IWE: workspace folder {} not opened: {}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+    }
+    let primary = if folders.is_empty() {
+        let path = &folder_paths[0];
+        FolderParams {
+            base_path: path.to_string_lossy().to_string(),
+            folder: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        }
+    } else {
+        folders.remove(0)
+    };
+    let configuration = primary.configuration.clone();
+
+    debug!("config: {:?}", configuration);
+
+    let mut server_capabilities = serde_json::to_value(&ServerCapabilities {
         references_provider: Some(OneOf::Left(true)),
         document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+            first_trigger_character: "\n".to_string(),
+            more_trigger_character: None,
+        }),
         definition_provider: Some(OneOf::Left(true)),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
         completion_provider: Some(CompletionOptions {
@@ -97,13 +165,32 @@ fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
                 all_action_types(&configuration)
                     .iter()
                     .map(|it| it.action_kind())
+                    .chain([
+                        CodeActionKind::QUICKFIX,
+                        CodeActionKind::new(MOVE_SECTION_KIND),
+                    ])
                     .collect(),
             ),
             resolve_provider: Some(true),
             ..Default::default()
         })),
+        call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(true),
+        }),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![
+                OPEN_COMMAND.to_string(),
+                JOURNAL_COMMAND.to_string(),
+                MOVE_SECTION_COMMAND.to_string(),
+            ],
+            work_done_progress_options: Default::default(),
+        }),
         workspace: Some(WorkspaceServerCapabilities {
-            workspace_folders: None,
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
             file_operations: Some(WorkspaceFileOperationsServerCapabilities {
                 did_create: None,
                 will_create: None,
@@ -113,36 +200,42 @@ fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
                 will_delete: None,
             }),
         }),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: Default::default(),
+        }),
         folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: semantic_tokens::legend(),
+                full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                range: None,
+                work_done_progress_options: Default::default(),
+            },
+        )),
         ..Default::default()
     })
     .unwrap();
-    let initialization_params_value = match connection.initialize(server_capabilities) {
-        Ok(it) => it,
-        Err(e) => {
-            if e.channel_is_disconnected() {
-                io_threads.join()?;
-            }
-            return Err(e.into());
+    // `ServerCapabilities` has no field for the type hierarchy provider.
+    server_capabilities["typeHierarchyProvider"] = serde_json::Value::Bool(true);
+    let initialize_data = serde_json::json!({
+        "capabilities": server_capabilities,
+    });
+    if let Err(e) = connection.initialize_finish(initialize_id, initialize_data) {
+        if e.channel_is_disconnected() {
+            io_threads.join()?;
         }
-    };
-
-    let initialize_params: InitializeParams =
-        serde_json::from_value(initialization_params_value).unwrap();
-
-    let current_dir = env::current_dir().expect("to get current dir");
-    let mut library_path = current_dir.clone();
-
-    debug!("config: {:?}", configuration);
-
-    if !configuration.library.path.is_empty() {
-        library_path.push(configuration.clone().library.path);
+        return Err(e.into());
     }
 
     let server_params = ServerParams {
         client_name: initialize_params.client_info.map(|it| it.name),
-        configuration: configuration.clone(),
-        base_path: library_path.to_string_lossy().to_string(),
+        configuration,
+        base_path: primary.base_path,
+        folder: primary.folder,
+        workspace_folders: folders,
+        folder_errors,
         ..Default::default()
     };
 
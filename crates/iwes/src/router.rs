@@ -1,30 +1,50 @@
+use std::collections::HashMap;
 use std::panic;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
 use anyhow::{bail, Result};
-use crossbeam_channel::{after, never, select, Receiver, Sender};
-use diwe::config::Configuration;
-use liwe::model::State;
+use crossbeam_channel::{after, never, select, unbounded, Receiver, Sender};
+use diwe::config::{schemas_dir_in, Configuration, Format};
+use diwe::fs::{new_for_path, new_from_hashmap};
+use diwe::schema::validate_pending_documents_in;
+use itertools::Itertools;
+use liwe::model::{Key, State};
 use log::{debug, error};
-use lsp_server::{ErrorCode, Message, Request};
+use lsp_server::{ErrorCode, Message, Request, RequestId};
 use lsp_server::{Notification, Response};
 use lsp_types::{
-    CodeAction, CodeActionParams, CompletionItem, DidChangeTextDocumentParams,
-    DidChangeWatchedFilesParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-    DidSaveTextDocumentParams, DocumentFormattingParams, DocumentSymbolParams, FoldingRangeParams,
-    GotoDefinitionResponse, HoverParams, InlayHintParams, InlineValueParams, ReferenceParams,
-    RenameParams, ShowDocumentParams, TextDocumentPositionParams, WorkspaceSymbolParams,
+    ApplyWorkspaceEditParams, ApplyWorkspaceEditResponse, CodeAction, CodeActionParams, CodeLens,
+    CodeLensParams, CompletionItem, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+    DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, DocumentFormattingParams, DocumentLinkParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams, DocumentSymbolParams,
+    ExecuteCommandParams, FoldingRangeParams, GotoDefinitionResponse, HoverParams, InlayHintParams,
+    InlineValueParams, MessageActionItem, MessageType, ReferenceParams, RenameParams,
+    SelectionRangeParams, SemanticTokensDeltaParams, SemanticTokensParams, ShowDocumentParams,
+    ShowMessageParams, ShowMessageRequestParams, TextDocumentPositionParams, Uri, WorkspaceEdit,
+    WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+use lsp_types::{
+    CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    TypeHierarchyPrepareParams, TypeHierarchySubtypesParams, TypeHierarchySupertypesParams,
 };
 use lsp_types::{CompletionParams, GotoDefinitionParams};
 
-use self::server::DefinitionResult;
+use self::server::move_section::MOVE_SECTION_COMMAND;
+use self::server::{DefinitionResult, JOURNAL_COMMAND, OPEN_COMMAND};
 use serde::Deserialize;
 use serde_json::to_value;
+use url::Url;
 use uuid::Uuid;
 
 use self::server::Server;
-use diwe::watcher::FsChange;
+use crate::FolderParams;
+use diwe::watcher::{
+    start_config_poll_watcher, start_config_watcher, start_poll_watcher, start_watcher, FsChange,
+};
 
 pub mod server;
 
@@ -45,11 +65,39 @@ pub struct ServerConfig {
     pub override_now: Option<std::time::SystemTime>,
 }
 
-#[derive(Clone)]
-pub struct Router {
+/// The library of a workspace folder, with the watchers that keep its documents and
+/// configuration in sync with the disk.
+struct Library {
+    path: String,
+    folder: Option<String>,
     server: Arc<Server>,
+    _watcher: Option<Box<dyn Send>>,
+    _config_watcher: Option<Box<dyn Send>>,
+}
+
+/// A section waiting for the user to pick the document to move it to.
+struct PendingMove {
+    uri: Uri,
+    line: usize,
+    targets: Vec<(String, Key)>,
+}
+
+/// Routes messages to the library whose folder holds the document they are about. Each
+/// workspace folder is a library with its own configuration, graph and search index.
+pub struct Router {
+    libraries: Vec<Library>,
     sender: Sender<Message>,
     inlay_hints_used: Arc<AtomicBool>,
+    lsp_client: LspClient,
+    override_now: Option<SystemTime>,
+    watch_poll_interval: Option<Duration>,
+    fs_sender: Sender<(String, FsChange)>,
+    fs_events: Receiver<(String, FsChange)>,
+    config_sender: Sender<String>,
+    config_events: Receiver<String>,
+    pending_moves: Mutex<HashMap<RequestId, PendingMove>>,
+    /// Documents to show once the client reports the edit creating them as applied.
+    pending_opens: Mutex<HashMap<RequestId, Uri>>,
 }
 
 impl Router {
@@ -73,29 +121,249 @@ impl Router {
         });
     }
 
-    pub fn new(sender: Sender<Message>, config: ServerConfig) -> Self {
+    pub fn new(
+        sender: Sender<Message>,
+        lsp_client: LspClient,
+        override_now: Option<SystemTime>,
+        watch_poll_interval: Option<Duration>,
+    ) -> Self {
+        let (fs_sender, fs_events) = unbounded();
+        let (config_sender, config_events) = unbounded();
+        Self {
+            libraries: vec![],
+            sender,
+            inlay_hints_used: Arc::new(AtomicBool::new(false)),
+            lsp_client,
+            override_now,
+            watch_poll_interval,
+            fs_sender,
+            fs_events,
+            config_sender,
+            config_events,
+            pending_moves: Mutex::new(HashMap::new()),
+            pending_opens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Opens the library of a workspace folder, unless one is already open at its path.
+    pub fn open_folder(&mut self, folder: FolderParams) {
+        if self
+            .libraries
+            .iter()
+            .any(|library| library.path == folder.base_path)
+        {
+            return;
+        }
+
+        let format = folder.configuration.format;
+        let watch = folder.state.is_none();
+        let state = match folder.state {
+            Some(state) => new_from_hashmap(state),
+            None => new_for_path(&PathBuf::from(&folder.base_path), format),
+        };
+
         debug!(
             "initializing LSP database at {}, with {} docs",
-            config.base_path,
-            config.state.len()
+            folder.base_path,
+            state.len()
         );
 
-        let router = Self {
-            server: Arc::new(Server::new(config)),
-            sender,
-            inlay_hints_used: Arc::new(AtomicBool::new(false)),
+        let server = Server::new(ServerConfig {
+            base_path: folder.base_path.clone(),
+            state,
+            sequential_ids: if watch { None } else { Some(true) },
+            configuration: folder.configuration,
+            lsp_client: self.lsp_client,
+            override_now: self.override_now,
+        });
+        let watcher = if watch {
+            self.watch(&folder.base_path, format)
+        } else {
+            None
         };
+        let config_watcher = folder
+            .folder
+            .as_deref()
+            .and_then(|path| self.watch_config(&folder.base_path, path));
+        self.libraries.push(Library {
+            path: folder.base_path,
+            folder: folder.folder,
+            server: Arc::new(server),
+            _watcher: watcher,
+            _config_watcher: config_watcher,
+        });
 
         debug!("initializing LSP database complete");
+    }
 
-        router
+    /// Closes the libraries inside a removed workspace folder. The last library stays open so
+    /// that requests always have one to go to.
+    fn close_folder(&mut self, folder: &Path) {
+        let (closed, open): (Vec<_>, Vec<_>) = self
+            .libraries
+            .drain(..)
+            .partition(|library| Path::new(&library.path).starts_with(folder));
+        if open.is_empty() {
+            debug!("keeping the last library open: {}", folder.display());
+            self.libraries = closed;
+        } else {
+            self.libraries = open;
+        }
     }
 
-    pub fn run(mut self, receiver: Receiver<Message>, fs_events: Receiver<FsChange>) -> Result<()> {
+    fn change_workspace_folders(&mut self, params: DidChangeWorkspaceFoldersParams) {
+        for folder in params.event.removed {
+            if let Some(path) = folder_path(&folder.uri) {
+                self.close_folder(&path);
+            }
+        }
+        for folder in params.event.added {
+            let Some(path) = folder_path(&folder.uri) else {
+                continue;
+            };
+            match FolderParams::load(&path) {
+                Ok(folder) => self.open_folder(folder),
+                Err(e) => error!("Failed to open workspace folder {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    fn watch(&self, path: &str, format: Format) -> Option<Box<dyn Send>> {
+        let sender = self.fs_sender.clone();
+        let library = path.to_string();
+        let handler = move |change| {
+            let _ = sender.send((library.clone(), change));
+        };
+        let root = PathBuf::from(path);
+        match self.watch_poll_interval {
+            Some(interval) => start_poll_watcher(root, format, interval, handler)
+                .map(|w| Box::new(w) as Box<dyn Send>),
+            None => start_watcher(root, format, handler).map(|w| Box::new(w) as Box<dyn Send>),
+        }
+    }
+
+    fn watch_config(&self, path: &str, folder: &str) -> Option<Box<dyn Send>> {
+        let sender = self.config_sender.clone();
+        let library = path.to_string();
+        let handler = move || {
+            let _ = sender.send(library.clone());
+        };
+        let root = PathBuf::from(folder);
+        match self.watch_poll_interval {
+            Some(interval) => start_config_poll_watcher(root, interval, handler)
+                .map(|w| Box::new(w) as Box<dyn Send>),
+            None => start_config_watcher(root, handler).map(|w| Box::new(w) as Box<dyn Send>),
+        }
+    }
+
+    /// Reloads the configuration of the library at `path` after its `config.toml` or a schema
+    /// changed. A library whose path or format changed is opened again from the disk. Errors
+    /// are shown to the user and the library keeps its previous configuration.
+    fn reload_configuration(&mut self, path: String) {
+        let Some(index) = self
+            .libraries
+            .iter()
+            .position(|library| library.path == path)
+        else {
+            return;
+        };
+        let Some(folder) = self.libraries[index].folder.clone() else {
+            return;
+        };
+
+        let params = match FolderParams::load(Path::new(&folder)) {
+            Ok(params) => params,
+            Err(e) => {
+                self.show_message(
+                    MessageType::ERROR,
+                    format!("IWE: configuration not reloaded: {}", e),
+                );
+                return;
+            }
+        };
+
+        if let Err(errors) = validate_pending_documents_in(
+            &schemas_dir_in(Path::new(&folder)),
+            &params.configuration,
+            &[],
+        ) {
+            self.show_message(
+                MessageType::ERROR,
+                format!("IWE: schema configuration error: {}", errors.join("; ")),
+            );
+        }
+
+        let format = self.libraries[index].server.configuration().format;
+        if params.base_path != path || params.configuration.format != format {
+            self.libraries.remove(index);
+            self.open_folder(params);
+            if self.libraries.len() > index {
+                let library = self.libraries.remove(self.libraries.len() - 1);
+                self.libraries.insert(index, library);
+            }
+        } else if let Some(server) = self.server_mut(index) {
+            server.reconfigure(params.configuration);
+        }
+        debug!("configuration reloaded: {}", folder);
+    }
+
+    pub(crate) fn show_message(&self, typ: MessageType, message: String) {
+        self.send(Message::Notification(Notification::new(
+            "window/showMessage".to_string(),
+            ShowMessageParams { typ, message },
+        )));
+    }
+
+    fn publish_lint(&self, uri: &Uri) {
+        if let Some(report) = self.server(Some(uri)).lint_report(uri) {
+            self.send(Message::Notification(Notification::new(
+                "textDocument/publishDiagnostics".to_string(),
+                report,
+            )));
+        }
+    }
+
+    /// Index of the library holding `uri`, the innermost one for nested folders, or of the
+    /// first library when none does.
+    fn library_at(&self, uri: Option<&Uri>) -> usize {
+        uri.and_then(|uri| {
+            self.libraries
+                .iter()
+                .enumerate()
+                .filter(|(_, library)| library.server.base_path().maybe_url_to_key(uri).is_some())
+                .max_by_key(|(_, library)| Path::new(&library.path).components().count())
+                .map(|(index, _)| index)
+        })
+        .unwrap_or(0)
+    }
+
+    fn server(&self, uri: Option<&Uri>) -> &Server {
+        &self.libraries[self.library_at(uri)].server
+    }
+
+    fn server_mut(&mut self, index: usize) -> Option<&mut Server> {
+        let server = Arc::get_mut(&mut self.libraries[index].server);
+        if server.is_none() {
+            error!("Failed to get mutable reference to server");
+        }
+        server
+    }
+
+    fn server_at_mut(&mut self, uri: &Uri) -> Option<&mut Server> {
+        self.server_mut(self.library_at(Some(uri)))
+    }
+
+    pub fn run(mut self, receiver: Receiver<Message>) -> Result<()> {
         use std::panic::AssertUnwindSafe;
 
+        let fs_events = self.fs_events.clone();
+        let config_events = self.config_events.clone();
         loop {
-            let idle_rebuild = if self.server.search_index_is_dirty() {
+            let idle_rebuild = if self
+                .libraries
+                .iter()
+                .any(|library| library.server.search_index_is_dirty())
+            {
                 after(IDLE_REBUILD_DELAY)
             } else {
                 never()
@@ -129,27 +397,37 @@ impl Router {
                         self.on_fs_event(event);
                     }
                 }
+                recv(config_events) -> path => {
+                    if let Ok(path) = path {
+                        self.reload_configuration(path);
+                    }
+                }
                 recv(idle_rebuild) -> _ => self.refresh_search_index(),
             }
         }
     }
 
-    fn on_fs_event(&mut self, event: FsChange) {
-        if let Some(server) = Arc::get_mut(&mut self.server) {
+    fn on_fs_event(&mut self, (path, event): (String, FsChange)) {
+        let Some(index) = self
+            .libraries
+            .iter()
+            .position(|library| library.path == path)
+        else {
+            return;
+        };
+        if let Some(server) = self.server_mut(index) {
             match event {
                 FsChange::Update(key, content) => server.apply_external_update(key, content),
                 FsChange::Remove(key) => server.apply_external_removal(key),
             }
-        } else {
-            error!("Failed to get mutable reference to server");
         }
     }
 
     fn refresh_search_index(&mut self) {
-        if let Some(server) = Arc::get_mut(&mut self.server) {
-            server.refresh_search_index();
-        } else {
-            error!("Failed to get mutable reference to server");
+        for index in 0..self.libraries.len() {
+            if let Some(server) = self.server_mut(index) {
+                server.refresh_search_index();
+            }
         }
     }
 
@@ -160,7 +438,10 @@ impl Router {
         match message {
             Message::Request(req) => self.on_request(req),
             Message::Notification(notification) => self.on_notification(notification),
-            Message::Response(_) => false,
+            Message::Response(response) => {
+                self.on_response(response);
+                false
+            }
         }
     }
 
@@ -173,11 +454,11 @@ impl Router {
             "textDocument/didOpen" => {
                 match DidOpenTextDocumentParams::deserialize(notification.params) {
                     Ok(params) => {
-                        if let Some(server) = Arc::get_mut(&mut self.server) {
+                        let uri = params.text_document.uri.clone();
+                        if let Some(server) = self.server_at_mut(&uri) {
                             server.handle_did_open_text_document(params);
-                        } else {
-                            error!("Failed to get mutable reference to server");
                         }
+                        self.publish_lint(&uri);
                     }
                     Err(e) => error!("Failed to deserialize didOpen params: {}", e),
                 }
@@ -185,10 +466,8 @@ impl Router {
             "textDocument/didClose" => {
                 match DidCloseTextDocumentParams::deserialize(notification.params) {
                     Ok(params) => {
-                        if let Some(server) = Arc::get_mut(&mut self.server) {
+                        if let Some(server) = self.server_at_mut(&params.text_document.uri) {
                             server.handle_did_close_text_document(params);
-                        } else {
-                            error!("Failed to get mutable reference to server");
                         }
                     }
                     Err(e) => error!("Failed to deserialize didClose params: {}", e),
@@ -197,11 +476,11 @@ impl Router {
             "textDocument/didChange" => {
                 match DidChangeTextDocumentParams::deserialize(notification.params) {
                     Ok(params) => {
-                        if let Some(server) = Arc::get_mut(&mut self.server) {
+                        let uri = params.text_document.uri.clone();
+                        if let Some(server) = self.server_at_mut(&uri) {
                             server.handle_did_change_text_document(params);
-                        } else {
-                            error!("Failed to get mutable reference to server");
                         }
+                        self.publish_lint(&uri);
                     }
                     Err(e) => error!("Failed to deserialize didChange params: {}", e),
                 }
@@ -209,11 +488,11 @@ impl Router {
             "textDocument/didSave" => {
                 match DidSaveTextDocumentParams::deserialize(notification.params) {
                     Ok(params) => {
-                        if let Some(server) = Arc::get_mut(&mut self.server) {
+                        let uri = params.text_document.uri.clone();
+                        if let Some(server) = self.server_at_mut(&uri) {
                             server.handle_did_save_text_document(params);
-                        } else {
-                            error!("Failed to get mutable reference to server");
                         }
+                        self.publish_lint(&uri);
                     }
                     Err(e) => error!("Failed to deserialize didSave params: {}", e),
                 }
@@ -221,15 +500,30 @@ impl Router {
             "workspace/didChangeWatchedFiles" => {
                 match DidChangeWatchedFilesParams::deserialize(notification.params) {
                     Ok(params) => {
-                        if let Some(server) = Arc::get_mut(&mut self.server) {
-                            server.handle_did_change_watched_files(params);
-                        } else {
-                            error!("Failed to get mutable reference to server");
+                        let by_library = params
+                            .changes
+                            .into_iter()
+                            .into_group_map_by(|change| self.library_at(Some(&change.uri)));
+                        for (index, changes) in by_library {
+                            if let Some(server) = self.server_mut(index) {
+                                server.handle_did_change_watched_files(
+                                    DidChangeWatchedFilesParams { changes },
+                                );
+                            }
                         }
                     }
                     Err(e) => error!("Failed to deserialize didChangeWatchedFiles params: {}", e),
                 }
             }
+            "workspace/didChangeWorkspaceFolders" => {
+                match DidChangeWorkspaceFoldersParams::deserialize(notification.params) {
+                    Ok(params) => self.change_workspace_folders(params),
+                    Err(e) => error!(
+                        "Failed to deserialize didChangeWorkspaceFolders params: {}",
+                        e
+                    ),
+                }
+            }
             default => {
                 debug!("unhandled request: {}", default)
             }
@@ -238,6 +532,203 @@ impl Router {
         false
     }
 
+    /// Runs a server command. Its result is `null`, except for the commands that answer with data.
+    fn execute_command(&self, params: ExecuteCommandParams) -> Result<serde_json::Value, String> {
+        let argument = params
+            .arguments
+            .first()
+            .and_then(|argument| argument.as_str());
+        match params.command.as_str() {
+            OPEN_COMMAND => {
+                let uri = argument
+                    .and_then(|uri| uri.parse().ok())
+                    .ok_or_else(|| format!("{}: expected a document URI", OPEN_COMMAND))?;
+                self.show_document(uri);
+            }
+            JOURNAL_COMMAND => {
+                let (edit, uri) = self
+                    .server(None)
+                    .handle_journal_command(argument.unwrap_or("today"))?;
+                match edit {
+                    Some(edit) => {
+                        let id = self.apply_edit("Journal", edit);
+                        self.pending_opens
+                            .lock()
+                            .expect("pending opens lock")
+                            .insert(id, uri);
+                    }
+                    None => self.show_document(uri),
+                }
+            }
+            MOVE_SECTION_COMMAND => {
+                let uri: Uri = argument
+                    .and_then(|uri| uri.parse().ok())
+                    .ok_or_else(|| format!("{}: expected a document URI", MOVE_SECTION_COMMAND))?;
+                let line = params
+                    .arguments
+                    .get(1)
+                    .and_then(|line| line.as_u64())
+                    .ok_or_else(|| format!("{}: expected a line number", MOVE_SECTION_COMMAND))?
+                    as usize;
+                let server = self.server(Some(&uri));
+                match params.arguments.get(2).and_then(|target| target.as_str()) {
+                    Some(target) => {
+                        let edit = server.handle_move_section(&uri, line, &Key::name(target))?;
+                        self.apply_edit("Move section", edit);
+                    }
+                    None => self.pick_move_target(uri, line)?,
+                }
+            }
+            command => return Err(format!("unknown command: {}", command)),
+        }
+        Ok(serde_json::Value::Null)
+    }
+
+    /// Asks which document to move a section to; the answer arrives in `on_response`.
+    fn pick_move_target(&self, uri: Uri, line: usize) -> Result<(), String> {
+        let mut targets: Vec<(String, Key)> = vec![];
+        for (key, title) in self.server(Some(&uri)).move_section_targets(&uri, line)? {
+            let title = if targets.iter().any(|(other, _)| *other == title) {
+                format!("{} ({})", title, key)
+            } else {
+                title
+            };
+            targets.push((title, key));
+        }
+        if targets.is_empty() {
+            return Err(format!(
+                "{}: no other documents to move the section to",
+                MOVE_SECTION_COMMAND
+            ));
+        }
+
+        let id: RequestId = Uuid::new_v4().to_string().into();
+        self.send(Message::Request(Request {
+            id: id.clone(),
+            method: "window/showMessageRequest".to_string(),
+            params: to_value(ShowMessageRequestParams {
+                typ: MessageType::INFO,
+                message: "Move section to".to_string(),
+                actions: Some(
+                    targets
+                        .iter()
+                        .map(|(title, _)| MessageActionItem {
+                            title: title.clone(),
+                            properties: HashMap::new(),
+                        })
+                        .collect(),
+                ),
+            })
+            .unwrap(),
+        }));
+        self.pending_moves
+            .lock()
+            .expect("pending moves lock")
+            .insert(id, PendingMove { uri, line, targets });
+        Ok(())
+    }
+
+    fn on_response(&self, response: Response) {
+        let opens = self
+            .pending_opens
+            .lock()
+            .expect("pending opens lock")
+            .remove(&response.id);
+        if let Some(uri) = opens {
+            let applied = response
+                .result
+                .and_then(|result| ApplyWorkspaceEditResponse::deserialize(result).ok())
+                .is_some_and(|result| result.applied);
+            if applied {
+                self.show_document(uri);
+            } else {
+                self.show_message(
+                    MessageType::ERROR,
+                    format!("IWE: {} was not created", uri.as_str()),
+                );
+            }
+            return;
+        }
+
+        let Some(pending) = self
+            .pending_moves
+            .lock()
+            .expect("pending moves lock")
+            .remove(&response.id)
+        else {
+            return;
+        };
+        let Some(choice) = response
+            .result
+            .and_then(|result| Option::<MessageActionItem>::deserialize(result).ok())
+            .flatten()
+        else {
+            return;
+        };
+        let Some((_, target)) = pending
+            .targets
+            .iter()
+            .find(|(title, _)| *title == choice.title)
+        else {
+            return;
+        };
+
+        match self.server(Some(&pending.uri)).handle_move_section(
+            &pending.uri,
+            pending.line,
+            target,
+        ) {
+            Ok(edit) => {
+                self.apply_edit("Move section", edit);
+            }
+            Err(message) => self.show_message(MessageType::ERROR, format!("IWE: {}", message)),
+        }
+    }
+
+    /// Asks the client to apply `edit`; its answer arrives in `on_response` under the returned id.
+    fn apply_edit(&self, label: &str, edit: WorkspaceEdit) -> RequestId {
+        let id: RequestId = Uuid::new_v4().to_string().into();
+        self.send(Message::Request(Request {
+            id: id.clone(),
+            method: "workspace/applyEdit".to_string(),
+            params: to_value(ApplyWorkspaceEditParams {
+                label: Some(label.to_string()),
+                edit,
+            })
+            .unwrap(),
+        }));
+        id
+    }
+
+    /// Symbols of every library, in library order.
+    fn workspace_symbols(&self, params: WorkspaceSymbolParams) -> WorkspaceSymbolResponse {
+        WorkspaceSymbolResponse::Flat(
+            self.libraries
+                .iter()
+                .flat_map(
+                    |library| match library.server.handle_workspace_symbols(params.clone()) {
+                        WorkspaceSymbolResponse::Flat(symbols) => symbols,
+                        WorkspaceSymbolResponse::Nested(_) => vec![],
+                    },
+                )
+                .collect(),
+        )
+    }
+
+    fn show_document(&self, uri: Uri) {
+        self.send(Message::Request(Request {
+            id: Uuid::new_v4().to_string().into(),
+            method: "window/showDocument".to_string(),
+            params: to_value(ShowDocumentParams {
+                uri,
+                external: Some(false),
+                take_focus: Some(true),
+                selection: None,
+            })
+            .unwrap(),
+        }));
+    }
+
     fn on_request(&self, request: Request) -> bool {
         if request.method == "shutdown" {
             self.respond(Response {
@@ -249,21 +740,22 @@ impl Router {
             return true;
         }
 
+        let server = self.server(params_uri(&request.params).as_ref());
         let response = match request.method.as_str() {
             "textDocument/inlayHint" => InlayHintParams::deserialize(request.params)
                 .map(|params| {
                     self.inlay_hints_used.store(true, Ordering::Relaxed);
-                    self.server.handle_inlay_hints(params)
+                    server.handle_inlay_hints(params)
                 })
                 .map(|response| to_value(response).unwrap()),
             "textDocument/inlineValues" => InlineValueParams::deserialize(request.params)
-                .map(|params| self.server.handle_inline_values(params))
+                .map(|params| server.handle_inline_values(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/documentSymbol" => DocumentSymbolParams::deserialize(request.params)
-                .map(|params| self.server.handle_document_symbols(params))
+                .map(|params| server.handle_document_symbols(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/definition" => match GotoDefinitionParams::deserialize(request.params) {
-                Ok(params) => match self.server.handle_goto_definition(params) {
+                Ok(params) => match server.handle_goto_definition(params) {
                     DefinitionResult::Internal(response) => Ok(to_value(response).unwrap()),
                     DefinitionResult::External(url) => {
                         if let Ok(uri) = url.parse() {
@@ -286,34 +778,68 @@ impl Router {
                 Err(e) => Err(e),
             },
             "workspace/symbol" => WorkspaceSymbolParams::deserialize(request.params)
-                .map(|params| self.server.handle_workspace_symbols(params))
+                .map(|params| self.workspace_symbols(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/hover" => HoverParams::deserialize(request.params)
-                .map(|params| self.server.handle_hover(params))
+                .map(|params| server.handle_hover(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/completion" => CompletionParams::deserialize(request.params)
-                .map(|params| self.server.handle_completion(params))
+                .map(|params| server.handle_completion(params))
                 .map(|response| to_value(response).unwrap()),
             "completionItem/resolve" => CompletionItem::deserialize(request.params)
-                .map(|params| self.server.resolve_completion(params))
+                .map(|params| server.resolve_completion(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/codeAction" => CodeActionParams::deserialize(request.params)
-                .map(|params| self.server.handle_code_action(&params))
+                .map(|params| server.handle_code_action(&params))
                 .map(|response| to_value(response).unwrap()),
             "codeAction/resolve" => CodeAction::deserialize(request.params)
-                .map(|params| self.server.handle_code_action_resolve(&params))
+                .map(|params| server.handle_code_action_resolve(&params))
+                .map(|response| to_value(response).unwrap()),
+            "textDocument/codeLens" => CodeLensParams::deserialize(request.params)
+                .map(|params| server.handle_code_lens(params))
+                .map(|response| to_value(response).unwrap()),
+            "codeLens/resolve" => CodeLens::deserialize(request.params)
+                .map(|params| server.handle_code_lens_resolve(&params))
+                .map(|response| to_value(response).unwrap()),
+            "textDocument/prepareCallHierarchy" => {
+                CallHierarchyPrepareParams::deserialize(request.params)
+                    .map(|params| server.handle_prepare_call_hierarchy(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "callHierarchy/incomingCalls" => {
+                CallHierarchyIncomingCallsParams::deserialize(request.params)
+                    .map(|params| server.handle_call_hierarchy_incoming_calls(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "callHierarchy/outgoingCalls" => {
+                CallHierarchyOutgoingCallsParams::deserialize(request.params)
+                    .map(|params| server.handle_call_hierarchy_outgoing_calls(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "textDocument/prepareTypeHierarchy" => {
+                TypeHierarchyPrepareParams::deserialize(request.params)
+                    .map(|params| server.handle_prepare_type_hierarchy(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "typeHierarchy/supertypes" => {
+                TypeHierarchySupertypesParams::deserialize(request.params)
+                    .map(|params| server.handle_type_hierarchy_supertypes(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "typeHierarchy/subtypes" => TypeHierarchySubtypesParams::deserialize(request.params)
+                .map(|params| server.handle_type_hierarchy_subtypes(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/formatting" => DocumentFormattingParams::deserialize(request.params)
-                .map(|params| self.server.handle_document_formatting(params))
+                .map(|params| server.handle_document_formatting(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/references" => ReferenceParams::deserialize(request.params)
-                .map(|params| self.server.handle_references(params))
+                .map(|params| server.handle_references(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/prepareRename" => TextDocumentPositionParams::deserialize(request.params)
-                .map(|params| self.server.handle_prepare_rename(params))
+                .map(|params| server.handle_prepare_rename(params))
                 .map(|response| to_value(response).unwrap()),
             "textDocument/rename" => match RenameParams::deserialize(request.params) {
-                Ok(params) => match self.server.handle_rename(params) {
+                Ok(params) => match server.handle_rename(params) {
                     Ok(response) => Ok(to_value(response).unwrap()),
                     Err(err) => {
                         self.respond(Response {
@@ -326,9 +852,47 @@ impl Router {
                 },
                 Err(e) => Err(e),
             },
+            "workspace/executeCommand" => match ExecuteCommandParams::deserialize(request.params) {
+                Ok(params) => match self.execute_command(params) {
+                    Ok(result) => Ok(result),
+                    Err(message) => {
+                        self.respond(Response::new_err(
+                            request.id.clone(),
+                            ErrorCode::InvalidParams as i32,
+                            message,
+                        ));
+                        return false;
+                    }
+                },
+                Err(e) => Err(e),
+            },
+            "textDocument/documentLink" => DocumentLinkParams::deserialize(request.params)
+                .map(|params| server.handle_document_link(params))
+                .map(|response| to_value(response).unwrap()),
+            "textDocument/rangeFormatting" => {
+                DocumentRangeFormattingParams::deserialize(request.params)
+                    .map(|params| server.handle_range_formatting(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "textDocument/onTypeFormatting" => {
+                DocumentOnTypeFormattingParams::deserialize(request.params)
+                    .map(|params| server.handle_on_type_formatting(params))
+                    .map(|response| to_value(response).unwrap())
+            }
+            "textDocument/selectionRange" => SelectionRangeParams::deserialize(request.params)
+                .map(|params| server.handle_selection_range(params))
+                .map(|response| to_value(response).unwrap()),
             "textDocument/foldingRange" => FoldingRangeParams::deserialize(request.params)
-                .map(|params| self.server.handle_folding_range(params))
+                .map(|params| server.handle_folding_range(params))
                 .map(|response| to_value(response).unwrap()),
+            "textDocument/semanticTokens/full" => SemanticTokensParams::deserialize(request.params)
+                .map(|params| server.handle_semantic_tokens_full(params))
+                .map(|response| to_value(response).unwrap()),
+            "textDocument/semanticTokens/full/delta" => {
+                SemanticTokensDeltaParams::deserialize(request.params)
+                    .map(|params| server.handle_semantic_tokens_full_delta(params))
+                    .map(|response| to_value(response).unwrap())
+            }
             default => {
                 self.respond(Response::new_err(
                     request.id.clone(),
@@ -366,3 +930,15 @@ impl Router {
         false
     }
 }
+
+/// Document a request is about: its text document, hierarchy item, or the document recorded in
+/// the data of a code action or lens to resolve.
+fn params_uri(params: &serde_json::Value) -> Option<Uri> {
+    ["/textDocument/uri", "/item/uri", "/data/uri"]
+        .iter()
+        .find_map(|pointer| params.pointer(pointer)?.as_str()?.parse().ok())
+}
+
+fn folder_path(uri: &Uri) -> Option<PathBuf> {
+    Url::parse(uri.as_str()).ok()?.to_file_path().ok()
+}
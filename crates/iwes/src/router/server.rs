@@ -1,8 +1,14 @@
-use actions::{all_action_types, ActionContext, ActionProvider};
-use diwe::config::{Command, Configuration, FormatOptions, MarkdownOptions};
+use actions::{
+    all_action_types, lint_fix_action, to_lsp_diagnostic, ActionContext, ActionProvider, LintCache,
+};
+use chrono::{DateTime, Local};
+use diwe::config::{Command, Configuration, FormatOptions, LinkType, MarkdownOptions};
 use diwe::fs::read_md_file;
+use diwe::journal::{resolve, Journal};
+use diwe::lint::{lint_document, LintRules};
 use itertools::Itertools;
 use liwe::model::node::Node;
+use liwe::query::live::refresh;
 use liwe::{
     graph::{DatabaseContext, Graph, GraphContext},
     model::{is_ref_url, node::NodePointer, reference::ReferenceType, tree::Tree, Key, NodeId},
@@ -10,10 +16,15 @@ use liwe::{
 use lsp_server::ResponseError;
 use lsp_types::*;
 use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use super::{LspClient, ServerConfig};
 
+pub const OPEN_COMMAND: &str = "iwe.open";
+pub const JOURNAL_COMMAND: &str = "iwe.journal";
+pub const MOVE_SECTION_KIND: &str = "refactor.move.section";
+
 pub enum DefinitionResult {
     Internal(GotoDefinitionResponse),
     External(String),
@@ -21,28 +32,43 @@ pub enum DefinitionResult {
 
 use self::base_path::BasePath;
 use self::extensions::*;
+use self::mounts::Mounts;
+use self::move_section::MOVE_SECTION_COMMAND;
 use self::search::SearchIndex;
+use self::semantic_tokens::SemanticTokensCache;
 
 pub mod actions;
 pub mod base_path;
+pub mod code_lens;
+pub mod document_links;
 pub mod extensions;
+pub mod hierarchy;
+pub mod mounts;
+pub mod move_section;
+pub mod on_type_formatting;
 pub mod query;
+pub mod range_formatting;
 pub mod search;
+pub mod selection_range;
+pub mod semantic_tokens;
 
 pub struct Server {
     base_path: BasePath,
-    graph: Graph,
+    graph: Arc<Graph>,
     lsp_client: LspClient,
     configuration: Configuration,
     search_index: SearchIndex,
     search_index_dirty: bool,
     override_now: Option<SystemTime>,
     open_documents: HashSet<Key>,
+    semantic_tokens: SemanticTokensCache,
+    lint_cache: LintCache,
+    mounts: Mounts,
 }
 
 impl Server {
     pub fn new(config: ServerConfig) -> Server {
-        let graph = Graph::from_state(
+        let mut graph = Graph::from_state(
             &config.state,
             config.sequential_ids.unwrap_or(false),
             config.configuration.format_options(),
@@ -52,19 +78,65 @@ impl Server {
                 .frontmatter_document_title
                 .clone(),
         );
+        let (fields, errors) = config.configuration.computed_fields();
+        for error in errors {
+            log::error!("ignoring {}", error);
+        }
+        graph.set_computed_fields(fields);
+        let base_path = BasePath::from_path(&config.base_path, config.configuration.format);
         Server {
-            base_path: BasePath::from_path(&config.base_path, config.configuration.format),
-            graph,
+            mounts: Mounts::new(&base_path, &config.configuration.library.mounts),
+            base_path,
+            graph: Arc::new(graph),
             lsp_client: config.lsp_client,
             configuration: config.configuration,
             search_index: SearchIndex::new(),
             search_index_dirty: true,
             override_now: config.override_now,
             open_documents: HashSet::new(),
+            semantic_tokens: SemanticTokensCache::default(),
+            lint_cache: LintCache::default(),
+        }
+    }
+    pub fn base_path(&self) -> &BasePath {
+        &self.base_path
+    }
+
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    /// Applies a changed configuration of the library. Documents are parsed again when the
+    /// format options or the title field change, and the search index is rebuilt.
+    pub fn reconfigure(&mut self, configuration: Configuration) {
+        if configuration.format_options() != self.configuration.format_options()
+            || configuration.library.frontmatter_document_title
+                != self.configuration.library.frontmatter_document_title
+        {
+            self.graph = Arc::new(self.graph.reparse(
+                configuration.format_options(),
+                configuration.library.frontmatter_document_title.clone(),
+            ));
+            self.semantic_tokens = SemanticTokensCache::default();
         }
+        let (fields, errors) = configuration.computed_fields();
+        for error in errors {
+            log::error!("ignoring {}", error);
+        }
+        self.graph_mut().set_computed_fields(fields);
+        self.mounts = Mounts::new(&self.base_path, &configuration.library.mounts);
+        self.configuration = configuration;
+        self.search_index_dirty = true;
+        self.lint_cache.clear();
     }
+
     pub fn graph(&self) -> impl DatabaseContext + '_ {
-        &self.graph
+        &*self.graph
+    }
+
+    /// The graph to change, copied first only if a template engine still holds it.
+    fn graph_mut(&mut self) -> &mut Graph {
+        Arc::make_mut(&mut self.graph)
     }
 
     pub fn search_index_is_dirty(&self) -> bool {
@@ -91,8 +163,9 @@ impl Server {
         if self.graph.get_document(&key).as_deref() == Some(content.as_str()) {
             return;
         }
-        self.graph.update_document(key, content);
+        self.graph_mut().update_document(key, content);
         self.search_index_dirty = true;
+        self.lint_cache.clear();
     }
 
     pub fn apply_external_removal(&mut self, key: Key) {
@@ -102,8 +175,9 @@ impl Server {
         if self.graph.get_document(&key).is_none() {
             return;
         }
-        self.graph.remove_document(key);
+        self.graph_mut().remove_document(key);
         self.search_index_dirty = true;
+        self.lint_cache.clear();
     }
 
     pub fn handle_did_open_text_document(&mut self, params: DidOpenTextDocumentParams) {
@@ -114,8 +188,10 @@ impl Server {
         if self.graph.get_document(&key).as_deref() == Some(params.text_document.text.as_str()) {
             return;
         }
-        self.graph.update_document(key, params.text_document.text);
+        self.graph_mut()
+            .update_document(key, params.text_document.text);
         self.search_index_dirty = true;
+        self.lint_cache.clear();
     }
 
     pub fn handle_did_close_text_document(&mut self, params: DidCloseTextDocumentParams) {
@@ -123,6 +199,7 @@ impl Server {
             return;
         };
         self.open_documents.remove(&key);
+        self.semantic_tokens.forget(&key);
         let disk_content = self
             .base_path
             .key_to_path(&key)
@@ -132,16 +209,17 @@ impl Server {
                 if self.graph.get_document(&key).as_deref() == Some(content.as_str()) {
                     return;
                 }
-                self.graph.update_document(key, content);
+                self.graph_mut().update_document(key, content);
             }
             None => {
                 if self.graph.get_document(&key).is_none() {
                     return;
                 }
-                self.graph.remove_document(key);
+                self.graph_mut().remove_document(key);
             }
         }
         self.search_index_dirty = true;
+        self.lint_cache.clear();
     }
 
     fn resolve_link_key(&self, url: &str, relative_to: &str, reference_type: ReferenceType) -> Key {
@@ -172,6 +250,16 @@ impl Server {
             .parser(&key)
             .and_then(|parser| parser.url_at(position.to_model()))?;
 
+        if let Some(preview) = document_links::preview(&self.graph, &self.base_path, &key, &url) {
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: preview,
+                }),
+                range: None,
+            });
+        }
+
         let url = url.split('#').next().unwrap_or(url.as_str());
         let url = url.split('?').next().unwrap_or(url);
 
@@ -198,11 +286,10 @@ impl Server {
 
     pub fn handle_did_save_text_document(&mut self, params: DidSaveTextDocumentParams) {
         if let Some(text) = params.text {
-            self.graph.update_document(
-                self.base_path.url_to_key(&params.text_document.uri.clone()),
-                text,
-            );
+            let key = self.base_path.url_to_key(&params.text_document.uri.clone());
+            self.graph_mut().update_document(key, text);
             self.search_index_dirty = true;
+            self.lint_cache.clear();
         }
     }
 
@@ -210,11 +297,10 @@ impl Server {
         let Some(content) = params.content_changes.first() else {
             return;
         };
-        self.graph.update_document(
-            self.base_path.url_to_key(&params.text_document.uri.clone()),
-            content.text.clone(),
-        );
+        let key = self.base_path.url_to_key(&params.text_document.uri.clone());
+        self.graph_mut().update_document(key, content.text.clone());
         self.search_index_dirty = true;
+        self.lint_cache.clear();
     }
 
     pub fn handle_did_change_watched_files(&mut self, params: DidChangeWatchedFilesParams) {
@@ -244,12 +330,12 @@ impl Server {
 
         let key_index = self.graph.key_index();
 
-        query::all_keys(&self.graph)
+        query::all_keys(&*self.graph)
             .iter()
             .map(|m| {
                 m.key.to_completion(
                     &current_key.parent(),
-                    &self.graph,
+                    &*self.graph,
                     &self.configuration.completion,
                     &self.base_path,
                     completion_context,
@@ -362,12 +448,19 @@ impl Server {
             return DefinitionResult::External(url);
         }
 
-        let location_url = match self.ref_type_at(&key, position.to_model()) {
+        let ref_type = self.ref_type_at(&key, position.to_model());
+        let path = url.split('#').next().unwrap_or(url.as_str());
+        let target = self.resolve_link_key(path, &relative_to, ref_type);
+        let location_url = match self.graph.get_document(&target) {
+            None => self.mounts.url(&target),
+            Some(_) => None,
+        }
+        .unwrap_or_else(|| match ref_type {
             ReferenceType::Regular => self.base_path.resolve_relative_url(&url, &relative_to),
             ReferenceType::WikiLink | ReferenceType::WikiLinkPiped => self
                 .base_path
                 .key_to_url(&self.graph.key_index().resolve_wiki(&url)),
-        };
+        });
 
         DefinitionResult::Internal(GotoDefinitionResponse::Scalar(Location::new(
             location_url,
@@ -382,17 +475,61 @@ impl Server {
             return Vec::new();
         }
 
-        let mut patch = self.graph.new_patch();
-        patch
-            .build_key(&key)
-            .insert_from_iter((&self.graph).collect(&key).iter());
+        let link_type = self
+            .configuration
+            .completion
+            .link_format
+            .clone()
+            .unwrap_or(LinkType::Markdown);
+        let refreshed = refresh(&self.graph, &key, &link_type);
+        for error in &refreshed.errors {
+            log::debug!("query block in {}: {}", key, error);
+        }
+        let new_text = match refreshed.content {
+            Some(refreshed) => refreshed,
+            None => {
+                let mut patch = self.graph.new_patch();
+                patch
+                    .build_key(&key)
+                    .insert_from_iter((&*self.graph).collect(&key).iter());
+                patch.export_key(&key).unwrap()
+            }
+        };
 
         vec![TextEdit {
             range: Range::new(Position::new(0, 0), Position::new(u32::MAX, 0)),
-            new_text: patch.export_key(&key).unwrap(),
+            new_text,
         }]
     }
 
+    pub fn handle_range_formatting(&self, params: DocumentRangeFormattingParams) -> Vec<TextEdit> {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        range_formatting::format_range(&self.graph, &key, params.range)
+    }
+
+    pub fn handle_on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Vec<TextEdit> {
+        let key = params
+            .text_document_position
+            .text_document
+            .uri
+            .to_key(&self.base_path);
+        if params.ch != "\n" {
+            return vec![];
+        }
+        let Some(text) = self.graph.get_document(&key) else {
+            return vec![];
+        };
+
+        on_type_formatting::on_newline(
+            &text,
+            params.text_document_position.position,
+            self.graph.format_options().formatting(),
+        )
+    }
+
     pub fn handle_inlay_hints(&self, params: InlayHintParams) -> Vec<InlayHint> {
         let key = params.text_document.uri.to_key(&self.base_path);
 
@@ -413,14 +550,14 @@ impl Server {
                     .map(|range| (id, range.start))
             })
             .flat_map(|(id, line)| {
-                (&self.graph)
+                (&*self.graph)
                     .node(id)
                     .ref_key()
                     .map(|key| self.graph.get_inclusion_edges_to(&key))
                     .map(|refs| {
                         refs.into_iter()
                             .filter_map(|ref_id| {
-                                let ref_key = (&self.graph).get_node_key(ref_id)?;
+                                let ref_key = (&*self.graph).get_node_key(ref_id)?;
                                 if ref_key.eq(key) {
                                     None
                                 } else {
@@ -429,7 +566,7 @@ impl Server {
                             })
                             .sorted_by_key(|(_, ref_key)| ref_key.clone())
                             .unique_by(|(_, ref_key)| ref_key.clone())
-                            .flat_map(|(id, _)| (&self.graph).get_container_document_ref_text(id))
+                            .flat_map(|(id, _)| (&*self.graph).get_container_document_ref_text(id))
                             .map(|s| format!("↖{}", s))
                             .join(" ")
                     })
@@ -444,7 +581,7 @@ impl Server {
         self.graph
             .get_inclusion_edges_to(key)
             .iter()
-            .flat_map(|id| (&self.graph).get_container_document_ref_text(*id))
+            .flat_map(|id| (&*self.graph).get_container_document_ref_text(*id))
             .sorted()
             .dedup()
             .map(|text| format!("↖{}", text).to_hint_at(0))
@@ -499,7 +636,7 @@ impl Server {
             })
             .map(|p| p.drop_first())
             .filter(|p| p.ids().len() < 4)
-            .filter_map(|p| p.to_nested_symbol(&self.graph, &self.base_path))
+            .filter_map(|p| p.to_nested_symbol(&*self.graph, &self.base_path))
             .filter(|p| !p.name.is_empty())
             .collect_vec()
     }
@@ -559,7 +696,7 @@ impl Server {
                     let mut patch = self.graph.new_patch();
 
                     patch.build_key(&new_key).insert_from_iter(
-                        (&self.graph)
+                        (&*self.graph)
                             .collect(&key)
                             .change_key(&key, &new_key)
                             .iter(),
@@ -567,7 +704,7 @@ impl Server {
 
                     affected_keys.iter().for_each(|affected_key| {
                         patch.build_key(affected_key).insert_from_iter(
-                            (&self.graph)
+                            (&*self.graph)
                                 .collect(affected_key)
                                 .change_key(&key, &new_key)
                                 .iter(),
@@ -635,7 +772,7 @@ impl Server {
                     .iter()
                     .filter(|_| params.context.include_declaration),
             )
-            .map(|id| (id, (&self.graph).node(*id).node_key()))
+            .map(|id| (id, (&*self.graph).node(*id).node_key()))
             .dedup()
             .filter(|(_, backlink_key)| backlink_key.ne(&key))
             .map(|(id, key)| {
@@ -663,6 +800,25 @@ impl Server {
             .collect_vec()
     }
 
+    /// Lint findings on the document at `uri`, published as diagnostics so that quick fixes can
+    /// name the one they resolve. `None` outside the library or with an invalid `[lint]` table.
+    pub fn lint_report(&self, uri: &Uri) -> Option<PublishDiagnosticsParams> {
+        let key = self.base_path.maybe_url_to_key(uri)?;
+        let rules = LintRules::compile(&self.configuration.lint).ok()?;
+        let text = self.graph.get_document(&key).unwrap_or_default();
+        let diagnostics = self
+            .lint_cache
+            .get_or_insert_with(&key, || lint_document(&self.graph, &rules, &key))
+            .iter()
+            .map(|diagnostic| to_lsp_diagnostic(diagnostic, &text))
+            .collect();
+        Some(PublishDiagnosticsParams::new(
+            uri.clone(),
+            diagnostics,
+            None,
+        ))
+    }
+
     pub fn handle_code_action(&self, params: &CodeActionParams) -> CodeActionResponse {
         let base_path: &BasePath = &self.base_path;
 
@@ -688,12 +844,42 @@ impl Server {
             },
         };
 
-        all_action_types(&self.configuration)
+        let mut actions = all_action_types(&self.configuration)
             .into_iter()
             .filter(|action_provider| params.only_includes(&action_provider.action_kind()))
             .flat_map(|action_type| action_type.action(key.clone(), selection.clone(), self))
-            .map(|action| action.to_code_action())
-            .collect_vec()
+            .map(|action| action.to_code_action(&params.text_document.uri))
+            .collect_vec();
+
+        if params.only_includes(&CodeActionKind::QUICKFIX) {
+            actions.extend(
+                lint_fix_action(&self.configuration)
+                    .actions(&key, &params.context.diagnostics, &self)
+                    .into_iter()
+                    .map(CodeActionOrCommand::CodeAction),
+            );
+        }
+
+        let line = params.range.start.line;
+        if params.only_includes(&CodeActionKind::new(MOVE_SECTION_KIND))
+            && move_section::section_at(&self.graph, &key, line as usize).is_some()
+        {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Move section to…".to_string(),
+                kind: Some(CodeActionKind::new(MOVE_SECTION_KIND)),
+                command: Some(lsp_types::Command {
+                    title: "Move section to…".to_string(),
+                    command: MOVE_SECTION_COMMAND.to_string(),
+                    arguments: Some(vec![
+                        serde_json::json!(params.text_document.uri.to_string()),
+                        serde_json::json!(line),
+                    ]),
+                }),
+                ..Default::default()
+            }));
+        }
+
+        actions
     }
 
     pub fn handle_code_action_resolve(&self, code_action: &CodeAction) -> CodeAction {
@@ -707,6 +893,15 @@ impl Server {
             return code_action.clone();
         };
 
+        if code_action.kind == Some(CodeActionKind::QUICKFIX) {
+            let diagnostics = code_action.diagnostics.clone().unwrap_or_default();
+            let mut action = code_action.clone();
+            action.edit = lint_fix_action(&self.configuration)
+                .changes(&key, &diagnostics, &self)
+                .map(|changes| self.workspace_edit(changes));
+            return action;
+        }
+
         let Some(range) = data.get("range") else {
             return code_action.clone();
         };
@@ -746,24 +941,207 @@ impl Server {
             return code_action.clone();
         };
 
-        let Some(changes) = action_provider.changes(key, selection, self) else {
+        let Some(changes) = action_provider.changes(key.clone(), selection, self) else {
             return code_action.clone();
         };
 
-        let lsp_changes = actions::into_lsp_changes(changes);
+        let opens = action_provider.opens(key, self);
 
         let mut action = code_action.clone();
-        action.edit = Some(WorkspaceEdit {
+        action.edit = Some(self.workspace_edit(changes));
+        action.command = opens.map(|target| lsp_types::Command {
+            title: code_action.title.clone(),
+            command: OPEN_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::json!(base_path
+                .key_to_url(&target)
+                .to_string())]),
+        });
+
+        action
+    }
+
+    /// `iwe.journal`: the journal note `when` names, and the edit that creates it when missing.
+    pub fn handle_journal_command(
+        &self,
+        when: &str,
+    ) -> Result<(Option<WorkspaceEdit>, Uri), String> {
+        let journal = Journal::new(&self.configuration)?;
+        let today: DateTime<Local> = self.now().into();
+        let (period, date) = resolve(when, today.date_naive())?;
+        let (key, changes) = journal.open(&self.graph, period, date)?;
+
+        let edit = (!changes.is_empty()).then(|| self.workspace_edit(changes));
+        Ok((edit, self.base_path.key_to_url(&key)))
+    }
+
+    /// `iwe.moveSection` without a target: the documents the section on `line` can move to,
+    /// with their titles.
+    pub fn move_section_targets(
+        &self,
+        uri: &Uri,
+        line: usize,
+    ) -> Result<Vec<(Key, String)>, String> {
+        let key = uri.to_key(&self.base_path);
+        move_section::section_at(&self.graph, &key, line)
+            .ok_or_else(|| format!("{}: no section on line {}", MOVE_SECTION_COMMAND, line))?;
+
+        Ok(move_section::targets(&self.graph, &key)
+            .into_iter()
+            .map(|target| {
+                let title = self
+                    .graph
+                    .get_key_title(&target)
+                    .filter(|title| !title.is_empty())
+                    .unwrap_or_else(|| target.to_string());
+                (target, title)
+            })
+            .collect())
+    }
+
+    /// `iwe.moveSection`: the edit moving the section on `line` to the end of `target`.
+    pub fn handle_move_section(
+        &self,
+        uri: &Uri,
+        line: usize,
+        target: &Key,
+    ) -> Result<WorkspaceEdit, String> {
+        let key = uri.to_key(&self.base_path);
+        let section_id = move_section::section_at(&self.graph, &key, line)
+            .ok_or_else(|| format!("{}: no section on line {}", MOVE_SECTION_COMMAND, line))?;
+        let changes = move_section::move_section(&self.graph, &key, section_id, target)?;
+        Ok(self.workspace_edit(changes))
+    }
+
+    fn workspace_edit(&self, changes: liwe::operations::Changes) -> WorkspaceEdit {
+        WorkspaceEdit {
             document_changes: Some(DocumentChanges::Operations(
-                lsp_changes
+                actions::into_lsp_changes(changes)
                     .iter()
-                    .map(|change| change.to_document_change(base_path))
+                    .map(|change| change.to_document_change(&self.base_path))
                     .collect_vec(),
             )),
             ..Default::default()
-        });
+        }
+    }
 
-        action
+    pub fn handle_document_link(&self, params: DocumentLinkParams) -> Vec<DocumentLink> {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        document_links::document_links(&self.graph, &self.base_path, &self.mounts, &key)
+    }
+
+    pub fn handle_code_lens(&self, params: CodeLensParams) -> Vec<CodeLens> {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        code_lens::lenses(&self.graph, &params.text_document.uri, &key)
+    }
+
+    pub fn handle_code_lens_resolve(&self, code_lens: &CodeLens) -> CodeLens {
+        code_lens::resolve(&self.graph, &self.base_path, code_lens)
+    }
+
+    /// The document linked under the cursor, or the current document.
+    fn key_at(&self, params: &TextDocumentPositionParams) -> Key {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        let position = params.position.to_model();
+
+        self.graph()
+            .parser(&key)
+            .and_then(|parser| parser.link_at(position))
+            .and_then(|link| link.url().map(|url| (url, link.ref_type())))
+            .filter(|(url, _)| is_ref_url(url))
+            .map(|(url, ref_type)| {
+                self.resolve_link_key(
+                    &url,
+                    &key.parent(),
+                    ref_type.unwrap_or(ReferenceType::Regular),
+                )
+            })
+            .unwrap_or(key)
+    }
+
+    pub fn handle_prepare_call_hierarchy(
+        &self,
+        params: CallHierarchyPrepareParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let key = self.key_at(&params.text_document_position_params);
+        hierarchy::call_item(&self.graph, &self.base_path, &key).map(|item| vec![item])
+    }
+
+    pub fn handle_call_hierarchy_incoming_calls(
+        &self,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let key = hierarchy::item_key(&params.item.data)?;
+        Some(hierarchy::incoming_calls(
+            &self.graph,
+            &self.base_path,
+            &key,
+        ))
+    }
+
+    pub fn handle_call_hierarchy_outgoing_calls(
+        &self,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let key = hierarchy::item_key(&params.item.data)?;
+        Some(hierarchy::outgoing_calls(
+            &self.graph,
+            &self.base_path,
+            &key,
+        ))
+    }
+
+    pub fn handle_prepare_type_hierarchy(
+        &self,
+        params: TypeHierarchyPrepareParams,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        let key = self.key_at(&params.text_document_position_params);
+        hierarchy::type_item(&self.graph, &self.base_path, &key).map(|item| vec![item])
+    }
+
+    pub fn handle_type_hierarchy_supertypes(
+        &self,
+        params: TypeHierarchySupertypesParams,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        let key = hierarchy::item_key(&params.item.data)?;
+        Some(hierarchy::supertypes(&self.graph, &self.base_path, &key))
+    }
+
+    pub fn handle_type_hierarchy_subtypes(
+        &self,
+        params: TypeHierarchySubtypesParams,
+    ) -> Option<Vec<TypeHierarchyItem>> {
+        let key = hierarchy::item_key(&params.item.data)?;
+        Some(hierarchy::subtypes(&self.graph, &self.base_path, &key))
+    }
+
+    pub fn handle_semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Option<SemanticTokensResult> {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        let tokens = semantic_tokens::document_tokens(&self.graph, &key);
+
+        Some(SemanticTokensResult::Tokens(
+            self.semantic_tokens.full(key, tokens),
+        ))
+    }
+
+    pub fn handle_semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> Option<SemanticTokensFullDeltaResult> {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        let tokens = semantic_tokens::document_tokens(&self.graph, &key);
+
+        Some(
+            self.semantic_tokens
+                .delta(key, &params.previous_result_id, tokens),
+        )
+    }
+
+    pub fn handle_selection_range(&self, params: SelectionRangeParams) -> Vec<SelectionRange> {
+        let key = params.text_document.uri.to_key(&self.base_path);
+        selection_range::selection_ranges(&self.graph, &key, &params.positions)
     }
 
     pub fn handle_folding_range(&self, params: FoldingRangeParams) -> Vec<FoldingRange> {
@@ -867,19 +1245,19 @@ impl Server {
 
 impl ActionContext for &Server {
     fn key_of(&self, node_id: NodeId) -> Key {
-        (&self.graph).node(node_id).node_key()
+        (&*self.graph).node(node_id).node_key()
     }
 
     fn collect(&self, key: &Key) -> Tree {
-        (&self.graph).collect(key)
+        (&*self.graph).collect(key)
     }
 
     fn squash(&self, key: &Key, depth: u8) -> Tree {
-        (&self.graph).squash(key, depth)
+        (&*self.graph).squash(key, depth)
     }
 
     fn random_key(&self, parent: &str) -> Key {
-        (&self.graph).random_key(parent)
+        (&*self.graph).random_key(parent)
     }
 
     fn markdown_options(&self) -> &MarkdownOptions {
@@ -894,7 +1272,7 @@ impl ActionContext for &Server {
         self.configuration.commands.get(name)
     }
 
-    fn graph(&self) -> &Graph {
+    fn graph(&self) -> &Arc<Graph> {
         &self.graph
     }
 
@@ -919,15 +1297,15 @@ impl ActionContext for &Server {
     }
 
     fn unique_ids(&self, parent: &str, number: usize) -> Vec<String> {
-        (&self.graph).unique_ids(parent, number)
+        (&*self.graph).unique_ids(parent, number)
     }
 
     fn random_keys(&self, parent: &str, number: usize) -> Vec<Key> {
-        (&self.graph).random_keys(parent, number)
+        (&*self.graph).random_keys(parent, number)
     }
 
     fn get_node_id_at(&self, key: &Key, line: usize) -> Option<NodeId> {
-        (&self.graph).get_node_id_at(key, line)
+        (&*self.graph).get_node_id_at(key, line)
     }
 
     fn get_document_markdown(&self, key: &Key) -> Option<String> {
@@ -935,7 +1313,7 @@ impl ActionContext for &Server {
     }
 
     fn get_link_key_at(&self, key: &Key, line: usize, character: usize) -> Option<Key> {
-        let parser = self.graph().parser(key)?;
+        let parser = (&*self.graph).parser(key)?;
         let position = liwe::model::Position { line, character };
         let url = parser.url_at(position)?;
 
@@ -950,7 +1328,7 @@ impl ActionContext for &Server {
     }
 
     fn get_link_text_at(&self, key: &Key, line: usize, character: usize) -> Option<String> {
-        let parser = self.graph().parser(key)?;
+        let parser = (&*self.graph).parser(key)?;
         let position = liwe::model::Position { line, character };
         let link = parser.link_at(position)?;
         Some(link.to_plain_text())
@@ -1,17 +1,19 @@
 use std::collections::HashMap;
 
 use diwe::config::{
-    ActionDefinition, Command, Configuration, FormatOptions, MarkdownOptions,
+    ActionDefinition, Attach, Command, Configuration, FormatOptions, LinkType, MarkdownOptions,
     DEFAULT_KEY_DATE_FORMAT,
 };
+use diwe::journal::Journal;
+use diwe::template::TemplateEngine;
 use liwe::graph::Graph;
 use liwe::locale::get_locale;
 use liwe::model::tree::Tree;
 use liwe::model::{Key, Markdown, NodeId};
 
-use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand};
+use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand, Uri};
 use once_cell::sync::Lazy;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use super::{BasePath, ChangeExt};
 
@@ -20,10 +22,14 @@ mod delete;
 mod extract;
 mod extract_all;
 mod inline;
+mod journal;
 mod link;
+mod lint_fix;
 mod list;
+mod mentions;
 mod section;
 mod sort;
+mod structure;
 pub mod templates;
 mod transform;
 
@@ -32,10 +38,14 @@ pub use delete::DeleteAction;
 pub use extract::SectionExtract;
 pub use extract_all::ExtractAll;
 pub use inline::InlineAction;
+pub use journal::{JournalAction, JournalStep};
 pub use link::LinkAction;
-pub use list::{ListChangeType, ListToSections};
+pub use lint_fix::{to_lsp_diagnostic, LintCache, LintFixAction};
+pub use list::{ListChangeType, ListToSections, ListToggleTask};
+pub use mentions::MentionsAction;
 pub use section::SectionToList;
 pub use sort::SortAction;
+pub use structure::{StructureAction, StructureEdit};
 pub use transform::TransformBlockAction;
 
 pub trait ActionContext {
@@ -49,7 +59,7 @@ pub trait ActionContext {
     fn markdown_options(&self) -> &MarkdownOptions;
     fn format_options(&self) -> FormatOptions;
     fn get_command(&self, name: &str) -> Option<&Command>;
-    fn graph(&self) -> &Graph;
+    fn graph(&self) -> &Arc<Graph>;
     fn patch(&self) -> Graph;
     fn get_inclusion_edges_to(&self, key: &Key) -> Vec<NodeId>;
     fn get_reference_edges_to(&self, key: &Key) -> Vec<NodeId>;
@@ -103,11 +113,12 @@ pub struct Remove {
 }
 
 impl Action {
-    pub fn to_code_action(&self) -> CodeActionOrCommand {
+    pub fn to_code_action(&self, uri: &Uri) -> CodeActionOrCommand {
         CodeActionOrCommand::CodeAction(CodeAction {
             title: self.title.to_string(),
             kind: Some(identifier_to_action_kind(self.identifier.to_string())),
             data: Some(serde_json::json!({
+                "uri": uri.to_string(),
                 "key": self.key.to_string(),
                 "range": {
                     "start": {
@@ -163,11 +174,17 @@ pub trait ActionProvider {
     fn action_kind(&self) -> CodeActionKind {
         identifier_to_action_kind(self.identifier())
     }
+
+    /// The document the editor should show once the changes are applied.
+    fn opens(&self, _key: Key, _context: impl ActionContext) -> Option<Key> {
+        None
+    }
 }
 
 pub enum ActionEnum {
     ListChangeType(ListChangeType),
     ListToSections(ListToSections),
+    ListToggleTask(ListToggleTask),
     SectionToList(SectionToList),
     SectionExtract(SectionExtract),
     ExtractAll(ExtractAll),
@@ -177,6 +194,9 @@ pub enum ActionEnum {
     InlineAction(InlineAction),
     DeleteAction(DeleteAction),
     LinkAction(LinkAction),
+    MentionsAction(MentionsAction),
+    JournalAction(JournalAction),
+    StructureAction(StructureAction),
 }
 
 impl ActionProvider for ActionEnum {
@@ -184,6 +204,7 @@ impl ActionProvider for ActionEnum {
         match self {
             ActionEnum::ListChangeType(inner) => inner.identifier(),
             ActionEnum::ListToSections(inner) => inner.identifier(),
+            ActionEnum::ListToggleTask(inner) => inner.identifier(),
             ActionEnum::SectionToList(inner) => inner.identifier(),
             ActionEnum::SectionExtract(inner) => inner.identifier(),
             ActionEnum::ExtractAll(inner) => inner.identifier(),
@@ -193,6 +214,9 @@ impl ActionProvider for ActionEnum {
             ActionEnum::InlineAction(inner) => inner.identifier(),
             ActionEnum::DeleteAction(inner) => inner.identifier(),
             ActionEnum::LinkAction(inner) => inner.identifier(),
+            ActionEnum::MentionsAction(inner) => inner.identifier(),
+            ActionEnum::JournalAction(inner) => inner.identifier(),
+            ActionEnum::StructureAction(inner) => inner.identifier(),
         }
     }
 
@@ -205,6 +229,7 @@ impl ActionProvider for ActionEnum {
         match self {
             ActionEnum::ListChangeType(inner) => inner.action(key, selection, context),
             ActionEnum::ListToSections(inner) => inner.action(key, selection, context),
+            ActionEnum::ListToggleTask(inner) => inner.action(key, selection, context),
             ActionEnum::SectionToList(inner) => inner.action(key, selection, context),
             ActionEnum::SectionExtract(inner) => inner.action(key, selection, context),
             ActionEnum::ExtractAll(inner) => inner.action(key, selection, context),
@@ -214,6 +239,9 @@ impl ActionProvider for ActionEnum {
             ActionEnum::InlineAction(inner) => inner.action(key, selection, context),
             ActionEnum::DeleteAction(inner) => inner.action(key, selection, context),
             ActionEnum::LinkAction(inner) => inner.action(key, selection, context),
+            ActionEnum::MentionsAction(inner) => inner.action(key, selection, context),
+            ActionEnum::JournalAction(inner) => inner.action(key, selection, context),
+            ActionEnum::StructureAction(inner) => inner.action(key, selection, context),
         }
     }
 
@@ -226,6 +254,7 @@ impl ActionProvider for ActionEnum {
         match self {
             ActionEnum::ListChangeType(inner) => inner.changes(key, selection, context),
             ActionEnum::ListToSections(inner) => inner.changes(key, selection, context),
+            ActionEnum::ListToggleTask(inner) => inner.changes(key, selection, context),
             ActionEnum::SectionToList(inner) => inner.changes(key, selection, context),
             ActionEnum::SectionExtract(inner) => inner.changes(key, selection, context),
             ActionEnum::ExtractAll(inner) => inner.changes(key, selection, context),
@@ -235,6 +264,16 @@ impl ActionProvider for ActionEnum {
             ActionEnum::InlineAction(inner) => inner.changes(key, selection, context),
             ActionEnum::DeleteAction(inner) => inner.changes(key, selection, context),
             ActionEnum::LinkAction(inner) => inner.changes(key, selection, context),
+            ActionEnum::MentionsAction(inner) => inner.changes(key, selection, context),
+            ActionEnum::JournalAction(inner) => inner.changes(key, selection, context),
+            ActionEnum::StructureAction(inner) => inner.changes(key, selection, context),
+        }
+    }
+
+    fn opens(&self, key: Key, context: impl ActionContext) -> Option<Key> {
+        match self {
+            ActionEnum::JournalAction(inner) => inner.opens(key, context),
+            _ => None,
         }
     }
 }
@@ -264,12 +303,20 @@ pub fn all_action_types(configuration: &Configuration) -> Vec<ActionEnum> {
         ActionEnum::ListChangeType(ListChangeType {}),
         ActionEnum::ListToSections(ListToSections {}),
         ActionEnum::SectionToList(SectionToList {}),
+        ActionEnum::ListToggleTask(ListToggleTask {}),
         ActionEnum::DeleteAction(DeleteAction {}),
     ];
+    actions.extend(
+        [
+            StructureEdit::MoveUp,
+            StructureEdit::MoveDown,
+            StructureEdit::Promote,
+            StructureEdit::Demote,
+        ]
+        .map(|edit| ActionEnum::StructureAction(StructureAction { edit })),
+    );
 
-    let format_options = configuration.format_options();
     let key_locale = get_locale(configuration.library.locale.as_deref());
-    let content_locale = get_locale(format_options.locale());
 
     actions.extend(configuration.actions.iter().map(|(identifier, action)| {
         match action {
@@ -279,37 +326,11 @@ pub fn all_action_types(configuration: &Configuration) -> Vec<ActionEnum> {
                     identifier: identifier.clone(),
                     command: transform.command.clone(),
                     input_template: transform.input_template.clone(),
+                    engine: TemplateEngine::new(configuration),
                 })
             }
             ActionDefinition::Attach(attach) => {
-                let content_date_fmt = format_options
-                    .date_format()
-                    .unwrap_or("%b %d, %Y")
-                    .to_string();
-                let key_date_fmt = configuration
-                    .clone()
-                    .library
-                    .date_format
-                    .unwrap_or(DEFAULT_KEY_DATE_FORMAT.into());
-                ActionEnum::AttachAction(AttachAction {
-                    title: attach.title.clone(),
-                    identifier: identifier.clone(),
-                    document_template: attach.document_template.clone(),
-                    key_template: attach.key_template.clone(),
-                    content_date_format: content_date_fmt.clone(),
-                    content_time_format: format_options
-                        .time_format()
-                        .map(|format| format.to_string())
-                        .unwrap_or_else(|| content_date_fmt.clone()),
-                    key_date_format: key_date_fmt.clone(),
-                    key_time_format: configuration
-                        .clone()
-                        .library
-                        .time_format
-                        .unwrap_or_else(|| key_date_fmt.clone()),
-                    key_locale,
-                    content_locale,
-                })
+                ActionEnum::AttachAction(attach_action(identifier, attach, configuration))
             }
             ActionDefinition::Sort(sort) => ActionEnum::SortAction(SortAction {
                 title: sort.title.clone(),
@@ -361,9 +382,78 @@ pub fn all_action_types(configuration: &Configuration) -> Vec<ActionEnum> {
         }
     }));
 
+    actions.push(ActionEnum::MentionsAction(MentionsAction {
+        language: configuration.search_language(),
+        link_type: configuration
+            .completion
+            .link_format
+            .clone()
+            .unwrap_or(LinkType::Markdown),
+    }));
+
+    if let Ok(journal) = Journal::new(configuration) {
+        if !journal.is_empty() {
+            actions.extend(
+                [JournalStep::Previous, JournalStep::Next, JournalStep::Up].map(|step| {
+                    ActionEnum::JournalAction(JournalAction {
+                        journal: journal.clone(),
+                        step,
+                    })
+                }),
+            );
+        }
+    }
+
     actions
 }
 
+/// Quick fixes for the lint diagnostics the server publishes, linking orphans from the attach
+/// action `[lint] attach_orphans` names.
+pub fn lint_fix_action(configuration: &Configuration) -> LintFixAction {
+    let hub = configuration.lint.attach_orphans.as_ref().and_then(|name| {
+        match configuration.actions.get(name) {
+            Some(ActionDefinition::Attach(attach)) => {
+                Some(attach_action(name, attach, configuration))
+            }
+            _ => None,
+        }
+    });
+    LintFixAction { hub }
+}
+
+fn attach_action(identifier: &str, attach: &Attach, configuration: &Configuration) -> AttachAction {
+    let format_options = configuration.format_options();
+    let content_date_fmt = format_options
+        .date_format()
+        .unwrap_or("%b %d, %Y")
+        .to_string();
+    let key_date_fmt = configuration
+        .clone()
+        .library
+        .date_format
+        .unwrap_or(DEFAULT_KEY_DATE_FORMAT.into());
+    AttachAction {
+        title: attach.title.clone(),
+        identifier: identifier.to_string(),
+        document_template: attach.document_template.clone(),
+        key_template: attach.key_template.clone(),
+        content_date_format: content_date_fmt.clone(),
+        content_time_format: format_options
+            .time_format()
+            .map(|format| format.to_string())
+            .unwrap_or_else(|| content_date_fmt.clone()),
+        key_date_format: key_date_fmt.clone(),
+        key_time_format: configuration
+            .clone()
+            .library
+            .time_format
+            .unwrap_or_else(|| key_date_fmt.clone()),
+        key_locale: get_locale(configuration.library.locale.as_deref()),
+        content_locale: get_locale(format_options.locale()),
+        engine: TemplateEngine::new(configuration),
+    }
+}
+
 pub use liwe::operations::string_to_slug;
 
 pub fn into_lsp_changes(liwe_changes: liwe::operations::Changes) -> Changes {
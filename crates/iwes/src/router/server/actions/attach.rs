@@ -1,8 +1,11 @@
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use chrono::{DateTime, Local, Locale};
-use minijinja::{context, Environment};
+use minijinja::context;
 
+use diwe::template::TemplateEngine;
+use liwe::graph::Graph;
 use liwe::model::Key;
 use liwe::operations::{attach_reference, AttachTarget, Changes};
 
@@ -20,10 +23,11 @@ pub struct AttachAction {
     pub key_time_format: String,
     pub key_locale: Locale,
     pub content_locale: Locale,
+    pub engine: TemplateEngine,
 }
 
 impl AttachAction {
-    fn format_target_key(&self, now: SystemTime) -> Key {
+    pub(super) fn format_target_key(&self, now: SystemTime, graph: &Arc<Graph>) -> Option<Key> {
         let now: DateTime<Local> = now.into();
         let today_formatted = now
             .format_localized(&self.key_date_format, self.key_locale)
@@ -32,19 +36,27 @@ impl AttachAction {
             .format_localized(&self.key_time_format, self.key_locale)
             .to_string();
 
-        Key::name(
-            &Environment::new()
-                .template_from_str(&self.key_template)
-                .expect("correct template")
-                .render(context! {
+        self.engine
+            .clone()
+            .with_graph(graph.clone())
+            .render(
+                &self.key_template,
+                context! {
                 today => today_formatted,
                 now => now_formatted,
-                })
-                .expect("template to work"),
-        )
+                },
+            )
+            .ok()
+            .map(|key| Key::name(&key))
     }
 
-    fn format_target_document(&self, now: SystemTime, content: String) -> String {
+    pub(super) fn format_target_document(
+        &self,
+        now: SystemTime,
+        key: &Key,
+        content: String,
+        graph: &Arc<Graph>,
+    ) -> Option<String> {
         let now: DateTime<Local> = now.into();
         let today_formatted = now
             .format_localized(&self.content_date_format, self.content_locale)
@@ -52,15 +64,19 @@ impl AttachAction {
         let now_formatted = now
             .format_localized(&self.content_time_format, self.content_locale)
             .to_string();
-        Environment::new()
-            .template_from_str(&self.document_template)
-            .expect("correct template")
-            .render(context! {
-            today => today_formatted,
-            now => now_formatted,
-            content => content
-            })
-            .expect("template to work")
+        self.engine
+            .clone()
+            .relative_to(key)
+            .with_graph(graph.clone())
+            .render(
+                &self.document_template,
+                context! {
+                today => today_formatted,
+                now => now_formatted,
+                content => content
+                },
+            )
+            .ok()
     }
 }
 
@@ -102,7 +118,7 @@ impl ActionProvider for AttachAction {
         };
 
         let now = context.now();
-        let attach_to_key = self.format_target_key(now);
+        let attach_to_key = self.format_target_key(now, context.graph())?;
 
         if context.key_exists(&attach_to_key)
             && context
@@ -156,7 +172,7 @@ impl ActionProvider for AttachAction {
         };
 
         let now = context.now();
-        let attach_to_key = self.format_target_key(now);
+        let attach_to_key = self.format_target_key(now, context.graph())?;
 
         match attach_reference(
             context.graph(),
@@ -167,7 +183,9 @@ impl ActionProvider for AttachAction {
             AttachTarget::AlreadyAttached => None,
             AttachTarget::Update(content) => Some(Changes::new().update(attach_to_key, content)),
             AttachTarget::Create(body) => {
-                Some(Changes::new().create(attach_to_key, self.format_target_document(now, body)))
+                let document =
+                    self.format_target_document(now, &attach_to_key, body, context.graph())?;
+                Some(Changes::new().create(attach_to_key, document))
             }
         }
     }
@@ -0,0 +1,89 @@
+use diwe::journal::{Journal, Parent, Period};
+use liwe::model::Key;
+use liwe::operations::Changes;
+
+use super::{Action, ActionContext, ActionProvider, TextRange};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JournalStep {
+    Previous,
+    Next,
+    Up,
+}
+
+/// Opens the previous, next or parent period of the journal note under the cursor, creating it
+/// when it is missing.
+pub struct JournalAction {
+    pub journal: Journal,
+    pub step: JournalStep,
+}
+
+impl JournalAction {
+    fn target(&self, key: &Key, context: &impl ActionContext) -> Option<(Key, String, Changes)> {
+        let (period, date) = self.journal.parse(key)?;
+        let (period, date) = match self.step {
+            JournalStep::Previous => (period, period.shift(date, -1)),
+            JournalStep::Next => (period, period.shift(date, 1)),
+            JournalStep::Up => match self.journal.parent(period, date)? {
+                Parent::Period(period, date) => (period, date),
+                Parent::Hub(hub) => {
+                    context.graph().maybe_key(&hub)?;
+                    let title = context.get_ref_text(&hub).unwrap_or_else(|| hub.source());
+                    return Some((hub, title, Changes::new()));
+                }
+            },
+        };
+        let (target, changes) = self.journal.open(context.graph(), period, date).ok()?;
+        let title = self.journal.title(period, date)?;
+        Some((target, title, changes))
+    }
+
+    fn label(&self, period: Period, title: &str) -> String {
+        match self.step {
+            JournalStep::Previous => format!("Previous {}: {}", period.name(), title),
+            JournalStep::Next => format!("Next {}: {}", period.name(), title),
+            JournalStep::Up => format!("Up: {}", title),
+        }
+    }
+}
+
+impl ActionProvider for JournalAction {
+    fn identifier(&self) -> String {
+        match self.step {
+            JournalStep::Previous => "custom.journal.previous",
+            JournalStep::Next => "custom.journal.next",
+            JournalStep::Up => "custom.journal.up",
+        }
+        .to_string()
+    }
+
+    fn action(
+        &self,
+        key: Key,
+        selection: TextRange,
+        context: impl ActionContext,
+    ) -> Option<Action> {
+        let (period, _) = self.journal.parse(&key)?;
+        let (_, title, _) = self.target(&key, &context)?;
+
+        Some(Action {
+            title: self.label(period, &title),
+            identifier: self.identifier(),
+            key,
+            range: selection,
+        })
+    }
+
+    fn changes(
+        &self,
+        key: Key,
+        _selection: TextRange,
+        context: impl ActionContext,
+    ) -> Option<Changes> {
+        self.target(&key, &context).map(|(_, _, changes)| changes)
+    }
+
+    fn opens(&self, key: Key, context: impl ActionContext) -> Option<Key> {
+        self.target(&key, &context).map(|(target, _, _)| target)
+    }
+}
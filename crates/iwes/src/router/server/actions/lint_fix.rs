@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use diwe::lint::fix::{fix, is_fixable, LintFixes, OrphanHub};
+use diwe::lint::{Diagnostic, Severity};
+use diwe::stats::{Finding, Rule};
+use liwe::model::Key;
+use liwe::operations::Changes;
+use lsp_types::{CodeAction, CodeActionKind, DiagnosticSeverity, NumberOrString, Position, Range};
+
+use super::{ActionContext, AttachAction};
+
+/// `source` of the lint diagnostics the server publishes; quick fixes only read these back.
+pub const LINT_SOURCE: &str = "iwe";
+
+/// Quick fix for one published lint diagnostic, including the document's orphan finding when an
+/// attach action is configured for orphans.
+pub struct LintFixAction {
+    pub hub: Option<AttachAction>,
+}
+
+/// Per-document lint diagnostics, kept until the graph or the configuration changes.
+#[derive(Default)]
+pub struct LintCache {
+    documents: Mutex<HashMap<Key, Vec<Diagnostic>>>,
+}
+
+impl LintCache {
+    pub fn get_or_insert_with(
+        &self,
+        key: &Key,
+        lint: impl FnOnce() -> Vec<Diagnostic>,
+    ) -> Vec<Diagnostic> {
+        let mut documents = self.documents.lock().expect("lint cache lock");
+        documents.entry(key.clone()).or_insert_with(lint).clone()
+    }
+
+    pub fn clear(&self) {
+        self.documents.lock().expect("lint cache lock").clear();
+    }
+}
+
+/// The LSP form of `diagnostic` on `text`, its document. Findings without a line sit on the first
+/// one; `data` carries what a quick fix needs to rebuild the finding.
+pub fn to_lsp_diagnostic(diagnostic: &Diagnostic, text: &str) -> lsp_types::Diagnostic {
+    let line = diagnostic.line.map(|line| line - 1).unwrap_or_default();
+    let width = text
+        .lines()
+        .nth(line)
+        .map(|content| content.encode_utf16().count())
+        .unwrap_or_default();
+    lsp_types::Diagnostic {
+        range: Range::new(
+            Position::new(line as u32, 0),
+            Position::new(line as u32, width as u32),
+        ),
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Info | Severity::Off => DiagnosticSeverity::INFORMATION,
+        }),
+        code: Some(NumberOrString::String(
+            diagnostic.finding.rule.label().to_string(),
+        )),
+        source: Some(LINT_SOURCE.to_string()),
+        message: diagnostic.finding.message.clone(),
+        data: Some(serde_json::json!({
+            "line": diagnostic.line,
+            "other": diagnostic.finding.other.as_ref().map(|other| other.to_string()),
+        })),
+        ..Default::default()
+    }
+}
+
+/// The lint finding `diagnostic` was published for, if it is one of ours.
+fn from_lsp_diagnostic(key: &Key, diagnostic: &lsp_types::Diagnostic) -> Option<Diagnostic> {
+    if diagnostic.source.as_deref() != Some(LINT_SOURCE) {
+        return None;
+    }
+    let Some(NumberOrString::String(code)) = &diagnostic.code else {
+        return None;
+    };
+    let rule = Rule::from_label(code)?;
+    let data = diagnostic.data.as_ref()?;
+    Some(Diagnostic {
+        finding: Finding {
+            rule,
+            key: key.clone(),
+            other: data
+                .get("other")
+                .and_then(|other| other.as_str())
+                .map(Key::name),
+            message: diagnostic.message.clone(),
+        },
+        severity: match diagnostic.severity {
+            Some(DiagnosticSeverity::ERROR) => Severity::Error,
+            Some(DiagnosticSeverity::WARNING) => Severity::Warning,
+            _ => Severity::Info,
+        },
+        line: data
+            .get("line")
+            .and_then(|line| line.as_u64())
+            .map(|line| line as usize),
+    })
+}
+
+impl LintFixAction {
+    /// One quick fix per fixable lint diagnostic in `diagnostics`, the client's code action
+    /// context. Each action names the diagnostic it resolves.
+    pub fn actions(
+        &self,
+        key: &Key,
+        diagnostics: &[lsp_types::Diagnostic],
+        context: &impl ActionContext,
+    ) -> Vec<CodeAction> {
+        diagnostics
+            .iter()
+            .filter_map(|diagnostic| {
+                let fixes = self.fix_for(key, diagnostic, context);
+                let fixed = fixes.fixed.first()?;
+                Some(CodeAction {
+                    title: format!(
+                        "Fix {}: {}",
+                        fixed.finding.rule.label(),
+                        fixed.finding.message
+                    ),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    data: Some(serde_json::json!({ "key": key.to_string() })),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// The edits resolving the diagnostic a quick fix from [`Self::actions`] names.
+    pub fn changes(
+        &self,
+        key: &Key,
+        diagnostics: &[lsp_types::Diagnostic],
+        context: &impl ActionContext,
+    ) -> Option<Changes> {
+        let fixes = self.fix_for(key, diagnostics.first()?, context);
+        (!fixes.changes.is_empty()).then_some(fixes.changes)
+    }
+
+    fn fix_for(
+        &self,
+        key: &Key,
+        diagnostic: &lsp_types::Diagnostic,
+        context: &impl ActionContext,
+    ) -> LintFixes {
+        let Some(diagnostic) =
+            from_lsp_diagnostic(key, diagnostic).filter(|d| is_fixable(d.finding.rule))
+        else {
+            return LintFixes::default();
+        };
+
+        let now = context.now();
+        let hub_key = self
+            .hub
+            .as_ref()
+            .and_then(|hub| hub.format_target_key(now, context.graph()));
+        let document = |body: &str| match (&self.hub, &hub_key) {
+            (Some(hub), Some(key)) => hub
+                .format_target_document(now, key, body.to_string(), context.graph())
+                .unwrap_or_else(|| body.to_string()),
+            _ => body.to_string(),
+        };
+        let hub = hub_key.clone().map(|key| OrphanHub {
+            key,
+            document: &document,
+        });
+        fix(context.graph(), &[diagnostic], hub.as_ref())
+    }
+}
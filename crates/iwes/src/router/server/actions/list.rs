@@ -1,4 +1,4 @@
-use liwe::model::node::NodeIter;
+use liwe::model::node::{Node, NodeIter};
 use liwe::operations::Changes;
 
 use super::{Action, ActionContext, ActionProvider};
@@ -102,3 +102,48 @@ impl ActionProvider for ListToSections {
             })
     }
 }
+
+pub struct ListToggleTask {}
+
+impl ActionProvider for ListToggleTask {
+    fn identifier(&self) -> String {
+        "refactor.rewrite.list.task".to_string()
+    }
+
+    fn action(
+        &self,
+        key: super::Key,
+        selection: super::TextRange,
+        context: impl ActionContext,
+    ) -> Option<Action> {
+        let target_id = context.get_node_id_at(&key, selection.start.line as usize)?;
+        match context.collect(&key).find_id(target_id)?.node {
+            Node::Item(Some(checked), _) => Some(Action {
+                title: match checked {
+                    true => "Uncheck task".to_string(),
+                    false => "Check task".to_string(),
+                },
+                identifier: self.identifier(),
+                key: key.clone(),
+                range: selection.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn changes(
+        &self,
+        key: super::Key,
+        selection: super::TextRange,
+        context: impl ActionContext,
+    ) -> Option<Changes> {
+        let target_id = context.get_node_id_at(&key, selection.start.line as usize)?;
+        context.collect(&key).toggle_task(target_id).map(|tree| {
+            Changes::new().update(
+                key.clone(),
+                tree.iter()
+                    .to_text(&key.parent(), &context.format_options()),
+            )
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use diwe::config::LinkType;
+use diwe::mentions::{link_mentions, Mention, MentionFinder};
+use diwe::search::Language;
+use liwe::model::Key;
+use liwe::operations::Changes;
+
+use super::{Action, ActionContext, ActionProvider, TextRange};
+
+/// Links the unlinked mentions of a document: the one linked under the cursor, or else the
+/// current document. With a cursor every mention in the library is linked; with a selection only
+/// the mentions inside it. Library-wide mentions are searched when the action is resolved, not
+/// when it is offered.
+pub struct MentionsAction {
+    pub language: Language,
+    pub link_type: LinkType,
+}
+
+impl MentionsAction {
+    fn target(&self, key: &Key, selection: &TextRange, context: &impl ActionContext) -> Key {
+        context
+            .get_link_key_at(
+                key,
+                selection.start.line as usize,
+                selection.start.character as usize,
+            )
+            .filter(|target| context.key_exists(target))
+            .unwrap_or_else(|| key.clone())
+    }
+
+    fn mentions(
+        &self,
+        key: &Key,
+        selection: &TextRange,
+        target: &Key,
+        context: &impl ActionContext,
+    ) -> Vec<Mention> {
+        let finder = MentionFinder::new(context.graph(), self.language.clone());
+        if is_cursor(selection) {
+            return finder.find(target);
+        }
+        finder
+            .find_in_document(target, key)
+            .into_iter()
+            .filter(|mention| {
+                let start = (mention.line as u32, mention.start as u32);
+                let end = (mention.line as u32, mention.end as u32);
+                (selection.start.line, selection.start.character) <= start
+                    && end <= (selection.end.line, selection.end.character)
+            })
+            .collect()
+    }
+}
+
+fn is_cursor(selection: &TextRange) -> bool {
+    selection.start.line == selection.end.line
+        && selection.start.character == selection.end.character
+}
+
+impl ActionProvider for MentionsAction {
+    fn identifier(&self) -> String {
+        "refactor.rewrite.mentions".to_string()
+    }
+
+    fn action(
+        &self,
+        key: Key,
+        selection: TextRange,
+        context: impl ActionContext,
+    ) -> Option<Action> {
+        let target = self.target(&key, &selection, &context);
+        let offered = if is_cursor(&selection) {
+            !MentionFinder::new(context.graph(), self.language.clone())
+                .names(&target)
+                .is_empty()
+        } else {
+            !self
+                .mentions(&key, &selection, &target, &context)
+                .is_empty()
+        };
+        if !offered {
+            return None;
+        }
+
+        let title = context
+            .get_ref_text(&target)
+            .filter(|title| !title.is_empty())
+            .unwrap_or_else(|| target.to_string());
+
+        Some(Action {
+            title: format!("Link all mentions of {}", title),
+            identifier: self.identifier(),
+            key,
+            range: selection,
+        })
+    }
+
+    fn changes(
+        &self,
+        key: Key,
+        selection: TextRange,
+        context: impl ActionContext,
+    ) -> Option<Changes> {
+        let target = self.target(&key, &selection, &context);
+        let mentions = self.mentions(&key, &selection, &target, &context);
+        if mentions.is_empty() {
+            return None;
+        }
+        Some(link_mentions(
+            context.graph(),
+            &target,
+            &mentions,
+            &self.link_type,
+        ))
+    }
+}
@@ -0,0 +1,84 @@
+use liwe::model::node::NodeIter;
+use liwe::model::tree::Tree;
+use liwe::model::NodeId;
+use liwe::operations::Changes;
+
+use super::{Action, ActionContext, ActionProvider};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum StructureEdit {
+    MoveUp,
+    MoveDown,
+    Promote,
+    Demote,
+}
+
+/// Moves the section or list item under the cursor past its neighbour, or changes the level of
+/// the section under the cursor together with its sub-sections.
+pub struct StructureAction {
+    pub edit: StructureEdit,
+}
+
+impl StructureAction {
+    fn apply(&self, tree: &Tree, node_id: NodeId) -> Option<Tree> {
+        match self.edit {
+            StructureEdit::MoveUp => tree.move_up(node_id),
+            StructureEdit::MoveDown => tree.move_down(node_id),
+            StructureEdit::Promote => tree.promote_section(node_id),
+            StructureEdit::Demote => tree.demote_section(node_id),
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self.edit {
+            StructureEdit::MoveUp => "Move up",
+            StructureEdit::MoveDown => "Move down",
+            StructureEdit::Promote => "Promote section",
+            StructureEdit::Demote => "Demote section",
+        }
+    }
+}
+
+impl ActionProvider for StructureAction {
+    fn identifier(&self) -> String {
+        match self.edit {
+            StructureEdit::MoveUp => "refactor.move.up",
+            StructureEdit::MoveDown => "refactor.move.down",
+            StructureEdit::Promote => "refactor.rewrite.section.promote",
+            StructureEdit::Demote => "refactor.rewrite.section.demote",
+        }
+        .to_string()
+    }
+
+    fn action(
+        &self,
+        key: super::Key,
+        selection: super::TextRange,
+        context: impl ActionContext,
+    ) -> Option<Action> {
+        let target_id = context.get_node_id_at(&key, selection.start.line as usize)?;
+        self.apply(&context.collect(&key), target_id)
+            .map(|_| Action {
+                title: self.title().to_string(),
+                identifier: self.identifier(),
+                key: key.clone(),
+                range: selection.clone(),
+            })
+    }
+
+    fn changes(
+        &self,
+        key: super::Key,
+        selection: super::TextRange,
+        context: impl ActionContext,
+    ) -> Option<Changes> {
+        let target_id = context.get_node_id_at(&key, selection.start.line as usize)?;
+        self.apply(&context.collect(&key), target_id).map(|tree| {
+            Changes::new().update(
+                key.clone(),
+                tree.iter()
+                    .to_text(&key.parent(), &context.format_options()),
+            )
+        })
+    }
+}
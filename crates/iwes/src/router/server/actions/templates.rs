@@ -1,5 +1,6 @@
+use diwe::template::TemplateEngine;
 use liwe::model::{node::NodeIter, tree::Tree, NodeId};
-use minijinja::{context, Environment};
+use minijinja::context;
 
 static UPDATE_START: &str = "<update_here>";
 static UPDATE_END: &str = "</update_here>";
@@ -7,20 +8,26 @@ static UPDATE_END: &str = "</update_here>";
 static CONTEXT_START: &str = "<context>";
 static CONTEXT_END: &str = "</context>";
 
-pub fn render_input_template(input_template: &str, node_id: NodeId, tree: &Tree) -> String {
+pub fn render_input_template(
+    engine: &TemplateEngine,
+    input_template: &str,
+    node_id: NodeId,
+    tree: &Tree,
+) -> Option<String> {
     let marked = tree.mark_node(node_id, UPDATE_START, UPDATE_END);
 
     let context: &str = &marked.iter().to_default_text();
 
-    Environment::new()
-        .template_from_str(input_template)
-        .expect("correct template")
-        .render(context! {
-        context => context,
-        context_start => CONTEXT_START,
-        context_end => CONTEXT_END,
-        update_start => UPDATE_START,
-        update_end => UPDATE_END
-        })
-        .unwrap()
+    engine
+        .render(
+            input_template,
+            context! {
+            context => context,
+            context_start => CONTEXT_START,
+            context_end => CONTEXT_END,
+            update_start => UPDATE_START,
+            update_end => UPDATE_END
+            },
+        )
+        .ok()
 }
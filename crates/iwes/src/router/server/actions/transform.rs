@@ -3,6 +3,7 @@ use std::process::{Command as ProcessCommand, Stdio};
 use std::time::Duration;
 
 use diwe::config::Command;
+use diwe::template::TemplateEngine;
 use liwe::markdown::MarkdownReader;
 use liwe::model::node::{NodeIter, NodePointer};
 use liwe::operations::Changes;
@@ -15,6 +16,7 @@ pub struct TransformBlockAction {
     pub identifier: String,
     pub command: String,
     pub input_template: String,
+    pub engine: TemplateEngine,
 }
 
 fn expand_env_var(value: &str) -> String {
@@ -193,7 +195,13 @@ impl ActionProvider for TransformBlockAction {
             .get_surrounding_top_level_block(target_id)
             .unwrap_or(target_id);
 
-        let input = templates::render_input_template(&self.input_template, target_id, tree);
+        let engine = self
+            .engine
+            .clone()
+            .relative_to(&key)
+            .with_graph(context.graph().clone());
+        let input =
+            templates::render_input_template(&engine, &self.input_template, target_id, tree)?;
 
         let command = context.get_command(&self.command)?;
 
@@ -42,6 +42,29 @@ impl BasePath {
         self.build_url(path)
     }
 
+    /// Another library, at `path` relative to this one.
+    pub fn directory(&self, path: &str) -> Option<BasePath> {
+        let url = self
+            .url
+            .join(&format!("{}/", path.trim_end_matches('/')))
+            .ok()?;
+        Some(Self {
+            url: canonical(url),
+            format: self.format,
+        })
+    }
+
+    /// URL of a library-relative file path, such as an image, taken as is.
+    pub fn file_to_url(&self, path: &str) -> Uri {
+        let mut url = self.url.clone();
+        {
+            let mut segs = url.path_segments_mut().expect("path-based URL");
+            segs.pop_if_empty();
+            segs.extend(path.split('/').filter(|s| !s.is_empty()));
+        }
+        Uri::from_str(url.as_str()).expect("valid URI")
+    }
+
     pub fn name_to_url(&self, name: &str) -> Uri {
         self.build_url(name)
     }
@@ -255,6 +278,13 @@ mod tests {
         assert_eq!(url.to_string(), file_url("basepath/one.md"));
     }
 
+    #[test]
+    fn file_to_url_keeps_extension() {
+        let base_path = BasePath::from_path(&abs_path("basepath"), Format::Markdown);
+        let url = base_path.file_to_url("images/a b.png");
+        assert_eq!(url.to_string(), file_url("basepath/images/a%20b.png"));
+    }
+
     #[test]
     fn test_resolve_relative_url_with_anchor_fragment() {
         let base_path = BasePath::from_path(&abs_path("basepath"), Format::Markdown);
@@ -0,0 +1,192 @@
+use itertools::Itertools;
+use liwe::graph::{Graph, GraphContext};
+use liwe::model::node::{Node, NodePointer};
+use liwe::model::tree::Tree;
+use liwe::model::{Key, NodeId};
+use lsp_types::*;
+
+use super::extensions::*;
+use super::BasePath;
+
+/// The client's references peek view, given the document URI, the lens position and the
+/// locations.
+pub const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+const BACKLINKS: &str = "backlinks";
+const INCLUDED_BY: &str = "includedBy";
+const CHILDREN: &str = "children";
+
+/// Unresolved lenses of a document: backlinks and inclusion parents on its first line, and a
+/// children lens on every section that includes other documents. Titles and locations are
+/// computed in [`resolve`].
+pub fn lenses(graph: &Graph, uri: &Uri, key: &Key) -> Vec<CodeLens> {
+    let Some(tree) = graph.maybe_key(key).map(|pointer| pointer.collect_tree()) else {
+        return vec![];
+    };
+
+    let header_line = tree
+        .children
+        .first()
+        .filter(|child| matches!(child.node, Node::Section(_)))
+        .and_then(|child| child.line_range.as_ref())
+        .map(|range| range.start as u32)
+        .unwrap_or(0);
+
+    let mut lenses = vec![
+        unresolved(uri, key, header_line, BACKLINKS),
+        unresolved(uri, key, header_line, INCLUDED_BY),
+    ];
+    collect_sections(&tree, &mut |section, line| {
+        if !section_children(section).is_empty() {
+            lenses.push(unresolved(uri, key, line, CHILDREN));
+        }
+    });
+    lenses
+}
+
+fn unresolved(uri: &Uri, key: &Key, line: u32, lens: &str) -> CodeLens {
+    CodeLens {
+        range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+        command: None,
+        data: Some(serde_json::json!({
+            "uri": uri.to_string(),
+            "key": key.to_string(),
+            "lens": lens,
+        })),
+    }
+}
+
+fn collect_sections(tree: &Tree, f: &mut impl FnMut(&Tree, u32)) {
+    if let (Node::Section(_), Some(range)) = (&tree.node, &tree.line_range) {
+        f(tree, range.start as u32);
+    }
+    for child in &tree.children {
+        collect_sections(child, f);
+    }
+}
+
+/// Documents included anywhere under a section, subsections included.
+fn section_children(tree: &Tree) -> Vec<Key> {
+    let mut keys = vec![];
+    if let Node::Reference(reference) = &tree.node {
+        keys.push(reference.key.clone());
+    }
+    for child in &tree.children {
+        keys.extend(section_children(child));
+    }
+    keys.into_iter().unique().collect()
+}
+
+pub fn resolve(graph: &Graph, base_path: &BasePath, code_lens: &CodeLens) -> CodeLens {
+    let Some(data) = code_lens.data.clone() else {
+        return code_lens.clone();
+    };
+
+    let Some(key) = data.get("key").and_then(|v| v.as_str()).map(Key::name) else {
+        return code_lens.clone();
+    };
+
+    let (title, locations) = match data.get("lens").and_then(|v| v.as_str()) {
+        Some(BACKLINKS) => {
+            let ids = graph
+                .get_inclusion_edges_to(&key)
+                .into_iter()
+                .chain(graph.get_reference_edges_to(&key))
+                .collect_vec();
+            let locations = edge_locations(graph, base_path, &key, ids);
+            (counted(locations.len(), "backlink", "backlinks"), locations)
+        }
+        Some(INCLUDED_BY) => {
+            let locations =
+                edge_locations(graph, base_path, &key, graph.get_inclusion_edges_to(&key));
+            let parents = graph
+                .get_inclusion_edges_to(&key)
+                .into_iter()
+                .filter(|id| graph.get_node_key(*id).as_ref() != Some(&key))
+                .filter_map(|id| graph.get_container_document_ref_text(id))
+                .sorted()
+                .dedup()
+                .join(", ");
+            if parents.is_empty() {
+                ("not included".to_string(), locations)
+            } else {
+                (format!("included by {}", parents), locations)
+            }
+        }
+        Some(CHILDREN) => {
+            let line = code_lens.range.start.line as usize;
+            let mut children = vec![];
+            if let Some(tree) = graph.maybe_key(&key).map(|pointer| pointer.collect_tree()) {
+                collect_sections(&tree, &mut |section, start| {
+                    if start as usize == line {
+                        children = section_children(section);
+                    }
+                });
+            }
+            let locations = children
+                .iter()
+                .map(|child| Location::new(child.to_full_url(base_path), Range::default()))
+                .collect_vec();
+            (counted(locations.len(), "child", "children"), locations)
+        }
+        _ => return code_lens.clone(),
+    };
+
+    let mut resolved = code_lens.clone();
+    resolved.command = Some(if locations.is_empty() {
+        Command {
+            title,
+            command: String::new(),
+            arguments: None,
+        }
+    } else {
+        Command {
+            title,
+            command: SHOW_REFERENCES_COMMAND.to_string(),
+            arguments: Some(vec![
+                serde_json::json!(key.to_full_url(base_path).to_string()),
+                serde_json::to_value(code_lens.range.start).unwrap(),
+                serde_json::to_value(&locations).unwrap(),
+            ]),
+        }
+    });
+    resolved
+}
+
+fn counted(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("1 {}", singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
+
+/// Locations of the blocks linking to `key` from other documents.
+fn edge_locations(
+    graph: &Graph,
+    base_path: &BasePath,
+    key: &Key,
+    ids: Vec<NodeId>,
+) -> Vec<Location> {
+    ids.into_iter()
+        .filter_map(|id| {
+            let source = graph.get_node_key(id)?;
+            let range = graph.node_line_range(id)?;
+            Some((source, range))
+        })
+        .filter(|(source, _)| source != key)
+        .map(|(source, range)| {
+            Location::new(
+                source.to_full_url(base_path),
+                Range::new(
+                    Position::new(range.start as u32, 0),
+                    Position::new(range.end as u32, 0),
+                ),
+            )
+        })
+        .sorted_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line).cmp(&(b.uri.as_str(), b.range.start.line))
+        })
+        .dedup()
+        .collect_vec()
+}
@@ -0,0 +1,275 @@
+use std::str::FromStr;
+
+use liwe::graph::assets::is_asset_link;
+use liwe::graph::{DatabaseContext, Graph};
+use liwe::model::asset::{is_local_url, resolve_asset};
+use liwe::model::document::{DocumentBlock, DocumentInline};
+use liwe::model::reference::ReferenceType;
+use liwe::model::{InlineRange, Key};
+use lsp_types::*;
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+use super::extensions::*;
+use super::mounts::Mounts;
+use super::BasePath;
+
+/// Links of a document for clients to follow: notes (wiki links and section fragments
+/// included), local files and external URLs, bare ones too. Note and file links carry the
+/// target's title or path as a tooltip, or a warning when the target is missing. Links to
+/// mounted libraries open the document there.
+pub fn document_links(
+    graph: &Graph,
+    base_path: &BasePath,
+    mounts: &Mounts,
+    key: &Key,
+) -> Vec<DocumentLink> {
+    let Some(parser) = graph.parser(key) else {
+        return vec![];
+    };
+
+    let mut targets = vec![];
+    let mut code = vec![];
+    let mut prose_lines = vec![];
+    for block in parser.document().all_blocks() {
+        if matches!(
+            block,
+            DocumentBlock::Para(_)
+                | DocumentBlock::Plain(_)
+                | DocumentBlock::Header(_)
+                | DocumentBlock::Table(_)
+        ) {
+            prose_lines.extend(block.line_range());
+        }
+        for inline in block.child_inlines() {
+            collect_targets(&inline, &mut targets, &mut code);
+        }
+    }
+
+    let mut links = targets
+        .iter()
+        .filter_map(|target| link(graph, base_path, mounts, key, target))
+        .collect::<Vec<_>>();
+
+    let mut taken = targets
+        .iter()
+        .map(|target| target.range.clone())
+        .chain(code)
+        .collect::<Vec<_>>();
+    for line in prose_lines {
+        for (range, url) in parser.bare_urls(line) {
+            if taken.iter().any(|other| overlaps(other, &range)) {
+                continue;
+            }
+            links.extend(external(&range, &url));
+            taken.push(range);
+        }
+    }
+
+    links.sort_by_key(|link| (link.range.start.line, link.range.start.character));
+    links
+}
+
+struct LinkTarget {
+    range: InlineRange,
+    url: String,
+    wiki: bool,
+    image: bool,
+}
+
+fn collect_targets(
+    inline: &DocumentInline,
+    targets: &mut Vec<LinkTarget>,
+    code: &mut Vec<InlineRange>,
+) {
+    match inline {
+        DocumentInline::Link(link) => targets.push(LinkTarget {
+            range: link.inline_range.clone(),
+            url: link.target.url.clone(),
+            wiki: link.link_type.to_ref_type() != ReferenceType::Regular,
+            image: false,
+        }),
+        DocumentInline::Image(image) => targets.push(LinkTarget {
+            range: image.inline_range.clone(),
+            url: image.target.url.clone(),
+            wiki: false,
+            image: true,
+        }),
+        DocumentInline::Code(_) | DocumentInline::Math(_) | DocumentInline::RawInline(_) => {
+            code.push(inline.inline_range())
+        }
+        _ => {
+            for child in inline.child_inlines() {
+                collect_targets(child, targets, code);
+            }
+        }
+    }
+}
+
+fn link(
+    graph: &Graph,
+    base_path: &BasePath,
+    mounts: &Mounts,
+    key: &Key,
+    target: &LinkTarget,
+) -> Option<DocumentLink> {
+    let url = target.url.as_str();
+    if url.is_empty() {
+        return None;
+    }
+
+    if !target.wiki && !url.starts_with('#') && !is_local_url(url) {
+        return external(&target.range, url);
+    }
+
+    if !target.wiki && (target.image || is_asset_link(graph, url, &key.parent())) {
+        let (path, uri, exists) = asset(base_path, key, url);
+        let tooltip = if exists {
+            path
+        } else {
+            format!("Missing file: {}", path)
+        };
+        return Some(document_link(&target.range, uri, tooltip));
+    }
+
+    let (path, fragment) = match url.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (url, None),
+    };
+    let target_key = if path.is_empty() {
+        key.clone()
+    } else if target.wiki {
+        graph.key_index().resolve_wiki(path)
+    } else {
+        Key::from_rel_link_url(path, &key.parent())
+    };
+    let uri = target_key.to_full_url(base_path);
+
+    if graph.get_document(&target_key).is_none() {
+        if let Some(uri) = mounts.url(&target_key) {
+            return Some(document_link(&target.range, uri, target_key.to_string()));
+        }
+        return Some(document_link(
+            &target.range,
+            uri,
+            format!("Missing document: {}", target_key),
+        ));
+    }
+
+    let title = graph
+        .get_key_title(&target_key)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| target_key.to_string());
+
+    let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) else {
+        return Some(document_link(&target.range, uri, title));
+    };
+
+    let fragment = percent_decode_str(fragment)
+        .decode_utf8_lossy()
+        .into_owned();
+    match heading(graph, &target_key, &fragment) {
+        Some((line, heading)) => Some(document_link(
+            &target.range,
+            Uri::from_str(&format!("{}#L{}", uri.as_str(), line + 1)).unwrap_or(uri),
+            format!("{} › {}", title, heading),
+        )),
+        None => Some(document_link(
+            &target.range,
+            uri,
+            format!("{}: missing section '{}'", title, fragment),
+        )),
+    }
+}
+
+/// Hover preview of a link that does not point at a note: the URL of a web page, or the
+/// library path of a file, rendered inline when it is an image. A link naming an existing
+/// note is left to the note hover, whatever its extension.
+pub fn preview(graph: &Graph, base_path: &BasePath, key: &Key, url: &str) -> Option<String> {
+    if !is_local_url(url) {
+        return (!url.starts_with('#')).then(|| format!("[{0}]({0})", url));
+    }
+    if !is_asset_link(graph, url, &key.parent()) {
+        return None;
+    }
+
+    let (path, uri, exists) = asset(base_path, key, url);
+    if !exists {
+        return Some(format!("**Missing file:** {}", path));
+    }
+    let extension = path.rsplit('.').next().unwrap_or_default().to_lowercase();
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+        Some(format!("![{}]({})", path, uri.as_str()))
+    } else {
+        Some(format!("[{}]({})", path, uri.as_str()))
+    }
+}
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp"];
+
+/// Library path, URL and existence of the file a link points at.
+fn asset(base_path: &BasePath, key: &Key, url: &str) -> (String, Uri, bool) {
+    let path = resolve_asset(url, &key.parent());
+    let uri = base_path.file_to_url(&path);
+    let exists = Url::parse(uri.as_str())
+        .ok()
+        .and_then(|url| url.to_file_path().ok())
+        .is_some_and(|path| path.exists());
+    (path, uri, exists)
+}
+
+/// Line and text of the heading a fragment names, by its text or its slug.
+fn heading(graph: &Graph, key: &Key, fragment: &str) -> Option<(usize, String)> {
+    let parser = graph.parser(key)?;
+    parser
+        .document()
+        .all_blocks()
+        .into_iter()
+        .filter_map(|block| match block {
+            DocumentBlock::Header(header) => Some((
+                header.line_range.start,
+                header
+                    .inlines
+                    .iter()
+                    .map(|inline| inline.to_plain_text())
+                    .collect::<String>(),
+            )),
+            _ => None,
+        })
+        .find(|(_, text)| text.eq_ignore_ascii_case(fragment) || slug(text) == fragment)
+}
+
+/// GitHub-style heading anchor: lowercase, punctuation dropped, spaces as dashes.
+fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn external(range: &InlineRange, url: &str) -> Option<DocumentLink> {
+    Some(DocumentLink {
+        range: range.clone().to_lsp(),
+        target: Some(Uri::from_str(url).ok()?),
+        tooltip: None,
+        data: None,
+    })
+}
+
+fn document_link(range: &InlineRange, uri: Uri, tooltip: String) -> DocumentLink {
+    DocumentLink {
+        range: range.clone().to_lsp(),
+        target: Some(uri),
+        tooltip: Some(tooltip),
+        data: None,
+    }
+}
+
+fn overlaps(a: &InlineRange, b: &InlineRange) -> bool {
+    a.start < b.end && b.start < a.end
+}
@@ -0,0 +1,145 @@
+use itertools::Itertools;
+use liwe::graph::{Graph, GraphContext};
+use liwe::model::node::NodePointer;
+use liwe::model::{Key, NodeId};
+use lsp_types::*;
+
+use super::extensions::*;
+use super::BasePath;
+
+/// Documents take part in two hierarchies. Call hierarchy follows inclusion links, with incoming
+/// calls for the parents and outgoing calls for the children. Type hierarchy follows inline
+/// references, with the linking documents as supertypes and the linked ones as subtypes.
+pub fn call_item(graph: &Graph, base_path: &BasePath, key: &Key) -> Option<CallHierarchyItem> {
+    graph.maybe_key(key)?;
+
+    let range = Range::new(Position::new(0, 0), Position::new(0, 0));
+    Some(CallHierarchyItem {
+        name: title(graph, key),
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: Some(key.to_string()),
+        uri: key.to_full_url(base_path),
+        range,
+        selection_range: range,
+        data: Some(serde_json::json!({ "key": key.to_string() })),
+    })
+}
+
+pub fn type_item(graph: &Graph, base_path: &BasePath, key: &Key) -> Option<TypeHierarchyItem> {
+    call_item(graph, base_path, key).map(|item| TypeHierarchyItem {
+        name: item.name,
+        kind: item.kind,
+        tags: None,
+        detail: item.detail,
+        uri: item.uri,
+        range: item.range,
+        selection_range: item.selection_range,
+        data: item.data,
+    })
+}
+
+pub fn item_key(data: &Option<serde_json::Value>) -> Option<Key> {
+    data.as_ref()?
+        .get("key")
+        .and_then(|v| v.as_str())
+        .map(Key::name)
+}
+
+/// Parents including `key`, with the ranges of the inclusion links in each parent.
+pub fn incoming_calls(
+    graph: &Graph,
+    base_path: &BasePath,
+    key: &Key,
+) -> Vec<CallHierarchyIncomingCall> {
+    graph
+        .get_inclusion_edges_to(key)
+        .into_iter()
+        .filter_map(|id| Some((graph.get_node_key(id)?, line_range(graph, id)?)))
+        .filter(|(parent, _)| parent != key)
+        .into_group_map()
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .filter_map(|(parent, ranges)| {
+            Some(CallHierarchyIncomingCall {
+                from: call_item(graph, base_path, &parent)?,
+                from_ranges: sorted(ranges),
+            })
+        })
+        .collect_vec()
+}
+
+/// Children included by `key`, with the ranges of the inclusion links in `key`.
+pub fn outgoing_calls(
+    graph: &Graph,
+    base_path: &BasePath,
+    key: &Key,
+) -> Vec<CallHierarchyOutgoingCall> {
+    graph
+        .get_inclusion_edges_in(key)
+        .into_iter()
+        .filter_map(|id| Some((graph.node(id).ref_key()?, line_range(graph, id)?)))
+        .filter(|(child, _)| child != key)
+        .into_group_map()
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .filter_map(|(child, ranges)| {
+            Some(CallHierarchyOutgoingCall {
+                to: call_item(graph, base_path, &child)?,
+                from_ranges: sorted(ranges),
+            })
+        })
+        .collect_vec()
+}
+
+/// Documents with an inline reference to `key`.
+pub fn supertypes(graph: &Graph, base_path: &BasePath, key: &Key) -> Vec<TypeHierarchyItem> {
+    let keys = graph
+        .get_reference_edges_to(key)
+        .into_iter()
+        .filter_map(|id| graph.get_node_key(id))
+        .collect_vec();
+    type_items(graph, base_path, key, keys)
+}
+
+/// Documents `key` references inline.
+pub fn subtypes(graph: &Graph, base_path: &BasePath, key: &Key) -> Vec<TypeHierarchyItem> {
+    type_items(graph, base_path, key, graph.get_reference_edges_in(key))
+}
+
+fn type_items(
+    graph: &Graph,
+    base_path: &BasePath,
+    key: &Key,
+    keys: Vec<Key>,
+) -> Vec<TypeHierarchyItem> {
+    keys.into_iter()
+        .filter(|other| other != key)
+        .sorted()
+        .dedup()
+        .filter_map(|other| type_item(graph, base_path, &other))
+        .collect_vec()
+}
+
+fn title(graph: &Graph, key: &Key) -> String {
+    graph
+        .get_key_title(key)
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| key.to_string())
+}
+
+fn sorted(ranges: Vec<Range>) -> Vec<Range> {
+    ranges
+        .into_iter()
+        .sorted_by_key(|range| (range.start.line, range.start.character))
+        .collect_vec()
+}
+
+fn line_range(graph: &Graph, id: NodeId) -> Option<Range> {
+    graph.node_line_range(id).map(|range| {
+        Range::new(
+            Position::new(range.start as u32, 0),
+            Position::new(range.end as u32, 0),
+        )
+    })
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use liwe::model::Key;
+use lsp_types::Uri;
+
+use super::BasePath;
+
+/// Libraries mounted into this one under a key prefix, from `[library.mounts]`. A link to
+/// `team/onboarding` that is not a document of this library opens `onboarding` in the library
+/// mounted as `team`.
+#[derive(Default)]
+pub struct Mounts {
+    libraries: Vec<(String, BasePath)>,
+}
+
+impl Mounts {
+    pub fn new(base_path: &BasePath, mounts: &HashMap<String, String>) -> Self {
+        let mut libraries = mounts
+            .iter()
+            .filter_map(|(prefix, path)| {
+                let prefix = prefix.trim_matches('/');
+                if prefix.is_empty() {
+                    return None;
+                }
+                Some((prefix.to_string(), base_path.directory(path)?))
+            })
+            .collect::<Vec<_>>();
+        libraries.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then(a.cmp(b)));
+        Self { libraries }
+    }
+
+    /// URL of `key` in the library mounted under its longest matching prefix.
+    pub fn url(&self, key: &Key) -> Option<Uri> {
+        self.libraries.iter().find_map(|(prefix, base_path)| {
+            let rest = key
+                .as_str()
+                .strip_prefix(prefix.as_str())?
+                .strip_prefix('/')?;
+            (!rest.is_empty()).then(|| base_path.key_to_url(&Key::name(rest)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diwe::config::Format;
+
+    fn mounts(entries: &[(&str, &str)]) -> Mounts {
+        let base = if cfg!(windows) { "C:/notes" } else { "/notes" };
+        let mounts = entries
+            .iter()
+            .map(|(prefix, path)| (prefix.to_string(), path.to_string()))
+            .collect();
+        Mounts::new(&BasePath::from_path(base, Format::Markdown), &mounts)
+    }
+
+    #[test]
+    fn resolves_keys_under_a_prefix() {
+        let url = mounts(&[("team", "../team")]).url(&Key::name("team/guides/onboarding"));
+
+        let expected = if cfg!(windows) {
+            "file:///C:/team/guides/onboarding.md"
+        } else {
+            "file:///team/guides/onboarding.md"
+        };
+        assert_eq!(url.map(|url| url.to_string()), Some(expected.to_string()));
+    }
+
+    #[test]
+    fn ignores_keys_outside_prefixes() {
+        let mounts = mounts(&[("team", "../team")]);
+
+        assert_eq!(mounts.url(&Key::name("teams/note")), None);
+        assert_eq!(mounts.url(&Key::name("team")), None);
+    }
+}
@@ -0,0 +1,80 @@
+use itertools::Itertools;
+use liwe::graph::{Graph, GraphContext};
+use liwe::model::node::{NodeIter, NodePointer};
+use liwe::model::{Key, NodeId};
+use liwe::operations::Changes;
+
+pub const MOVE_SECTION_COMMAND: &str = "iwe.moveSection";
+
+/// The section whose header is on `line` of `key`.
+pub fn section_at(graph: &Graph, key: &Key, line: usize) -> Option<NodeId> {
+    graph.maybe_key(key)?;
+    let node_id = graph.get_node_id_at(key, line)?;
+    graph.collect(key).is_header(node_id).then_some(node_id)
+}
+
+/// Documents a section of `key` can move to: first its parents, the documents it includes, the
+/// ones it references and the ones referencing it, then every other document by key.
+pub fn targets(graph: &Graph, key: &Key) -> Vec<Key> {
+    let parents = graph
+        .get_inclusion_edges_to(key)
+        .into_iter()
+        .filter_map(|id| graph.get_node_key(id));
+    let children = graph
+        .get_inclusion_edges_in(key)
+        .into_iter()
+        .filter_map(|id| graph.node(id).ref_key());
+    let referenced = graph.get_reference_edges_in(key);
+    let referencing = graph
+        .get_reference_edges_to(key)
+        .into_iter()
+        .filter_map(|id| graph.get_node_key(id));
+    let others = graph.keys().into_iter().sorted();
+
+    parents
+        .chain(children)
+        .chain(referenced)
+        .chain(referencing)
+        .filter(|target| graph.maybe_key(target).is_some())
+        .chain(others)
+        .filter(|target| target != key)
+        .unique()
+        .collect()
+}
+
+/// Updates moving the section with its sub-sections from `key` to the end of `target`.
+pub fn move_section(
+    graph: &Graph,
+    key: &Key,
+    section_id: NodeId,
+    target: &Key,
+) -> Result<Changes, String> {
+    if target == key {
+        return Err("the section is already in this document".to_string());
+    }
+    if graph.maybe_key(target).is_none() {
+        return Err(format!("document not found: {}", target));
+    }
+
+    let tree = graph.collect(key);
+    let section = tree
+        .find_id(section_id)
+        .ok_or_else(|| "section not found".to_string())?;
+    let options = graph.format_options();
+
+    Ok(Changes::new()
+        .update(
+            key.clone(),
+            tree.remove_node(section_id)
+                .iter()
+                .to_text(&key.parent(), options),
+        )
+        .update(
+            target.clone(),
+            graph
+                .collect(target)
+                .append_section(section.rebase_assets(&key.parent(), &target.parent()))
+                .iter()
+                .to_text(&target.parent(), options),
+        ))
+}
@@ -0,0 +1,230 @@
+use diwe::config::FormattingOptions;
+use lsp_types::{Position, Range, TextEdit};
+
+enum Marker {
+    Bullet(char),
+    Ordered(usize, char),
+}
+
+/// A list item as written on the line that opens it.
+struct ListLine<'a> {
+    indent: &'a str,
+    marker: Marker,
+    task: bool,
+    content: &'a str,
+}
+
+impl Marker {
+    fn text(&self) -> String {
+        match self {
+            Marker::Bullet(token) => token.to_string(),
+            Marker::Ordered(number, token) => format!("{}{}", number, token),
+        }
+    }
+}
+
+/// Edits after a newline typed at `position`. A list item on the line above is continued
+/// with the next marker, and a task item with an unchecked box. An empty item ends the list
+/// instead. Ordered items below the new one are renumbered.
+pub fn on_newline(text: &str, position: Position, options: &FormattingOptions) -> Vec<TextEdit> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let line = position.line as usize;
+    let Some(previous) = line.checked_sub(1) else {
+        return vec![];
+    };
+    if in_code_block(&lines[..previous.min(lines.len())]) {
+        return vec![];
+    }
+    let Some(item) = lines.get(previous).and_then(|text| parse_list_line(text)) else {
+        return vec![];
+    };
+    let current = lines.get(line).copied().unwrap_or_default();
+    let current_indent = indent_of(current).len() as u32;
+
+    if item.content.is_empty() {
+        return vec![
+            TextEdit::new(
+                Range::new(
+                    Position::new(previous as u32, 0),
+                    Position::new(previous as u32, utf16_len(lines[previous])),
+                ),
+                String::new(),
+            ),
+            TextEdit::new(
+                Range::new(
+                    Position::new(line as u32, 0),
+                    Position::new(line as u32, current_indent),
+                ),
+                String::new(),
+            ),
+        ];
+    }
+
+    let marker = match item.marker {
+        Marker::Bullet(token) => Marker::Bullet(token),
+        Marker::Ordered(number, token) if options.increment_ordered_list_bullets() => {
+            Marker::Ordered(number + 1, token)
+        }
+        Marker::Ordered(number, token) => Marker::Ordered(number, token),
+    };
+    let marker_text = marker.text();
+    let content_indent = match marker {
+        Marker::Bullet(_) => options.bullet_list_content_indent(),
+        Marker::Ordered(_, _) => options.ordered_list_content_indent(),
+    }
+    .unwrap_or(0);
+    let width = marker_text.chars().count();
+    let prefix = format!(
+        "{}{}{}{}",
+        item.indent,
+        marker_text,
+        " ".repeat(content_indent.max(width + 1) - width),
+        if item.task { "[ ] " } else { "" }
+    );
+
+    let mut edits = vec![TextEdit::new(
+        Range::new(
+            Position::new(line as u32, 0),
+            Position::new(line as u32, current_indent),
+        ),
+        prefix,
+    )];
+    if let Marker::Ordered(number, token) = marker {
+        if options.increment_ordered_list_bullets() {
+            edits.extend(renumber(&lines, line + 1, item.indent, token, number + 1));
+        }
+    }
+    edits
+}
+
+/// Edits numbering the ordered items that continue the list from `start` on with `number`,
+/// `number + 1` and so on.
+fn renumber(
+    lines: &[&str],
+    start: usize,
+    indent: &str,
+    token: char,
+    mut number: usize,
+) -> Vec<TextEdit> {
+    let mut edits = vec![];
+    for (index, text) in lines.iter().enumerate().skip(start) {
+        if text.trim().is_empty() || indent_of(text).len() > indent.len() {
+            continue;
+        }
+        let Some(ListLine {
+            indent: item_indent,
+            marker: Marker::Ordered(current, item_token),
+            ..
+        }) = parse_list_line(text)
+        else {
+            break;
+        };
+        if item_indent != indent || item_token != token {
+            break;
+        }
+        if current != number {
+            let start = indent.len() as u32;
+            edits.push(TextEdit::new(
+                Range::new(
+                    Position::new(index as u32, start),
+                    Position::new(index as u32, start + current.to_string().len() as u32),
+                ),
+                number.to_string(),
+            ));
+        }
+        number += 1;
+    }
+    edits
+}
+
+fn parse_list_line(text: &str) -> Option<ListLine<'_>> {
+    if is_thematic_break(text) {
+        return None;
+    }
+    let indent = indent_of(text);
+    let rest = &text[indent.len()..];
+
+    let (marker, rest) = match rest.chars().next()? {
+        token @ ('-' | '*' | '+') => (Marker::Bullet(token), &rest[1..]),
+        _ => {
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 || digits > 9 {
+                return None;
+            }
+            let token = rest[digits..]
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '.' | ')'))?;
+            (
+                Marker::Ordered(rest[..digits].parse().ok()?, token),
+                &rest[digits + 1..],
+            )
+        }
+    };
+    if !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+
+    let rest = rest.trim_start();
+    let (task, content) = ["[ ]", "[x]", "[X]"]
+        .iter()
+        .find_map(|checkbox| {
+            rest.strip_prefix(checkbox)
+                .filter(|after| after.is_empty() || after.starts_with(' '))
+        })
+        .map(|after| (true, after.trim()))
+        .unwrap_or((false, rest.trim()));
+
+    Some(ListLine {
+        indent,
+        marker,
+        task,
+        content,
+    })
+}
+
+/// Whether a fenced code block is left open by `lines`. A block is closed only by a fence of
+/// the opening character, at least as long and without an info string.
+fn in_code_block(lines: &[&str]) -> bool {
+    let mut open: Option<(char, usize)> = None;
+    for line in lines {
+        let Some((token, length, rest)) = fence(line) else {
+            continue;
+        };
+        match open {
+            None if !(token == '`' && rest.contains('`')) => open = Some((token, length)),
+            Some((opening, opening_length))
+                if token == opening && length >= opening_length && rest.trim().is_empty() =>
+            {
+                open = None
+            }
+            _ => {}
+        }
+    }
+    open.is_some()
+}
+
+/// The character and length of the fence `line` starts with, and what follows it.
+fn fence(line: &str) -> Option<(char, usize, &str)> {
+    let line = line.trim_start();
+    let token = line.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let length = line.chars().take_while(|c| *c == token).count();
+    (length >= 3).then(|| (token, length, &line[length..]))
+}
+
+/// Whether `text` is a line of three or more `-`, `*` or `_`, which is a break, not a list item.
+fn is_thematic_break(text: &str) -> bool {
+    let marks = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    marks.len() >= 3 && matches!(marks[0], '-' | '*' | '_') && marks.iter().all(|c| *c == marks[0])
+}
+
+fn indent_of(text: &str) -> &str {
+    &text[..text.len() - text.trim_start_matches([' ', '\t']).len()]
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
@@ -0,0 +1,114 @@
+use itertools::Itertools;
+use liwe::graph::{Graph, GraphContext};
+use liwe::model::node::Node;
+use liwe::model::tree::Tree;
+use liwe::model::{Key, NodeId};
+use lsp_types::{Range, TextEdit};
+
+use super::selection_range::{extent, lines_range, path_to};
+
+/// Edit normalizing the blocks `range` touches: paragraphs, whole lists, quotes, tables and
+/// section headers, each written as whole-document formatting would write it.
+pub fn format_range(graph: &Graph, key: &Key, range: Range) -> Vec<TextEdit> {
+    if graph.maybe_key(key).is_none() {
+        return vec![];
+    }
+    let tree = graph.collect(key);
+
+    let last_line = match range.end.character {
+        0 if range.end.line > range.start.line => range.end.line - 1,
+        _ => range.end.line,
+    };
+    let blocks = (range.start.line..=last_line)
+        .filter_map(|line| graph.get_node_id_at(key, line as usize))
+        .filter_map(|id| block_of(&tree, id))
+        .unique()
+        .collect_vec();
+    if blocks.is_empty() {
+        return vec![];
+    }
+
+    let mut patch = graph.new_patch();
+    patch.build_key(key).insert_from_iter(tree.iter());
+    let Some(formatted) = patch.export_key(key) else {
+        return vec![];
+    };
+    let mut formatted_graph = graph.new_patch();
+    formatted_graph.update_document(key.clone(), formatted.clone());
+    let formatted_tree = (&formatted_graph).collect(key);
+
+    let ids = preorder(&tree);
+    let formatted_ids = preorder(&formatted_tree);
+    if ids.len() != formatted_ids.len() {
+        return vec![];
+    }
+
+    let content = graph.get_document(key).unwrap_or_default();
+    let lines = content.lines().collect_vec();
+    let formatted_lines = formatted.lines().collect_vec();
+
+    let positions = blocks
+        .iter()
+        .filter_map(|id| ids.iter().position(|other| other == id))
+        .sorted()
+        .collect_vec();
+    let (Some(first), Some(last)) = (positions.first(), positions.last()) else {
+        return vec![];
+    };
+    let Some((start, end)) = span(graph, &tree, ids[*first], ids[*last]) else {
+        return vec![];
+    };
+    let Some((formatted_start, formatted_end)) = span(
+        &formatted_graph,
+        &formatted_tree,
+        formatted_ids[*first],
+        formatted_ids[*last],
+    ) else {
+        return vec![];
+    };
+
+    let range = lines_range(&lines, start, end);
+    let formatted_range = lines_range(&formatted_lines, formatted_start, formatted_end);
+    let new_text = formatted_lines
+        [formatted_range.start.line as usize..=formatted_range.end.line as usize]
+        .join("\n");
+    let old_text = lines[range.start.line as usize..=range.end.line as usize].join("\n");
+
+    if new_text == old_text {
+        return vec![];
+    }
+    vec![TextEdit::new(range, new_text)]
+}
+
+/// The block holding `id`: the outermost node under the sections around it, or the section
+/// itself for a header.
+fn block_of(tree: &Tree, id: NodeId) -> Option<NodeId> {
+    let path = path_to(tree, id);
+    path.iter()
+        .find(|node| !node.is_section() && !matches!(node.node, Node::Document(..)))
+        .or_else(|| path.last().filter(|node| node.is_section()))
+        .map(|node| node.id)
+}
+
+/// Lines from the start of block `first` to the end of block `last`, exclusive.
+fn span(graph: &Graph, tree: &Tree, first: NodeId, last: NodeId) -> Option<(usize, usize)> {
+    let (start, _) = block_lines(graph, &tree.find_id(first)?)?;
+    let (_, end) = block_lines(graph, &tree.find_id(last)?)?;
+    Some((start, end))
+}
+
+fn block_lines(graph: &Graph, block: &Tree) -> Option<(usize, usize)> {
+    if block.is_section() {
+        graph
+            .node_line_range(block.id)
+            .map(|range| (range.start, range.end))
+    } else {
+        extent(graph, block)
+    }
+}
+
+fn preorder(tree: &Tree) -> Vec<NodeId> {
+    std::iter::once(tree.id)
+        .chain(tree.children.iter().flat_map(preorder))
+        .collect()
+}
@@ -0,0 +1,98 @@
+use liwe::graph::{Graph, GraphContext};
+use liwe::model::tree::Tree;
+use liwe::model::{Key, NodeId};
+use lsp_types::{Position, Range, SelectionRange};
+
+/// Selection ranges at each position. Each one grows from the block under the cursor through
+/// the list items, lists, quotes and sections around it to the whole document.
+pub fn selection_ranges(graph: &Graph, key: &Key, positions: &[Position]) -> Vec<SelectionRange> {
+    let content = graph.get_document(key).unwrap_or_default();
+    let lines = content.lines().collect::<Vec<_>>();
+    let tree = graph.maybe_key(key).map(|_| graph.collect(key));
+
+    positions
+        .iter()
+        .map(|position| {
+            let mut ranges = vec![document_range(&lines)];
+            if let Some(tree) = &tree {
+                let path = graph
+                    .get_node_id_at(key, position.line as usize)
+                    .map(|id| path_to(tree, id))
+                    .unwrap_or_default();
+                ranges.extend(
+                    path.iter()
+                        .filter_map(|tree| extent(graph, tree))
+                        .map(|(start, end)| lines_range(&lines, start, end)),
+                );
+            }
+            ranges.dedup();
+
+            ranges
+                .into_iter()
+                .fold(None, |parent, range| {
+                    Some(SelectionRange {
+                        range,
+                        parent: parent.map(Box::new),
+                    })
+                })
+                .expect("document range")
+        })
+        .collect()
+}
+
+/// Trees from the document root down to `id`.
+pub fn path_to(tree: &Tree, id: NodeId) -> Vec<&Tree> {
+    if tree.id_eq(id) {
+        return vec![tree];
+    }
+    tree.children
+        .iter()
+        .find(|child| child.contains(id))
+        .map(|child| {
+            let mut path = vec![tree];
+            path.extend(path_to(child, id));
+            path
+        })
+        .unwrap_or_default()
+}
+
+/// First and last line, exclusive, of a node with everything nested in it.
+pub fn extent(graph: &Graph, tree: &Tree) -> Option<(usize, usize)> {
+    let own = graph
+        .node_line_range(tree.id)
+        .map(|range| (range.start, range.end));
+    tree.children
+        .iter()
+        .filter_map(|child| extent(graph, child))
+        .chain(own)
+        .reduce(|(start, end), (other_start, other_end)| {
+            (start.min(other_start), end.max(other_end))
+        })
+}
+
+/// Range of whole lines from `start` to `end`, exclusive, without the blank lines that end it.
+pub fn lines_range(lines: &[&str], start: usize, end: usize) -> Range {
+    let mut last = end.max(start + 1) - 1;
+    while last > start && lines.get(last).is_none_or(|line| line.trim().is_empty()) {
+        last -= 1;
+    }
+    Range::new(
+        Position::new(start as u32, 0),
+        Position::new(last as u32, line_length(lines, last)),
+    )
+}
+
+fn document_range(lines: &[&str]) -> Range {
+    let last = lines.len().saturating_sub(1);
+    Range::new(
+        Position::new(0, 0),
+        Position::new(last as u32, line_length(lines, last)),
+    )
+}
+
+fn line_length(lines: &[&str], line: usize) -> u32 {
+    lines
+        .get(line)
+        .map(|text| text.encode_utf16().count() as u32)
+        .unwrap_or(0)
+}
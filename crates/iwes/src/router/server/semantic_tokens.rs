@@ -0,0 +1,422 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use liwe::graph::{DatabaseContext, Graph};
+use liwe::model::document::{DocumentBlock, Link, LinkType};
+use liwe::model::node::{Node, NodePointer};
+use liwe::model::tree::Tree;
+use liwe::model::Key;
+use lsp_types::*;
+
+pub const LINK: SemanticTokenType = SemanticTokenType::new("link");
+pub const INCLUSION: SemanticTokenType = SemanticTokenType::new("inclusion");
+pub const HEADING: SemanticTokenType = SemanticTokenType::new("heading");
+pub const CHECKBOX: SemanticTokenType = SemanticTokenType::new("checkbox");
+
+pub const UNRESOLVED: SemanticTokenModifier = SemanticTokenModifier::new("unresolved");
+pub const WIKI: SemanticTokenModifier = SemanticTokenModifier::new("wiki");
+pub const MARKDOWN: SemanticTokenModifier = SemanticTokenModifier::new("markdown");
+pub const CHECKED: SemanticTokenModifier = SemanticTokenModifier::new("checked");
+
+const TOKEN_TYPES: [SemanticTokenType; 6] = [
+    LINK,
+    INCLUSION,
+    HEADING,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::STRING,
+    CHECKBOX,
+];
+
+const TOKEN_MODIFIERS: [SemanticTokenModifier; 10] = [
+    UNRESOLVED,
+    WIKI,
+    MARKDOWN,
+    CHECKED,
+    SemanticTokenModifier::new("level1"),
+    SemanticTokenModifier::new("level2"),
+    SemanticTokenModifier::new("level3"),
+    SemanticTokenModifier::new("level4"),
+    SemanticTokenModifier::new("level5"),
+    SemanticTokenModifier::new("level6"),
+];
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+fn token_type(token_type: &SemanticTokenType) -> u32 {
+    TOKEN_TYPES
+        .iter()
+        .position(|it| it == token_type)
+        .expect("token type in legend") as u32
+}
+
+fn modifier(modifier: &SemanticTokenModifier) -> u32 {
+    let index = TOKEN_MODIFIERS
+        .iter()
+        .position(|it| it == modifier)
+        .expect("token modifier in legend");
+    1 << index
+}
+
+fn level_modifier(level: u8) -> u32 {
+    1 << (TOKEN_MODIFIERS.len() - 6 + level.clamp(1, 6) as usize - 1)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Span {
+    line: u32,
+    start: u32,
+    end: u32,
+    token_type: u32,
+    modifiers: u32,
+}
+
+/// Semantic tokens of a document. Positions come from the parsed document, while inclusion
+/// references, link resolution and task state come from the graph.
+pub fn document_tokens(graph: &Graph, key: &Key) -> Vec<SemanticToken> {
+    let Some(parser) = graph.parser(key) else {
+        return vec![];
+    };
+    let lines = parser.content().lines().collect::<Vec<_>>();
+    let tree = graph.maybe_key(key).map(|pointer| pointer.collect_tree());
+
+    let mut reference_lines = HashSet::new();
+    let mut spans = frontmatter_spans(&lines);
+
+    if let Some(tree) = &tree {
+        collect_reference_lines(tree, &mut reference_lines);
+        collect_checkbox_spans(tree, &lines, &mut spans);
+    }
+
+    for block in parser.document().all_blocks() {
+        let is_inclusion = block.is_ref() && reference_lines.contains(&block.line_range().start);
+        for inline in block.child_inlines() {
+            for link in inline.links() {
+                if link.is_reference() {
+                    spans.extend(link_spans(graph, key, link, is_inclusion, &lines));
+                }
+            }
+        }
+    }
+
+    let headings = parser
+        .document()
+        .all_blocks()
+        .into_iter()
+        .filter_map(|block| match block {
+            DocumentBlock::Header(header) => Some(header),
+            _ => None,
+        })
+        .flat_map(|header| heading_spans(header.line_range.start, header.level, &lines, &spans))
+        .collect::<Vec<_>>();
+    spans.extend(headings);
+
+    spans.sort();
+    encode(&spans)
+}
+
+fn collect_reference_lines(tree: &Tree, lines: &mut HashSet<usize>) {
+    if let (Node::Reference(_), Some(range)) = (&tree.node, &tree.line_range) {
+        lines.insert(range.start);
+    }
+    for child in &tree.children {
+        collect_reference_lines(child, lines);
+    }
+}
+
+fn collect_checkbox_spans(tree: &Tree, lines: &[&str], spans: &mut Vec<Span>) {
+    if let (Node::Item(Some(checked), _), Some(range)) = (&tree.node, &tree.line_range) {
+        if let Some(start) = lines
+            .get(range.start)
+            .and_then(|line| checkbox_column(line))
+        {
+            spans.push(Span {
+                line: range.start as u32,
+                start,
+                end: start + 3,
+                token_type: token_type(&CHECKBOX),
+                modifiers: if *checked { modifier(&CHECKED) } else { 0 },
+            });
+        }
+    }
+    for child in &tree.children {
+        collect_checkbox_spans(child, lines, spans);
+    }
+}
+
+fn checkbox_column(line: &str) -> Option<u32> {
+    let marker = line.trim_start_matches(|c: char| c.is_whitespace() || c == '>');
+    let rest = marker
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| {
+            let digits = marker.trim_start_matches(|c: char| c.is_ascii_digit());
+            (digits.len() < marker.len())
+                .then(|| digits.strip_prefix(['.', ')']))
+                .flatten()
+        })?
+        .trim_start();
+
+    ["[ ]", "[x]", "[X]"]
+        .iter()
+        .any(|checkbox| rest.starts_with(checkbox))
+        .then(|| width(&line[..line.len() - rest.len()]))
+}
+
+fn link_spans(graph: &Graph, key: &Key, link: &Link, inclusion: bool, lines: &[&str]) -> Vec<Span> {
+    let link_key = graph.key_index().resolve_link_key(
+        &link.target.url,
+        &key.parent(),
+        link.link_type.to_ref_type(),
+    );
+
+    let mut modifiers = match link.link_type {
+        LinkType::Markdown => modifier(&MARKDOWN),
+        LinkType::WikiLink | LinkType::WikiLinkPiped => modifier(&WIKI),
+    };
+    if graph.get_document(&link_key).is_none() {
+        modifiers |= modifier(&UNRESOLVED);
+    }
+    let token_type = if inclusion {
+        token_type(&INCLUSION)
+    } else {
+        token_type(&LINK)
+    };
+
+    let range = &link.inline_range;
+    (range.start.line..=range.end.line)
+        .filter_map(|line| {
+            let text = lines.get(line)?;
+            let start = if line == range.start.line {
+                range.start.character as u32
+            } else {
+                width(text) - width(text.trim_start())
+            };
+            let end = if line == range.end.line {
+                range.end.character as u32
+            } else {
+                width(text)
+            };
+            (end > start).then_some(Span {
+                line: line as u32,
+                start,
+                end,
+                token_type,
+                modifiers,
+            })
+        })
+        .collect()
+}
+
+/// The heading line minus the parts already covered by other tokens, as tokens must not overlap.
+fn heading_spans(line: usize, level: u8, lines: &[&str], taken: &[Span]) -> Vec<Span> {
+    let Some(text) = lines.get(line) else {
+        return vec![];
+    };
+    let mut start = width(text) - width(text.trim_start());
+    let end = width(text.trim_end());
+
+    let mut covered = taken
+        .iter()
+        .filter(|span| span.line == line as u32)
+        .map(|span| (span.start, span.end))
+        .collect::<Vec<_>>();
+    covered.sort();
+    covered.push((end, end));
+
+    let mut spans = vec![];
+    for (covered_start, covered_end) in covered {
+        let segment_end = covered_start.min(end);
+        if segment_end > start {
+            spans.push(Span {
+                line: line as u32,
+                start,
+                end: segment_end,
+                token_type: token_type(&HEADING),
+                modifiers: level_modifier(level),
+            });
+        }
+        start = start.max(covered_end);
+    }
+    spans
+}
+
+fn frontmatter_spans(lines: &[&str]) -> Vec<Span> {
+    if lines.first().map(|line| line.trim_end()) != Some("---") {
+        return vec![];
+    }
+
+    let mut spans = vec![];
+    for (index, line) in lines.iter().enumerate().skip(1) {
+        if matches!(line.trim_end(), "---" | "...") {
+            break;
+        }
+        let content = line.trim_start().trim_end();
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        let mut column = width(line) - width(line.trim_start());
+        let mut entry = content;
+        if let Some(item) = entry.strip_prefix("- ") {
+            column += width(&entry[..entry.len() - item.trim_start().len()]);
+            entry = item.trim_start();
+        }
+
+        let separator = entry
+            .find(": ")
+            .or_else(|| entry.ends_with(':').then(|| entry.len() - 1))
+            .filter(|_| !entry.starts_with(['"', '\'', '[', '{']));
+
+        let mut push = |start: u32, text: &str, token: &SemanticTokenType| {
+            if !text.is_empty() {
+                spans.push(Span {
+                    line: index as u32,
+                    start,
+                    end: start + width(text),
+                    token_type: token_type(token),
+                    modifiers: 0,
+                });
+            }
+        };
+
+        match separator {
+            Some(separator) => {
+                push(
+                    column,
+                    entry[..separator].trim_end(),
+                    &SemanticTokenType::PROPERTY,
+                );
+                let value = &entry[separator + 1..];
+                let offset = width(&entry[..entry.len() - value.trim_start().len()]);
+                push(column + offset, value.trim(), &SemanticTokenType::STRING);
+            }
+            None => push(column, entry, &SemanticTokenType::STRING),
+        }
+    }
+    spans
+}
+
+fn width(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+fn encode(spans: &[Span]) -> Vec<SemanticToken> {
+    let mut previous_line = 0;
+    let mut previous_start = 0;
+
+    spans
+        .iter()
+        .map(|span| {
+            let delta_line = span.line - previous_line;
+            let delta_start = if delta_line == 0 {
+                span.start - previous_start
+            } else {
+                span.start
+            };
+            previous_line = span.line;
+            previous_start = span.start;
+
+            SemanticToken {
+                delta_line,
+                delta_start,
+                length: span.end - span.start,
+                token_type: span.token_type,
+                token_modifiers_bitset: span.modifiers,
+            }
+        })
+        .collect()
+}
+
+/// Tokens last sent for each document, so that delta requests can be answered with edits.
+#[derive(Default)]
+pub struct SemanticTokensCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    next_id: u64,
+    documents: HashMap<Key, (String, Vec<SemanticToken>)>,
+}
+
+impl SemanticTokensCache {
+    pub fn full(&self, key: Key, tokens: Vec<SemanticToken>) -> SemanticTokens {
+        let mut state = self.state.lock().expect("semantic tokens cache lock");
+        SemanticTokens {
+            result_id: Some(state.store(key, tokens.clone())),
+            data: tokens,
+        }
+    }
+
+    pub fn delta(
+        &self,
+        key: Key,
+        previous_result_id: &str,
+        tokens: Vec<SemanticToken>,
+    ) -> SemanticTokensFullDeltaResult {
+        let mut state = self.state.lock().expect("semantic tokens cache lock");
+        let previous = state
+            .documents
+            .get(&key)
+            .filter(|(result_id, _)| result_id == previous_result_id)
+            .map(|(_, previous)| previous.clone());
+        let result_id = state.store(key, tokens.clone());
+
+        match previous {
+            Some(previous) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                result_id: Some(result_id),
+                edits: edits(&previous, &tokens),
+            }),
+            None => SemanticTokensFullDeltaResult::Tokens(SemanticTokens {
+                result_id: Some(result_id),
+                data: tokens,
+            }),
+        }
+    }
+
+    pub fn forget(&self, key: &Key) {
+        let mut state = self.state.lock().expect("semantic tokens cache lock");
+        state.documents.remove(key);
+    }
+}
+
+impl CacheState {
+    fn store(&mut self, key: Key, tokens: Vec<SemanticToken>) -> String {
+        self.next_id += 1;
+        let result_id = self.next_id.to_string();
+        self.documents.insert(key, (result_id.clone(), tokens));
+        result_id
+    }
+}
+
+/// A single edit replacing the tokens between the common prefix and suffix. Edit offsets count
+/// integers in the encoded array, five per token.
+fn edits(previous: &[SemanticToken], current: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let deleted = previous.len() - prefix - suffix;
+    let inserted = &current[prefix..current.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return vec![];
+    }
+
+    vec![SemanticTokensEdit {
+        start: (prefix * 5) as u32,
+        delete_count: (deleted * 5) as u32,
+        data: Some(inserted.to_vec()),
+    }]
+}
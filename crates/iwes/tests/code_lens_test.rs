@@ -0,0 +1,182 @@
+use indoc::indoc;
+use lsp_types::{
+    request::{CodeLensRequest, CodeLensResolve},
+    CodeLens, CodeLensParams, Command, Location, Position, Range,
+};
+use serde_json::json;
+
+use crate::fixture::*;
+
+fn code_lens_params(key: u32) -> CodeLensParams {
+    CodeLensParams {
+        text_document: lsp_types::TextDocumentIdentifier { uri: uri(key) },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+fn lens(key: &str, line: u32, kind: &str) -> CodeLens {
+    CodeLens {
+        range: Range::new(Position::new(line, 0), Position::new(line, 0)),
+        command: None,
+        data: Some(json!({ "uri": uri_from(key).to_string(), "key": key, "lens": kind })),
+    }
+}
+
+fn resolved(unresolved: CodeLens, title: &str, key: u32, locations: Vec<Location>) -> CodeLens {
+    let position = unresolved.range.start;
+    CodeLens {
+        command: Some(Command {
+            title: title.to_string(),
+            command: "editor.action.showReferences".to_string(),
+            arguments: Some(vec![
+                json!(uri(key).to_string()),
+                serde_json::to_value(position).unwrap(),
+                serde_json::to_value(locations).unwrap(),
+            ]),
+        }),
+        ..unresolved
+    }
+}
+
+fn location(key: u32, start: u32, end: u32) -> Location {
+    Location::new(
+        uri(key),
+        Range::new(Position::new(start, 0), Position::new(end, 0)),
+    )
+}
+
+#[test]
+fn lenses_on_header_and_sections_with_children() {
+    Fixture::with(indoc! {"
+        # test
+
+        [two](2)
+
+        ## notes
+
+        text
+
+        ## more
+
+        [three](3)
+        _
+        # two
+        _
+        # three
+    "})
+    .assert_response::<CodeLensRequest>(
+        code_lens_params(1),
+        Some(vec![
+            lens("1", 0, "backlinks"),
+            lens("1", 0, "includedBy"),
+            lens("1", 0, "children"),
+            lens("1", 8, "children"),
+        ]),
+    );
+}
+
+#[test]
+fn lenses_without_header() {
+    Fixture::with(indoc! {"
+        text
+    "})
+    .assert_response::<CodeLensRequest>(
+        code_lens_params(1),
+        Some(vec![lens("1", 0, "backlinks"), lens("1", 0, "includedBy")]),
+    );
+}
+
+#[test]
+fn resolve_backlinks() {
+    Fixture::with(indoc! {"
+        # test
+        _
+        # two
+
+        [test](1)
+        _
+        # three
+
+        See [test](1) and [[1]].
+    "})
+    .assert_response::<CodeLensResolve>(
+        lens("1", 0, "backlinks"),
+        resolved(
+            lens("1", 0, "backlinks"),
+            "2 backlinks",
+            1,
+            vec![location(2, 2, 3), location(3, 2, 3)],
+        ),
+    );
+}
+
+#[test]
+fn resolve_included_by() {
+    Fixture::with(indoc! {"
+        # test
+        _
+        # Index
+
+        [test](1)
+        _
+        # Journal
+
+        [test](1)
+    "})
+    .assert_response::<CodeLensResolve>(
+        lens("1", 0, "includedBy"),
+        resolved(
+            lens("1", 0, "includedBy"),
+            "included by Index, Journal",
+            1,
+            vec![location(2, 2, 3), location(3, 2, 3)],
+        ),
+    );
+}
+
+#[test]
+fn resolve_not_included() {
+    Fixture::with(indoc! {"
+        # test
+    "})
+    .assert_response::<CodeLensResolve>(
+        lens("1", 0, "includedBy"),
+        CodeLens {
+            command: Some(Command {
+                title: "not included".to_string(),
+                command: "".to_string(),
+                arguments: None,
+            }),
+            ..lens("1", 0, "includedBy")
+        },
+    );
+}
+
+#[test]
+fn resolve_section_children() {
+    Fixture::with(indoc! {"
+        # test
+
+        ## first
+
+        [two](2)
+
+        ## second
+
+        [three](3)
+        _
+        # two
+        _
+        # three
+    "})
+    .assert_response::<CodeLensResolve>(
+        lens("1", 2, "children"),
+        resolved(
+            lens("1", 2, "children"),
+            "1 child",
+            1,
+            vec![Location::new(uri(2), Range::default())],
+        ),
+    );
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use lsp_types::{
+    notification::ShowMessage, request::DocumentLinkRequest, DocumentLinkParams, MessageType,
+    TextDocumentIdentifier,
+};
+use serde_json::Value;
+
+use crate::fixture::*;
+
+const NOTE: &str = "---\ntitle: Custom Title\n---\n\n# Header Title\n";
+const LINKS: &str = "# Links\n\n[note](note)\n";
+
+fn link_tooltip(fixture: &Fixture) -> Value {
+    let links = fixture.send_request::<DocumentLinkRequest>(DocumentLinkParams {
+        text_document: TextDocumentIdentifier {
+            uri: fixture.workspace_uri("links"),
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    });
+    links[0]["tooltip"].clone()
+}
+
+fn wait_for_tooltip(fixture: &Fixture, expected: &str) {
+    for _ in 0..200 {
+        if link_tooltip(fixture) == expected {
+            return;
+        }
+        std::thread::sleep(Duration::from_millis(25));
+    }
+    assert_eq!(link_tooltip(fixture), expected);
+}
+
+#[test]
+fn reparses_documents_when_the_title_field_changes() {
+    let fixture = Fixture::with_workspace_config(
+        vec![("note", NOTE), ("links", LINKS)],
+        Some("version = 3\n"),
+    );
+    wait_for_tooltip(&fixture, "Header Title");
+
+    fixture.write_config("version = 3\n\n[library]\nfrontmatter_document_title = \"title\"\n");
+
+    wait_for_tooltip(&fixture, "Custom Title");
+}
+
+#[test]
+fn reports_invalid_configuration_and_keeps_the_previous_one() {
+    let fixture = Fixture::with_workspace_config(
+        vec![("note", NOTE), ("links", LINKS)],
+        Some("version = 3\n"),
+    );
+    wait_for_tooltip(&fixture, "Header Title");
+
+    fixture.write_config("version = 3\n\n[library\n");
+
+    let message = fixture.next_notification::<ShowMessage>();
+    assert_eq!(message.typ, MessageType::ERROR);
+    assert!(
+        message
+            .message
+            .starts_with("IWE: configuration not reloaded: "),
+        "{}",
+        message.message
+    );
+    assert_eq!(link_tooltip(&fixture), "Header Title");
+}
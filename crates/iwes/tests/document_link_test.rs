@@ -0,0 +1,154 @@
+use std::str::FromStr;
+
+use indoc::indoc;
+use lsp_types::{
+    request::DocumentLinkRequest, DocumentLink, DocumentLinkParams, Position, Range,
+    TextDocumentIdentifier, Uri,
+};
+
+use crate::fixture::*;
+
+fn params(key: u32) -> DocumentLinkParams {
+    DocumentLinkParams {
+        text_document: TextDocumentIdentifier { uri: uri(key) },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+fn link(line: u32, start: u32, end: u32, target: Uri, tooltip: Option<&str>) -> DocumentLink {
+    DocumentLink {
+        range: Range::new(Position::new(line, start), Position::new(line, end)),
+        target: Some(target),
+        tooltip: tooltip.map(|tooltip| tooltip.to_string()),
+        data: None,
+    }
+}
+
+fn at_line(uri: Uri, line: u32) -> Uri {
+    Uri::from_str(&format!("{}#L{}", uri.as_str(), line)).unwrap()
+}
+
+#[test]
+fn note_links_with_titles() {
+    Fixture::with(indoc! {"
+        # test
+
+        See [two](2) and [[2]] or [gone](gone).
+        _
+        # Second
+    "})
+    .assert_response::<DocumentLinkRequest>(
+        params(1),
+        Some(vec![
+            link(2, 4, 12, uri(2), Some("Second")),
+            link(2, 17, 22, uri(2), Some("Second")),
+            link(2, 26, 38, uri_from("gone"), Some("Missing document: gone")),
+        ]),
+    );
+}
+
+#[test]
+fn section_fragments() {
+    Fixture::with(indoc! {"
+        # test
+
+        [a](2#next-steps) [[2#Next Steps]] [b](2#nowhere) [c](#test)
+        _
+        # Second
+
+        ## Next Steps
+    "})
+    .assert_response::<DocumentLinkRequest>(
+        params(1),
+        Some(vec![
+            link(2, 0, 17, at_line(uri(2), 3), Some("Second › Next Steps")),
+            link(2, 18, 34, at_line(uri(2), 3), Some("Second › Next Steps")),
+            link(2, 35, 49, uri(2), Some("Second: missing section 'nowhere'")),
+            link(2, 50, 60, at_line(uri(1), 1), Some("test › test")),
+        ]),
+    );
+}
+
+#[test]
+fn asset_paths() {
+    Fixture::with(indoc! {"
+        # test
+
+        ![chart](images/chart.png) [spec](docs/spec.pdf)
+    "})
+    .assert_response::<DocumentLinkRequest>(
+        params(1),
+        Some(vec![
+            link(
+                2,
+                0,
+                26,
+                file_uri("images/chart.png"),
+                Some("Missing file: images/chart.png"),
+            ),
+            link(
+                2,
+                27,
+                48,
+                file_uri("docs/spec.pdf"),
+                Some("Missing file: docs/spec.pdf"),
+            ),
+        ]),
+    );
+}
+
+#[test]
+fn note_keys_win_over_asset_extensions() {
+    Fixture::with_documents(vec![
+        (
+            "index",
+            "# Index\n\n[report](report.pdf) [spec](spec.pdf)\n",
+        ),
+        ("report.pdf", "# Report notes\n"),
+    ])
+    .assert_response::<DocumentLinkRequest>(
+        DocumentLinkParams {
+            text_document: TextDocumentIdentifier {
+                uri: uri_from("index"),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        Some(vec![
+            link(2, 0, 20, uri_from("report.pdf"), Some("Report notes")),
+            link(
+                2,
+                21,
+                37,
+                file_uri("spec.pdf"),
+                Some("Missing file: spec.pdf"),
+            ),
+        ]),
+    );
+}
+
+#[test]
+fn external_and_bare_urls() {
+    Fixture::with(indoc! {"
+        # test
+
+        [site](https://example.com) and https://bare.org or mailto:a@b.org
+
+        `https://code.org`
+    "})
+    .assert_response::<DocumentLinkRequest>(
+        params(1),
+        Some(vec![
+            link(
+                2,
+                0,
+                27,
+                Uri::from_str("https://example.com").unwrap(),
+                None,
+            ),
+            link(2, 32, 48, Uri::from_str("https://bare.org").unwrap(), None),
+            link(2, 52, 66, Uri::from_str("mailto:a@b.org").unwrap(), None),
+        ]),
+    );
+}
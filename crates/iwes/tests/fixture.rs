@@ -15,7 +15,7 @@ use assert_json_diff::assert_json_eq;
 use crossbeam_channel::{after, select, Receiver};
 use diwe::config::Configuration;
 use liwe::state::from_indoc;
-use lsp_server::{Connection, Message, Notification, Request, ResponseError};
+use lsp_server::{Connection, Message, Notification, Request, RequestId, ResponseError};
 use lsp_types::{notification::*, request::*, *};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -24,12 +24,15 @@ use tempfile::TempDir;
 use std::time::SystemTime;
 
 use diwe::config::MarkdownOptions;
-use iwes::{main_loop, ServerParams};
+use iwes::{main_loop, FolderParams, ServerParams};
 
 pub struct Fixture {
     req_id: Cell<i32>,
     messages: RefCell<Vec<Message>>,
     last_show_document_uri: RefCell<Option<String>>,
+    last_apply_edit: RefCell<Option<Value>>,
+    applies_edits: Cell<bool>,
+    last_message_request: RefCell<Option<Request>>,
     client: Connection,
     _thread: std::thread::JoinHandle<()>,
     workspace_dir: Option<TempDir>,
@@ -358,6 +361,23 @@ fn write_workspace_doc(root: &std::path::Path, key: &str, content: &str) {
     std::fs::write(path, content).expect("write doc");
 }
 
+pub fn folder_base_path(folder: &str) -> String {
+    if cfg!(windows) {
+        format!("C:/{folder}")
+    } else {
+        format!("/{folder}")
+    }
+}
+
+pub fn folder_uri(folder: &str, key: &str) -> Uri {
+    let prefix = if cfg!(windows) {
+        "file:///c:/"
+    } else {
+        "file:///"
+    };
+    Uri::from_str(&format!("{prefix}{folder}/{key}.md")).unwrap()
+}
+
 pub fn file_uri(rel: &str) -> Uri {
     Uri::from_str(&format!("{}{}", base_uri_prefix(), rel)).unwrap()
 }
@@ -522,6 +542,9 @@ impl Fixture {
                         client_name,
                         sequential_ids: Some(true),
                         base_path: server_base_path(),
+                        folder: None,
+                        workspace_folders: vec![],
+                        folder_errors: vec![],
                         configuration,
                         override_now,
                         watch_poll_interval: None,
@@ -535,6 +558,73 @@ impl Fixture {
             req_id: Cell::new(1),
             messages: Default::default(),
             last_show_document_uri: RefCell::new(None),
+            last_apply_edit: RefCell::new(None),
+            applies_edits: Cell::new(true),
+            last_message_request: RefCell::new(None),
+            client,
+            _thread,
+            workspace_dir: None,
+        }
+    }
+
+    /// A workspace of in-memory libraries, one per `(folder, configuration, documents)`; the
+    /// first is the primary one.
+    pub fn with_folders(folders: Vec<(&str, Configuration, &str)>) -> Fixture {
+        Self::with_folders_and_errors(folders, vec![])
+    }
+
+    /// A workspace of in-memory libraries started with `folder_errors` for the folders that
+    /// failed to open.
+    pub fn with_folders_and_errors(
+        folders: Vec<(&str, Configuration, &str)>,
+        folder_errors: Vec<&str>,
+    ) -> Fixture {
+        let folder_errors = folder_errors
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let mut folders = folders
+            .into_iter()
+            .map(|(folder, configuration, indoc)| FolderParams {
+                state: Some(from_indoc(indoc)),
+                configuration,
+                base_path: folder_base_path(folder),
+                folder: None,
+            })
+            .collect::<Vec<_>>();
+        let primary = folders.remove(0);
+
+        let (connection, client) = Connection::memory();
+
+        let _thread: std::thread::JoinHandle<()> = std::thread::Builder::new()
+            .name("test workspace folders server".to_owned())
+            .spawn(move || {
+                main_loop(
+                    connection,
+                    ServerParams {
+                        state: primary.state,
+                        client_name: Some(String::new()),
+                        sequential_ids: Some(true),
+                        base_path: primary.base_path,
+                        folder: primary.folder,
+                        workspace_folders: folders,
+                        folder_errors,
+                        configuration: primary.configuration,
+                        override_now: None,
+                        watch_poll_interval: Some(Duration::from_millis(10)),
+                    },
+                )
+                .unwrap()
+            })
+            .expect("failed to spawn a thread");
+
+        Fixture {
+            req_id: Cell::new(1),
+            messages: Default::default(),
+            last_show_document_uri: RefCell::new(None),
+            last_apply_edit: RefCell::new(None),
+            applies_edits: Cell::new(true),
+            last_message_request: RefCell::new(None),
             client,
             _thread,
             workspace_dir: None,
@@ -542,12 +632,22 @@ impl Fixture {
     }
 
     pub fn with_workspace(files: Vec<(&str, &str)>) -> Fixture {
+        Self::with_workspace_config(files, None)
+    }
+
+    /// A workspace on disk, with `.iwe/config.toml` holding `config` when given.
+    pub fn with_workspace_config(files: Vec<(&str, &str)>, config: Option<&str>) -> Fixture {
         let dir = tempfile::tempdir().expect("temp dir");
         let base_path = dir.path().to_string_lossy().to_string();
 
         for (key, content) in &files {
             write_workspace_doc(dir.path(), key, content);
         }
+        if let Some(config) = config {
+            std::fs::create_dir_all(dir.path().join(".iwe")).expect("create config dir");
+            std::fs::write(dir.path().join(".iwe").join("config.toml"), config)
+                .expect("write config");
+        }
 
         let (connection, client) = Connection::memory();
 
@@ -560,7 +660,10 @@ impl Fixture {
                         state: None,
                         client_name: Some(String::new()),
                         sequential_ids: Some(true),
+                        folder: Some(base_path.clone()),
                         base_path,
+                        workspace_folders: vec![],
+                        folder_errors: vec![],
                         configuration: Configuration::default(),
                         override_now: None,
                         watch_poll_interval: Some(Duration::from_millis(10)),
@@ -574,6 +677,9 @@ impl Fixture {
             req_id: Cell::new(1),
             messages: Default::default(),
             last_show_document_uri: RefCell::new(None),
+            last_apply_edit: RefCell::new(None),
+            applies_edits: Cell::new(true),
+            last_message_request: RefCell::new(None),
             client,
             _thread,
             workspace_dir: Some(dir),
@@ -592,6 +698,14 @@ impl Fixture {
         write_workspace_doc(&self.workspace_root(), key, content);
     }
 
+    pub fn write_config(&self, config: &str) {
+        std::fs::write(
+            self.workspace_root().join(".iwe").join("config.toml"),
+            config,
+        )
+        .expect("write config");
+    }
+
     pub fn remove_doc(&self, key: &str) {
         std::fs::remove_file(self.workspace_root().join(format!("{key}.md"))).expect("remove doc");
     }
@@ -698,6 +812,25 @@ impl Fixture {
         panic!("never got expected notification");
     }
 
+    /// Params of the next notification the server sends with `N`'s method, skipping others.
+    pub fn next_notification<N>(&self) -> N::Params
+    where
+        N: lsp_types::notification::Notification,
+        N::Params: serde::de::DeserializeOwned,
+    {
+        loop {
+            match recv_timeout(&self.client.receiver).unwrap_or_else(|_| panic!("timed out")) {
+                Some(Message::Notification(notification)) if notification.method == N::METHOD => {
+                    return notification
+                        .extract::<N::Params>(N::METHOD)
+                        .expect("was not able to extract notification");
+                }
+                Some(_) => continue,
+                None => panic!("server disconnected"),
+            }
+        }
+    }
+
     pub fn request<R>(&self, params: R::Params, expected_resp: Value)
     where
         R: lsp_types::request::Request,
@@ -806,6 +939,7 @@ impl Fixture {
     pub fn code_action(&self, params: CodeActionParams, expected: CodeAction) -> &Self {
         let mut expected_no_edits = expected.clone();
         expected_no_edits.edit.take();
+        expected_no_edits.command.take();
 
         let actual: Value = self.send_request::<CodeActionRequest>(params);
         let actual_action = actual.as_array().unwrap().first().unwrap();
@@ -862,6 +996,120 @@ impl Fixture {
         self
     }
 
+    /// Runs `workspace/executeCommand`; returns the edit the server asked the client to apply
+    /// and the URI it asked it to show.
+    pub fn execute_command(
+        &self,
+        command: &str,
+        arguments: Vec<Value>,
+    ) -> (Option<Value>, Option<String>) {
+        let (result, edit, shown) = self.execute_command_with_result(command, arguments);
+        assert_json_eq!(&Value::Null, &result);
+        (edit, shown)
+    }
+
+    /// The result of a command, the edit it applied and the URI it showed.
+    pub fn execute_command_with_result(
+        &self,
+        command: &str,
+        arguments: Vec<Value>,
+    ) -> (Value, Option<Value>, Option<String>) {
+        *self.last_show_document_uri.borrow_mut() = None;
+        *self.last_apply_edit.borrow_mut() = None;
+        let result = self.send_request::<ExecuteCommand>(ExecuteCommandParams {
+            command: command.to_string(),
+            arguments,
+            work_done_progress_params: Default::default(),
+        });
+        (
+            result,
+            self.last_apply_edit.borrow().clone(),
+            self.last_show_document_uri.borrow().clone(),
+        )
+    }
+
+    /// Answers the next `workspace/applyEdit` requests as not applied.
+    pub fn rejecting_edits(self) -> Self {
+        self.applies_edits.set(false);
+        self
+    }
+
+    fn answer_apply_edit(&self, id: RequestId) {
+        let result = ApplyWorkspaceEditResponse {
+            applied: self.applies_edits.get(),
+            failure_reason: None,
+            failed_change: None,
+        };
+        self.client
+            .sender
+            .send(Message::Response(lsp_server::Response::new_ok(id, result)))
+            .unwrap();
+    }
+
+    /// The URI of the next `window/showDocument` request, or `None` when the server shows a
+    /// message instead.
+    pub fn next_shown_document(&self) -> Option<String> {
+        loop {
+            match recv_timeout(&self.client.receiver).unwrap_or_else(|_| panic!("timed out")) {
+                Some(Message::Request(req)) if req.method == "window/showDocument" => {
+                    return req
+                        .params
+                        .get("uri")
+                        .and_then(|uri| uri.as_str())
+                        .map(str::to_string);
+                }
+                Some(Message::Notification(notification))
+                    if notification.method == "window/showMessage" =>
+                {
+                    return None;
+                }
+                Some(_) => continue,
+                None => panic!("server disconnected"),
+            }
+        }
+    }
+
+    /// Params of the last `window/showMessageRequest` the server sent.
+    pub fn message_request(&self) -> Option<Value> {
+        self.last_message_request
+            .borrow()
+            .as_ref()
+            .map(|request| request.params.clone())
+    }
+
+    /// Answers the last `window/showMessageRequest` with the action titled `title`, returning the
+    /// edit the server applies in response.
+    pub fn answer_message_request(&self, title: &str) -> Option<Value> {
+        let request = self
+            .last_message_request
+            .borrow_mut()
+            .take()
+            .expect("a message request");
+        self.client
+            .sender
+            .send(Message::Response(lsp_server::Response {
+                id: request.id,
+                result: Some(serde_json::json!({ "title": title })),
+                error: None,
+            }))
+            .unwrap();
+
+        loop {
+            match self.recv().unwrap_or_else(|_| panic!("timed out")) {
+                Some(Message::Request(req)) if req.method == "workspace/applyEdit" => {
+                    return req.params.get("edit").cloned();
+                }
+                Some(Message::Notification(notification))
+                    if notification.method == "window/showMessage" =>
+                {
+                    return None;
+                }
+                Some(_) => continue,
+                None => panic!("server disconnected"),
+            }
+        }
+    }
+
     pub fn did_change_text_document(&self, params: DidChangeTextDocumentParams) -> &Self {
         self.notification::<DidChangeTextDocument>(params);
         self
@@ -872,6 +1120,12 @@ impl Fixture {
         self
     }
 
+    /// The lint diagnostics the server publishes for `uri` when it is saved.
+    pub fn published_diagnostics(&self, uri: Uri) -> Vec<Diagnostic> {
+        self.did_save_text_document(uri.to_did_save_params(None));
+        self.next_notification::<PublishDiagnostics>().diagnostics
+    }
+
     pub fn did_delete_files(&self, params: DidChangeWatchedFilesParams) -> &Self {
         self.notification::<DidChangeWatchedFiles>(params);
         self
@@ -936,6 +1190,15 @@ impl Fixture {
                         }
                         continue;
                     }
+                    if req.method == "workspace/applyEdit" {
+                        *self.last_apply_edit.borrow_mut() = req.params.get("edit").cloned();
+                        self.answer_apply_edit(req.id);
+                        continue;
+                    }
+                    if req.method == "window/showMessageRequest" {
+                        *self.last_message_request.borrow_mut() = Some(req);
+                        continue;
+                    }
                     panic!("unexpected request: {req:?}")
                 }
                 Message::Notification(_) => (),
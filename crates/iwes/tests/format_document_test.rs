@@ -206,6 +206,48 @@ fn format_document_not_in_graph_returns_no_edits() {
     Fixture::with("# test\n").format_document(uri(999).to_document_formatting_params(), vec![]);
 }
 
+#[test]
+fn refresh_query_block_results() {
+    assert_formatted(
+        indoc! {"
+            # index
+
+            ``` iwe-query
+            filter:
+              status: active
+            ```
+
+            <!-- iwe-query:begin -->
+
+            - [stale](3)
+
+            <!-- iwe-query:end -->
+            _
+            ---
+            status: active
+            ---
+
+            # project
+            _
+            # archived
+            "},
+        indoc! {"
+            # index
+
+            ``` iwe-query
+            filter:
+              status: active
+            ```
+
+            <!-- iwe-query:begin -->
+
+            - [project](2)
+
+            <!-- iwe-query:end -->
+        "},
+    );
+}
+
 fn assert_formatted(source: &str, formatted: &str) {
     Fixture::with(source).format_document(
         uri(1).to_document_formatting_params(),
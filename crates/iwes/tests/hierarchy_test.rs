@@ -0,0 +1,185 @@
+use indoc::indoc;
+use lsp_types::{
+    request::{
+        CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+        TypeHierarchyPrepare, TypeHierarchySubtypes, TypeHierarchySupertypes,
+    },
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    Position, Range, SymbolKind, TextDocumentIdentifier, TextDocumentPositionParams,
+    TypeHierarchyItem, TypeHierarchyPrepareParams, TypeHierarchySubtypesParams,
+    TypeHierarchySupertypesParams,
+};
+use serde_json::json;
+
+use crate::fixture::*;
+
+fn position_params(key: u32, line: u32, character: u32) -> TextDocumentPositionParams {
+    TextDocumentPositionParams {
+        text_document: TextDocumentIdentifier { uri: uri(key) },
+        position: Position::new(line, character),
+    }
+}
+
+fn call_item(key: u32, name: &str) -> CallHierarchyItem {
+    CallHierarchyItem {
+        name: name.to_string(),
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: Some(key.to_string()),
+        uri: uri(key),
+        range: Range::default(),
+        selection_range: Range::default(),
+        data: Some(json!({ "key": key.to_string() })),
+    }
+}
+
+fn type_item(key: u32, name: &str) -> TypeHierarchyItem {
+    TypeHierarchyItem {
+        name: name.to_string(),
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: Some(key.to_string()),
+        uri: uri(key),
+        range: Range::default(),
+        selection_range: Range::default(),
+        data: Some(json!({ "key": key.to_string() })),
+    }
+}
+
+fn lines(start: u32, end: u32) -> Range {
+    Range::new(Position::new(start, 0), Position::new(end, 0))
+}
+
+fn structure() -> Fixture {
+    Fixture::with(indoc! {"
+        # Index
+
+        [Projects](2)
+
+        See [Archive](4).
+        _
+        # Projects
+
+        [Garden](3)
+
+        [Garden](3)
+        _
+        # Garden
+
+        Back to [[2]].
+        _
+        # Archive
+
+        [Garden](3)
+    "})
+}
+
+#[test]
+fn prepare_call_hierarchy_for_current_document() {
+    structure().assert_response::<CallHierarchyPrepare>(
+        CallHierarchyPrepareParams {
+            text_document_position_params: position_params(1, 0, 0),
+            work_done_progress_params: Default::default(),
+        },
+        Some(vec![call_item(1, "Index")]),
+    );
+}
+
+#[test]
+fn prepare_call_hierarchy_for_link_under_cursor() {
+    structure().assert_response::<CallHierarchyPrepare>(
+        CallHierarchyPrepareParams {
+            text_document_position_params: position_params(1, 2, 3),
+            work_done_progress_params: Default::default(),
+        },
+        Some(vec![call_item(2, "Projects")]),
+    );
+}
+
+#[test]
+fn incoming_calls_are_inclusion_parents() {
+    structure().assert_response::<CallHierarchyIncomingCalls>(
+        CallHierarchyIncomingCallsParams {
+            item: call_item(3, "Garden"),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        Some(vec![
+            CallHierarchyIncomingCall {
+                from: call_item(2, "Projects"),
+                from_ranges: vec![lines(2, 3), lines(4, 5)],
+            },
+            CallHierarchyIncomingCall {
+                from: call_item(4, "Archive"),
+                from_ranges: vec![lines(2, 3)],
+            },
+        ]),
+    );
+}
+
+#[test]
+fn outgoing_calls_are_inclusion_children() {
+    structure().assert_response::<CallHierarchyOutgoingCalls>(
+        CallHierarchyOutgoingCallsParams {
+            item: call_item(1, "Index"),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        Some(vec![CallHierarchyOutgoingCall {
+            to: call_item(2, "Projects"),
+            from_ranges: vec![lines(2, 3)],
+        }]),
+    );
+}
+
+#[test]
+fn prepare_type_hierarchy() {
+    structure().assert_response::<TypeHierarchyPrepare>(
+        TypeHierarchyPrepareParams {
+            text_document_position_params: position_params(3, 0, 0),
+            work_done_progress_params: Default::default(),
+        },
+        Some(vec![type_item(3, "Garden")]),
+    );
+}
+
+#[test]
+fn supertypes_are_referencing_documents() {
+    structure().assert_response::<TypeHierarchySupertypes>(
+        TypeHierarchySupertypesParams {
+            item: type_item(2, "Projects"),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        Some(vec![type_item(3, "Garden")]),
+    );
+}
+
+#[test]
+fn subtypes_are_referenced_documents() {
+    structure().assert_response::<TypeHierarchySubtypes>(
+        TypeHierarchySubtypesParams {
+            item: type_item(1, "Index"),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        Some(vec![type_item(4, "Archive")]),
+    );
+}
+
+#[test]
+fn prepare_for_missing_link_target() {
+    Fixture::with(indoc! {"
+        # test
+
+        [missing](missing)
+    "})
+    .assert_response::<CallHierarchyPrepare>(
+        CallHierarchyPrepareParams {
+            text_document_position_params: position_params(1, 2, 3),
+            work_done_progress_params: Default::default(),
+        },
+        None,
+    );
+}
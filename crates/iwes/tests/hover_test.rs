@@ -162,3 +162,51 @@ fn hover_markdown_link_resolves_relative_to_current_directory() {
         }),
     );
 }
+
+#[test]
+fn hover_external_link_previews_url() {
+    Fixture::with_documents(vec![("note", "[site](https://example.com/page)\n")])
+        .assert_response::<HoverRequest>(
+            uri_from("note").to_hover_params(0, 3),
+            Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: "[https://example.com/page](https://example.com/page)".to_string(),
+                }),
+                range: None,
+            }),
+        );
+}
+
+#[test]
+fn hover_missing_asset_reports_path() {
+    Fixture::with_documents(vec![("note", "[spec](docs/spec.pdf)\n")])
+        .assert_response::<HoverRequest>(
+            uri_from("note").to_hover_params(0, 3),
+            Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: "**Missing file:** docs/spec.pdf".to_string(),
+                }),
+                range: None,
+            }),
+        );
+}
+
+#[test]
+fn hover_prefers_a_note_over_an_asset_extension() {
+    Fixture::with_documents(vec![
+        ("note", "[report](report.pdf)\n"),
+        ("report.pdf", "# Report notes\n\nSummary.\n"),
+    ])
+    .assert_response::<HoverRequest>(
+        uri_from("note").to_hover_params(0, 3),
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "# Report notes\n\nSummary.\n".to_string(),
+            }),
+            range: None,
+        }),
+    );
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use chrono::{Local, TimeZone};
+use diwe::config::{Configuration, JournalOptions, PeriodOptions};
+use indoc::indoc;
+use lsp_types::{CodeAction, Command, Uri};
+use serde_json::json;
+
+use crate::fixture::*;
+
+fn fixed_now() -> SystemTime {
+    Local
+        .with_ymd_and_hms(2026, 10, 18, 9, 0, 0)
+        .unwrap()
+        .into()
+}
+
+fn journal_fixture(documents: Vec<(&str, &str)>, weekly: bool) -> Fixture {
+    let mut configuration = Configuration {
+        journal: JournalOptions {
+            daily: Some(PeriodOptions::default()),
+            weekly: weekly.then(PeriodOptions::default),
+            monthly: None,
+        },
+        ..Default::default()
+    };
+    configuration.library.locale = Some("en_US".to_string());
+
+    let state: HashMap<String, String> = documents
+        .into_iter()
+        .map(|(key, text)| (key.to_string(), text.to_string()))
+        .collect();
+    Fixture::with_options_and_client(state, configuration, "", Some(fixed_now()))
+}
+
+fn open_command(title: &str, uri: &Uri) -> Command {
+    Command {
+        title: title.to_string(),
+        command: "iwe.open".to_string(),
+        arguments: Some(vec![json!(uri.to_string())]),
+    }
+}
+
+#[test]
+fn next_day_is_created_and_opened() {
+    let title = "Next day: Sunday, October 18, 2026";
+    let target = uri_from("journal/2026-10-18");
+
+    journal_fixture(
+        vec![("journal/2026-10-17", "# Saturday, October 17, 2026\n")],
+        false,
+    )
+    .code_action(
+        uri_from("journal/2026-10-17").to_code_action_params(0, "custom.journal.next"),
+        CodeAction {
+            command: Some(open_command(title, &target)),
+            ..vec![
+                target.clone().to_create_file(),
+                target.to_edit(indoc! {"
+                    # Sunday, October 18, 2026
+
+                    [← Saturday, October 17, 2026](2026-10-17)
+                "}),
+            ]
+            .to_workspace_edit()
+            .to_code_action(title, "custom.journal.next")
+        },
+    );
+}
+
+#[test]
+fn existing_week_is_opened_without_changes() {
+    let title = "Up: Week 42, 2026";
+    let target = uri_from("journal/2026-W42");
+
+    journal_fixture(
+        vec![
+            ("journal/2026-10-17", "# Saturday, October 17, 2026\n"),
+            ("journal/2026-W42", "# Week 42, 2026\n"),
+        ],
+        true,
+    )
+    .code_action(
+        uri_from("journal/2026-10-17").to_code_action_params(0, "custom.journal.up"),
+        CodeAction {
+            command: Some(open_command(title, &target)),
+            ..vec![]
+                .to_workspace_edit()
+                .to_code_action(title, "custom.journal.up")
+        },
+    );
+}
+
+#[test]
+fn no_journal_actions_outside_the_journal() {
+    journal_fixture(vec![("notes", "# Notes\n")], true)
+        .no_code_action(uri_from("notes").to_code_action_params(0, "custom.journal.next"));
+}
+
+#[test]
+fn journal_command_creates_the_day_and_shows_it() {
+    let fixture = journal_fixture(vec![("journal/2026-W42", "# Week 42, 2026\n")], true);
+
+    let (edit, shown) = fixture.execute_command("iwe.journal", vec![json!("yesterday")]);
+    assert_eq!(shown, None);
+
+    let day = uri_from("journal/2026-10-17");
+    let week = uri_from("journal/2026-W42");
+    let expected = vec![
+        day.clone().to_create_file(),
+        day.clone().to_edit(indoc! {"
+            # Saturday, October 17, 2026
+        "}),
+        week.to_edit(indoc! {"
+            # Week 42, 2026
+
+            [Saturday, October 17, 2026](2026-10-17)
+        "}),
+    ]
+    .to_workspace_edit();
+    assert_eq!(edit, Some(serde_json::to_value(expected).unwrap()));
+    assert_eq!(fixture.next_shown_document(), Some(day.to_string()));
+}
+
+#[test]
+fn journal_command_does_not_show_a_note_the_client_did_not_create() {
+    let fixture =
+        journal_fixture(vec![("journal/2026-W42", "# Week 42, 2026\n")], true).rejecting_edits();
+
+    let (edit, _) = fixture.execute_command("iwe.journal", vec![json!("yesterday")]);
+
+    assert!(edit.is_some());
+    assert_eq!(fixture.next_shown_document(), None);
+}
+
+#[test]
+fn journal_command_shows_an_existing_note_right_away() {
+    let fixture = journal_fixture(vec![("journal/2026-10-17", "# Saturday\n")], true);
+
+    let (edit, shown) = fixture.execute_command("iwe.journal", vec![json!("yesterday")]);
+
+    assert_eq!(edit, None);
+    assert_eq!(shown, Some(uri_from("journal/2026-10-17").to_string()));
+}
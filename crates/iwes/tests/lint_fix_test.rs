@@ -0,0 +1,190 @@
+use diwe::config::{ActionDefinition, Attach, Configuration};
+use indoc::indoc;
+use lsp_types::{
+    CodeAction, CodeActionParams, Diagnostic, DiagnosticSeverity, NumberOrString, Uri,
+};
+
+use crate::fixture::*;
+
+#[test]
+fn repoints_dangling_link() {
+    let fixture = Fixture::with(indoc! {"
+        # a
+
+        [b](22)
+        _
+        # b
+
+        text
+        "});
+    let diagnostics = findings(fixture.published_diagnostics(uri(1)), "dangling-link");
+    fixture.code_action(
+        quickfix_params(uri(1), 2, &diagnostics),
+        fixing(
+            vec![uri(1).to_edit("# a\n\n[b](2)")]
+                .to_workspace_edit()
+                .to_code_action("Fix dangling-link: links to missing '22'", "quickfix"),
+            &diagnostics[0],
+        ),
+    );
+}
+
+#[test]
+fn relevels_heading_jump() {
+    let fixture = Fixture::with(indoc! {"
+        # a
+
+        ### b
+
+        text
+        "});
+    let diagnostics = findings(fixture.published_diagnostics(uri(1)), "heading-jump");
+    fixture.code_action(
+        quickfix_params(uri(1), 2, &diagnostics),
+        fixing(
+            vec![uri(1).to_edit("# a\n\n## b\n\ntext")]
+                .to_workspace_edit()
+                .to_code_action(
+                    "Fix heading-jump: heading 'b' jumps from level 1 to 3",
+                    "quickfix",
+                ),
+            &diagnostics[0],
+        ),
+    );
+}
+
+#[test]
+fn attaches_orphan_to_hub() {
+    let mut configuration = Configuration::default();
+    configuration.lint.attach_orphans = Some("hub".into());
+    configuration.actions.insert(
+        "hub".into(),
+        ActionDefinition::Attach(Attach {
+            title: "Hub".into(),
+            key_template: "3".into(),
+            document_template: "# template\n\n{{content}}".into(),
+        }),
+    );
+
+    let fixture = Fixture::with_config(
+        indoc! {"
+            # a
+
+            text
+            _
+            # b
+
+            [a](1)
+            _
+            # target
+
+            text
+            "},
+        configuration,
+    );
+    let diagnostics = findings(fixture.published_diagnostics(uri(2)), "orphan");
+    fixture.code_action(
+        quickfix_params(uri(2), 0, &diagnostics),
+        fixing(
+            vec![uri(3).to_edit("# target\n\ntext\n\n[b](2)\n")]
+                .to_workspace_edit()
+                .to_code_action("Fix orphan: no page links here", "quickfix"),
+            &diagnostics[0],
+        ),
+    );
+}
+
+#[test]
+fn no_fix_without_fixable_finding() {
+    let fixture = Fixture::with(indoc! {"
+        # a
+
+        [b](zzzz)
+        "});
+    let diagnostics = findings(fixture.published_diagnostics(uri(1)), "dangling-link");
+    assert_eq!(diagnostics.len(), 1);
+    fixture.no_code_action(quickfix_params(uri(1), 2, &diagnostics));
+}
+
+#[test]
+fn publishes_findings_as_diagnostics() {
+    let diagnostics = findings(
+        Fixture::with(indoc! {"
+            # a
+
+            [b](22)
+            "})
+        .published_diagnostics(uri(1)),
+        "dangling-link",
+    );
+
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics[0];
+    assert_eq!(diagnostic.range.start.line, 2);
+    assert_eq!(diagnostic.range.end.character, 7);
+    assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostic.message, "links to missing '22'");
+}
+
+#[test]
+fn offers_one_fix_per_diagnostic() {
+    let fixture = Fixture::with(indoc! {"
+        # a
+
+        [b](22) and [c](33)
+        _
+        # b
+
+        text
+        _
+        # c
+
+        text
+        "});
+    let diagnostics = findings(fixture.published_diagnostics(uri(1)), "dangling-link");
+    assert_eq!(diagnostics.len(), 2);
+
+    fixture.code_action(
+        quickfix_params(uri(1), 2, &diagnostics[1..]),
+        fixing(
+            vec![uri(1).to_edit("# a\n\n[b](22) and [c](3)")]
+                .to_workspace_edit()
+                .to_code_action("Fix dangling-link: links to missing '33'", "quickfix"),
+            &diagnostics[1],
+        ),
+    );
+}
+
+#[test]
+fn ignores_diagnostics_from_other_sources() {
+    let fixture = Fixture::with(indoc! {"
+        # a
+
+        [b](22)
+        _
+        # b
+
+        text
+        "});
+    let mut diagnostics = findings(fixture.published_diagnostics(uri(1)), "dangling-link");
+    diagnostics[0].source = Some("spelling".into());
+    fixture.no_code_action(quickfix_params(uri(1), 2, &diagnostics));
+}
+
+fn findings(diagnostics: Vec<Diagnostic>, rule: &str) -> Vec<Diagnostic> {
+    diagnostics
+        .into_iter()
+        .filter(|d| d.code == Some(NumberOrString::String(rule.to_string())))
+        .collect()
+}
+
+fn quickfix_params(uri: Uri, line: u32, diagnostics: &[Diagnostic]) -> CodeActionParams {
+    let mut params = uri.to_code_action_params(line, "quickfix");
+    params.context.diagnostics = diagnostics.to_vec();
+    params
+}
+
+fn fixing(mut action: CodeAction, diagnostic: &Diagnostic) -> CodeAction {
+    action.diagnostics = Some(vec![diagnostic.clone()]);
+    action
+}
@@ -2,29 +2,44 @@ mod fixture;
 
 mod attach_test;
 mod change_list_type_test;
+mod code_lens_test;
 mod completion_test;
+mod config_reload_test;
 mod custom_actions_test;
 mod delete_test;
 mod did_change_test;
 mod did_save_test;
+mod document_link_test;
 mod extract_all_test;
 mod extract_test;
 mod folding_range_test;
 mod format_document_test;
 mod go_to_definition_test;
+mod hierarchy_test;
 mod hover_test;
 mod inlay_hints_test;
 mod inline_quote_test;
 mod inline_section_test;
+mod journal_test;
 mod link_test;
+mod lint_fix_test;
 mod list_to_sections_test;
+mod mentions_test;
+mod move_section_test;
+mod on_type_formatting_test;
+mod range_formatting_test;
 mod reconcile_test;
 mod references_test;
 mod rename_test;
 mod sections_to_list_test;
+mod selection_range_test;
+mod semantic_tokens_test;
 mod sort_test;
+mod structure_test;
+mod toggle_task_test;
 mod transform_test;
 mod unknown_method_test;
 mod watched_files_test;
 mod watcher_test;
+mod workspace_folders_test;
 mod workspace_symbols_test;
@@ -0,0 +1,84 @@
+use indoc::indoc;
+use lsp_types::Position;
+
+use crate::fixture::*;
+
+const KIND: &str = "refactor.rewrite.mentions";
+
+#[test]
+fn links_mentions_of_current_document() {
+    Fixture::with(indoc! {"
+        # garden
+        _
+        # notes
+
+        Work in the gardens.
+        _
+        # more
+
+        The garden grows.
+        "})
+    .code_action(
+        uri(1).to_code_action_params(0, KIND),
+        vec![
+            uri(2).to_edit("# notes\n\nWork in the [gardens](1)."),
+            uri(3).to_edit("# more\n\nThe [garden](1) grows."),
+        ]
+        .to_workspace_edit()
+        .to_code_action("Link all mentions of garden", KIND),
+    );
+}
+
+#[test]
+fn links_mentions_of_document_under_cursor() {
+    Fixture::with(indoc! {"
+        # garden
+        _
+        # notes
+
+        [garden](1) and the gardens.
+        "})
+    .code_action(
+        uri(2).to_code_action_params(2, KIND),
+        vec![uri(2).to_edit("# notes\n\n[garden](1) and the [gardens](1).")]
+            .to_workspace_edit()
+            .to_code_action("Link all mentions of garden", KIND),
+    );
+}
+
+#[test]
+fn links_only_mentions_inside_the_selection() {
+    let mut params = uri(2).to_code_action_params(2, KIND);
+    params.range.end = Position::new(2, 28);
+
+    Fixture::with(indoc! {"
+        # garden
+        _
+        # notes
+
+        [garden](1) and the gardens.
+
+        A garden again.
+        "})
+    .code_action(
+        params,
+        vec![uri(2).to_edit("# notes\n\n[garden](1) and the [gardens](1).\n\nA garden again.")]
+            .to_workspace_edit()
+            .to_code_action("Link all mentions of garden", KIND),
+    );
+}
+
+#[test]
+fn no_action_without_mentions_in_the_selection() {
+    let mut params = uri(2).to_code_action_params(2, KIND);
+    params.range.end = Position::new(2, 11);
+
+    Fixture::with(indoc! {"
+        # garden
+        _
+        # notes
+
+        [garden](1) and the gardens.
+        "})
+    .no_code_action(params);
+}
@@ -0,0 +1,121 @@
+use indoc::indoc;
+use lsp_types::{request::CodeActionRequest, CodeAction, CodeActionOrCommand, Command};
+use serde_json::json;
+
+use crate::fixture::*;
+
+const SOURCE: &str = indoc! {"
+    # One
+
+    [Two](2)
+
+    ## Moved
+
+    moved text
+    _
+    # Two
+
+    two text
+"};
+
+fn moved() -> serde_json::Value {
+    serde_json::to_value(
+        vec![
+            uri(1).to_edit(indoc! {"
+                # One
+
+                [Two](2)
+            "}),
+            uri(2).to_edit(indoc! {"
+                # Two
+
+                two text
+
+                ## Moved
+
+                moved text
+            "}),
+        ]
+        .to_workspace_edit(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn offers_to_move_a_section() {
+    let title = "Move section to…";
+    Fixture::with(SOURCE).assert_response::<CodeActionRequest>(
+        uri(1).to_code_action_params(4, "refactor.move.section"),
+        Some(vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: action_kind("refactor.move.section"),
+            command: Some(Command {
+                title: title.to_string(),
+                command: "iwe.moveSection".to_string(),
+                arguments: Some(vec![json!(uri(1).to_string()), json!(4)]),
+            }),
+            ..Default::default()
+        })]),
+    );
+}
+
+#[test]
+fn does_not_offer_to_move_a_paragraph() {
+    Fixture::with(SOURCE).no_code_action(uri(1).to_code_action_params(6, "refactor.move.section"));
+}
+
+#[test]
+fn moves_section_to_the_given_document() {
+    let (edit, _) = Fixture::with(SOURCE).execute_command(
+        "iwe.moveSection",
+        vec![json!(uri(1).to_string()), json!(4), json!("2")],
+    );
+
+    assert_eq!(edit, Some(moved()));
+}
+
+#[test]
+fn asks_for_the_target_document() {
+    let fixture = Fixture::with(SOURCE);
+
+    let (edit, _) =
+        fixture.execute_command("iwe.moveSection", vec![json!(uri(1).to_string()), json!(4)]);
+    assert_eq!(edit, None);
+    assert_eq!(
+        fixture.message_request(),
+        Some(json!({
+            "type": 3,
+            "message": "Move section to",
+            "actions": [{ "title": "Two" }],
+        }))
+    );
+
+    assert_eq!(fixture.answer_message_request("Two"), Some(moved()));
+}
+
+#[test]
+fn offers_every_document_with_linked_ones_first() {
+    let fixture = Fixture::with(indoc! {"
+        # One
+
+        [Three](3)
+
+        ## Moved
+
+        moved text
+        _
+        # Two
+        _
+        # Three
+    "});
+
+    fixture.execute_command("iwe.moveSection", vec![json!(uri(1).to_string()), json!(4)]);
+    assert_eq!(
+        fixture.message_request(),
+        Some(json!({
+            "type": 3,
+            "message": "Move section to",
+            "actions": [{ "title": "Three" }, { "title": "Two" }],
+        }))
+    );
+}
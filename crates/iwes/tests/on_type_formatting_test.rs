@@ -0,0 +1,193 @@
+use diwe::config::{FormattingOptions, MarkdownOptions};
+use indoc::indoc;
+use lsp_types::{
+    request::OnTypeFormatting, DocumentOnTypeFormattingParams, Position, Range,
+    TextDocumentIdentifier, TextDocumentPositionParams, TextEdit,
+};
+
+use crate::fixture::*;
+
+fn params(line: u32, character: u32) -> DocumentOnTypeFormattingParams {
+    DocumentOnTypeFormattingParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: uri(1) },
+            position: Position::new(line, character),
+        },
+        ch: "\n".to_string(),
+        options: Default::default(),
+    }
+}
+
+fn edit(line: u32, start: u32, end: u32, text: &str) -> TextEdit {
+    TextEdit::new(
+        Range::new(Position::new(line, start), Position::new(line, end)),
+        text.to_string(),
+    )
+}
+
+#[test]
+fn continues_bullet_list() {
+    Fixture::with(indoc! {"
+        # Notes
+
+        - first
+
+    "})
+    .assert_response::<OnTypeFormatting>(params(3, 0), Some(vec![edit(3, 0, 0, "- ")]));
+}
+
+#[test]
+fn continues_nested_item_with_its_indent() {
+    Fixture::with(indoc! {"
+        - first
+          * nested
+          rest
+    "})
+    .assert_response::<OnTypeFormatting>(params(2, 2), Some(vec![edit(2, 0, 2, "  * ")]));
+}
+
+#[test]
+fn continues_task_unchecked() {
+    Fixture::with(indoc! {"
+        - [x] done
+
+    "})
+    .assert_response::<OnTypeFormatting>(params(1, 0), Some(vec![edit(1, 0, 0, "- [ ] ")]));
+}
+
+#[test]
+fn continues_ordered_list_and_renumbers_items_below() {
+    Fixture::with(indoc! {"
+        1. first
+
+        2. second
+           - nested
+        3. third
+
+        Paragraph
+    "})
+    .assert_response::<OnTypeFormatting>(
+        params(1, 0),
+        Some(vec![
+            edit(1, 0, 0, "2. "),
+            edit(2, 0, 1, "3"),
+            edit(4, 0, 1, "4"),
+        ]),
+    );
+}
+
+#[test]
+fn keeps_numbers_without_incrementing_bullets() {
+    Fixture::with_options(
+        indoc! {"
+            1. first
+
+            1. second
+        "},
+        MarkdownOptions {
+            formatting: FormattingOptions {
+                increment_ordered_list_bullets: Some(false),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .assert_response::<OnTypeFormatting>(params(1, 0), Some(vec![edit(1, 0, 0, "1. ")]));
+}
+
+#[test]
+fn pads_marker_to_content_indent() {
+    Fixture::with_options(
+        indoc! {"
+            - first
+
+        "},
+        MarkdownOptions {
+            formatting: FormattingOptions {
+                bullet_list_content_indent: Some(4),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+    .assert_response::<OnTypeFormatting>(params(1, 0), Some(vec![edit(1, 0, 0, "-   ")]));
+}
+
+#[test]
+fn ends_list_on_empty_item() {
+    Fixture::with("- first\n")
+        .did_change_text_document(uri(1).to_did_change_params(2, "- first\n- \n  \n".to_string()))
+        .assert_response::<OnTypeFormatting>(
+            params(2, 2),
+            Some(vec![edit(1, 0, 2, ""), edit(2, 0, 2, "")]),
+        );
+}
+
+#[test]
+fn ignores_lines_outside_lists() {
+    Fixture::with(indoc! {"
+        Paragraph
+
+    "})
+    .assert_response::<OnTypeFormatting>(params(1, 0), Some(vec![]));
+}
+
+#[test]
+fn ignores_code_blocks() {
+    Fixture::with(indoc! {"
+        ```
+        - code
+
+        ```
+    "})
+    .assert_response::<OnTypeFormatting>(params(2, 0), Some(vec![]));
+}
+
+#[test]
+fn ignores_code_blocks_holding_shorter_fences() {
+    Fixture::with(indoc! {"
+        ````
+        ```
+        - code
+
+        ````
+    "})
+    .assert_response::<OnTypeFormatting>(params(3, 0), Some(vec![]));
+}
+
+#[test]
+fn ignores_tilde_code_blocks_holding_backtick_fences() {
+    Fixture::with(indoc! {"
+        ~~~
+        ```
+        ```
+        - code
+
+        ~~~
+    "})
+    .assert_response::<OnTypeFormatting>(params(4, 0), Some(vec![]));
+}
+
+#[test]
+fn continues_lists_after_closed_code_blocks() {
+    Fixture::with(indoc! {"
+        ~~~
+        ```
+        ~~~~
+
+        - first
+
+    "})
+    .assert_response::<OnTypeFormatting>(params(5, 0), Some(vec![edit(5, 0, 0, "- ")]));
+}
+
+#[test]
+fn ignores_thematic_breaks() {
+    Fixture::with(indoc! {"
+        Paragraph
+
+        * * *
+
+    "})
+    .assert_response::<OnTypeFormatting>(params(3, 0), Some(vec![]));
+}
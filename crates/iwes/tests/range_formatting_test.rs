@@ -0,0 +1,83 @@
+use indoc::indoc;
+use lsp_types::{
+    request::RangeFormatting, DocumentRangeFormattingParams, Position, Range,
+    TextDocumentIdentifier, TextEdit,
+};
+
+use crate::fixture::*;
+
+fn params(start: u32, end: u32) -> DocumentRangeFormattingParams {
+    DocumentRangeFormattingParams {
+        text_document: TextDocumentIdentifier { uri: uri(1) },
+        range: Range::new(Position::new(start, 0), Position::new(end, 0)),
+        options: Default::default(),
+        work_done_progress_params: Default::default(),
+    }
+}
+
+#[test]
+fn formats_selected_list_only() {
+    Fixture::with(indoc! {"
+        # Title
+
+        * one
+        * two
+
+        Some _text_
+    "})
+    .assert_response::<RangeFormatting>(
+        params(2, 2),
+        Some(vec![TextEdit::new(
+            Range::new(Position::new(2, 0), Position::new(3, 5)),
+            "- one\n- two".to_string(),
+        )]),
+    );
+}
+
+#[test]
+fn formats_header_at_its_level() {
+    Fixture::with(indoc! {"
+        # Title
+
+        ##   Sub
+
+        * item
+    "})
+    .assert_response::<RangeFormatting>(
+        params(2, 2),
+        Some(vec![TextEdit::new(
+            Range::new(Position::new(2, 0), Position::new(2, 8)),
+            "## Sub".to_string(),
+        )]),
+    );
+}
+
+#[test]
+fn formats_blocks_between_range_ends() {
+    Fixture::with(indoc! {"
+        # Title
+
+        * one
+
+
+        Some _text_
+    "})
+    .assert_response::<RangeFormatting>(
+        params(2, 6),
+        Some(vec![TextEdit::new(
+            Range::new(Position::new(2, 0), Position::new(5, 11)),
+            "- one\n\nSome *text*".to_string(),
+        )]),
+    );
+}
+
+#[test]
+fn skips_formatted_blocks() {
+    Fixture::with(indoc! {"
+        # Title
+
+        - one
+        - two
+    "})
+    .assert_response::<RangeFormatting>(params(2, 3), Some(vec![]));
+}
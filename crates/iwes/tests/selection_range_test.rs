@@ -0,0 +1,62 @@
+use indoc::indoc;
+use lsp_types::{
+    request::SelectionRangeRequest, Position, Range, SelectionRange, SelectionRangeParams,
+    TextDocumentIdentifier,
+};
+
+use crate::fixture::*;
+
+fn params(line: u32, character: u32) -> SelectionRangeParams {
+    SelectionRangeParams {
+        text_document: TextDocumentIdentifier { uri: uri(1) },
+        positions: vec![Position::new(line, character)],
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+/// Nested selection ranges, innermost first, from `(start line, end line, end character)`.
+fn nested(ranges: &[(u32, u32, u32)]) -> SelectionRange {
+    ranges
+        .iter()
+        .rev()
+        .fold(None, |parent, (start, end, character)| {
+            Some(SelectionRange {
+                range: Range::new(Position::new(*start, 0), Position::new(*end, *character)),
+                parent: parent.map(Box::new),
+            })
+        })
+        .unwrap()
+}
+
+#[test]
+fn grows_from_list_item_to_document() {
+    Fixture::with(indoc! {"
+        # Top
+
+        ## Section
+
+        - first
+          - nested
+        - second
+
+        # Other
+    "})
+    .assert_response::<SelectionRangeRequest>(
+        params(5, 4),
+        Some(vec![nested(&[
+            (5, 5, 10),
+            (4, 5, 10),
+            (4, 6, 8),
+            (2, 6, 8),
+            (0, 6, 8),
+            (0, 8, 7),
+        ])]),
+    );
+}
+
+#[test]
+fn selects_the_document_on_blank_lines() {
+    Fixture::with("# Top\n\npara\n")
+        .assert_response::<SelectionRangeRequest>(params(1, 0), Some(vec![nested(&[(0, 2, 4)])]));
+}
@@ -0,0 +1,249 @@
+use indoc::indoc;
+use lsp_types::{
+    notification::DidChangeTextDocument,
+    request::{SemanticTokensFullDeltaRequest, SemanticTokensFullRequest},
+    SemanticToken, SemanticTokens, SemanticTokensDelta, SemanticTokensDeltaParams,
+    SemanticTokensEdit, SemanticTokensFullDeltaResult, SemanticTokensParams, SemanticTokensResult,
+};
+
+use crate::fixture::*;
+
+const LINK: u32 = 0;
+const INCLUSION: u32 = 1;
+const HEADING: u32 = 2;
+const PROPERTY: u32 = 3;
+const STRING: u32 = 4;
+const CHECKBOX: u32 = 5;
+
+const UNRESOLVED: u32 = 1;
+const WIKI: u32 = 1 << 1;
+const MARKDOWN: u32 = 1 << 2;
+const CHECKED: u32 = 1 << 3;
+
+fn level(level: u32) -> u32 {
+    1 << (3 + level)
+}
+
+fn params(key: u32) -> SemanticTokensParams {
+    SemanticTokensParams {
+        text_document: lsp_types::TextDocumentIdentifier { uri: uri(key) },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+fn delta_params(key: u32, previous_result_id: &str) -> SemanticTokensDeltaParams {
+    SemanticTokensDeltaParams {
+        text_document: lsp_types::TextDocumentIdentifier { uri: uri(key) },
+        previous_result_id: previous_result_id.to_string(),
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+/// Encodes `(line, start, length, type, modifiers)` tuples relative to each other.
+fn tokens(absolute: &[(u32, u32, u32, u32, u32)]) -> Vec<SemanticToken> {
+    let mut previous = (0, 0);
+    absolute
+        .iter()
+        .map(|&(line, start, length, token_type, modifiers)| {
+            let delta_line = line - previous.0;
+            let delta_start = if delta_line == 0 {
+                start - previous.1
+            } else {
+                start
+            };
+            previous = (line, start);
+            SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type,
+                token_modifiers_bitset: modifiers,
+            }
+        })
+        .collect()
+}
+
+fn full(result_id: &str, absolute: &[(u32, u32, u32, u32, u32)]) -> Option<SemanticTokensResult> {
+    Some(SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: Some(result_id.to_string()),
+        data: tokens(absolute),
+    }))
+}
+
+#[test]
+fn links_resolved_and_unresolved() {
+    Fixture::with(indoc! {"
+        # test
+
+        See [two](2) and [[2]] or [gone](gone) and [[missing]].
+        _
+        # two
+    "})
+    .assert_response::<SemanticTokensFullRequest>(
+        params(1),
+        full(
+            "1",
+            &[
+                (0, 0, 6, HEADING, level(1)),
+                (2, 4, 8, LINK, MARKDOWN),
+                (2, 17, 5, LINK, WIKI),
+                (2, 26, 12, LINK, MARKDOWN | UNRESOLVED),
+                (2, 43, 11, LINK, WIKI | UNRESOLVED),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn inclusion_reference() {
+    Fixture::with(indoc! {"
+        # test
+
+        [two](2)
+
+        [[missing]]
+        _
+        # two
+    "})
+    .assert_response::<SemanticTokensFullRequest>(
+        params(1),
+        full(
+            "1",
+            &[
+                (0, 0, 6, HEADING, level(1)),
+                (2, 0, 8, INCLUSION, MARKDOWN),
+                (4, 0, 11, INCLUSION, WIKI | UNRESOLVED),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn heading_levels_around_links() {
+    Fixture::with(indoc! {"
+        # test
+
+        ## about [[2]] notes
+        _
+        # two
+    "})
+    .assert_response::<SemanticTokensFullRequest>(
+        params(1),
+        full(
+            "1",
+            &[
+                (0, 0, 6, HEADING, level(1)),
+                (2, 0, 9, HEADING, level(2)),
+                (2, 9, 5, LINK, WIKI),
+                (2, 14, 6, HEADING, level(2)),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn frontmatter_keys_and_values() {
+    Fixture::with(indoc! {"
+        ---
+        title: Plan
+        tags:
+          - work
+        ---
+
+        # test
+    "})
+    .assert_response::<SemanticTokensFullRequest>(
+        params(1),
+        full(
+            "1",
+            &[
+                (1, 0, 5, PROPERTY, 0),
+                (1, 7, 4, STRING, 0),
+                (2, 0, 4, PROPERTY, 0),
+                (3, 4, 4, STRING, 0),
+                (6, 0, 6, HEADING, level(1)),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn task_checkboxes() {
+    Fixture::with(indoc! {"
+        # test
+
+        - [ ] open
+        - [x] done
+        - plain
+    "})
+    .assert_response::<SemanticTokensFullRequest>(
+        params(1),
+        full(
+            "1",
+            &[
+                (0, 0, 6, HEADING, level(1)),
+                (2, 2, 3, CHECKBOX, 0),
+                (3, 2, 3, CHECKBOX, CHECKED),
+            ],
+        ),
+    );
+}
+
+#[test]
+fn delta_after_change() {
+    let fixture = Fixture::with(indoc! {"
+        # test
+
+        [[2]]
+        _
+        # two
+    "});
+
+    fixture.assert_response::<SemanticTokensFullRequest>(
+        params(1),
+        full(
+            "1",
+            &[(0, 0, 6, HEADING, level(1)), (2, 0, 5, INCLUSION, WIKI)],
+        ),
+    );
+
+    fixture.notification::<DidChangeTextDocument>(
+        uri(1).to_did_change_params(2, "# test\n\n[[3]]\n".to_string()),
+    );
+
+    fixture.assert_response::<SemanticTokensFullDeltaRequest>(
+        delta_params(1, "1"),
+        Some(SemanticTokensFullDeltaResult::TokensDelta(
+            SemanticTokensDelta {
+                result_id: Some("2".to_string()),
+                edits: vec![SemanticTokensEdit {
+                    start: 5,
+                    delete_count: 5,
+                    data: Some(
+                        tokens(&[
+                            (0, 0, 6, HEADING, level(1)),
+                            (2, 0, 5, INCLUSION, WIKI | UNRESOLVED),
+                        ])[1..]
+                            .to_vec(),
+                    ),
+                }],
+            },
+        )),
+    );
+}
+
+#[test]
+fn delta_with_unknown_result_id() {
+    Fixture::with(indoc! {"
+        # test
+    "})
+    .assert_response::<SemanticTokensFullDeltaRequest>(
+        delta_params(1, "42"),
+        Some(SemanticTokensFullDeltaResult::Tokens(SemanticTokens {
+            result_id: Some("1".to_string()),
+            data: tokens(&[(0, 0, 6, HEADING, level(1))]),
+        })),
+    );
+}
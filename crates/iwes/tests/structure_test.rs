@@ -0,0 +1,174 @@
+use indoc::indoc;
+
+use crate::fixture::*;
+
+fn assert_action(source: &str, line: u32, kind: &'static str, title: &str, expected: &str) {
+    Fixture::with(source).code_action(
+        uri(1).to_code_action_params(line, kind),
+        vec![uri(1).to_edit(expected)]
+            .to_workspace_edit()
+            .to_code_action(title, kind),
+    );
+}
+
+fn assert_no_action(source: &str, line: u32, kind: &'static str) {
+    Fixture::with(source).no_code_action(uri(1).to_code_action_params(line, kind));
+}
+
+const SECTIONS: &str = indoc! {"
+    # Top
+
+    ## First
+
+    first
+
+    ## Second
+
+    second
+
+    ### Nested
+
+    nested
+"};
+
+#[test]
+fn moves_section_up() {
+    assert_action(
+        SECTIONS,
+        6,
+        "refactor.move.up",
+        "Move up",
+        indoc! {"
+            # Top
+
+            ## Second
+
+            second
+
+            ### Nested
+
+            nested
+
+            ## First
+
+            first
+        "},
+    );
+}
+
+#[test]
+fn moves_section_down() {
+    assert_action(
+        SECTIONS,
+        2,
+        "refactor.move.down",
+        "Move down",
+        indoc! {"
+            # Top
+
+            ## Second
+
+            second
+
+            ### Nested
+
+            nested
+
+            ## First
+
+            first
+        "},
+    );
+}
+
+#[test]
+fn moves_list_item_with_its_sub_list() {
+    assert_action(
+        indoc! {"
+            - first
+            - second
+              - nested
+        "},
+        1,
+        "refactor.move.up",
+        "Move up",
+        indoc! {"
+            - second
+              - nested
+            - first
+        "},
+    );
+}
+
+#[test]
+fn does_not_move_past_the_first_sibling() {
+    assert_no_action(SECTIONS, 2, "refactor.move.up");
+    assert_no_action("- first\n- second\n", 1, "refactor.move.down");
+}
+
+#[test]
+fn does_not_move_past_a_paragraph() {
+    assert_no_action("# Top\n\npara\n\n## Sub\n", 4, "refactor.move.up");
+}
+
+#[test]
+fn promotes_section_keeping_the_text_order() {
+    assert_action(
+        indoc! {"
+            # Top
+
+            ## First
+
+            ## Second
+
+            ## Third
+        "},
+        4,
+        "refactor.rewrite.section.promote",
+        "Promote section",
+        indoc! {"
+            # Top
+
+            ## First
+
+            # Second
+
+            ## Third
+        "},
+    );
+}
+
+#[test]
+fn does_not_promote_top_level_section() {
+    assert_no_action("# Top\n\n# Other\n", 2, "refactor.rewrite.section.promote");
+}
+
+#[test]
+fn demotes_section_under_the_previous_one() {
+    assert_action(
+        SECTIONS,
+        6,
+        "refactor.rewrite.section.demote",
+        "Demote section",
+        indoc! {"
+            # Top
+
+            ## First
+
+            first
+
+            ### Second
+
+            second
+
+            #### Nested
+
+            nested
+        "},
+    );
+}
+
+#[test]
+fn does_not_demote_first_section() {
+    assert_no_action(SECTIONS, 2, "refactor.rewrite.section.demote");
+}
@@ -0,0 +1,50 @@
+use indoc::indoc;
+
+use crate::fixture::*;
+
+#[test]
+fn check_task() {
+    assert_toggle(
+        indoc! {"
+            - [ ] first
+            - [ ] second
+            "},
+        1,
+        indoc! {"
+            - [ ] first
+            - [x] second
+        "},
+        "Check task",
+    );
+}
+
+#[test]
+fn uncheck_task() {
+    assert_toggle(
+        indoc! {"
+            - [x] first
+            "},
+        0,
+        indoc! {"
+            - [ ] first
+        "},
+        "Uncheck task",
+    );
+}
+
+#[test]
+fn no_toggle_on_plain_item() {
+    Fixture::with(indoc! {"
+        - first
+        "})
+    .no_code_action(uri(1).to_code_action_params(0, "refactor.rewrite.list.task"));
+}
+
+fn assert_toggle(source: &str, line: u32, expected: &str, title: &str) {
+    Fixture::with(source).code_action(
+        uri(1).to_code_action_params(line, "refactor.rewrite.list.task"),
+        vec![uri(1).to_edit(expected)]
+            .to_workspace_edit()
+            .to_code_action(title, "refactor.rewrite.list.task"),
+    );
+}
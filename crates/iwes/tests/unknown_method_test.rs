@@ -5,6 +5,26 @@ use crate::fixture::*;
 
 #[test]
 fn unknown_method_returns_method_not_found_without_hanging() {
+    let response = Fixture::with("# test\n").raw_response(
+        "textDocument/linkedEditingRange",
+        json!({
+            "textDocument": { "uri": "file:///basepath/1.md" },
+            "position": { "line": 0, "character": 0 }
+        }),
+    );
+
+    assert_eq!(response.result, None);
+
+    let error = response.error.expect("expected an error response");
+    assert_eq!(error.code, ErrorCode::MethodNotFound as i32);
+    assert_eq!(
+        error.message,
+        "unhandled method: textDocument/linkedEditingRange"
+    );
+}
+
+#[test]
+fn unknown_command_returns_invalid_params() {
     let response = Fixture::with("# test\n").raw_response(
         "workspace/executeCommand",
         json!({ "command": "generate", "arguments": [] }),
@@ -13,6 +33,6 @@ fn unknown_method_returns_method_not_found_without_hanging() {
     assert_eq!(response.result, None);
 
     let error = response.error.expect("expected an error response");
-    assert_eq!(error.code, ErrorCode::MethodNotFound as i32);
-    assert_eq!(error.message, "unhandled method: workspace/executeCommand");
+    assert_eq!(error.code, ErrorCode::InvalidParams as i32);
+    assert_eq!(error.message, "unknown command: generate");
 }
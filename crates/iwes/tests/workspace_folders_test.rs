@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use diwe::config::{Configuration, LibraryOptions};
+use indoc::indoc;
+use lsp_types::{
+    notification::{DidChangeWorkspaceFolders, ShowMessage},
+    request::DocumentLinkRequest,
+    DidChangeWorkspaceFoldersParams, DocumentLink, DocumentLinkParams, Location, MessageType,
+    Position, Range, TextDocumentIdentifier, Uri, WorkspaceFolder, WorkspaceFoldersChangeEvent,
+};
+
+use crate::fixture::*;
+
+fn document_links(uri: Uri) -> DocumentLinkParams {
+    DocumentLinkParams {
+        text_document: TextDocumentIdentifier { uri },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    }
+}
+
+fn link(start: u32, end: u32, target: Uri, tooltip: &str) -> DocumentLink {
+    DocumentLink {
+        range: Range::new(Position::new(2, start), Position::new(2, end)),
+        target: Some(target),
+        tooltip: Some(tooltip.to_string()),
+        data: None,
+    }
+}
+
+fn two_libraries() -> Fixture {
+    Fixture::with_folders(vec![
+        (
+            "personal",
+            Configuration::default(),
+            indoc! {"
+                # Personal
+
+                [next](2)
+                _
+                # Personal Next
+            "},
+        ),
+        (
+            "team",
+            Configuration::default(),
+            indoc! {"
+                # Team
+
+                [next](2)
+                _
+                # Team Next
+            "},
+        ),
+    ])
+}
+
+fn workspace_folder(path: &std::path::Path) -> WorkspaceFolder {
+    WorkspaceFolder {
+        uri: Uri::from_str(url::Url::from_directory_path(path).unwrap().as_str()).unwrap(),
+        name: "notes".to_string(),
+    }
+}
+
+#[test]
+fn routes_requests_to_the_folder_of_the_document() {
+    let fixture = two_libraries();
+
+    fixture.assert_response::<DocumentLinkRequest>(
+        document_links(folder_uri("personal", "1")),
+        Some(vec![link(
+            0,
+            9,
+            folder_uri("personal", "2"),
+            "Personal Next",
+        )]),
+    );
+    fixture.assert_response::<DocumentLinkRequest>(
+        document_links(folder_uri("team", "1")),
+        Some(vec![link(0, 9, folder_uri("team", "2"), "Team Next")]),
+    );
+}
+
+#[test]
+fn reports_folders_that_failed_to_open_and_serves_the_rest() {
+    let fixture = Fixture::with_folders_and_errors(
+        vec![(
+            "personal",
+            Configuration::default(),
+            indoc! {"
+                # Personal
+
+                [next](2)
+                _
+                # Personal Next
+            "},
+        )],
+        vec!["IWE: workspace folder team not opened: invalid config"],
+    );
+
+    let message = fixture.next_notification::<ShowMessage>();
+    assert_eq!(message.typ, MessageType::ERROR);
+    assert_eq!(
+        message.message,
+        "IWE: workspace folder team not opened: invalid config"
+    );
+
+    fixture.assert_response::<DocumentLinkRequest>(
+        document_links(folder_uri("personal", "1")),
+        Some(vec![link(
+            0,
+            9,
+            folder_uri("personal", "2"),
+            "Personal Next",
+        )]),
+    );
+}
+
+#[test]
+fn workspace_symbols_span_all_folders() {
+    two_libraries().wait_for_symbols(
+        "",
+        &[
+            "Personal",
+            "Personal Next ↖Personal",
+            "Team",
+            "Team Next ↖Team",
+        ],
+    );
+}
+
+#[test]
+fn links_resolve_through_mounted_libraries() {
+    let personal = Configuration {
+        library: LibraryOptions {
+            mounts: HashMap::from([("team".to_string(), "../team".to_string())]),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let fixture = Fixture::with_folders(vec![
+        (
+            "personal",
+            personal,
+            indoc! {"
+                # Personal
+
+                [guide](team/2)
+            "},
+        ),
+        ("team", Configuration::default(), "# Team\n_\n# Guide\n"),
+    ]);
+
+    fixture.assert_response::<DocumentLinkRequest>(
+        document_links(folder_uri("personal", "1")),
+        Some(vec![link(0, 15, folder_uri("team", "2"), "team/2")]),
+    );
+    fixture.go_to_definition(
+        folder_uri("personal", "1").to_goto_definition_params(2, 3),
+        lsp_types::GotoDefinitionResponse::Scalar(Location::new(
+            folder_uri("team", "2"),
+            Range::default(),
+        )),
+    );
+}
+
+#[test]
+fn opens_and_closes_added_workspace_folders() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    std::fs::write(dir.path().join("shared.md"), "# Shared\n").expect("write doc");
+
+    let fixture =
+        Fixture::with_folders(vec![("personal", Configuration::default(), "# Personal\n")]);
+    fixture.wait_for_symbols("", &["Personal"]);
+
+    fixture.notification::<DidChangeWorkspaceFolders>(DidChangeWorkspaceFoldersParams {
+        event: WorkspaceFoldersChangeEvent {
+            added: vec![workspace_folder(dir.path())],
+            removed: vec![],
+        },
+    });
+    fixture.wait_for_symbols("", &["Personal", "Shared"]);
+
+    fixture.notification::<DidChangeWorkspaceFolders>(DidChangeWorkspaceFoldersParams {
+        event: WorkspaceFoldersChangeEvent {
+            added: vec![],
+            removed: vec![workspace_folder(dir.path())],
+        },
+    });
+    fixture.wait_for_symbols("", &["Personal"]);
+}
@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
+    sync::Arc,
 };
 
 use basic_iter::GraphNodePointer;
@@ -18,7 +19,9 @@ use itertools::Itertools;
 use path::{graph_to_paths, NodePath};
 use rayon::prelude::*;
 
+use crate::model::marker::Marker;
 use crate::parser::Parser;
+use crate::query::computed::ComputedFields;
 
 use crate::graph::graph_node::GraphNode;
 use crate::model::config::{Format, FormatOptions, MarkdownOptions, WikiLinkPath};
@@ -28,9 +31,13 @@ use crate::model::key_index::KeyIndex;
 use crate::model::node::Node;
 use crate::model::node::{NodeIter, NodePointer};
 use crate::model::InlinesContext;
-use crate::model::{Content, Key, LineId, LineNumber, LineRange, NodeId, NodesMap, State};
+use crate::model::{
+    Content, Key, LineId, LineNumber, LineRange, MaybeNodeId, NodeId, NodesMap, State,
+};
 
+pub mod analytics;
 mod arena;
+pub mod assets;
 pub mod basic_iter;
 pub mod builder;
 mod graph_line;
@@ -64,6 +71,7 @@ pub struct Graph {
     content: Documents,
     frontmatter_document_title: Option<String>,
     key_index: KeyIndex,
+    computed_fields: Arc<ComputedFields>,
 }
 
 pub trait Reader {
@@ -123,6 +131,7 @@ impl Graph {
             frontmatter: self.frontmatter.clone(),
             frontmatter_document_title: self.frontmatter_document_title.clone(),
             key_index: self.key_index.clone(),
+            computed_fields: self.computed_fields.clone(),
             ..Default::default()
         }
     }
@@ -134,6 +143,14 @@ impl Graph {
         }
     }
 
+    pub fn computed_fields(&self) -> &ComputedFields {
+        &self.computed_fields
+    }
+
+    pub fn set_computed_fields(&mut self, computed_fields: ComputedFields) {
+        self.computed_fields = Arc::new(computed_fields);
+    }
+
     pub fn set_sequential_keys(&mut self, sequential_keys: bool) {
         self.sequential_keys = sequential_keys;
     }
@@ -559,6 +576,25 @@ impl Graph {
         graph
     }
 
+    /// The same documents, as read or last updated, parsed again with other options.
+    pub fn reparse(
+        &self,
+        format_options: impl Into<FormatOptions>,
+        frontmatter_document_title: Option<String>,
+    ) -> Graph {
+        let state: State = self
+            .content
+            .iter()
+            .map(|(key, content)| (key.to_string(), content.clone()))
+            .collect();
+        Self::from_state(
+            &state,
+            self.sequential_keys,
+            format_options,
+            frontmatter_document_title,
+        )
+    }
+
     pub fn export_key(&self, key: &Key) -> Option<String> {
         Some(self.to_markdown(key))
     }
@@ -615,17 +651,57 @@ impl Graph {
     }
 
     pub fn get_inclusion_edges_in(&self, key: &Key) -> Vec<NodeId> {
+        let generated = self.generated_without_edges(key);
         self.maybe_key(key)
             .map(|node| {
                 node.get_all_sub_nodes()
                     .into_iter()
                     .filter(|id| !self.graph_node(*id).is_empty())
                     .filter(|id| self.graph_node(*id).is_ref())
+                    .filter(|id| !generated.contains(id))
                     .collect()
             })
             .unwrap_or_default()
     }
 
+    /// The end marker of the generated results that start at `id`, or `None` when they run to
+    /// the end of their container.
+    pub fn generated_region_end(&self, mut id: MaybeNodeId) -> MaybeNodeId {
+        while let Some(node_id) = id {
+            match self.graph_node(node_id) {
+                GraphNode::Raw(raw) if raw.marker() == Some(Marker::End) => return Some(node_id),
+                node => id = node.next_id(),
+            }
+        }
+        None
+    }
+
+    /// Nodes of `key` inside generated results whose links are not edges.
+    fn generated_without_edges(&self, key: &Key) -> HashSet<NodeId> {
+        let mut generated = HashSet::new();
+        let Some(document) = self.maybe_key(key) else {
+            return generated;
+        };
+        for id in document.get_all_sub_nodes() {
+            let GraphNode::Raw(raw) = self.graph_node(id) else {
+                continue;
+            };
+            if raw.marker() != Some(Marker::Begin { edges: false }) {
+                continue;
+            }
+            let end = self.generated_region_end(raw.next_id());
+            let mut next = raw.next_id();
+            while let Some(node_id) = next.filter(|node_id| Some(*node_id) != end) {
+                generated.insert(node_id);
+                if let Some(child) = self.node(node_id).to_child() {
+                    generated.extend(child.get_all_sub_nodes());
+                }
+                next = self.graph_node(node_id).next_id();
+            }
+        }
+        generated
+    }
+
     pub fn get_reference_edges_to(&self, key: &Key) -> Vec<NodeId> {
         self.index
             .get_reference_edges_to(key)
@@ -639,8 +715,12 @@ impl Graph {
         let Some(pointer) = self.maybe_key(key) else {
             return Vec::new();
         };
+        let generated = self.generated_without_edges(key);
         let mut keys = Vec::new();
         for node_id in pointer.get_all_sub_nodes() {
+            if generated.contains(&node_id) {
+                continue;
+            }
             match self.graph_node(node_id) {
                 GraphNode::Section(section) => {
                     keys.extend(self.get_line(section.line_id()).ref_keys());
@@ -0,0 +1,458 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
+use serde_yaml::Value;
+
+use crate::graph::Graph;
+use crate::model::Key;
+
+const DAMPING: f64 = 0.85;
+const PAGERANK_ITERATIONS: usize = 100;
+const PAGERANK_TOLERANCE: f64 = 1e-10;
+const PROPAGATION_ROUNDS: usize = 100;
+const PRECISION: f64 = 1e6;
+
+/// Per-document metric exposed to queries as a `$`-prefixed pseudo-field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphMetric {
+    PageRank,
+    Betweenness,
+    Component,
+    Community,
+}
+
+impl GraphMetric {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "$pagerank" => Some(GraphMetric::PageRank),
+            "$betweenness" => Some(GraphMetric::Betweenness),
+            "$component" => Some(GraphMetric::Component),
+            "$community" => Some(GraphMetric::Community),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GraphMetric::PageRank => "$pagerank",
+            GraphMetric::Betweenness => "$betweenness",
+            GraphMetric::Component => "$component",
+            GraphMetric::Community => "$community",
+        }
+    }
+}
+
+/// Whole-graph structure over documents, treating every reference and inclusion between two
+/// existing documents as one edge.
+///
+/// PageRank follows edges in their direction; betweenness, components and communities ignore it.
+/// Component and community ids are numbered from 0 by size, largest first, ties broken by the
+/// smallest key. Inclusion cycles are the strongly connected groups of the inclusion edges alone.
+/// Only the adjacency is built up front; each metric is computed on first use.
+#[derive(Debug, Clone)]
+pub struct GraphAnalytics {
+    keys: Vec<Key>,
+    index: HashMap<Key, usize>,
+    inclusions: Vec<Vec<usize>>,
+    outbound: Vec<Vec<usize>>,
+    undirected: Vec<Vec<usize>>,
+    pagerank: OnceLock<Vec<f64>>,
+    betweenness: OnceLock<Vec<f64>>,
+    component: OnceLock<Vec<usize>>,
+    community: OnceLock<Vec<usize>>,
+    inclusion_cycles: OnceLock<Vec<Vec<Key>>>,
+}
+
+impl GraphAnalytics {
+    pub fn compute(graph: &Graph) -> Self {
+        let mut keys = graph.keys();
+        keys.sort();
+        let index: HashMap<Key, usize> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (key.clone(), i))
+            .collect();
+
+        let inclusions = adjacency(&keys, &index, |key| {
+            graph
+                .get_inclusion_edges_in(key)
+                .into_iter()
+                .filter_map(|id| graph.graph_node(id).ref_key())
+                .collect()
+        });
+        let outbound = adjacency(&keys, &index, |key| {
+            let mut targets = graph.get_reference_edges_in(key);
+            targets.extend(
+                graph
+                    .get_inclusion_edges_in(key)
+                    .into_iter()
+                    .filter_map(|id| graph.graph_node(id).ref_key()),
+            );
+            targets
+        });
+        let mut undirected = vec![Vec::new(); keys.len()];
+        for (from, targets) in outbound.iter().enumerate() {
+            for &to in targets {
+                undirected[from].push(to);
+                undirected[to].push(from);
+            }
+        }
+        for neighbours in undirected.iter_mut() {
+            neighbours.sort_unstable();
+            neighbours.dedup();
+        }
+
+        GraphAnalytics {
+            keys,
+            index,
+            inclusions,
+            outbound,
+            undirected,
+            pagerank: OnceLock::new(),
+            betweenness: OnceLock::new(),
+            component: OnceLock::new(),
+            community: OnceLock::new(),
+            inclusion_cycles: OnceLock::new(),
+        }
+    }
+
+    /// Documents in key order.
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// Distinct document-to-document edges.
+    pub fn edge_count(&self) -> usize {
+        self.outbound.iter().map(Vec::len).sum()
+    }
+
+    pub fn pagerank(&self, key: &Key) -> Option<f64> {
+        let pagerank = self.pagerank.get_or_init(|| pagerank(&self.outbound));
+        self.index.get(key).map(|&i| pagerank[i])
+    }
+
+    pub fn betweenness(&self, key: &Key) -> Option<f64> {
+        let betweenness = self
+            .betweenness
+            .get_or_init(|| betweenness(&self.undirected));
+        self.index.get(key).map(|&i| betweenness[i])
+    }
+
+    pub fn component(&self, key: &Key) -> Option<usize> {
+        self.index.get(key).map(|&i| self.component_ids()[i])
+    }
+
+    pub fn community(&self, key: &Key) -> Option<usize> {
+        self.index.get(key).map(|&i| self.community_ids()[i])
+    }
+
+    /// Metric of `key` as a YAML value; null for unknown keys.
+    pub fn value(&self, key: &Key, metric: GraphMetric) -> Value {
+        let value = match metric {
+            GraphMetric::PageRank => self.pagerank(key).map(Value::from),
+            GraphMetric::Betweenness => self.betweenness(key).map(Value::from),
+            GraphMetric::Component => self.component(key).map(|c| Value::from(c as u64)),
+            GraphMetric::Community => self.community(key).map(|c| Value::from(c as u64)),
+        };
+        value.unwrap_or(Value::Null)
+    }
+
+    /// Members of each component, indexed by component id.
+    pub fn components(&self) -> Vec<Vec<Key>> {
+        self.groups(self.component_ids())
+    }
+
+    /// Members of each community, indexed by community id.
+    pub fn communities(&self) -> Vec<Vec<Key>> {
+        self.groups(self.community_ids())
+    }
+
+    /// Groups of documents that include each other, directly or transitively.
+    pub fn inclusion_cycles(&self) -> &[Vec<Key>] {
+        self.inclusion_cycles.get_or_init(|| {
+            strongly_connected(&self.inclusions)
+                .into_iter()
+                .filter(|group| group.len() > 1)
+                .map(|group| group.into_iter().map(|i| self.keys[i].clone()).collect())
+                .collect()
+        })
+    }
+
+    fn component_ids(&self) -> &[usize] {
+        self.component
+            .get_or_init(|| renumber(&components(&self.undirected)))
+    }
+
+    fn community_ids(&self) -> &[usize] {
+        self.community
+            .get_or_init(|| renumber(&label_propagation(&self.undirected)))
+    }
+
+    fn groups(&self, ids: &[usize]) -> Vec<Vec<Key>> {
+        let count = ids.iter().max().map_or(0, |max| max + 1);
+        let mut groups = vec![Vec::new(); count];
+        for (i, &id) in ids.iter().enumerate() {
+            groups[id].push(self.keys[i].clone());
+        }
+        groups
+    }
+}
+
+/// Graph metrics for one query: the analytics are built when a clause or sort first reads a
+/// metric and shared by every later read.
+pub struct MetricCache<'a> {
+    graph: &'a Graph,
+    analytics: OnceLock<GraphAnalytics>,
+}
+
+impl<'a> MetricCache<'a> {
+    pub fn new(graph: &'a Graph) -> Self {
+        MetricCache {
+            graph,
+            analytics: OnceLock::new(),
+        }
+    }
+
+    pub fn value(&self, key: &Key, metric: GraphMetric) -> Value {
+        self.analytics
+            .get_or_init(|| GraphAnalytics::compute(self.graph))
+            .value(key, metric)
+    }
+}
+
+fn adjacency(
+    keys: &[Key],
+    index: &HashMap<Key, usize>,
+    targets: impl Fn(&Key) -> Vec<Key>,
+) -> Vec<Vec<usize>> {
+    keys.iter()
+        .enumerate()
+        .map(|(from, key)| {
+            let mut out: Vec<usize> = targets(key)
+                .iter()
+                .filter_map(|target| index.get(target).copied())
+                .filter(|&to| to != from)
+                .collect();
+            out.sort_unstable();
+            out.dedup();
+            out
+        })
+        .collect()
+}
+
+fn round(value: f64) -> f64 {
+    (value * PRECISION).round() / PRECISION
+}
+
+fn pagerank(outbound: &[Vec<usize>]) -> Vec<f64> {
+    let n = outbound.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_ITERATIONS {
+        let dangling: f64 = (0..n)
+            .filter(|&i| outbound[i].is_empty())
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - DAMPING) / n as f64 + DAMPING * dangling / n as f64;
+        let mut next = vec![base; n];
+        for (from, targets) in outbound.iter().enumerate() {
+            let share = DAMPING * rank[from] / targets.len().max(1) as f64;
+            for &to in targets {
+                next[to] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+    rank.into_iter().map(round).collect()
+}
+
+/// Brandes' algorithm over the undirected graph, normalised to the fraction of shortest paths
+/// between other pairs that pass through each node.
+fn betweenness(undirected: &[Vec<usize>]) -> Vec<f64> {
+    let n = undirected.len();
+    if n < 3 {
+        return vec![0.0; n];
+    }
+    let totals = (0..n)
+        .into_par_iter()
+        .map(|source| single_source_dependencies(undirected, source))
+        .reduce(
+            || vec![0.0; n],
+            |mut acc, partial| {
+                acc.iter_mut().zip(partial).for_each(|(a, p)| *a += p);
+                acc
+            },
+        );
+    let scale = ((n - 1) * (n - 2)) as f64;
+    totals.into_iter().map(|b| round(b / scale)).collect()
+}
+
+fn single_source_dependencies(undirected: &[Vec<usize>], source: usize) -> Vec<f64> {
+    let n = undirected.len();
+    let mut order = Vec::with_capacity(n);
+    let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut paths = vec![0.0; n];
+    let mut distance = vec![usize::MAX; n];
+    paths[source] = 1.0;
+    distance[source] = 0;
+    let mut queue = VecDeque::from([source]);
+    while let Some(v) = queue.pop_front() {
+        order.push(v);
+        for &w in &undirected[v] {
+            if distance[w] == usize::MAX {
+                distance[w] = distance[v] + 1;
+                queue.push_back(w);
+            }
+            if distance[w] == distance[v] + 1 {
+                paths[w] += paths[v];
+                predecessors[w].push(v);
+            }
+        }
+    }
+    let mut dependency = vec![0.0; n];
+    for &w in order.iter().rev() {
+        for &v in &predecessors[w] {
+            dependency[v] += paths[v] / paths[w] * (1.0 + dependency[w]);
+        }
+    }
+    dependency[source] = 0.0;
+    dependency
+}
+
+fn components(undirected: &[Vec<usize>]) -> Vec<usize> {
+    let mut labels = vec![usize::MAX; undirected.len()];
+    for start in 0..undirected.len() {
+        if labels[start] != usize::MAX {
+            continue;
+        }
+        labels[start] = start;
+        let mut queue = VecDeque::from([start]);
+        while let Some(v) = queue.pop_front() {
+            for &w in &undirected[v] {
+                if labels[w] == usize::MAX {
+                    labels[w] = start;
+                    queue.push_back(w);
+                }
+            }
+        }
+    }
+    labels
+}
+
+/// Asynchronous label propagation in key order. Each node adopts the label most common among its
+/// neighbours, keeping its own on a tie that includes it and otherwise taking the smallest.
+fn label_propagation(undirected: &[Vec<usize>]) -> Vec<usize> {
+    let mut labels: Vec<usize> = (0..undirected.len()).collect();
+    for _ in 0..PROPAGATION_ROUNDS {
+        let mut changed = false;
+        for (v, neighbours) in undirected.iter().enumerate() {
+            if neighbours.is_empty() {
+                continue;
+            }
+            let mut counts: HashMap<usize, usize> = HashMap::new();
+            for &w in neighbours {
+                *counts.entry(labels[w]).or_default() += 1;
+            }
+            let best = counts.values().copied().max().unwrap_or(0);
+            if counts.get(&labels[v]) == Some(&best) {
+                continue;
+            }
+            let label = counts
+                .into_iter()
+                .filter(|&(_, count)| count == best)
+                .map(|(label, _)| label)
+                .min()
+                .unwrap_or(labels[v]);
+            labels[v] = label;
+            changed = true;
+        }
+        if !changed {
+            break;
+        }
+    }
+    labels
+}
+
+/// Maps arbitrary group labels to ids ordered by group size, then by first member.
+fn renumber(labels: &[usize]) -> Vec<usize> {
+    let mut groups: HashMap<usize, (usize, usize)> = HashMap::new();
+    for (i, &label) in labels.iter().enumerate() {
+        let entry = groups.entry(label).or_insert((0, i));
+        entry.0 += 1;
+    }
+    let mut ordered: Vec<(usize, usize, usize)> = groups
+        .into_iter()
+        .map(|(label, (size, first))| (label, size, first))
+        .collect();
+    ordered.sort_by(|a, b| b.1.cmp(&a.1).then(a.2.cmp(&b.2)));
+    let ids: HashMap<usize, usize> = ordered
+        .into_iter()
+        .enumerate()
+        .map(|(id, (label, _, _))| (label, id))
+        .collect();
+    labels.iter().map(|label| ids[label]).collect()
+}
+
+/// Tarjan's strongly connected components, iterative so deep inclusion chains cannot overflow the
+/// stack. Members of each group are sorted, and groups are ordered by their first member.
+fn strongly_connected(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let n = adjacency.len();
+    let mut order = vec![usize::MAX; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut counter = 0;
+    let mut groups = Vec::new();
+    for root in 0..n {
+        if order[root] != usize::MAX {
+            continue;
+        }
+        let mut work = vec![(root, 0)];
+        order[root] = counter;
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&(v, next)) = work.last() {
+            if let Some(&w) = adjacency[v].get(next) {
+                work.last_mut().unwrap().1 += 1;
+                if order[w] == usize::MAX {
+                    order[w] = counter;
+                    low[w] = counter;
+                    counter += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(order[w]);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == order[v] {
+                let mut group = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    group.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                group.sort_unstable();
+                groups.push(group);
+            }
+        }
+    }
+    groups.sort();
+    groups
+}
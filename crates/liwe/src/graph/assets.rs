@@ -0,0 +1,59 @@
+use itertools::Itertools;
+
+use crate::graph::{Graph, GraphContext};
+use crate::model::asset::{is_asset_url, resolve_asset};
+use crate::model::Key;
+
+/// A local file a document embeds or links to: an image, a PDF or any other file that is not a
+/// document.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetReference {
+    pub key: Key,
+    /// Library-relative path of the file.
+    pub path: String,
+    /// The url as written in the document.
+    pub url: String,
+}
+
+/// Asset references of every document, ordered by key, then path. A url repeated within one
+/// document is listed once.
+pub fn asset_references(graph: &Graph) -> Vec<AssetReference> {
+    graph
+        .keys()
+        .iter()
+        .sorted()
+        .flat_map(|key| document_asset_references(graph, key))
+        .collect()
+}
+
+/// Whether a link to `url` from a document in `relative_to` targets an asset: it has an asset
+/// extension and names no existing document.
+pub fn is_asset_link(graph: &Graph, url: &str, relative_to: &str) -> bool {
+    is_asset_url(url)
+        && graph
+            .maybe_key(&Key::from_rel_link_url(url, relative_to))
+            .is_none()
+}
+
+/// Asset references of the document `key`, ordered by path. Links that resolve to an existing
+/// document are not asset references.
+pub fn document_asset_references(graph: &Graph, key: &Key) -> Vec<AssetReference> {
+    let relative_to = key.parent();
+    graph
+        .collect(key)
+        .asset_urls()
+        .into_iter()
+        .filter(|url| {
+            graph
+                .maybe_key(&Key::from_rel_link_url(url, &relative_to))
+                .is_none()
+        })
+        .map(|url| AssetReference {
+            key: key.clone(),
+            path: resolve_asset(&url, &relative_to),
+            url,
+        })
+        .sorted_by(|a, b| (&a.path, &a.url).cmp(&(&b.path, &b.url)))
+        .dedup()
+        .collect()
+}
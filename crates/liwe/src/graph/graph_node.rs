@@ -1,3 +1,4 @@
+use crate::model::marker::{Marker, MARKER_FORMAT};
 use crate::model::node::{ColumnAlignment, ReferenceType};
 use crate::model::{Key, LineId, MaybeLineId, MaybeNodeId, NodeId};
 
@@ -208,6 +209,14 @@ impl RawLeaf {
         self.lang.clone()
     }
 
+    /// The generated-results marker this block holds, if it is one.
+    pub fn marker(&self) -> Option<Marker> {
+        if self.lang.as_deref() != Some(MARKER_FORMAT) {
+            return None;
+        }
+        Marker::parse(&self.content)
+    }
+
     pub fn content(&self) -> &str {
         &self.content
     }
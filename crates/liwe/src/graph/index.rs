@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::model::marker::Marker;
 use crate::model::{Key, NodeId};
 
 use super::{graph_node::GraphNode, Graph};
@@ -149,7 +150,12 @@ impl RefIndex {
                 }
                 GraphNode::Empty => {}
                 GraphNode::Raw(raw_leaf) => {
-                    if let Some(child_id) = raw_leaf.next_id() {
+                    let next_id = if raw_leaf.marker() == Some(Marker::Begin { edges: false }) {
+                        graph.generated_region_end(raw_leaf.next_id())
+                    } else {
+                        raw_leaf.next_id()
+                    };
+                    if let Some(child_id) = next_id {
                         stack.push(child_id);
                     }
                 }
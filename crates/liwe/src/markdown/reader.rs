@@ -9,6 +9,7 @@ use serde_yaml::{Mapping, Value};
 
 use crate::model::document::SoftBreak as DocumentSoftBreak;
 use crate::model::document::*;
+use crate::model::marker::{Marker, MARKER_FORMAT};
 use crate::model::*;
 
 pub(crate) const PARSER_OPTIONS: Options = Options::ENABLE_YAML_STYLE_METADATA_BLOCKS
@@ -25,6 +26,7 @@ pub struct MarkdownEventsReader {
     line_offset: usize,
     metadata_block: bool,
     html_block: bool,
+    html: String,
     frontmatter: Option<Mapping>,
     content: Option<String>,
 }
@@ -47,6 +49,7 @@ impl MarkdownEventsReader {
             line_offset: 0,
             metadata_block: false,
             html_block: false,
+            html: String::new(),
             frontmatter: None,
             content: None,
         }
@@ -63,6 +66,7 @@ impl MarkdownEventsReader {
             line_offset: 0,
             metadata_block: false,
             html_block: false,
+            html: String::new(),
             frontmatter: None,
             content: None,
         }
@@ -149,7 +153,11 @@ impl MarkdownEventsReader {
                     self.pop_inline();
                 }
                 DisplayMath(_) => {}
-                Html(_) => {}
+                Html(text) => {
+                    if self.html_block {
+                        self.html.push_str(&text);
+                    }
+                }
                 InlineHtml(text) => {
                     self.push_inline(
                         DocumentInline::Str(text.to_string()),
@@ -272,7 +280,10 @@ impl MarkdownEventsReader {
                     text: String::default(),
                 }))
             }
-            Tag::HtmlBlock => self.html_block = true,
+            Tag::HtmlBlock => {
+                self.html_block = true;
+                self.html.clear();
+            }
             Tag::List(num) => {
                 let line_range = self.to_line_range(range);
                 if num.is_some() {
@@ -390,7 +401,7 @@ impl MarkdownEventsReader {
         }
     }
 
-    fn end_tag(&mut self, tag: TagEnd, _: Range<usize>) {
+    fn end_tag(&mut self, tag: TagEnd, range: Range<usize>) {
         match tag {
             TagEnd::Paragraph => {
                 self.pop_block();
@@ -400,7 +411,17 @@ impl MarkdownEventsReader {
             TagEnd::CodeBlock => {
                 self.pop_block();
             }
-            TagEnd::HtmlBlock => self.html_block = false,
+            TagEnd::HtmlBlock => {
+                self.html_block = false;
+                if let Some(marker) = Marker::parse(&self.html) {
+                    self.push_block(DocumentBlock::RawBlock(RawBlock {
+                        line_range: self.to_line_range(range),
+                        format: MARKER_FORMAT.to_string(),
+                        text: marker.text().to_string(),
+                    }));
+                    self.pop_block();
+                }
+            }
             TagEnd::List(_) => {
                 self.pop_block();
             }
@@ -1,5 +1,6 @@
 use pulldown_cmark::{Event, HeadingLevel, MetadataBlockKind, Tag, TagEnd};
 use pulldown_cmark_to_cmark::cmark_with_options;
+use unicode_width::UnicodeWidthStr;
 
 use crate::model::config::MarkdownOptions;
 use crate::model::document::MathType;
@@ -252,12 +253,12 @@ impl CmarkTableWriter {
             .collect();
 
         let num_cols = header.len();
-        let mut widths: Vec<usize> = header_strs.iter().map(|s| s.chars().count()).collect();
+        let mut widths: Vec<usize> = header_strs.iter().map(|s| s.width()).collect();
 
         for row in &row_strs {
             for (i, cell) in row.iter().enumerate() {
                 if i < widths.len() {
-                    widths[i] = widths[i].max(cell.chars().count());
+                    widths[i] = widths[i].max(cell.width());
                 }
             }
         }
@@ -304,7 +305,7 @@ impl CmarkTableWriter {
 }
 
 fn pad_cell(content: &str, width: usize, alignment: Option<ColumnAlignment>) -> String {
-    let content_len = content.chars().count();
+    let content_len = content.width();
     if content_len >= width {
         return content.to_string();
     }
@@ -6,12 +6,14 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use config::RefsPath;
 
+pub mod asset;
 pub mod config;
 pub mod document;
 pub mod frontmatter;
 pub mod ids;
 pub mod inline;
 pub mod key_index;
+pub mod marker;
 pub mod node;
 pub mod node_iter;
 pub mod node_pointer;
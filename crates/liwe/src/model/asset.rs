@@ -0,0 +1,109 @@
+use percent_encoding::percent_decode_str;
+use relative_path::RelativePath;
+
+/// File extensions, lowercase, that make a local link an asset: images, audio and video, office
+/// documents, data tables and archives.
+const ASSET_EXTENSIONS: [&str; 36] = [
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "bmp", "ico", "tif", "tiff", "heic", "pdf",
+    "epub", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "rtf", "csv", "tsv",
+    "mp3", "wav", "ogg", "flac", "m4a", "mp4", "mov", "webm", "mkv", "zip",
+];
+
+/// Whether `url` points into the library rather than at a web page, an anchor within the same
+/// document or another scheme such as `mailto:` or `data:`.
+pub fn is_local_url(url: &str) -> bool {
+    if url.is_empty() || url.starts_with('#') || url.starts_with("//") {
+        return false;
+    }
+    match url.find(':') {
+        Some(colon) => url[..colon].contains(['/', '?', '#']),
+        None => true,
+    }
+}
+
+/// Whether a link to `url` targets an asset, a local file with a known asset extension such as
+/// `diagram.png` or `../papers/spec.pdf`, rather than another note. Dotted note keys like
+/// `john.doe` are not assets.
+pub fn is_asset_url(url: &str) -> bool {
+    is_local_url(url) && has_asset_extension(url_path(url))
+}
+
+/// Library-relative path of the file `url` points at from a document in `relative_to`, without
+/// fragment or query and with percent-escapes decoded.
+pub fn resolve_asset(url: &str, relative_to: &str) -> String {
+    let decoded = percent_decode_str(url_path(url))
+        .decode_utf8_lossy()
+        .into_owned();
+    match decoded.strip_prefix('/') {
+        Some(absolute) => RelativePath::new(absolute).normalize().to_string(),
+        None => RelativePath::new(relative_to)
+            .join_normalized(&decoded)
+            .to_string(),
+    }
+}
+
+/// `url` rewritten to reach the same file from a document in `to` instead of one in `from`.
+/// Absolute urls do not depend on the document location and come back unchanged.
+pub fn rebase_asset_url(url: &str, from: &str, to: &str) -> String {
+    if from == to || url.starts_with('/') {
+        return url.to_string();
+    }
+    let (path, suffix) = url.split_at(url.find(['#', '?']).unwrap_or(url.len()));
+    let target = RelativePath::new(from).join_normalized(path);
+    format!("{}{}", RelativePath::new(to).relative(target), suffix)
+}
+
+fn url_path(url: &str) -> &str {
+    url.split(['#', '?']).next().unwrap_or_default()
+}
+
+fn has_asset_extension(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let Some((stem, extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    !stem.is_empty() && ASSET_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asset_urls_are_local_files_other_than_documents() {
+        assert!(is_asset_url("diagram.png"));
+        assert!(is_asset_url("../papers/spec.pdf#page=2"));
+        assert!(is_asset_url("/files/data%20set.csv"));
+        assert!(!is_asset_url("note"));
+        assert!(!is_asset_url("note.md"));
+        assert!(!is_asset_url("2024.01.05"));
+        assert!(!is_asset_url("john.doe"));
+        assert!(!is_asset_url("people/jane.smith#bio"));
+        assert!(!is_asset_url("build.sh"));
+        assert!(!is_asset_url("package.json"));
+        assert!(is_asset_url("Photo.JPG"));
+        assert!(!is_asset_url("https://example.com/a.png"));
+        assert!(!is_asset_url("data:image/png;base64,AAAA"));
+        assert!(!is_asset_url("#section.png"));
+    }
+
+    #[test]
+    fn resolves_relative_and_absolute_urls() {
+        assert_eq!(resolve_asset("img/a.png", "notes"), "notes/img/a.png");
+        assert_eq!(resolve_asset("../img/a%20b.png#x", "notes"), "img/a b.png");
+        assert_eq!(resolve_asset("/img/a.png", "notes"), "img/a.png");
+    }
+
+    #[test]
+    fn rebases_to_new_location() {
+        assert_eq!(
+            rebase_asset_url("img/a.png", "", "notes/deep"),
+            "../../img/a.png"
+        );
+        assert_eq!(
+            rebase_asset_url("../img/a.png?raw=1", "notes", "img"),
+            "a.png?raw=1"
+        );
+        assert_eq!(rebase_asset_url("/img/a.png", "", "notes"), "/img/a.png");
+    }
+}
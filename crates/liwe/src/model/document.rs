@@ -66,6 +66,22 @@ impl Document {
             .iter()
             .find_map(|block| block.block_at_position(position))
     }
+
+    /// Every block in document order, including the ones nested in lists, quotes and divs.
+    pub fn all_blocks(&self) -> Vec<&DocumentBlock> {
+        fn collect<'a>(block: &'a DocumentBlock, blocks: &mut Vec<&'a DocumentBlock>) {
+            blocks.push(block);
+            for child in block.child_blocks() {
+                collect(child, blocks);
+            }
+        }
+
+        let mut blocks = vec![];
+        for block in &self.blocks {
+            collect(block, &mut blocks);
+        }
+        blocks
+    }
 }
 
 impl DocumentBlock {
@@ -585,7 +601,7 @@ impl DocumentInline {
             DocumentInline::LineBreak(_) => Inline::LineBreak,
             DocumentInline::Link(link) => {
                 let inlines = to_graph_inlines(&link.inlines, relative_to, key_index);
-                if model::is_ref_url(&link.target.url) && !link.target.url.starts_with('#') {
+                if link.is_reference() {
                     Inline::Reference(Reference {
                         key: key_index.resolve_link_key(
                             &link.target.url,
@@ -652,7 +668,7 @@ impl DocumentInline {
 
     fn is_ref(&self) -> bool {
         match self {
-            DocumentInline::Link(link) => model::is_ref_url(&link.target.url),
+            DocumentInline::Link(link) => model::is_ref_url(&link.target.url) && !link.is_asset(),
             _ => false,
         }
     }
@@ -779,6 +795,18 @@ impl DocumentInline {
             .iter()
             .find_map(|child| child.link_at_position(position))
     }
+
+    /// Links in this inline and its children, in document order.
+    pub fn links(&self) -> Vec<&Link> {
+        match self {
+            DocumentInline::Link(link) => vec![link],
+            _ => self
+                .child_inlines()
+                .into_iter()
+                .flat_map(|child| child.links())
+                .collect(),
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -808,6 +836,18 @@ pub struct Link {
     pub link_type: LinkType,
 }
 
+impl Link {
+    /// Whether the link points at another document, as opposed to a web page, an anchor or an
+    /// asset such as an image or a PDF.
+    pub fn is_reference(&self) -> bool {
+        model::is_ref_url(&self.target.url) && !self.target.url.starts_with('#') && !self.is_asset()
+    }
+
+    fn is_asset(&self) -> bool {
+        self.link_type == LinkType::Markdown && model::asset::is_asset_url(&self.target.url)
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct Ref {
     pub key: Key,
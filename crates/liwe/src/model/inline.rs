@@ -143,6 +143,29 @@ impl Inline {
         }
     }
 
+    pub fn references(&self) -> Vec<&Reference> {
+        match self {
+            Inline::Emph(inner)
+            | Inline::Underline(inner)
+            | Inline::Strong(inner)
+            | Inline::Strikeout(inner)
+            | Inline::Superscript(inner)
+            | Inline::Subscript(inner)
+            | Inline::SmallCaps(inner)
+            | Inline::Mark(inner)
+            | Inline::Insert(inner)
+            | Inline::Delete(inner)
+            | Inline::Span(_, inner)
+            | Inline::Link(_, _, _, inner)
+            | Inline::Image(_, _, inner) => inner
+                .iter()
+                .flat_map(|inline| inline.references())
+                .collect(),
+            Inline::Reference(reference) => vec![reference],
+            _ => vec![],
+        }
+    }
+
     pub fn normalize(&self, context: impl InlinesContext) -> Inline {
         match self {
             Inline::Emph(emph) => Inline::Emph(
@@ -304,6 +327,82 @@ impl Inline {
         }
     }
 
+    /// Urls of the local images and asset links in this inline, as written.
+    pub fn asset_urls(&self) -> Vec<&str> {
+        match self {
+            Inline::Image(url, _, inner) => model::asset::is_local_url(url)
+                .then_some(url.as_str())
+                .into_iter()
+                .chain(inner.iter().flat_map(|inline| inline.asset_urls()))
+                .collect(),
+            Inline::Link(url, _, link_type, inner) => (*link_type == LinkType::Markdown
+                && model::asset::is_asset_url(url))
+            .then_some(url.as_str())
+            .into_iter()
+            .chain(inner.iter().flat_map(|inline| inline.asset_urls()))
+            .collect(),
+            Inline::Emph(inner)
+            | Inline::Underline(inner)
+            | Inline::Strong(inner)
+            | Inline::Strikeout(inner)
+            | Inline::Superscript(inner)
+            | Inline::Subscript(inner)
+            | Inline::SmallCaps(inner)
+            | Inline::Mark(inner)
+            | Inline::Insert(inner)
+            | Inline::Delete(inner)
+            | Inline::Span(_, inner) => inner
+                .iter()
+                .flat_map(|inline| inline.asset_urls())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Rewrites relative asset urls written for a document in `from` so they still resolve from a
+    /// document in `to`.
+    pub fn rebase_assets(&self, from: &str, to: &str) -> Inline {
+        let rebase = |inner: &Inlines| -> Inlines {
+            inner
+                .iter()
+                .map(|inline| inline.rebase_assets(from, to))
+                .collect()
+        };
+        match self {
+            Inline::Image(url, title, inner) => Inline::Image(
+                if model::asset::is_local_url(url) {
+                    model::asset::rebase_asset_url(url, from, to)
+                } else {
+                    url.clone()
+                },
+                title.clone(),
+                rebase(inner),
+            ),
+            Inline::Link(url, title, link_type, inner) => Inline::Link(
+                if *link_type == LinkType::Markdown && model::asset::is_asset_url(url) {
+                    model::asset::rebase_asset_url(url, from, to)
+                } else {
+                    url.clone()
+                },
+                title.clone(),
+                *link_type,
+                rebase(inner),
+            ),
+            Inline::Emph(inner) => Inline::Emph(rebase(inner)),
+            Inline::Underline(inner) => Inline::Underline(rebase(inner)),
+            Inline::Strong(inner) => Inline::Strong(rebase(inner)),
+            Inline::Strikeout(inner) => Inline::Strikeout(rebase(inner)),
+            Inline::Superscript(inner) => Inline::Superscript(rebase(inner)),
+            Inline::Subscript(inner) => Inline::Subscript(rebase(inner)),
+            Inline::SmallCaps(inner) => Inline::SmallCaps(rebase(inner)),
+            Inline::Mark(inner) => Inline::Mark(rebase(inner)),
+            Inline::Insert(inner) => Inline::Insert(rebase(inner)),
+            Inline::Delete(inner) => Inline::Delete(rebase(inner)),
+            Inline::Span(attr, inner) => Inline::Span(attr.clone(), rebase(inner)),
+            _ => self.clone(),
+        }
+    }
+
     pub fn is_ref(&self) -> bool {
         matches!(self, Inline::Reference(_))
     }
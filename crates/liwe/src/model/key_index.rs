@@ -73,6 +73,69 @@ impl KeyIndex {
             .unwrap_or_else(|| Key::name(&target))
     }
 
+    /// Every key a wiki link target could resolve to, in resolution order. More than one means the
+    /// link is ambiguous and [`KeyIndex::resolve_wiki`] picks the first.
+    pub fn wiki_candidates(&self, url: &str) -> Vec<Key> {
+        let decoded = percent_decode_str(url).decode_utf8_lossy().into_owned();
+        let target = strip_doc_extension(&decoded).to_string();
+        let segs = segments(&target);
+
+        let Some(bucket) = segs
+            .last()
+            .and_then(|basename| self.by_basename.get(*basename))
+        else {
+            return Vec::new();
+        };
+
+        let matching: Vec<Key> = bucket
+            .iter()
+            .filter(|key| ends_with_segments(key, &segs))
+            .cloned()
+            .collect();
+        if matching.is_empty() {
+            bucket.clone()
+        } else {
+            matching
+        }
+    }
+
+    /// The existing key a link to the missing `key` most likely meant. Keys sharing its basename
+    /// come first, the one sharing the longest path suffix winning; otherwise the basename fewest
+    /// edits away, within a third of its length, preferring a key in the same directory. `None`
+    /// when nothing is that close.
+    pub fn closest(&self, key: &Key) -> Option<Key> {
+        let segs = segments(key.as_str());
+        let basename = segs.last()?;
+
+        if let Some(bucket) = self.by_basename.get(*basename) {
+            let mut best: Option<(&Key, usize)> = None;
+            for candidate in bucket {
+                let shared = shared_suffix(candidate, &segs);
+                if best.is_none_or(|(_, most)| shared > most) {
+                    best = Some((candidate, shared));
+                }
+            }
+            return best.map(|(candidate, _)| candidate.clone());
+        }
+
+        let wanted = basename.to_lowercase();
+        let limit = (wanted.chars().count() / 3).max(1);
+        let (_, bucket) = self
+            .by_basename
+            .iter()
+            .map(|(name, bucket)| (edit_distance(&wanted, &name.to_lowercase()), name, bucket))
+            .filter(|(distance, _, _)| *distance <= limit)
+            .min_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)))
+            .map(|(distance, _, bucket)| (distance, bucket))?;
+
+        let parent = key.parent();
+        bucket
+            .iter()
+            .find(|candidate| candidate.parent() == parent)
+            .or_else(|| bucket.first())
+            .cloned()
+    }
+
     pub fn shorten_wiki(&self, target: &Key) -> String {
         let path = target.as_str().to_string();
 
@@ -120,6 +183,34 @@ fn ends_with_segments(key: &Key, suffix: &[&str]) -> bool {
     key_segs.len() >= suffix.len() && key_segs[key_segs.len() - suffix.len()..] == *suffix
 }
 
+fn shared_suffix(key: &Key, segs: &[&str]) -> usize {
+    segments(key.as_str())
+        .iter()
+        .rev()
+        .zip(segs.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
 fn segment_count(key: &Key) -> usize {
     segments(key.as_str()).len()
 }
@@ -193,6 +284,40 @@ mod test {
         assert_eq!(Key::name("folder/target"), index.resolve_wiki("target.md"));
     }
 
+    #[test]
+    fn wiki_candidates_lists_every_suffix_match() {
+        let index = index(&["x/a/note", "y/b/note", "other"]);
+        assert_eq!(
+            vec![Key::name("x/a/note"), Key::name("y/b/note")],
+            index.wiki_candidates("note")
+        );
+        assert_eq!(vec![Key::name("y/b/note")], index.wiki_candidates("b/note"));
+        assert!(index.wiki_candidates("missing").is_empty());
+    }
+
+    #[test]
+    fn closest_prefers_shared_basename_then_few_edits() {
+        let index = index(&[
+            "archive/2023/plan",
+            "notes/plan",
+            "notes/project",
+            "other/project",
+        ]);
+        assert_eq!(
+            Some(Key::name("archive/2023/plan")),
+            index.closest(&Key::name("old/2023/plan"))
+        );
+        assert_eq!(
+            Some(Key::name("notes/project")),
+            index.closest(&Key::name("notes/projet"))
+        );
+        assert_eq!(
+            Some(Key::name("other/project")),
+            index.closest(&Key::name("other/porject"))
+        );
+        assert_eq!(None, index.closest(&Key::name("notes/unrelated")));
+    }
+
     #[test]
     fn suffix_disambiguates_shared_basename() {
         let index = index(&["x/a/note", "y/b/note"]);
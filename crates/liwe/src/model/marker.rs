@@ -0,0 +1,38 @@
+//! Comments delimiting the generated results of a live query block (see [`crate::query::live`]).
+//! The reader keeps them as raw blocks so the graph can tell generated content from authored.
+
+/// Raw block format of the generated markers, which are written as HTML comments.
+pub const MARKER_FORMAT: &str = "iwe-generated";
+
+const BEGIN: &str = "<!-- iwe-query:begin -->";
+const BEGIN_WITHOUT_EDGES: &str = "<!-- iwe-query:begin no-edges -->";
+const END: &str = "<!-- iwe-query:end -->";
+
+/// A comment that delimits generated results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    /// Start of the results. Without `edges`, their links are not graph edges.
+    Begin {
+        edges: bool,
+    },
+    End,
+}
+
+impl Marker {
+    pub fn parse(text: &str) -> Option<Marker> {
+        match text.trim() {
+            BEGIN => Some(Marker::Begin { edges: true }),
+            BEGIN_WITHOUT_EDGES => Some(Marker::Begin { edges: false }),
+            END => Some(Marker::End),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            Marker::Begin { edges: true } => BEGIN,
+            Marker::Begin { edges: false } => BEGIN_WITHOUT_EDGES,
+            Marker::End => END,
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::model::config::RefsPath;
 use crate::model::inline::{prepend_checkbox, Inline, Inlines};
+use crate::model::marker::MARKER_FORMAT;
 use crate::model::node::{Node, NodeIter, ReferenceType};
 use crate::model::writer::Block;
 use crate::model::Key;
@@ -140,6 +141,9 @@ impl Projector {
                     blocks.extend(self.with(self.header_level).project_node(child));
                 }
             }
+            Node::Raw(Some(lang), content) if lang == MARKER_FORMAT => {
+                blocks.push(Block::RawBlock(lang, content));
+            }
             Node::Raw(_, _) => {
                 blocks.push(Block::CodeBlock(
                     iter.lang(),
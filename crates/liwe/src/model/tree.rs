@@ -76,6 +76,10 @@ impl Tree {
         matches!(self.node, Node::BulletList() | Node::OrderedList())
     }
 
+    pub fn is_item(&self) -> bool {
+        matches!(self.node, Node::Item(_, _))
+    }
+
     pub fn is_quote(&self) -> bool {
         matches!(self.node, Node::Quote())
     }
@@ -125,6 +129,21 @@ impl Tree {
         }
     }
 
+    /// Checks an unchecked task item or unchecks a checked one.
+    pub fn toggle_task(&self, node_id: NodeId) -> Option<Tree> {
+        let item = self.find_id(node_id)?;
+        let Node::Item(Some(checked), inlines) = &item.node else {
+            return None;
+        };
+        Some(self.replace(
+            node_id,
+            &Tree {
+                node: Node::Item(Some(!checked), inlines.clone()),
+                ..item.clone()
+            },
+        ))
+    }
+
     fn map_children(&self, f: impl Fn(&Tree) -> Tree) -> Tree {
         Tree {
             id: self.id,
@@ -134,6 +153,123 @@ impl Tree {
         }
     }
 
+    /// Swaps a section or list item with the previous sibling of the same kind.
+    pub fn move_up(&self, node_id: NodeId) -> Option<Tree> {
+        self.update_parent_of(node_id, &|parent| {
+            let index = parent.position(node_id);
+            let previous = index.checked_sub(1)?;
+            parent.swap_children(previous, index)
+        })
+    }
+
+    /// Swaps a section or list item with the next sibling of the same kind.
+    pub fn move_down(&self, node_id: NodeId) -> Option<Tree> {
+        self.update_parent_of(node_id, &|parent| {
+            let index = parent.position(node_id);
+            parent.swap_children(index, index + 1)
+        })
+    }
+
+    /// Raises a section one level: it follows its parent section and takes the sections after
+    /// it along as its own sub-sections, so the text keeps its order.
+    pub fn promote_section(&self, node_id: NodeId) -> Option<Tree> {
+        let parent_id = self.get_surrounding_section_id(node_id)?;
+        self.find_id(node_id).filter(|tree| tree.is_section())?;
+        self.update_parent_of(parent_id, &|grandparent| {
+            let index = grandparent.position(parent_id);
+            let parent = &grandparent.children[index];
+            let position = parent.position(node_id);
+
+            let mut section = parent.children[position].clone();
+            section
+                .children
+                .extend(parent.children[position + 1..].iter().cloned());
+            let parent = Tree {
+                children: parent.children[..position].to_vec(),
+                ..parent.clone()
+            };
+
+            let mut children = grandparent.children.clone();
+            children.splice(index..=index, [parent, section]);
+            Some(Tree {
+                children,
+                ..grandparent.clone()
+            })
+        })
+    }
+
+    /// Lowers a section one level, making it the last sub-section of the section before it.
+    pub fn demote_section(&self, node_id: NodeId) -> Option<Tree> {
+        self.update_parent_of(node_id, &|parent| {
+            let index = parent.position(node_id);
+            let previous = index.checked_sub(1)?;
+            let section = &parent.children[index];
+            if !section.is_section() || !parent.children[previous].is_section() {
+                return None;
+            }
+
+            let mut children = parent.children.clone();
+            let section = children.remove(index);
+            children[previous].children.push(section);
+            Some(Tree {
+                children,
+                ..parent.clone()
+            })
+        })
+    }
+
+    /// Appends a section at the end of the document, under its first top-level section when
+    /// there is one.
+    pub fn append_section(&self, section: Tree) -> Tree {
+        let mut children = self.children.clone();
+        match children.iter_mut().find(|child| child.is_section()) {
+            Some(first) => first.children.push(section),
+            None => children.push(section),
+        }
+        Tree {
+            children,
+            ..self.clone()
+        }
+    }
+
+    fn swap_children(&self, a: usize, b: usize) -> Option<Tree> {
+        let (first, second) = (self.children.get(a)?, self.children.get(b)?);
+        let same_kind =
+            (first.is_section() && second.is_section()) || (first.is_item() && second.is_item());
+        if !same_kind {
+            return None;
+        }
+
+        let mut children = self.children.clone();
+        children.swap(a, b);
+        Some(Tree {
+            children,
+            ..self.clone()
+        })
+    }
+
+    /// Rebuilds the tree with the parent of `node_id` replaced by what `f` makes of it.
+    fn update_parent_of(
+        &self,
+        node_id: NodeId,
+        f: &impl Fn(&Tree) -> Option<Tree>,
+    ) -> Option<Tree> {
+        if self.parent_of(node_id) {
+            return f(self);
+        }
+
+        let index = self
+            .children
+            .iter()
+            .position(|child| child.contains(node_id))?;
+        let mut children = self.children.clone();
+        children[index] = children[index].update_parent_of(node_id, f)?;
+        Some(Tree {
+            children,
+            ..self.clone()
+        })
+    }
+
     pub fn get_all_inclusion_edge_keys(&self) -> Vec<Key> {
         if self.is_reference() {
             return self.node.reference_key().into_iter().collect();
@@ -428,6 +564,56 @@ impl Tree {
         }
     }
 
+    /// Urls of the local images and asset links anywhere in the tree, as written.
+    pub fn asset_urls(&self) -> Vec<String> {
+        let inlines: Vec<&Inline> = match &self.node {
+            Node::Section(inlines) | Node::Leaf(inlines) | Node::Item(_, inlines) => {
+                inlines.iter().collect()
+            }
+            Node::Table(table) => table
+                .header
+                .iter()
+                .chain(table.rows.iter().flatten())
+                .flatten()
+                .collect(),
+            _ => vec![],
+        };
+        inlines
+            .into_iter()
+            .flat_map(|inline| inline.asset_urls())
+            .map(str::to_string)
+            .chain(self.children.iter().flat_map(|child| child.asset_urls()))
+            .collect()
+    }
+
+    /// Moves the tree from a document in `from` to one in `to`, rewriting relative asset urls so
+    /// they keep pointing at the same files.
+    pub fn rebase_assets(&self, from: &str, to: &str) -> Tree {
+        if from == to {
+            return self.clone();
+        }
+        let rebase = |inlines: &[Inline]| {
+            inlines
+                .iter()
+                .map(|inline| inline.rebase_assets(from, to))
+                .collect_vec()
+        };
+        Tree {
+            id: self.id,
+            line_range: self.line_range.clone(),
+            node: match &self.node {
+                Node::Section(inlines) => Node::Section(rebase(inlines)),
+                Node::Leaf(inlines) => Node::Leaf(rebase(inlines)),
+                Node::Item(checked, inlines) => Node::Item(*checked, rebase(inlines)),
+                Node::Table(table) => Node::Table(map_table_cells(table, rebase)),
+                _ => self.node.clone(),
+            },
+            children: self
+                .map_children(|child| child.rebase_assets(from, to))
+                .children,
+        }
+    }
+
     pub fn contains(&self, id: NodeId) -> bool {
         self.id_eq(id) || self.children.iter().any(|child| child.contains(id))
     }
@@ -102,7 +102,7 @@ impl Block {
                         format!("{}\n{}\n{}\n", fence, text.trim_matches('\n'), fence)
                     })
             }
-            Block::RawBlock(_, text) => text.clone(),
+            Block::RawBlock(_, text) => format!("{}\n", text.trim_end()),
             Block::BlockQuote(blocks) => {
                 blocks_to_markdown_sparce_indented(blocks, options, indent + 2)
                     .lines()
@@ -8,7 +8,7 @@ mod rename;
 mod select;
 mod util;
 
-pub use attach::{attach_reference, AttachTarget};
+pub use attach::{attach_reference, attach_references, AttachTarget};
 pub use changes::{Changes, OperationError};
 pub use config::{ExtractConfig, InlineConfig};
 pub use delete::delete;
@@ -15,9 +15,60 @@ pub fn attach_reference(
     target_key: &Key,
     reference_key: &Key,
     reference_text: &str,
+) -> AttachTarget {
+    attach_references(
+        graph,
+        target_key,
+        &[(reference_key.clone(), reference_text.to_string())],
+    )
+}
+
+/// Attaches several references to one target in a single rewrite, skipping those the target
+/// already includes. `AlreadyAttached` when none is left to add.
+pub fn attach_references(
+    graph: &Graph,
+    target_key: &Key,
+    references: &[(Key, String)],
 ) -> AttachTarget {
     let format_options = graph.format_options();
-    let reference = Tree {
+    let exists = graph.get_node_id(target_key).is_some();
+    let tree = if exists {
+        graph.collect(target_key)
+    } else {
+        Tree {
+            id: alloc_node_id(),
+            line_range: None,
+            node: Node::Document(target_key.clone(), None),
+            children: vec![],
+        }
+    };
+
+    let included = tree.get_all_inclusion_edge_keys();
+    let mut attached = tree;
+    let mut added = false;
+    for (reference_key, reference_text) in references {
+        if included.contains(reference_key) {
+            continue;
+        }
+        attached = attached.attach(reference_tree(reference_key, reference_text));
+        added = true;
+    }
+
+    if !added {
+        return AttachTarget::AlreadyAttached;
+    }
+    let text = attached
+        .iter()
+        .to_text(&target_key.parent(), format_options);
+    if exists {
+        AttachTarget::Update(text)
+    } else {
+        AttachTarget::Create(text)
+    }
+}
+
+fn reference_tree(reference_key: &Key, reference_text: &str) -> Tree {
+    Tree {
         id: alloc_node_id(),
         line_range: None,
         node: Node::Reference(Reference {
@@ -28,23 +79,5 @@ pub fn attach_reference(
             display_url: None,
         }),
         children: vec![],
-    };
-
-    if graph.get_node_id(target_key).is_some() {
-        let tree = graph.collect(target_key);
-        if tree.get_all_inclusion_edge_keys().contains(reference_key) {
-            return AttachTarget::AlreadyAttached;
-        }
-        AttachTarget::Update(
-            tree.attach(reference)
-                .iter()
-                .to_text(&target_key.parent(), format_options),
-        )
-    } else {
-        AttachTarget::Create(
-            reference
-                .iter()
-                .to_text(&target_key.parent(), format_options),
-        )
     }
 }
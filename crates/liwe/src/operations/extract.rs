@@ -72,7 +72,9 @@ pub fn extract(
 
     let format = graph.format_options().clone();
 
-    let extracted = tree.get(target_id);
+    let extracted = tree
+        .get(target_id)
+        .rebase_assets(&source_key.parent(), &new_key.parent());
     let new_markdown = extracted.iter().to_text(&new_key.parent(), &format);
 
     let reference_type = match &config.link_type {
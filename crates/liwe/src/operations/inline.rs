@@ -37,7 +37,9 @@ pub fn inline(
     }
 
     let format = graph.format_options().clone();
-    let inline_tree = graph.collect(&inline_key);
+    let inline_tree = graph
+        .collect(&inline_key)
+        .rebase_assets(&inline_key.parent(), &source_key.parent());
 
     let mut result = Changes::default();
 
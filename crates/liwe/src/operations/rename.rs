@@ -42,7 +42,9 @@ pub fn rename(graph: &Graph, old_key: &Key, new_key: &Key) -> Result<Changes, Op
     }
 
     let tree = graph.collect(old_key);
-    let updated_tree = tree.change_key(old_key, new_key);
+    let updated_tree = tree
+        .change_key(old_key, new_key)
+        .rebase_assets(&old_key.parent(), &new_key.parent());
     let markdown = updated_tree.iter().to_text(&new_key.parent(), &format);
     result.add_create(new_key.clone(), markdown);
     result.add_remove(old_key.clone());
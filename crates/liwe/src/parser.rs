@@ -1,7 +1,7 @@
 use crate::model::{
     config::FormatOptions,
     document::{Document, DocumentInline},
-    Position,
+    InlineRange, Position,
 };
 
 pub struct Parser {
@@ -18,6 +18,14 @@ impl Parser {
         }
     }
 
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
     pub fn link_at(&self, position: Position) -> Option<DocumentInline> {
         self.document.link_at(position)
     }
@@ -31,19 +39,41 @@ impl Parser {
     }
 
     fn bare_url_at(&self, position: Position) -> Option<String> {
-        let line = self.content.lines().nth(position.line)?;
-        let char_pos = position.character;
-
-        for prefix in ["https://", "http://", "mailto:"] {
-            if let Some(url) = Self::find_url_at_position(line, char_pos, prefix) {
-                return Some(url);
-            }
-        }
+        self.bare_urls(position.line)
+            .into_iter()
+            .find(|(range, _)| {
+                range.start.character <= position.character
+                    && position.character < range.end.character
+            })
+            .map(|(_, url)| url)
+    }
 
-        None
+    /// Bare `https://`, `http://` and `mailto:` URLs on a line, with UTF-16 column ranges.
+    pub fn bare_urls(&self, line: usize) -> Vec<(InlineRange, String)> {
+        let Some(text) = self.content.lines().nth(line) else {
+            return vec![];
+        };
+
+        ["https://", "http://", "mailto:"]
+            .iter()
+            .flat_map(|prefix| Self::find_urls(text, prefix))
+            .map(|(start, end, url)| {
+                (
+                    Position {
+                        line,
+                        character: start,
+                    }..Position {
+                        line,
+                        character: end,
+                    },
+                    url,
+                )
+            })
+            .collect()
     }
 
-    fn find_url_at_position(line: &str, char_pos: usize, prefix: &str) -> Option<String> {
+    fn find_urls(line: &str, prefix: &str) -> Vec<(usize, usize, String)> {
+        let mut urls = vec![];
         let mut search_start = 0;
 
         while let Some(start) = line[search_start..].find(prefix) {
@@ -62,14 +92,12 @@ impl Parser {
             let char_start = line[..absolute_start].encode_utf16().count();
             let char_end = line[..absolute_end].encode_utf16().count();
 
-            if char_pos >= char_start && char_pos < char_end {
-                return Some(url.to_string());
-            }
+            urls.push((char_start, char_end, url.to_string()));
 
             search_start = absolute_start + 1;
         }
 
-        None
+        urls
     }
 }
 
@@ -98,6 +126,35 @@ mod tests {
         assert_eq!(None, parser.url_at((2, 21).into()));
     }
 
+    #[test]
+    fn links_in_nested_blocks() {
+        let parser = Parser::new(
+            indoc! {"
+                # test [[one]]
+
+                - item [two](two)
+                  > quote [three](three)
+                "},
+            &FormatOptions::default(),
+        );
+
+        let urls: Vec<String> = parser
+            .document()
+            .all_blocks()
+            .into_iter()
+            .flat_map(|block| block.child_inlines())
+            .flat_map(|inline| {
+                inline
+                    .links()
+                    .into_iter()
+                    .map(|link| link.target.url.clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        assert_eq!(vec!["one", "two", "three"], urls);
+    }
+
     #[test]
     fn bare_https_url() {
         let parser = Parser::new(
@@ -121,6 +178,29 @@ mod tests {
         assert_eq!(None, parser.url_at((0, 30).into()));
     }
 
+    #[test]
+    fn bare_urls_on_line() {
+        let parser = Parser::new(
+            "See https://a.com and mailto:b@c.org",
+            &FormatOptions::default(),
+        );
+
+        assert_eq!(
+            vec![
+                (
+                    Position::from((0, 4))..Position::from((0, 17)),
+                    "https://a.com".to_string()
+                ),
+                (
+                    Position::from((0, 22))..Position::from((0, 36)),
+                    "mailto:b@c.org".to_string()
+                ),
+            ],
+            parser.bare_urls(0)
+        );
+        assert!(parser.bare_urls(1).is_empty());
+    }
+
     #[test]
     fn bare_http_url() {
         let parser = Parser::new("Visit http://example.org today", &FormatOptions::default());
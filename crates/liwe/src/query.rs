@@ -3,13 +3,17 @@ pub mod block_eval;
 pub mod block_update;
 pub mod builder;
 pub mod cli;
+pub mod computed;
 pub mod document;
 pub mod edges;
 mod eval;
 pub mod execute;
+pub mod explain;
 pub mod filter;
 pub mod frontmatter;
 mod graph_match;
+pub mod links;
+pub mod live;
 pub mod project;
 pub mod scores;
 pub mod search;
@@ -21,13 +25,21 @@ pub use builder::{
     build_projection, build_update_doc, parse_expect, parse_filter_expression, parse_operation,
     ParseError,
 };
+pub use computed::{ComputedField, ComputedFields, EdgeDirection};
 pub use document::{
     BlockUpdate, BlockUpdateOp, CountCmp, CountOp, CountPred, DeleteOp, Expect, FieldOp, FieldPath,
-    Filter, FindOp, InclusionAnchor, KeyOp, Limit, Operation, OperationKind, Projection,
-    ProjectionBase, ProjectionField, ProjectionSource, PseudoField, ReferenceAnchor, Sort, SortDir,
-    Update, UpdateOp, UpdateOperator, YamlType,
+    Filter, FindOp, InclusionAnchor, KeyOp, Limit, Lookup, LookupFrom, Operation, OperationKind,
+    Projection, ProjectionBase, ProjectionField, ProjectionSource, PseudoField, ReferenceAnchor,
+    Sort, SortDir, Update, UpdateOp, UpdateOperator, YamlType,
 };
-pub use eval::evaluate;
-pub use execute::{execute, execute_with_scores, strict_guard_violations, FindMatch, Outcome};
+pub use eval::{evaluate, evaluate_with};
+pub use execute::{
+    execute, execute_with_scores, explain_with_candidates, explain_with_scores,
+    strict_guard_violations, FindMatch, Outcome,
+};
+pub use explain::{
+    evaluate_explained, evaluate_explained_with, ClauseRole, ClauseTrace, Explain, SearchTrace,
+};
+pub use links::{LinkEdge, LinkPredicate, LinkText};
 pub use scores::QueryScores;
 pub use search::SearchSpec;
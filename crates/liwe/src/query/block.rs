@@ -53,6 +53,43 @@ impl TextMatch {
     }
 }
 
+/// A test on the plain text of one table cell.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellMatch {
+    Text(TextMatch),
+    Matches(BlockRegex),
+}
+
+impl CellMatch {
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            CellMatch::Text(m) => m.matches(text),
+            CellMatch::Matches(r) => r.is_match(text),
+        }
+    }
+}
+
+/// Cell tests keyed by column name. A row matches when every named column exists and its cell
+/// passes; column names compare case-insensitively.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RowMatch(pub Vec<(String, CellMatch)>);
+
+impl RowMatch {
+    pub fn matches(&self, columns: &[String], cells: &[String]) -> bool {
+        self.0.iter().all(|(column, m)| {
+            column_index(columns, column)
+                .map(|i| m.matches(cells.get(i).map(String::as_str).unwrap_or("")))
+                .unwrap_or(false)
+        })
+    }
+}
+
+pub fn column_index(columns: &[String], name: &str) -> Option<usize> {
+    columns
+        .iter()
+        .position(|column| column.eq_ignore_ascii_case(name.trim()))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlockType {
     Header,
@@ -75,6 +112,7 @@ pub enum BlockOp {
     List(Box<BlockPredicate>),
     Type(BlockType, Box<BlockPredicate>),
     References(Key),
+    Row(RowMatch),
     And(Vec<BlockPredicate>),
     Or(Vec<BlockPredicate>),
     Nor(Vec<BlockPredicate>),
@@ -214,6 +252,20 @@ impl BlockPredicate {
         self.with(BlockOp::References(Key::name(key)))
     }
 
+    pub fn row(self, cells: &[(&str, &str)]) -> Self {
+        self.with(BlockOp::Row(RowMatch(
+            cells
+                .iter()
+                .map(|(column, text)| {
+                    (
+                        column.to_string(),
+                        CellMatch::Text(TextMatch::Exact(text.to_string())),
+                    )
+                })
+                .collect(),
+        )))
+    }
+
     pub fn and(self, preds: Vec<BlockPredicate>) -> Self {
         self.with(BlockOp::And(preds))
     }
@@ -283,6 +335,7 @@ fn parse_entry(key: &str, value: &Value) -> Result<BlockOp, ParseError> {
             Value::String(s) => Ok(BlockOp::References(Key::name(s))),
             _ => Err(ParseError::OperatorExpectedString { op: "$references" }),
         },
+        "$row" => parse_row(value).map(BlockOp::Row),
         "$and" => parse_list(value, "$and").map(BlockOp::And),
         "$or" => parse_list(value, "$or").map(BlockOp::Or),
         "$nor" => parse_list(value, "$nor").map(BlockOp::Nor),
@@ -307,6 +360,46 @@ fn parse_text(value: &Value) -> Result<BlockOp, ParseError> {
     }
 }
 
+fn parse_row(value: &Value) -> Result<RowMatch, ParseError> {
+    let Value::Mapping(m) = value else {
+        return Err(ParseError::OperatorExpectedMapping { op: "$row" });
+    };
+    let mut cells = Vec::new();
+    for (k, v) in m {
+        let column = k.as_str().ok_or(ParseError::NonStringKey)?;
+        let cell = match v {
+            Value::String(s) => CellMatch::Text(TextMatch::Exact(s.clone())),
+            Value::Number(n) => CellMatch::Text(TextMatch::Exact(n.to_string())),
+            Value::Bool(b) => CellMatch::Text(TextMatch::Exact(b.to_string())),
+            Value::Mapping(inner) if inner.len() == 1 => {
+                match inner
+                    .iter()
+                    .next()
+                    .and_then(|(k, v)| Some((k.as_str()?, v)))
+                {
+                    Some(("$text", v)) => match parse_text(v)? {
+                        BlockOp::Text(m) => CellMatch::Text(m),
+                        _ => unreachable!("parse_text yields a text operator"),
+                    },
+                    Some(("$matches", v)) => CellMatch::Matches(parse_regex(v)?),
+                    _ => {
+                        return Err(ParseError::InvalidRowCell {
+                            column: column.to_string(),
+                        })
+                    }
+                }
+            }
+            _ => {
+                return Err(ParseError::InvalidRowCell {
+                    column: column.to_string(),
+                })
+            }
+        };
+        cells.push((column.to_string(), cell));
+    }
+    Ok(RowMatch(cells))
+}
+
 pub fn parse_regex(value: &Value) -> Result<BlockRegex, ParseError> {
     match value {
         Value::String(s) => BlockRegex::compile(s),
@@ -314,7 +407,7 @@ pub fn parse_regex(value: &Value) -> Result<BlockRegex, ParseError> {
     }
 }
 
-fn parse_within(value: &Value) -> Result<BlockOp, ParseError> {
+pub(crate) fn parse_within(value: &Value) -> Result<BlockOp, ParseError> {
     match value {
         Value::String(s) => Ok(BlockOp::Within(Box::new(BlockPredicate(vec![
             BlockOp::Section(Box::new(BlockPredicate::text_exact(s))),
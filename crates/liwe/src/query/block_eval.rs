@@ -3,14 +3,14 @@ use serde_yaml::{Mapping, Value};
 use crate::graph::{Graph, GraphContext};
 use crate::model::config::MarkdownOptions;
 use crate::model::ids::alloc_node_id;
-use crate::model::inline::{inlines_to_markdown, to_plain_text};
-use crate::model::node::Node;
+use crate::model::inline::{inlines_to_markdown, to_plain_text, Inlines};
+use crate::model::node::{Node, Table};
 use crate::model::projector::Projector;
-use crate::model::reference::ReferenceType;
+use crate::model::reference::{Reference, ReferenceType};
 use crate::model::tree::{Tree, TreeIter};
 use crate::model::writer::{blocks_to_markdown_sparce, Block};
 use crate::model::{Key, NodeId};
-use crate::query::block::{BlockOp, BlockPredicate, BlockType, MatchesSource};
+use crate::query::block::{BlockOp, BlockPredicate, BlockType, MatchesSource, RowMatch};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Kind {
@@ -128,6 +128,20 @@ impl BlockIndex {
         self.eval(pred).into_iter().any(|selected| selected)
     }
 
+    pub fn mask(&self, pred: &BlockPredicate) -> Vec<bool> {
+        self.eval(pred)
+    }
+
+    /// Inline links of every block, paired with the block index. Block references (a paragraph
+    /// holding only a link) are inclusions and are not listed.
+    pub fn inline_links(&self) -> Vec<(usize, &Reference)> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .flat_map(|(i, b)| inline_references(&b.node).into_iter().map(move |r| (i, r)))
+            .collect()
+    }
+
     pub fn coalesced_targets(&self, pred: &BlockPredicate) -> Vec<Target> {
         let mask = self.eval(pred);
         let mut sub_full = vec![false; self.blocks.len()];
@@ -202,6 +216,13 @@ impl BlockIndex {
         self.blocks[i].kind == Kind::List
     }
 
+    pub fn table(&self, i: usize) -> Option<&Table> {
+        match &self.blocks[i].node {
+            Node::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
     pub fn own_text(&self, i: usize) -> Option<&str> {
         self.blocks[i].own_text.as_deref()
     }
@@ -375,6 +396,14 @@ impl BlockIndex {
                 .iter()
                 .map(|b| b.ref_targets.contains(key))
                 .collect(),
+            BlockOp::Row(row) => self
+                .blocks
+                .iter()
+                .map(|b| match &b.node {
+                    Node::Table(table) => has_matching_row(table, row),
+                    _ => false,
+                })
+                .collect(),
             BlockOp::And(preds) => {
                 let mut out = vec![true; self.blocks.len()];
                 for p in preds {
@@ -568,6 +597,75 @@ impl BlockIndex {
             .collect()
     }
 
+    /// One record per row of the selected tables, keyed by column name. Rows are narrowed by
+    /// the predicate's `$row` tests, see [`BlockIndex::passing_rows`].
+    pub fn rows_entries(&self, pred: &BlockPredicate) -> Vec<Mapping> {
+        let selected = self.eval(pred);
+        let mut out = Vec::new();
+        for (i, b) in self.blocks.iter().enumerate() {
+            let (true, Node::Table(table)) = (selected[i], &b.node) else {
+                continue;
+            };
+            let columns = column_names(table);
+            for r in self.passing_rows(i, pred) {
+                let mut entry = Mapping::new();
+                for (column, cell) in columns.iter().zip(table.rows[r].iter()) {
+                    let resolved =
+                        Projector::resolve(&self.parent_dir, self.options.refs_path, cell.clone());
+                    entry.insert(
+                        Value::String(column.clone()),
+                        Value::String(inlines_to_markdown(&resolved, &self.options)),
+                    );
+                }
+                out.push(entry);
+            }
+        }
+        out
+    }
+
+    /// Indexes of the rows of the table at block `i` that pass `pred`. A `$row` test reads the
+    /// row, wherever it sits under `$and`, `$or`, `$nor` or `$table`; every other test reads the
+    /// table block as a whole. Without `$row` tests every row passes when the table does.
+    pub fn passing_rows(&self, i: usize, pred: &BlockPredicate) -> Vec<usize> {
+        let Some(table) = self.table(i) else {
+            return Vec::new();
+        };
+        let test = self.row_test(i, pred);
+        let columns = column_names(table);
+        table
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| test.passes(&columns, &plain_cells(row)))
+            .map(|(r, _)| r)
+            .collect()
+    }
+
+    fn row_test<'a>(&self, i: usize, pred: &'a BlockPredicate) -> RowTest<'a> {
+        let tests = |preds: &'a [BlockPredicate]| {
+            preds
+                .iter()
+                .map(|p| self.row_test(i, p))
+                .collect::<Vec<_>>()
+        };
+        RowTest::All(
+            pred.0
+                .iter()
+                .map(|op| match op {
+                    BlockOp::Row(row) => RowTest::Row(row),
+                    BlockOp::And(preds) => RowTest::All(tests(preds)),
+                    BlockOp::Or(preds) => RowTest::Any(tests(preds)),
+                    BlockOp::Nor(preds) => RowTest::Nor(tests(preds)),
+                    BlockOp::Type(t, p) => RowTest::All(vec![
+                        RowTest::Fixed(self.blocks[i].kind == Kind::of(*t)),
+                        self.row_test(i, p),
+                    ]),
+                    op => RowTest::Fixed(self.eval_op(op)[i]),
+                })
+                .collect(),
+        )
+    }
+
     pub fn matches_entries(&self, source: &MatchesSource) -> Vec<Mapping> {
         let scope = self.eval(&source.scope);
         let mut out = Vec::new();
@@ -609,6 +707,60 @@ fn kind_of(node: &Node) -> Kind {
     }
 }
 
+/// Header cells as plain text; an empty header cell is named by its position, as `column2`.
+pub fn column_names(table: &Table) -> Vec<String> {
+    table
+        .header
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let name = to_plain_text(cell).trim().to_string();
+            if name.is_empty() {
+                format!("column{}", i + 1)
+            } else {
+                name
+            }
+        })
+        .collect()
+}
+
+/// Whether any row of `table` passes `row`, compared on the plain text of its cells.
+fn has_matching_row(table: &Table, row: &RowMatch) -> bool {
+    let columns = column_names(table);
+    table
+        .rows
+        .iter()
+        .any(|cells| row.matches(&columns, &plain_cells(cells)))
+}
+
+fn plain_cells(row: &[Inlines]) -> Vec<String> {
+    row.iter()
+        .map(|cell| to_plain_text(cell).trim().to_string())
+        .collect()
+}
+
+/// A block predicate resolved against one table: `$row` tests still to run per row, the rest
+/// already decided for the table.
+enum RowTest<'a> {
+    Row(&'a RowMatch),
+    Fixed(bool),
+    All(Vec<RowTest<'a>>),
+    Any(Vec<RowTest<'a>>),
+    Nor(Vec<RowTest<'a>>),
+}
+
+impl RowTest<'_> {
+    fn passes(&self, columns: &[String], cells: &[String]) -> bool {
+        match self {
+            RowTest::Row(row) => row.matches(columns, cells),
+            RowTest::Fixed(passes) => *passes,
+            RowTest::All(tests) => tests.iter().all(|test| test.passes(columns, cells)),
+            RowTest::Any(tests) => tests.iter().any(|test| test.passes(columns, cells)),
+            RowTest::Nor(tests) => !tests.iter().any(|test| test.passes(columns, cells)),
+        }
+    }
+}
+
 fn ref_targets(node: &Node) -> Vec<Key> {
     match node {
         Node::Reference(reference) => vec![reference.key.clone()],
@@ -626,6 +778,22 @@ fn ref_targets(node: &Node) -> Vec<Key> {
     }
 }
 
+fn inline_references(node: &Node) -> Vec<&Reference> {
+    match node {
+        Node::Section(inlines) | Node::Leaf(inlines) | Node::Item(_, inlines) => {
+            inlines.iter().flat_map(|i| i.references()).collect()
+        }
+        Node::Table(table) => table
+            .header
+            .iter()
+            .chain(table.rows.iter().flatten())
+            .flatten()
+            .flat_map(|i| i.references())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
 fn path_value(path: &[String]) -> Value {
     Value::Sequence(path.iter().map(|p| Value::String(p.clone())).collect())
 }
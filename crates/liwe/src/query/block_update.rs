@@ -5,11 +5,12 @@ use crate::markdown::MarkdownReader;
 use crate::model::config::MarkdownOptions;
 use crate::model::ids::alloc_node_id;
 use crate::model::inline::Inline;
-use crate::model::node::Node;
+use crate::model::node::{Node, Table};
 use crate::model::tree::Tree;
 use crate::model::{Key, NodeId};
+use crate::query::block::column_index;
 use crate::query::block::BlockPredicate;
-use crate::query::block_eval::{BlockIndex, Target};
+use crate::query::block_eval::{column_names, BlockIndex, Target};
 use crate::query::document::{BlockUpdate, BlockUpdateOp, Expect};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -73,6 +74,11 @@ pub enum EvalError {
         actual: usize,
         documents: Vec<DocRef>,
     },
+    UnknownColumn {
+        op: &'static str,
+        column: String,
+        blocks: Vec<BlockRef>,
+    },
     SearchIndexMissing,
 }
 
@@ -179,6 +185,13 @@ impl std::fmt::Display for EvalError {
                 actual,
                 docs_str(documents)
             ),
+            EvalError::UnknownColumn { op, column, blocks } => write!(
+                f,
+                "{} column '{}' is not in the table\n{}",
+                op,
+                column,
+                blocks_str(blocks)
+            ),
             EvalError::SearchIndexMissing => write!(
                 f,
                 "'search' requires the search-indexed graph, which is not built for this command"
@@ -205,6 +218,7 @@ enum Action {
     SetInlines(Vec<Inline>),
     SetRefText(String),
     SetRawContent(String),
+    SetTable(Table),
 }
 
 struct DocCtx {
@@ -244,6 +258,12 @@ fn acted_targets(index: &BlockIndex, op: &BlockUpdateOp, selector: &BlockPredica
             .into_iter()
             .map(|idx| Target { idx, tree: false })
             .collect(),
+        BlockUpdateOp::AppendRow { .. } | BlockUpdateOp::SetCells { .. } => index
+            .select(selector)
+            .into_iter()
+            .filter(|&idx| index.is_table(idx))
+            .map(|idx| Target { idx, tree: false })
+            .collect(),
         _ => index.coalesced_targets(selector),
     }
 }
@@ -265,6 +285,7 @@ pub fn plan_and_apply(
     let mut replacetext_no_text: Vec<BlockRef> = Vec::new();
     let mut fragment_not_list: Vec<(&'static str, BlockRef)> = Vec::new();
     let mut anchor_fail: Vec<BlockRef> = Vec::new();
+    let mut unknown_column: Vec<(&'static str, String, BlockRef)> = Vec::new();
 
     for (d, doc) in docs.iter().enumerate() {
         for (o, bu) in ops.iter().enumerate() {
@@ -380,6 +401,23 @@ pub fn plan_and_apply(
                             None => fragment_not_list.push(("$append", doc.block_ref(i))),
                         }
                     }
+                    BlockUpdateOp::AppendRow { cells } | BlockUpdateOp::SetCells { cells } => {
+                        let Some(table) = doc.index.table(i) else {
+                            continue;
+                        };
+                        let rows = match &bu.op {
+                            BlockUpdateOp::AppendRow { .. } => vec![table.rows.len()],
+                            _ => doc.index.passing_rows(i, &bu.selector),
+                        };
+                        match set_cells(doc, table, &rows, cells) {
+                            Ok(updated) => {
+                                edits[d].insert(id, Action::SetTable(updated));
+                            }
+                            Err(column) => {
+                                unknown_column.push((bu.op.name(), column, doc.block_ref(i)))
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -400,6 +438,11 @@ pub fn plan_and_apply(
         let blocks = fragment_not_list.into_iter().map(|(_, b)| b).collect();
         return Err(EvalError::FragmentNotList { op, blocks });
     }
+    if let Some((op, column, _)) = unknown_column.first() {
+        let (op, column) = (*op, column.clone());
+        let blocks = unknown_column.into_iter().map(|(_, _, b)| b).collect();
+        return Err(EvalError::UnknownColumn { op, column, blocks });
+    }
     if !anchor_fail.is_empty() {
         return Err(EvalError::ReplaceTextAnchor {
             blocks: anchor_fail,
@@ -506,6 +549,41 @@ pub fn check_document_expect(
     Ok(())
 }
 
+/// `table` with `cells` written into each of `rows`; a row index past the end appends a row.
+/// Fails with the first column name the header does not have.
+fn set_cells(
+    doc: &DocCtx,
+    table: &Table,
+    rows: &[usize],
+    cells: &[(String, String)],
+) -> Result<Table, String> {
+    let columns = column_names(table);
+    let mut resolved = Vec::new();
+    for (column, text) in cells {
+        let index = column_index(&columns, column).ok_or_else(|| column.clone())?;
+        let inlines = if text.trim().is_empty() {
+            Vec::new()
+        } else {
+            parse_inlines(doc.index.options(), doc.index.parent_dir(), text)
+        };
+        resolved.push((index, inlines));
+    }
+    let mut updated = table.clone();
+    for &r in rows {
+        if r == updated.rows.len() {
+            updated.rows.push(Vec::new());
+        }
+        let row = &mut updated.rows[r];
+        if row.len() < columns.len() {
+            row.resize(columns.len(), Vec::new());
+        }
+        for (index, inlines) in &resolved {
+            row[*index] = inlines.clone();
+        }
+    }
+    Ok(updated)
+}
+
 fn fragment_for(doc: &DocCtx, content: &str, list_mode: bool) -> Option<Vec<Tree>> {
     let blocks = parse_fragment(doc.index.options(), doc.index.parent_dir(), content);
     if list_mode {
@@ -627,6 +705,11 @@ fn apply_edits(tree: &Tree, edits: &HashMap<NodeId, Action>) -> Tree {
                 updated.node = with_raw_content(&updated.node, content.clone());
                 children.push(updated);
             }
+            Some(Action::SetTable(table)) => {
+                let mut updated = apply_edits(child, edits);
+                updated.node = Node::Table(table.clone());
+                children.push(updated);
+            }
             None => children.push(apply_edits(child, edits)),
         }
     }
@@ -1,17 +1,19 @@
 use serde_yaml::{Mapping, Value};
 
+use crate::graph::analytics::GraphMetric;
 use crate::model::Key;
 use crate::query::block::{parse_block_predicate, parse_matches_source, BlockPredicate};
 use crate::query::document::{
     BlockUpdate, BlockUpdateOp, CountCmp, CountOp, CountPred, DeleteOp, Expect, FieldOp, FieldPath,
-    Filter, FindOp, InclusionAnchor, KeyOp, Limit, Operation, OperationKind, Projection,
-    ProjectionBase, ProjectionField, ProjectionSource, PseudoField, ReferenceAnchor, Sort, SortDir,
-    Update, UpdateOp, UpdateOperator, YamlType,
+    Filter, FindOp, InclusionAnchor, KeyOp, Limit, Lookup, LookupFrom, Operation, OperationKind,
+    Projection, ProjectionBase, ProjectionField, ProjectionSource, PseudoField, ReferenceAnchor,
+    Sort, SortDir, Update, UpdateOp, UpdateOperator, YamlType,
 };
+use crate::query::links::parse_link_predicate;
 use crate::query::search::SearchSpec;
 use crate::query::wire::{
-    self, RawFilter, RawKeyOpMap, RawOperation, RawProjection, RawRelationalObj, RawSearch,
-    RawSort, RawUpdate,
+    self, RawFilter, RawKeyOpMap, RawLookup, RawOperation, RawProjection, RawRelationalObj,
+    RawSearch, RawSort, RawUpdate,
 };
 
 #[derive(Debug)]
@@ -160,8 +162,21 @@ pub enum ParseError {
         op: &'static str,
         key: &'static str,
     },
+    InvalidRowCell {
+        column: String,
+    },
+    BlockPayloadExpectedCells {
+        op: &'static str,
+        key: &'static str,
+    },
     InvalidExpect,
     EmptySearch,
+    UnknownLinkKey {
+        key: String,
+    },
+    UnknownLinkType {
+        name: String,
+    },
 }
 
 fn fmt_path(path: &[String]) -> String {
@@ -324,6 +339,16 @@ impl std::fmt::Display for ParseError {
             Self::BlockPayloadExpectedString { op, key } => {
                 write!(f, "'{}' key '{}' expects a string", op, key)
             }
+            Self::InvalidRowCell { column } => write!(
+                f,
+                "'$row' column '{}' expects a scalar, or a mapping with $text or $matches",
+                column
+            ),
+            Self::BlockPayloadExpectedCells { op, key } => write!(
+                f,
+                "'{}' key '{}' expects a mapping of column names to scalar values",
+                op, key
+            ),
             Self::InvalidExpect => write!(
                 f,
                 "'expect' must be a non-negative integer or a mapping of 'min' / 'max'"
@@ -332,6 +357,16 @@ impl std::fmt::Display for ParseError {
                 f,
                 "'search' requires at least one of 'lexical' / 'fuzzy'"
             ),
+            Self::UnknownLinkKey { key } => write!(
+                f,
+                "unknown key '{}' in 'link' (expected type, text, titleDiffers, within)",
+                key
+            ),
+            Self::UnknownLinkType { name } => write!(
+                f,
+                "unknown link type '{}' (expected regular, wikiLink, wikiLinkPiped)",
+                name
+            ),
         }
     }
 }
@@ -605,10 +640,39 @@ fn build_filter_op(op: &str, value: &Value, path: &[String]) -> Result<Filter, P
             value,
             "$referencedBy",
         )?))),
-        other => Err(ParseError::UnknownOperator {
-            op: other.to_string(),
-            path: path.to_vec(),
-        }),
+        other => match GraphMetric::from_name(other) {
+            Some(metric) => build_metric_clause(metric, value, path),
+            None => Err(ParseError::UnknownOperator {
+                op: other.to_string(),
+                path: path.to_vec(),
+            }),
+        },
+    }
+}
+
+fn build_metric_clause(
+    metric: GraphMetric,
+    value: &Value,
+    path: &[String],
+) -> Result<Filter, ParseError> {
+    let Value::Mapping(map) = value else {
+        return Ok(Filter::Metric {
+            metric,
+            op: FieldOp::Eq(value.clone()),
+        });
+    };
+    let mut ops = Vec::with_capacity(map.len());
+    for (k, v) in map {
+        let op = k.as_str().ok_or(ParseError::NonStringKey)?;
+        ops.push(Filter::Metric {
+            metric,
+            op: build_field_op(op, v.clone(), path)?,
+        });
+    }
+    if ops.len() == 1 {
+        Ok(ops.into_iter().next().unwrap())
+    } else {
+        Ok(Filter::And(ops))
     }
 }
 
@@ -926,6 +990,9 @@ fn build_projection_source(output: &str, v: &Value) -> Result<ProjectionSource,
             if s == "$blocks" {
                 return Ok(ProjectionSource::Blocks(BlockPredicate::empty()));
             }
+            if s == "$rows" {
+                return Ok(ProjectionSource::Rows(BlockPredicate::empty()));
+            }
             if let Some(stripped) = s.strip_prefix('$') {
                 let selector = format!("${}", stripped);
                 if let Some(pf) = PseudoField::from_selector(&selector) {
@@ -959,7 +1026,13 @@ fn build_projection_source(output: &str, v: &Value) -> Result<ProjectionSource,
                 "$blocks" => Ok(ProjectionSource::Blocks(parse_block_predicate(
                     v, "$blocks",
                 )?)),
+                "$rows" => Ok(ProjectionSource::Rows(parse_block_predicate(v, "$rows")?)),
                 "$matches" => Ok(ProjectionSource::Matches(parse_matches_source(v)?)),
+                "$lookup" => Ok(ProjectionSource::Lookup(Box::new(parse_lookup(v)?))),
+                "$references" | "$referencedBy" => Ok(ProjectionSource::Links {
+                    outbound: selector == "$references",
+                    predicate: parse_link_predicate(v)?,
+                }),
                 s if s.starts_with('$') => Err(ParseError::UnknownProjectionSource {
                     selector: s.to_string(),
                 }),
@@ -974,6 +1047,49 @@ fn build_projection_source(output: &str, v: &Value) -> Result<ProjectionSource,
     }
 }
 
+fn parse_lookup(value: &Value) -> Result<Lookup, ParseError> {
+    if !value.is_mapping() {
+        return Err(ParseError::OperatorExpectedMapping { op: "$lookup" });
+    }
+    let raw: RawLookup = serde_yaml::from_value(value.clone()).map_err(ParseError::Wire)?;
+    let (from, depth_modifier) = match raw.from.as_str() {
+        "$includes" => (LookupFrom::Includes, "maxDepth"),
+        "$includedBy" => (LookupFrom::IncludedBy, "maxDepth"),
+        "$references" => (LookupFrom::References, "maxDistance"),
+        "$referencedBy" => (LookupFrom::ReferencedBy, "maxDistance"),
+        s if s.starts_with('$') => {
+            return Err(ParseError::UnknownProjectionSource {
+                selector: s.to_string(),
+            })
+        }
+        s => {
+            let segments: Vec<String> = s.split('.').map(|p| p.to_string()).collect();
+            check_path_segments(&segments)?;
+            (LookupFrom::Field(FieldPath(segments)), "maxDepth")
+        }
+    };
+    let (depth, wrong) = if depth_modifier == "maxDepth" {
+        (raw.max_depth, raw.max_distance.map(|_| "maxDistance"))
+    } else {
+        (raw.max_distance, raw.max_depth.map(|_| "maxDepth"))
+    };
+    if let Some(modifier) = wrong {
+        return Err(ParseError::WrongBoundFamily {
+            op: "$lookup",
+            modifier,
+        });
+    }
+    let mut lookup =
+        Lookup::new(from).max_depth(parse_max_bound(depth, "$lookup", depth_modifier)?);
+    if let Some(m) = raw.match_ {
+        lookup = lookup.filter(build_filter_at(m, &[])?);
+    }
+    if let Some(p) = raw.project {
+        lookup = lookup.project(build_projection(p, ProjectionBase::Empty)?);
+    }
+    Ok(lookup)
+}
+
 fn build_sort(raw: RawSort) -> Result<Sort, ParseError> {
     let map = raw.0;
     if map.is_empty() {
@@ -1052,7 +1168,7 @@ pub fn build_update_doc(raw: RawUpdate) -> Result<Update, ParseError> {
                 walk_update_unset(unset, &[], &mut operators)?;
             }
             "$replace" | "$replaceText" | "$insertBefore" | "$insertAfter" | "$append"
-            | "$delete" => {
+            | "$delete" | "$appendRow" | "$setCells" => {
                 block_ops.push(build_block_update(key, v)?);
             }
             other => {
@@ -1080,6 +1196,8 @@ fn block_op_static_name(key: &str) -> &'static str {
         "$insertAfter" => "$insertAfter",
         "$append" => "$append",
         "$delete" => "$delete",
+        "$appendRow" => "$appendRow",
+        "$setCells" => "$setCells",
         _ => unreachable!("only block operator keys reach here"),
     }
 }
@@ -1094,6 +1212,7 @@ fn build_block_update(key: &str, value: &Value) -> Result<BlockUpdate, ParseErro
     let mut content: Option<String> = None;
     let mut from: Option<String> = None;
     let mut to: Option<String> = None;
+    let mut cells: Option<Vec<(String, String)>> = None;
     let mut expect: Option<Expect> = None;
 
     for (k, v) in map {
@@ -1118,6 +1237,12 @@ fn build_block_update(key: &str, value: &Value) -> Result<BlockUpdate, ParseErro
             "to" if op == "$replaceText" => {
                 to = Some(payload_string(v, op, "to")?);
             }
+            "row" if op == "$appendRow" => {
+                cells = Some(payload_cells(v, op, "row")?);
+            }
+            "set" if op == "$setCells" => {
+                cells = Some(payload_cells(v, op, "set")?);
+            }
             "expect" => {
                 expect = Some(parse_expect(v)?);
             }
@@ -1150,6 +1275,12 @@ fn build_block_update(key: &str, value: &Value) -> Result<BlockUpdate, ParseErro
             content: require_payload(content, op, "content")?,
         },
         "$delete" => BlockUpdateOp::Delete,
+        "$appendRow" => BlockUpdateOp::AppendRow {
+            cells: require_payload(cells, op, "row")?,
+        },
+        "$setCells" => BlockUpdateOp::SetCells {
+            cells: require_payload(cells, op, "set")?,
+        },
         _ => unreachable!(),
     };
 
@@ -1171,11 +1302,34 @@ fn payload_string(
     }
 }
 
-fn require_payload(
-    value: Option<String>,
+fn payload_cells(
+    value: &Value,
     op: &'static str,
     key: &'static str,
-) -> Result<String, ParseError> {
+) -> Result<Vec<(String, String)>, ParseError> {
+    let map = value
+        .as_mapping()
+        .ok_or(ParseError::BlockPayloadExpectedCells { op, key })?;
+    map.iter()
+        .map(|(k, v)| {
+            let column = k.as_str().ok_or(ParseError::NonStringKey)?;
+            let text = match v {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                Value::Null => String::new(),
+                _ => return Err(ParseError::BlockPayloadExpectedCells { op, key }),
+            };
+            Ok((column.to_string(), text))
+        })
+        .collect()
+}
+
+fn require_payload<T>(
+    value: Option<T>,
+    op: &'static str,
+    key: &'static str,
+) -> Result<T, ParseError> {
     value.ok_or(ParseError::MissingBlockPayload { op, key })
 }
 
@@ -1505,6 +1659,12 @@ fn parse_inclusion_arg(value: &Value, op: &'static str) -> Result<InclusionAncho
             modifier: "minDistance",
         });
     }
+    if raw.link.is_some() {
+        return Err(ParseError::WrongBoundFamily {
+            op,
+            modifier: "link",
+        });
+    }
     let match_filter = match_to_filter(&raw)?;
     let max_depth = parse_max_bound(raw.max_depth, op, "maxDepth")?;
     let min_depth = parse_min_bound(raw.min_depth, op, "minDepth")?;
@@ -1555,8 +1715,10 @@ fn parse_reference_arg(value: &Value, op: &'static str) -> Result<ReferenceAncho
         .as_ref()
         .map(|v| parse_count_pred(v, "$size"))
         .transpose()?;
+    let link = raw.link.as_ref().map(parse_link_predicate).transpose()?;
     let mut anchor = ReferenceAnchor::with_match(match_filter, min_distance, max_distance);
     anchor.size = size;
+    anchor.link = link;
     Ok(anchor)
 }
 
@@ -66,6 +66,13 @@ fn parse_item(item: &str) -> Result<ProjectionField, String> {
         });
     }
 
+    if item == "$rows" {
+        return Ok(ProjectionField {
+            output: "rows".to_string(),
+            source: ProjectionSource::Rows(BlockPredicate::empty()),
+        });
+    }
+
     if let Some(stripped) = item.strip_prefix('$') {
         let selector = format!("${}", stripped);
         let pf = PseudoField::from_selector(&selector)
@@ -87,6 +94,9 @@ fn parse_source(src: &str) -> Result<ProjectionSource, String> {
     if src == "$blocks" {
         return Ok(ProjectionSource::Blocks(BlockPredicate::empty()));
     }
+    if src == "$rows" {
+        return Ok(ProjectionSource::Rows(BlockPredicate::empty()));
+    }
     if let Some(stripped) = src.strip_prefix('$') {
         let selector = format!("${}", stripped);
         let pf = PseudoField::from_selector(&selector)
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Local, NaiveDate};
+use serde_yaml::{Mapping, Value};
+
+use crate::graph::analytics::{GraphMetric, MetricCache};
+use crate::graph::Graph;
+use crate::model::Key;
+use crate::query::block::BlockPredicate;
+use crate::query::block_eval::BlockIndex;
+use crate::query::document::{FieldPath, Sort};
+use crate::query::edges;
+use crate::query::filter::{resolve_path, Resolution};
+use crate::query::sort::sort_in_place;
+
+/// Token counter injected by the host; `liwe` has no tokenizer of its own.
+pub type TokenCounter = fn(&str) -> usize;
+
+/// Built-in function behind a virtual field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComputedField {
+    /// Tokens in the document body, counted by the registered [`TokenCounter`].
+    Tokens,
+    /// Whitespace-separated words in the document body.
+    Words,
+    /// Blocks matching a block predicate.
+    Count(BlockPredicate),
+    /// Distinct documents on the other end of one edge family.
+    Edges(EdgeDirection),
+    /// Whole days from the date at `from` to the date at `to`, or to today when `to` is unset.
+    Days {
+        from: FieldPath,
+        to: Option<FieldPath>,
+    },
+    /// First present, non-null frontmatter value among the paths.
+    Coalesce(Vec<FieldPath>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeDirection {
+    Includes,
+    IncludedBy,
+    References,
+    ReferencedBy,
+}
+
+impl EdgeDirection {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "includes" => Some(EdgeDirection::Includes),
+            "includedBy" => Some(EdgeDirection::IncludedBy),
+            "references" => Some(EdgeDirection::References),
+            "referencedBy" => Some(EdgeDirection::ReferencedBy),
+            _ => None,
+        }
+    }
+}
+
+/// Named virtual fields resolved on read, next to each document's frontmatter.
+///
+/// Filters, sorts and projections see a computed field wherever a frontmatter field of the same
+/// name would be read; a computed field shadows a frontmatter key with the same name. Values are
+/// never stored in the frontmatter, so updates do not write them back.
+#[derive(Debug, Clone, Default)]
+pub struct ComputedFields {
+    fields: BTreeMap<String, ComputedField>,
+    tokens: Option<TokenCounter>,
+    today: Option<NaiveDate>,
+}
+
+impl ComputedFields {
+    pub fn new() -> Self {
+        ComputedFields::default()
+    }
+
+    pub fn field(mut self, name: impl Into<String>, field: ComputedField) -> Self {
+        self.fields.insert(name.into(), field);
+        self
+    }
+
+    pub fn token_counter(mut self, counter: TokenCounter) -> Self {
+        self.tokens = Some(counter);
+        self
+    }
+
+    /// Pins the date `days` fields measure to when `to` is unset.
+    pub fn today(mut self, today: NaiveDate) -> Self {
+        self.today = Some(today);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ComputedField> {
+        self.fields.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.fields.keys().map(|name| name.as_str())
+    }
+
+    /// Computed field named by the first segment of `path`, if any.
+    pub fn for_path(&self, path: &FieldPath) -> Option<(&str, &ComputedField)> {
+        let first = path.segments().first()?;
+        self.fields
+            .get_key_value(first)
+            .map(|(name, field)| (name.as_str(), field))
+    }
+
+    pub fn value(&self, graph: &Graph, key: &Key, field: &ComputedField) -> Value {
+        match field {
+            ComputedField::Tokens => match self.tokens {
+                Some(count) => count_value(count(&graph.to_markdown_skip_frontmatter(key))),
+                None => Value::Null,
+            },
+            ComputedField::Words => count_value(
+                graph
+                    .to_markdown_skip_frontmatter(key)
+                    .split_whitespace()
+                    .count(),
+            ),
+            ComputedField::Count(pred) => {
+                count_value(BlockIndex::build(graph, key).select(pred).len())
+            }
+            ComputedField::Edges(direction) => count_value(
+                match direction {
+                    EdgeDirection::Includes => edges::includes(graph, key),
+                    EdgeDirection::IncludedBy => edges::included_by(graph, key),
+                    EdgeDirection::References => edges::references(graph, key),
+                    EdgeDirection::ReferencedBy => edges::referenced_by(graph, key),
+                }
+                .len(),
+            ),
+            ComputedField::Days { from, to } => {
+                let frontmatter = graph.frontmatter(key).cloned().unwrap_or_default();
+                let from = date_at(&frontmatter, from);
+                let to = match to {
+                    Some(path) => date_at(&frontmatter, path),
+                    None => Some(self.today.unwrap_or_else(|| Local::now().date_naive())),
+                };
+                match (from, to) {
+                    (Some(from), Some(to)) => Value::Number((to - from).num_days().into()),
+                    _ => Value::Null,
+                }
+            }
+            ComputedField::Coalesce(paths) => {
+                let frontmatter = graph.frontmatter(key).cloned().unwrap_or_default();
+                paths
+                    .iter()
+                    .find_map(|path| match resolve_path(&frontmatter, path) {
+                        Resolution::Present(Value::Null) | Resolution::Missing => None,
+                        Resolution::Present(value) => Some(value.clone()),
+                    })
+                    .unwrap_or(Value::Null)
+            }
+        }
+    }
+
+    /// Every computed field of `key`, keyed by field name.
+    pub fn values(&self, graph: &Graph, key: &Key) -> Mapping {
+        self.fields
+            .iter()
+            .map(|(name, field)| (Value::String(name.clone()), self.value(graph, key, field)))
+            .collect()
+    }
+}
+
+fn count_value(n: usize) -> Value {
+    Value::Number((n as u64).into())
+}
+
+fn date_at(frontmatter: &Mapping, path: &FieldPath) -> Option<NaiveDate> {
+    match resolve_path(frontmatter, path) {
+        Resolution::Present(Value::String(s)) => {
+            NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()
+        }
+        _ => None,
+    }
+}
+
+/// Frontmatter of `key` as a read of `path` sees it: the stored mapping, plus the computed field
+/// named by the first segment of `path` when there is one.
+pub fn frontmatter_for(graph: &Graph, key: &Key, path: &FieldPath) -> Mapping {
+    let mut mapping = graph.frontmatter(key).cloned().unwrap_or_default();
+    if let Some((name, field)) = graph.computed_fields().for_path(path) {
+        let value = graph.computed_fields().value(graph, key, field);
+        mapping.insert(Value::String(name.to_string()), value);
+    }
+    mapping
+}
+
+/// [`sort_in_place`] that also orders by computed fields and graph metrics such as `$pagerank`.
+/// Rows keep their stored frontmatter; the computed sort value only lives for the duration of the
+/// sort.
+pub fn sort_rows(
+    graph: &Graph,
+    metrics: &MetricCache,
+    rows: &mut Vec<(Key, Mapping)>,
+    sort: &Sort,
+) {
+    let metric = match sort.key.segments() {
+        [name] => GraphMetric::from_name(name),
+        _ => None,
+    };
+    let mut keyed: Vec<(Key, Mapping)> = if let Some(metric) = metric {
+        rows.iter()
+            .map(|(key, _)| {
+                let mut mapping = Mapping::new();
+                mapping.insert(Value::from("metric"), metrics.value(key, metric));
+                (key.clone(), mapping)
+            })
+            .collect()
+    } else if graph.computed_fields().for_path(&sort.key).is_some() {
+        rows.iter()
+            .map(|(key, _)| (key.clone(), frontmatter_for(graph, key, &sort.key)))
+            .collect()
+    } else {
+        sort_in_place(rows, sort);
+        return;
+    };
+    let sort = match metric {
+        Some(_) => Sort {
+            key: FieldPath::from_dotted("metric"),
+            dir: sort.dir,
+        },
+        None => sort.clone(),
+    };
+    sort_in_place(&mut keyed, &sort);
+    let mut stored: HashMap<Key, Mapping> = rows.drain(..).collect();
+    rows.extend(keyed.into_iter().map(|(key, _)| {
+        let mapping = stored.remove(&key).unwrap_or_default();
+        (key, mapping)
+    }));
+}
@@ -1,7 +1,9 @@
 use serde_yaml::Value;
 
+use crate::graph::analytics::GraphMetric;
 use crate::model::Key;
 use crate::query::block::{BlockPredicate, MatchesSource};
+use crate::query::links::LinkPredicate;
 use crate::query::search::SearchSpec;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -189,13 +191,21 @@ pub enum Filter {
     And(Vec<Filter>),
     Or(Vec<Filter>),
     Nor(Vec<Filter>),
-    Field { path: FieldPath, op: FieldOp },
+    Field {
+        path: FieldPath,
+        op: FieldOp,
+    },
     Key(KeyOp),
     Content(BlockPredicate),
     Includes(Box<InclusionAnchor>),
     IncludedBy(Box<InclusionAnchor>),
     References(Box<ReferenceAnchor>),
     ReferencedBy(Box<ReferenceAnchor>),
+    /// A graph-wide metric of each document, such as `$pagerank`, compared like a field.
+    Metric {
+        metric: GraphMetric,
+        op: FieldOp,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -261,6 +271,7 @@ pub struct ReferenceAnchor {
     pub min_distance: u32,
     pub max_distance: u32,
     pub size: Option<CountPred>,
+    pub link: Option<LinkPredicate>,
 }
 
 impl ReferenceAnchor {
@@ -270,6 +281,7 @@ impl ReferenceAnchor {
             min_distance,
             max_distance,
             size: None,
+            link: None,
         }
     }
     pub fn with_max(key: impl Into<String>, max_distance: u32) -> Self {
@@ -281,12 +293,17 @@ impl ReferenceAnchor {
             min_distance,
             max_distance,
             size: None,
+            link: None,
         }
     }
     pub fn with_size(mut self, size: CountPred) -> Self {
         self.size = Some(size);
         self
     }
+    pub fn with_link(mut self, link: LinkPredicate) -> Self {
+        self.link = Some(link);
+        self
+    }
 }
 
 impl Filter {
@@ -533,7 +550,71 @@ pub enum ProjectionSource {
     Pseudo(PseudoField),
     ContentBlocks(BlockPredicate),
     Blocks(BlockPredicate),
+    /// One record per row of the selected tables, keyed by column name.
+    Rows(BlockPredicate),
     Matches(MatchesSource),
+    /// One entry per inline link accepted by the predicate, with its edge attributes.
+    Links {
+        outbound: bool,
+        predicate: LinkPredicate,
+    },
+    Lookup(Box<Lookup>),
+}
+
+/// Where a `$lookup` finds its related documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LookupFrom {
+    Includes,
+    IncludedBy,
+    References,
+    ReferencedBy,
+    /// A frontmatter field holding a key or a list of keys.
+    Field(FieldPath),
+}
+
+/// A `$lookup` projection: the related documents reached through `from` within `max_depth`
+/// hops, narrowed by `match_filter`, each projected through `project`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lookup {
+    pub from: LookupFrom,
+    pub max_depth: u32,
+    pub match_filter: Option<Filter>,
+    pub project: Projection,
+}
+
+impl Lookup {
+    pub fn new(from: LookupFrom) -> Self {
+        Lookup {
+            from,
+            max_depth: 1,
+            match_filter: None,
+            project: Projection::replace(vec![
+                ProjectionField {
+                    output: "key".to_string(),
+                    source: ProjectionSource::Pseudo(PseudoField::Key),
+                },
+                ProjectionField {
+                    output: "title".to_string(),
+                    source: ProjectionSource::Pseudo(PseudoField::Title),
+                },
+            ]),
+        }
+    }
+
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.match_filter = Some(filter);
+        self
+    }
+
+    pub fn project(mut self, project: Projection) -> Self {
+        self.project = project;
+        self
+    }
 }
 
 impl ProjectionSource {
@@ -635,7 +716,10 @@ impl Projection {
             ProjectionSource::Pseudo(p) => p.is_content_or_edge(),
             ProjectionSource::ContentBlocks(_)
             | ProjectionSource::Blocks(_)
-            | ProjectionSource::Matches(_) => true,
+            | ProjectionSource::Rows(_)
+            | ProjectionSource::Matches(_)
+            | ProjectionSource::Links { .. }
+            | ProjectionSource::Lookup(_) => true,
             _ => false,
         })
     }
@@ -719,12 +803,31 @@ pub struct BlockUpdate {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum BlockUpdateOp {
-    Replace { content: String },
-    ReplaceText { from: Option<String>, to: String },
-    InsertBefore { content: String },
-    InsertAfter { content: String },
-    Append { content: String },
+    Replace {
+        content: String,
+    },
+    ReplaceText {
+        from: Option<String>,
+        to: String,
+    },
+    InsertBefore {
+        content: String,
+    },
+    InsertAfter {
+        content: String,
+    },
+    Append {
+        content: String,
+    },
     Delete,
+    /// Adds a row to the end of each selected table; columns left out stay empty.
+    AppendRow {
+        cells: Vec<(String, String)>,
+    },
+    /// Sets cells in the rows of each selected table that pass the selector's `$row` tests.
+    SetCells {
+        cells: Vec<(String, String)>,
+    },
 }
 
 impl BlockUpdateOp {
@@ -736,6 +839,8 @@ impl BlockUpdateOp {
             BlockUpdateOp::InsertAfter { .. } => "$insertAfter",
             BlockUpdateOp::Append { .. } => "$append",
             BlockUpdateOp::Delete => "$delete",
+            BlockUpdateOp::AppendRow { .. } => "$appendRow",
+            BlockUpdateOp::SetCells { .. } => "$setCells",
         }
     }
 }
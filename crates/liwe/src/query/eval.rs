@@ -1,7 +1,10 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use rayon::prelude::*;
+use serde_yaml::Value;
 
+use crate::graph::analytics::{GraphMetric, MetricCache};
 use crate::graph::walk::{
     ancestors_inclusion, descendants_inclusion, inbound_reference, outbound_reference,
 };
@@ -9,48 +12,66 @@ use crate::graph::Graph;
 use crate::model::Key;
 use crate::query::block::BlockPredicate;
 use crate::query::block_eval::BlockIndex;
+use crate::query::computed::frontmatter_for;
 use crate::query::document::{
     CountPred, FieldOp, FieldPath, Filter, InclusionAnchor, KeyOp, ReferenceAnchor,
 };
 use crate::query::filter::{match_field_op, resolve_path, Resolution};
 use crate::query::graph_match::match_key_op;
+use crate::query::links::LinkWalk;
 
 const PARALLEL_THRESHOLD: usize = 64;
 
 pub fn evaluate(filter: &Filter, graph: &Graph) -> Vec<Key> {
-    let set = eval(filter, graph, None);
+    evaluate_with(filter, graph, &MetricCache::new(graph))
+}
+
+/// [`evaluate`] reading graph metrics from the query's shared `metrics`.
+pub fn evaluate_with(filter: &Filter, graph: &Graph, metrics: &MetricCache) -> Vec<Key> {
+    let set = eval(filter, graph, metrics, None);
     let mut keys: Vec<Key> = set.into_iter().collect();
     keys.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
     keys
 }
 
-fn all_keys(graph: &Graph) -> HashSet<Key> {
+pub(super) fn all_keys(graph: &Graph) -> HashSet<Key> {
     graph.keys().into_iter().collect()
 }
 
-fn eval(filter: &Filter, graph: &Graph, scope: Option<&HashSet<Key>>) -> HashSet<Key> {
+pub(super) fn eval(
+    filter: &Filter,
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+) -> HashSet<Key> {
     match filter {
-        Filter::And(children) => eval_and(children, graph, scope),
-        Filter::Or(children) => eval_or(children, graph, scope),
-        Filter::Nor(children) => eval_nor(children, graph, scope),
+        Filter::And(children) => eval_and(children, graph, metrics, scope),
+        Filter::Or(children) => eval_or(children, graph, metrics, scope),
+        Filter::Nor(children) => eval_nor(children, graph, metrics, scope),
         Filter::Field { path, op } => eval_field(path, op, graph, scope),
         Filter::Key(op) => eval_key(op, graph, scope),
         Filter::Content(pred) => eval_content(pred, graph, scope),
-        Filter::Includes(anchor) => eval_inclusion(anchor, graph, scope, true),
-        Filter::IncludedBy(anchor) => eval_inclusion(anchor, graph, scope, false),
-        Filter::References(anchor) => eval_reference(anchor, graph, scope, true),
-        Filter::ReferencedBy(anchor) => eval_reference(anchor, graph, scope, false),
+        Filter::Includes(anchor) => eval_inclusion(anchor, graph, metrics, scope, true),
+        Filter::IncludedBy(anchor) => eval_inclusion(anchor, graph, metrics, scope, false),
+        Filter::References(anchor) => eval_reference(anchor, graph, metrics, scope, true),
+        Filter::ReferencedBy(anchor) => eval_reference(anchor, graph, metrics, scope, false),
+        Filter::Metric { metric, op } => eval_metric(*metric, op, graph, metrics, scope),
     }
 }
 
-fn is_predicate(filter: &Filter) -> bool {
+pub(super) fn is_predicate(filter: &Filter) -> bool {
     matches!(
         filter,
         Filter::Field { .. } | Filter::Key(_) | Filter::Content(_)
     )
 }
 
-fn eval_and(children: &[Filter], graph: &Graph, scope: Option<&HashSet<Key>>) -> HashSet<Key> {
+fn eval_and(
+    children: &[Filter],
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+) -> HashSet<Key> {
     if children.is_empty() {
         return scope.cloned().unwrap_or_else(|| all_keys(graph));
     }
@@ -63,7 +84,7 @@ fn eval_and(children: &[Filter], graph: &Graph, scope: Option<&HashSet<Key>>) ->
     } else {
         let sets: Vec<HashSet<Key>> = generators
             .par_iter()
-            .map(|f| eval(f, graph, scope))
+            .map(|f| eval(f, graph, metrics, scope))
             .collect();
         intersect_sets(sets)
     };
@@ -75,13 +96,18 @@ fn eval_and(children: &[Filter], graph: &Graph, scope: Option<&HashSet<Key>>) ->
     apply_predicates(&predicates, candidate, graph)
 }
 
-fn eval_or(children: &[Filter], graph: &Graph, scope: Option<&HashSet<Key>>) -> HashSet<Key> {
+fn eval_or(
+    children: &[Filter],
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+) -> HashSet<Key> {
     if children.is_empty() {
         return HashSet::new();
     }
     children
         .par_iter()
-        .map(|f| eval(f, graph, scope))
+        .map(|f| eval(f, graph, metrics, scope))
         .reduce(HashSet::new, |mut a, b| {
             if a.is_empty() {
                 b
@@ -94,9 +120,14 @@ fn eval_or(children: &[Filter], graph: &Graph, scope: Option<&HashSet<Key>>) ->
         })
 }
 
-fn eval_nor(children: &[Filter], graph: &Graph, scope: Option<&HashSet<Key>>) -> HashSet<Key> {
+fn eval_nor(
+    children: &[Filter],
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+) -> HashSet<Key> {
     let universe = scope.cloned().unwrap_or_else(|| all_keys(graph));
-    let union = eval_or(children, graph, Some(&universe));
+    let union = eval_or(children, graph, metrics, Some(&universe));
     universe
         .into_iter()
         .filter(|k| !union.contains(k))
@@ -113,6 +144,25 @@ fn eval_field(
     filter_by_field(candidate, path, op, graph)
 }
 
+/// Graph metrics depend on the whole graph, so the clause is a generator reading them from the
+/// query's shared `metrics`.
+fn eval_metric(
+    metric: GraphMetric,
+    op: &FieldOp,
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+) -> HashSet<Key> {
+    let candidate = scope.cloned().unwrap_or_else(|| all_keys(graph));
+    candidate
+        .into_iter()
+        .filter(|key| match metrics.value(key, metric) {
+            Value::Null => match_field_op(op, None),
+            value => match_field_op(op, Some(&value)),
+        })
+        .collect()
+}
+
 fn eval_key(op: &KeyOp, graph: &Graph, scope: Option<&HashSet<Key>>) -> HashSet<Key> {
     let universe = scope.cloned().unwrap_or_else(|| all_keys(graph));
     universe
@@ -126,17 +176,28 @@ fn eval_content(
     graph: &Graph,
     scope: Option<&HashSet<Key>>,
 ) -> HashSet<Key> {
+    eval_content_counted(pred, graph, scope).0
+}
+
+/// [`eval_content`], also returning how many block indexes it built.
+pub(super) fn eval_content_counted(
+    pred: &BlockPredicate,
+    graph: &Graph,
+    scope: Option<&HashSet<Key>>,
+) -> (HashSet<Key>, usize) {
     let candidate = scope.cloned().unwrap_or_else(|| all_keys(graph));
     let keys: Vec<Key> = candidate.into_iter().collect();
-    if keys.len() >= PARALLEL_THRESHOLD {
-        keys.into_par_iter()
-            .filter(|k| match_content_at(graph, k, pred))
-            .collect()
+    let builds = AtomicUsize::new(0);
+    let matches = |k: &Key| {
+        builds.fetch_add(1, Ordering::Relaxed);
+        match_content_at(graph, k, pred)
+    };
+    let set = if keys.len() >= PARALLEL_THRESHOLD {
+        keys.into_par_iter().filter(|k| matches(k)).collect()
     } else {
-        keys.into_iter()
-            .filter(|k| match_content_at(graph, k, pred))
-            .collect()
-    }
+        keys.into_iter().filter(|k| matches(k)).collect()
+    };
+    (set, builds.into_inner())
 }
 
 fn match_content_at(graph: &Graph, key: &Key, pred: &BlockPredicate) -> bool {
@@ -146,19 +207,34 @@ fn match_content_at(graph: &Graph, key: &Key, pred: &BlockPredicate) -> bool {
 fn eval_inclusion(
     anchor: &InclusionAnchor,
     graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+    outbound: bool,
+) -> HashSet<Key> {
+    let anchor_keys = eval(&anchor.match_filter, graph, metrics, None);
+    inclusion_from_anchors(anchor, &anchor_keys, graph, scope, outbound)
+}
+
+/// Resolve an inclusion anchor whose `$match` filter has already been evaluated to `anchor_keys`.
+pub(super) fn inclusion_from_anchors(
+    anchor: &InclusionAnchor,
+    anchor_keys: &HashSet<Key>,
+    graph: &Graph,
     scope: Option<&HashSet<Key>>,
     outbound: bool,
 ) -> HashSet<Key> {
     match &anchor.size {
-        None => eval_inclusion_existential(anchor, graph, scope, outbound),
+        None => eval_inclusion_existential(anchor, anchor_keys, graph, scope, outbound),
         Some(pred) => {
-            let walk: WalkFn = if outbound {
-                descendants_inclusion
-            } else {
-                ancestors_inclusion
+            let walk = |k: &Key, max: u32| {
+                if outbound {
+                    descendants_inclusion(graph, k, max)
+                } else {
+                    ancestors_inclusion(graph, k, max)
+                }
             };
             eval_relation_count(
-                &anchor.match_filter,
+                anchor_keys,
                 anchor.min_depth,
                 anchor.max_depth,
                 pred,
@@ -172,13 +248,13 @@ fn eval_inclusion(
 
 fn eval_inclusion_existential(
     anchor: &InclusionAnchor,
+    anchor_keys: &HashSet<Key>,
     graph: &Graph,
     scope: Option<&HashSet<Key>>,
     outbound: bool,
 ) -> HashSet<Key> {
-    let anchor_keys = eval(&anchor.match_filter, graph, None);
     let mut combined: HashSet<Key> = HashSet::new();
-    for ak in &anchor_keys {
+    for ak in anchor_keys {
         let walk = if outbound {
             ancestors_inclusion(graph, ak, anchor.max_depth)
         } else {
@@ -190,7 +266,7 @@ fn eval_inclusion_existential(
             }
         }
     }
-    for ak in &anchor_keys {
+    for ak in anchor_keys {
         combined.remove(ak);
     }
     if let Some(s) = scope {
@@ -202,19 +278,29 @@ fn eval_inclusion_existential(
 fn eval_reference(
     anchor: &ReferenceAnchor,
     graph: &Graph,
+    metrics: &MetricCache,
     scope: Option<&HashSet<Key>>,
     outbound: bool,
 ) -> HashSet<Key> {
+    let anchor_keys = eval(&anchor.match_filter, graph, metrics, None);
+    reference_from_anchors(anchor, &anchor_keys, graph, scope, outbound)
+}
+
+/// Resolve a reference anchor whose `$match` filter has already been evaluated to `anchor_keys`.
+pub(super) fn reference_from_anchors(
+    anchor: &ReferenceAnchor,
+    anchor_keys: &HashSet<Key>,
+    graph: &Graph,
+    scope: Option<&HashSet<Key>>,
+    outbound: bool,
+) -> HashSet<Key> {
+    let links = anchor.link.as_ref().map(|p| LinkWalk::new(graph, p));
     match &anchor.size {
-        None => eval_reference_existential(anchor, graph, scope, outbound),
+        None => eval_reference_existential(anchor, anchor_keys, graph, scope, outbound, &links),
         Some(pred) => {
-            let walk: WalkFn = if outbound {
-                outbound_reference
-            } else {
-                inbound_reference
-            };
+            let walk = |k: &Key, max: u32| reference_walk(graph, links.as_ref(), k, max, outbound);
             eval_relation_count(
-                &anchor.match_filter,
+                anchor_keys,
                 anchor.min_distance,
                 anchor.max_distance,
                 pred,
@@ -228,25 +314,22 @@ fn eval_reference(
 
 fn eval_reference_existential(
     anchor: &ReferenceAnchor,
+    anchor_keys: &HashSet<Key>,
     graph: &Graph,
     scope: Option<&HashSet<Key>>,
     outbound: bool,
+    links: &Option<LinkWalk>,
 ) -> HashSet<Key> {
-    let anchor_keys = eval(&anchor.match_filter, graph, None);
     let mut combined: HashSet<Key> = HashSet::new();
-    for ak in &anchor_keys {
-        let walk = if outbound {
-            inbound_reference(graph, ak, anchor.max_distance)
-        } else {
-            outbound_reference(graph, ak, anchor.max_distance)
-        };
+    for ak in anchor_keys {
+        let walk = reference_walk(graph, links.as_ref(), ak, anchor.max_distance, !outbound);
         for (k, d) in walk {
             if d >= anchor.min_distance && d <= anchor.max_distance {
                 combined.insert(k);
             }
         }
     }
-    for ak in &anchor_keys {
+    for ak in anchor_keys {
         combined.remove(ak);
     }
     if let Some(s) = scope {
@@ -255,25 +338,37 @@ fn eval_reference_existential(
     combined
 }
 
-type WalkFn = fn(&Graph, &Key, u32) -> HashMap<Key, u32>;
+/// Reference BFS from `key`, following only the links accepted by `links` when it is set.
+fn reference_walk(
+    graph: &Graph,
+    links: Option<&LinkWalk>,
+    key: &Key,
+    max: u32,
+    outbound: bool,
+) -> HashMap<Key, u32> {
+    match (links, outbound) {
+        (Some(links), _) => links.walk(key, max, outbound),
+        (None, true) => outbound_reference(graph, key, max),
+        (None, false) => inbound_reference(graph, key, max),
+    }
+}
 
 fn eval_relation_count(
-    match_filter: &Filter,
+    match_set: &HashSet<Key>,
     min: u32,
     max: u32,
     pred: &CountPred,
     graph: &Graph,
     scope: Option<&HashSet<Key>>,
-    walk: WalkFn,
+    walk: impl Fn(&Key, u32) -> HashMap<Key, u32> + Sync,
 ) -> HashSet<Key> {
-    let match_set = eval(match_filter, graph, None);
     let candidates: Vec<Key> = scope
         .cloned()
         .unwrap_or_else(|| all_keys(graph))
         .into_iter()
         .collect();
     let test = |d: &Key| -> bool {
-        let count = walk(graph, d, max)
+        let count = walk(d, max)
             .into_iter()
             .filter(|(k, depth)| *depth >= min && *depth <= max && k != d && match_set.contains(k))
             .count() as u64;
@@ -286,7 +381,7 @@ fn eval_relation_count(
     }
 }
 
-fn intersect_sets(mut sets: Vec<HashSet<Key>>) -> HashSet<Key> {
+pub(super) fn intersect_sets(mut sets: Vec<HashSet<Key>>) -> HashSet<Key> {
     if sets.is_empty() {
         return HashSet::new();
     }
@@ -344,7 +439,7 @@ fn filter_by_field(
 }
 
 fn match_field_at(graph: &Graph, key: &Key, path: &FieldPath, op: &FieldOp) -> bool {
-    let mapping = graph.frontmatter(key).cloned().unwrap_or_default();
+    let mapping = frontmatter_for(graph, key, path);
     match resolve_path(&mapping, path) {
         Resolution::Present(value) => match_field_op(op, Some(value)),
         Resolution::Missing => match_field_op(op, None),
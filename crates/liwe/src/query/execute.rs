@@ -1,17 +1,21 @@
+use std::time::Instant;
+
 use rayon::prelude::*;
 use serde_yaml::Mapping;
 
+use crate::graph::analytics::MetricCache;
 use crate::graph::{Graph, GraphContext};
 use crate::model::node::{Node, NodeIter};
 use crate::model::tree::Tree;
 use crate::model::Key;
 use crate::query::block_update::{self, DocRef, EvalError};
+use crate::query::computed::sort_rows;
 use crate::query::document::{CountOp, DeleteOp, Filter, FindOp, Limit, Operation, Sort, UpdateOp};
 use crate::query::eval;
+use crate::query::explain::{elapsed_us, evaluate_explained_with, ClauseTrace, Explain};
 use crate::query::frontmatter::strip_reserved;
 use crate::query::project::{apply_projection, ProjectionContext};
 use crate::query::scores::QueryScores;
-use crate::query::sort::sort_in_place;
 use crate::query::update;
 
 #[derive(Debug)]
@@ -40,11 +44,78 @@ pub fn execute_with_scores(
     graph: &Graph,
     scores: &QueryScores,
 ) -> Result<Outcome, EvalError> {
+    run(op, graph, scores, None)
+}
+
+/// Execute `op` like [`execute_with_scores`], also profiling the filter evaluation.
+///
+/// The returned [`Explain`] carries the filter trace and totals; its `search` stage is left empty
+/// for the caller that resolved `scores` to fill in. Mutations are only planned — applying the
+/// returned changes stays with the caller, which runs explained mutations as a dry run.
+pub fn explain_with_scores(
+    op: &Operation,
+    graph: &Graph,
+    scores: &QueryScores,
+) -> Result<(Outcome, Explain), EvalError> {
+    explain_run(op, graph, scores, None)
+}
+
+/// [`explain_with_scores`] for a caller that already evaluated the operation's filter with
+/// [`evaluate_explained_with`], typically to resolve `scores` over its result.
+///
+/// `candidates` and `trace` stand in for the filter stage, so the filter runs once per explained
+/// query. Without a filter, `trace` is `None` and `candidates` is the whole corpus.
+pub fn explain_with_candidates(
+    op: &Operation,
+    graph: &Graph,
+    scores: &QueryScores,
+    candidates: Vec<Key>,
+    trace: Option<ClauseTrace>,
+) -> Result<(Outcome, Explain), EvalError> {
+    explain_run(op, graph, scores, Some((candidates, trace)))
+}
+
+fn explain_run(
+    op: &Operation,
+    graph: &Graph,
+    scores: &QueryScores,
+    selected: Option<(Vec<Key>, Option<ClauseTrace>)>,
+) -> Result<(Outcome, Explain), EvalError> {
+    let start = Instant::now();
+    let mut explain = Explain::default();
+    let profile = Profile {
+        explain: &mut explain,
+        selected,
+    };
+    let outcome = run(op, graph, scores, Some(profile))?;
+    explain.matched = match &outcome {
+        Outcome::Find { matches } => matches.len(),
+        Outcome::Count(n) => *n,
+        Outcome::Update { changes } => changes.len(),
+        Outcome::Delete { removed } => removed.len(),
+    };
+    explain.elapsed_us = elapsed_us(start);
+    Ok((outcome, explain))
+}
+
+/// Where an explained run records its profile, plus the filter result when the caller has it.
+struct Profile<'a> {
+    explain: &'a mut Explain,
+    selected: Option<(Vec<Key>, Option<ClauseTrace>)>,
+}
+
+fn run(
+    op: &Operation,
+    graph: &Graph,
+    scores: &QueryScores,
+    profile: Option<Profile<'_>>,
+) -> Result<Outcome, EvalError> {
+    let metrics = MetricCache::new(graph);
     match op {
-        Operation::Find(find) => execute_find(find, graph, scores),
-        Operation::Count(count) => Ok(execute_count(count, graph)),
-        Operation::Update(upd) => execute_update(upd, graph),
-        Operation::Delete(del) => execute_delete(del, graph),
+        Operation::Find(find) => execute_find(find, graph, &metrics, scores, profile),
+        Operation::Count(count) => Ok(execute_count(count, graph, &metrics, profile)),
+        Operation::Update(upd) => execute_update(upd, graph, &metrics, profile),
+        Operation::Delete(del) => execute_delete(del, graph, &metrics, profile),
     }
 }
 
@@ -77,15 +148,40 @@ pub fn strict_guard_violations(op: &Operation) -> Vec<String> {
     missing
 }
 
-fn select_keys(filter: Option<&Filter>, graph: &Graph) -> Vec<Key> {
-    match filter {
-        None => {
-            let mut k = graph.keys();
-            k.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
-            k
+fn select_keys(
+    filter: Option<&Filter>,
+    graph: &Graph,
+    metrics: &MetricCache,
+    profile: Option<Profile<'_>>,
+) -> Vec<Key> {
+    let Some(Profile { explain, selected }) = profile else {
+        return match filter {
+            None => sorted_keys(graph),
+            Some(f) => eval::evaluate_with(f, graph, metrics),
+        };
+    };
+    let keys = match (filter, selected) {
+        (_, Some((keys, trace))) => {
+            if let Some(trace) = trace {
+                explain.set_filter(trace);
+            }
+            keys
         }
-        Some(f) => eval::evaluate(f, graph),
-    }
+        (None, None) => sorted_keys(graph),
+        (Some(f), None) => {
+            let (keys, trace) = evaluate_explained_with(f, graph, metrics);
+            explain.set_filter(trace);
+            keys
+        }
+    };
+    explain.candidates = keys.len();
+    keys
+}
+
+fn sorted_keys(graph: &Graph) -> Vec<Key> {
+    let mut keys = graph.keys();
+    keys.sort_by(|a, b| a.to_string().cmp(&b.to_string()));
+    keys
 }
 
 fn rows_for(keys: Vec<Key>, graph: &Graph) -> Vec<(Key, Mapping)> {
@@ -97,14 +193,21 @@ fn rows_for(keys: Vec<Key>, graph: &Graph) -> Vec<(Key, Mapping)> {
         .collect()
 }
 
-fn select(filter: Option<&Filter>, graph: &Graph) -> Vec<(Key, Mapping)> {
-    rows_for(select_keys(filter, graph), graph)
+fn select(
+    filter: Option<&Filter>,
+    graph: &Graph,
+    metrics: &MetricCache,
+    profile: Option<Profile<'_>>,
+) -> Vec<(Key, Mapping)> {
+    rows_for(select_keys(filter, graph, metrics, profile), graph)
 }
 
 /// Order and cap `rows`. With `preserve_order`, the incoming order is kept (the search stage already
 /// ranked by relevance); otherwise rows sort by key ascending first. An explicit `sort` then reorders
 /// with a stable algorithm, so key order remains the tie-break.
 fn apply_sort_and_limit(
+    graph: &Graph,
+    metrics: &MetricCache,
     mut rows: Vec<(Key, Mapping)>,
     sort: Option<&Sort>,
     limit: Option<&Limit>,
@@ -114,7 +217,7 @@ fn apply_sort_and_limit(
         rows.sort_by(|a, b| a.0.to_string().cmp(&b.0.to_string()));
     }
     if let Some(s) = sort {
-        sort_in_place(&mut rows, s);
+        sort_rows(graph, metrics, &mut rows, s);
     }
     if let Some(l) = limit {
         if !l.is_unbounded() {
@@ -124,8 +227,14 @@ fn apply_sort_and_limit(
     rows
 }
 
-fn execute_find(op: &FindOp, graph: &Graph, scores: &QueryScores) -> Result<Outcome, EvalError> {
-    let candidates = select_keys(op.filter.as_ref(), graph);
+fn execute_find(
+    op: &FindOp,
+    graph: &Graph,
+    metrics: &MetricCache,
+    scores: &QueryScores,
+    profile: Option<Profile<'_>>,
+) -> Result<Outcome, EvalError> {
+    let candidates = select_keys(op.filter.as_ref(), graph, metrics, profile);
 
     let (keys, preserve_order) = match &op.search {
         None => (candidates, false),
@@ -151,11 +260,18 @@ fn execute_find(op: &FindOp, graph: &Graph, scores: &QueryScores) -> Result<Outc
     };
 
     let rows = rows_for(keys, graph);
-    let rows = apply_sort_and_limit(rows, op.sort.as_ref(), op.limit.as_ref(), preserve_order);
+    let rows = apply_sort_and_limit(
+        graph,
+        metrics,
+        rows,
+        op.sort.as_ref(),
+        op.limit.as_ref(),
+        preserve_order,
+    );
     let matches: Vec<FindMatch> = rows
         .into_iter()
         .map(|(key, _)| {
-            let ctx = ProjectionContext::new(graph, &key);
+            let ctx = ProjectionContext::new(graph, metrics, &key);
             let document = apply_projection(&ctx, &op.project);
             FindMatch { key, document }
         })
@@ -163,15 +279,39 @@ fn execute_find(op: &FindOp, graph: &Graph, scores: &QueryScores) -> Result<Outc
     Ok(Outcome::Find { matches })
 }
 
-fn execute_count(op: &CountOp, graph: &Graph) -> Outcome {
-    let rows = select(op.filter.as_ref(), graph);
-    let rows = apply_sort_and_limit(rows, op.sort.as_ref(), op.limit.as_ref(), false);
+fn execute_count(
+    op: &CountOp,
+    graph: &Graph,
+    metrics: &MetricCache,
+    profile: Option<Profile<'_>>,
+) -> Outcome {
+    let rows = select(op.filter.as_ref(), graph, metrics, profile);
+    let rows = apply_sort_and_limit(
+        graph,
+        metrics,
+        rows,
+        op.sort.as_ref(),
+        op.limit.as_ref(),
+        false,
+    );
     Outcome::Count(rows.len())
 }
 
-fn execute_update(op: &UpdateOp, graph: &Graph) -> Result<Outcome, EvalError> {
-    let rows = select(Some(&op.filter), graph);
-    let rows = apply_sort_and_limit(rows, op.sort.as_ref(), op.limit.as_ref(), false);
+fn execute_update(
+    op: &UpdateOp,
+    graph: &Graph,
+    metrics: &MetricCache,
+    profile: Option<Profile<'_>>,
+) -> Result<Outcome, EvalError> {
+    let rows = select(Some(&op.filter), graph, metrics, profile);
+    let rows = apply_sort_and_limit(
+        graph,
+        metrics,
+        rows,
+        op.sort.as_ref(),
+        op.limit.as_ref(),
+        false,
+    );
     let mut bodies = if op.update.block_ops.is_empty() {
         None
     } else {
@@ -195,9 +335,21 @@ fn execute_update(op: &UpdateOp, graph: &Graph) -> Result<Outcome, EvalError> {
     Ok(Outcome::Update { changes })
 }
 
-fn execute_delete(op: &DeleteOp, graph: &Graph) -> Result<Outcome, EvalError> {
-    let rows = select(Some(&op.filter), graph);
-    let rows = apply_sort_and_limit(rows, op.sort.as_ref(), op.limit.as_ref(), false);
+fn execute_delete(
+    op: &DeleteOp,
+    graph: &Graph,
+    metrics: &MetricCache,
+    profile: Option<Profile<'_>>,
+) -> Result<Outcome, EvalError> {
+    let rows = select(Some(&op.filter), graph, metrics, profile);
+    let rows = apply_sort_and_limit(
+        graph,
+        metrics,
+        rows,
+        op.sort.as_ref(),
+        op.limit.as_ref(),
+        false,
+    );
     let documents: Vec<DocRef> = rows.iter().map(|(key, _)| doc_ref(graph, key)).collect();
     block_update::check_document_expect("delete", op.expect, &documents)?;
     let removed = rows.into_iter().map(|(k, _)| k).collect();
@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::graph::analytics::MetricCache;
+use crate::graph::Graph;
+use crate::model::Key;
+use crate::query::block::TextMatch;
+use crate::query::document::{CountCmp, CountPred, FieldOp, Filter, KeyOp};
+use crate::query::eval::{
+    all_keys, eval, eval_content_counted, inclusion_from_anchors, intersect_sets, is_predicate,
+    reference_from_anchors,
+};
+use crate::query::links::{link_type_name, LinkPredicate, LinkText};
+
+/// Profile of one query run: the evaluated filter tree, the search stage, and totals.
+///
+/// Produced by `iwe find --explain` and `iwe_query` with `explain: true`. Timings are wall-clock
+/// microseconds of the explained run; the evaluation itself runs sequentially so each clause's
+/// time is its own.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Explain {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<ClauseTrace>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search: Option<SearchTrace>,
+    /// Documents selected by the filter (the whole corpus without one).
+    pub candidates: usize,
+    /// Documents in the final result, after search, sort and limit.
+    pub matched: usize,
+    /// `$content` block-index builds across the whole filter tree.
+    pub block_evaluations: usize,
+    pub elapsed_us: u64,
+}
+
+impl Explain {
+    pub fn set_filter(&mut self, trace: ClauseTrace) {
+        self.block_evaluations = trace.total_block_evaluations();
+        self.filter = Some(trace);
+    }
+}
+
+/// How a clause takes part in its parent's evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClauseRole {
+    /// The top-level filter.
+    Root,
+    /// An `$and` child producing a candidate set; generator sets are intersected smallest first.
+    Generator,
+    /// An `$and` child tested per candidate after the generators ran, in declaration order.
+    Predicate,
+    /// An `$or` / `$nor` branch, evaluated against the parent's scope.
+    Branch,
+    /// The `$match` filter of a relation operator, evaluated against the whole corpus.
+    Anchor,
+}
+
+/// One evaluated filter clause. `children` appear in evaluation order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClauseTrace {
+    pub clause: String,
+    pub role: ClauseRole,
+    /// Size of the candidate set the clause was evaluated against.
+    pub input: usize,
+    /// Size of the set the clause produced.
+    pub output: usize,
+    pub elapsed_us: u64,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub block_evaluations: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ClauseTrace>,
+}
+
+impl ClauseTrace {
+    pub fn total_block_evaluations(&self) -> usize {
+        self.block_evaluations
+            + self
+                .children
+                .iter()
+                .map(|c| c.total_block_evaluations())
+                .sum::<usize>()
+    }
+}
+
+/// The search stage of a `find`: how many candidates each ranker matched and the fused result.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchTrace {
+    /// Candidates handed to the rankers (the filter result).
+    pub candidates: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lexical: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzzy: Option<usize>,
+    /// Candidates matched by at least one ranker.
+    pub fused: usize,
+    pub elapsed_us: u64,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+pub(crate) fn elapsed_us(start: Instant) -> u64 {
+    start.elapsed().as_micros() as u64
+}
+
+/// Evaluate `filter` like [`crate::query::evaluate`], recording a [`ClauseTrace`] per clause.
+pub fn evaluate_explained(filter: &Filter, graph: &Graph) -> (Vec<Key>, ClauseTrace) {
+    evaluate_explained_with(filter, graph, &MetricCache::new(graph))
+}
+
+/// [`evaluate_explained`] reading graph metrics from the query's shared `metrics`.
+pub fn evaluate_explained_with(
+    filter: &Filter,
+    graph: &Graph,
+    metrics: &MetricCache,
+) -> (Vec<Key>, ClauseTrace) {
+    let (set, trace) = trace(filter, graph, metrics, None, ClauseRole::Root);
+    let mut keys: Vec<Key> = set.into_iter().collect();
+    keys.sort_by_key(|k| k.to_string());
+    (keys, trace)
+}
+
+fn trace(
+    filter: &Filter,
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+    role: ClauseRole,
+) -> (HashSet<Key>, ClauseTrace) {
+    let start = Instant::now();
+    let input = scope.map(|s| s.len()).unwrap_or_else(|| graph.keys().len());
+    let mut children = Vec::new();
+    let mut block_evaluations = 0;
+    let set = match filter {
+        Filter::And(list) => trace_and(list, graph, metrics, scope, &mut children),
+        Filter::Or(list) => {
+            let mut union = HashSet::new();
+            for child in list {
+                let (set, t) = trace(child, graph, metrics, scope, ClauseRole::Branch);
+                children.push(t);
+                union.extend(set);
+            }
+            union
+        }
+        Filter::Nor(list) => {
+            let universe = scope.cloned().unwrap_or_else(|| all_keys(graph));
+            let mut union = HashSet::new();
+            for child in list {
+                let (set, t) = trace(child, graph, metrics, Some(&universe), ClauseRole::Branch);
+                children.push(t);
+                union.extend(set);
+            }
+            universe
+                .into_iter()
+                .filter(|k| !union.contains(k))
+                .collect()
+        }
+        Filter::Includes(anchor) | Filter::IncludedBy(anchor) => {
+            let (anchor_keys, t) = trace(
+                &anchor.match_filter,
+                graph,
+                metrics,
+                None,
+                ClauseRole::Anchor,
+            );
+            children.push(t);
+            let outbound = matches!(filter, Filter::Includes(_));
+            inclusion_from_anchors(anchor, &anchor_keys, graph, scope, outbound)
+        }
+        Filter::References(anchor) | Filter::ReferencedBy(anchor) => {
+            let (anchor_keys, t) = trace(
+                &anchor.match_filter,
+                graph,
+                metrics,
+                None,
+                ClauseRole::Anchor,
+            );
+            children.push(t);
+            let outbound = matches!(filter, Filter::References(_));
+            reference_from_anchors(anchor, &anchor_keys, graph, scope, outbound)
+        }
+        Filter::Content(pred) => {
+            let (set, builds) = eval_content_counted(pred, graph, scope);
+            block_evaluations = builds;
+            set
+        }
+        Filter::Field { .. } | Filter::Key(_) | Filter::Metric { .. } => {
+            eval(filter, graph, metrics, scope)
+        }
+    };
+    let trace = ClauseTrace {
+        clause: describe(filter),
+        role,
+        input,
+        output: set.len(),
+        elapsed_us: elapsed_us(start),
+        block_evaluations,
+        children,
+    };
+    (set, trace)
+}
+
+/// Mirrors `eval_and`: generators first, intersected smallest first, then each predicate over the
+/// surviving candidates. A predicate tested per key with short-circuiting `all` sees exactly the
+/// keys that passed the predicates before it, so applying them one by one reports the same work.
+fn trace_and(
+    list: &[Filter],
+    graph: &Graph,
+    metrics: &MetricCache,
+    scope: Option<&HashSet<Key>>,
+    children: &mut Vec<ClauseTrace>,
+) -> HashSet<Key> {
+    if list.is_empty() {
+        return scope.cloned().unwrap_or_else(|| all_keys(graph));
+    }
+    let (predicates, generators): (Vec<&Filter>, Vec<&Filter>) =
+        list.iter().partition(|f| is_predicate(f));
+
+    let mut candidate = if generators.is_empty() {
+        scope.cloned().unwrap_or_else(|| all_keys(graph))
+    } else {
+        let mut sets = Vec::new();
+        for generator in generators {
+            let (set, t) = trace(generator, graph, metrics, scope, ClauseRole::Generator);
+            children.push(t);
+            sets.push(set);
+        }
+        intersect_sets(sets)
+    };
+
+    for predicate in predicates {
+        if candidate.is_empty() {
+            break;
+        }
+        let (set, t) = trace(
+            predicate,
+            graph,
+            metrics,
+            Some(&candidate),
+            ClauseRole::Predicate,
+        );
+        children.push(t);
+        candidate = set;
+    }
+    candidate
+}
+
+/// A one-line rendering of `filter`'s own operator; nested filters appear as trace children.
+pub fn describe(filter: &Filter) -> String {
+    match filter {
+        Filter::And(list) if list.is_empty() => "{}".to_string(),
+        Filter::And(_) => "$and".to_string(),
+        Filter::Or(_) => "$or".to_string(),
+        Filter::Nor(_) => "$nor".to_string(),
+        Filter::Field { path, op } => format!("{}: {}", path.0.join("."), describe_field_op(op)),
+        Filter::Key(op) => format!("$key: {}", describe_key_op(op)),
+        Filter::Metric { metric, op } => format!("{}: {}", metric.name(), describe_field_op(op)),
+        Filter::Content(_) => "$content".to_string(),
+        Filter::Includes(a) => describe_relation("$includes", a.min_depth, a.max_depth, &a.size),
+        Filter::IncludedBy(a) => {
+            describe_relation("$includedBy", a.min_depth, a.max_depth, &a.size)
+        }
+        Filter::References(a) => {
+            describe_relation("$references", a.min_distance, a.max_distance, &a.size)
+                + &describe_link(&a.link)
+        }
+        Filter::ReferencedBy(a) => {
+            describe_relation("$referencedBy", a.min_distance, a.max_distance, &a.size)
+                + &describe_link(&a.link)
+        }
+    }
+}
+
+fn describe_link(link: &Option<LinkPredicate>) -> String {
+    let Some(link) = link else {
+        return String::new();
+    };
+    let mut parts = Vec::new();
+    if !link.types.is_empty() {
+        let types: Vec<&str> = link.types.iter().map(|t| link_type_name(*t)).collect();
+        parts.push(format!("type: [{}]", types.join(", ")));
+    }
+    if let Some(text) = &link.text {
+        parts.push(match text {
+            LinkText::Text(TextMatch::Substring(s)) => format!("text: {:?}", s),
+            LinkText::Text(TextMatch::Exact(s)) => format!("text: {{$eq: {:?}}}", s),
+            LinkText::Matches(r) => format!("text: {{$matches: {:?}}}", r.pattern()),
+        });
+    }
+    if let Some(differs) = link.title_differs {
+        parts.push(format!("titleDiffers: {}", differs));
+    }
+    if link.within.is_some() {
+        parts.push("within".to_string());
+    }
+    format!(" link {{{}}}", parts.join(", "))
+}
+
+fn describe_relation(name: &str, min: u32, max: u32, size: &Option<CountPred>) -> String {
+    let mut out = format!("{} (min {}, max {})", name, min, max);
+    if let Some(pred) = size {
+        out.push_str(&format!(" $size {}", describe_count(pred)));
+    }
+    out
+}
+
+fn describe_key_op(op: &KeyOp) -> String {
+    let list = |keys: &[Key]| {
+        keys.iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match op {
+        KeyOp::Eq(k) => format!("{{$eq: {}}}", k),
+        KeyOp::Ne(k) => format!("{{$ne: {}}}", k),
+        KeyOp::In(keys) => format!("{{$in: [{}]}}", list(keys)),
+        KeyOp::Nin(keys) => format!("{{$nin: [{}]}}", list(keys)),
+    }
+}
+
+fn describe_field_op(op: &FieldOp) -> String {
+    let values = |vs: &[Value]| vs.iter().map(describe_value).collect::<Vec<_>>().join(", ");
+    match op {
+        FieldOp::Eq(v) => format!("{{$eq: {}}}", describe_value(v)),
+        FieldOp::Ne(v) => format!("{{$ne: {}}}", describe_value(v)),
+        FieldOp::Gt(v) => format!("{{$gt: {}}}", describe_value(v)),
+        FieldOp::Gte(v) => format!("{{$gte: {}}}", describe_value(v)),
+        FieldOp::Lt(v) => format!("{{$lt: {}}}", describe_value(v)),
+        FieldOp::Lte(v) => format!("{{$lte: {}}}", describe_value(v)),
+        FieldOp::In(vs) => format!("{{$in: [{}]}}", values(vs)),
+        FieldOp::Nin(vs) => format!("{{$nin: [{}]}}", values(vs)),
+        FieldOp::All(vs) => format!("{{$all: [{}]}}", values(vs)),
+        FieldOp::Exists(b) => format!("{{$exists: {}}}", b),
+        FieldOp::Type(types) => format!(
+            "{{$type: [{}]}}",
+            types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        FieldOp::Size(pred) => format!("{{$size: {}}}", describe_count(pred)),
+        FieldOp::Not(inner) => format!("{{$not: {}}}", describe_field_op(inner)),
+        FieldOp::And(ops) => ops
+            .iter()
+            .map(describe_field_op)
+            .collect::<Vec<_>>()
+            .join(" "),
+    }
+}
+
+fn describe_count(pred: &CountPred) -> String {
+    let parts: Vec<String> = pred
+        .comparisons
+        .iter()
+        .map(|c| match c {
+            CountCmp::Eq(n) => format!("$eq: {}", n),
+            CountCmp::Ne(n) => format!("$ne: {}", n),
+            CountCmp::Gt(n) => format!("$gt: {}", n),
+            CountCmp::Gte(n) => format!("$gte: {}", n),
+            CountCmp::Lt(n) => format!("$lt: {}", n),
+            CountCmp::Lte(n) => format!("$lte: {}", n),
+        })
+        .collect();
+    format!("{{{}}}", parts.join(", "))
+}
+
+fn describe_value(value: &Value) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "?".to_string())
+}
@@ -0,0 +1,314 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::graph::{Graph, GraphContext};
+use crate::model::reference::ReferenceType;
+use crate::model::Key;
+use crate::query::block::{parse_regex, parse_within, BlockPredicate, BlockRegex, TextMatch};
+use crate::query::block_eval::BlockIndex;
+use crate::query::builder::ParseError;
+
+/// Edge-level conditions on the inline links behind `$references` / `$referencedBy`.
+///
+/// Every set condition must hold for a link to count. An empty predicate accepts every link.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LinkPredicate {
+    /// Accepted link types; empty accepts all.
+    pub types: Vec<ReferenceType>,
+    pub text: Option<LinkText>,
+    /// Whether the link text must differ from (`true`) or equal (`false`) the target's title.
+    pub title_differs: Option<bool>,
+    /// Block predicate the block holding the link must satisfy.
+    pub within: Option<BlockPredicate>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LinkText {
+    Text(TextMatch),
+    Matches(BlockRegex),
+}
+
+/// One inline link as seen from a document: `key` is the document at the other end.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkEdge {
+    pub key: String,
+    pub title: String,
+    pub text: String,
+    pub link_type: &'static str,
+    pub section_path: Vec<String>,
+}
+
+struct Link {
+    target: Key,
+    target_title: String,
+    text: String,
+    link_type: ReferenceType,
+    section_path: Vec<String>,
+}
+
+impl LinkPredicate {
+    pub fn any() -> Self {
+        LinkPredicate::default()
+    }
+
+    pub fn link_type(mut self, link_type: ReferenceType) -> Self {
+        self.types.push(link_type);
+        self
+    }
+
+    pub fn text(mut self, text: LinkText) -> Self {
+        self.text = Some(text);
+        self
+    }
+
+    pub fn title_differs(mut self, differs: bool) -> Self {
+        self.title_differs = Some(differs);
+        self
+    }
+
+    pub fn within(mut self, scope: BlockPredicate) -> Self {
+        self.within = Some(scope);
+        self
+    }
+
+    fn accepts(&self, link: &Link) -> bool {
+        if !self.types.is_empty() && !self.types.contains(&link.link_type) {
+            return false;
+        }
+        let text_ok = match &self.text {
+            Some(LinkText::Text(m)) => m.matches(&link.text),
+            Some(LinkText::Matches(r)) => r.is_match(&link.text),
+            None => true,
+        };
+        if !text_ok {
+            return false;
+        }
+        match self.title_differs {
+            Some(differs) => (link.text.trim() != link.target_title.trim()) == differs,
+            None => true,
+        }
+    }
+}
+
+pub fn link_type_name(link_type: ReferenceType) -> &'static str {
+    match link_type {
+        ReferenceType::Regular => "regular",
+        ReferenceType::WikiLink => "wikiLink",
+        ReferenceType::WikiLinkPiped => "wikiLinkPiped",
+    }
+}
+
+fn parse_link_type(value: &Value) -> Result<ReferenceType, ParseError> {
+    match value.as_str() {
+        Some("regular") => Ok(ReferenceType::Regular),
+        Some("wikiLink") => Ok(ReferenceType::WikiLink),
+        Some("wikiLinkPiped") => Ok(ReferenceType::WikiLinkPiped),
+        Some(other) => Err(ParseError::UnknownLinkType {
+            name: other.to_string(),
+        }),
+        None => Err(ParseError::OperatorExpectedString { op: "type" }),
+    }
+}
+
+fn parse_link_text(value: &Value) -> Result<LinkText, ParseError> {
+    match value {
+        Value::String(s) => Ok(LinkText::Text(TextMatch::Substring(s.clone()))),
+        Value::Mapping(m) if m.len() == 1 => {
+            let (k, v) = m.iter().next().unwrap();
+            match (k.as_str(), v) {
+                (Some("$eq"), Value::String(s)) => Ok(LinkText::Text(TextMatch::Exact(s.clone()))),
+                (Some("$matches"), _) => parse_regex(v).map(LinkText::Matches),
+                _ => Err(ParseError::OperatorExpectedString { op: "text" }),
+            }
+        }
+        _ => Err(ParseError::OperatorExpectedString { op: "text" }),
+    }
+}
+
+pub fn parse_link_predicate(value: &Value) -> Result<LinkPredicate, ParseError> {
+    let map = value
+        .as_mapping()
+        .ok_or(ParseError::OperatorExpectedMapping { op: "link" })?;
+    let mut pred = LinkPredicate::any();
+    for (k, v) in map {
+        let key = k.as_str().ok_or(ParseError::NonStringKey)?;
+        match key {
+            "type" => {
+                pred.types = match v {
+                    Value::Sequence(items) => items
+                        .iter()
+                        .map(parse_link_type)
+                        .collect::<Result<_, _>>()?,
+                    _ => vec![parse_link_type(v)?],
+                }
+            }
+            "text" => pred.text = Some(parse_link_text(v)?),
+            "titleDiffers" => {
+                pred.title_differs = Some(
+                    v.as_bool()
+                        .ok_or(ParseError::OperatorExpectedBool { op: "titleDiffers" })?,
+                )
+            }
+            "within" => pred.within = Some(BlockPredicate(vec![parse_within(v)?])),
+            other => {
+                return Err(ParseError::UnknownLinkKey {
+                    key: other.to_string(),
+                })
+            }
+        }
+    }
+    Ok(pred)
+}
+
+fn title_of(graph: &Graph, key: &Key) -> String {
+    graph.get_key_title(key).unwrap_or_else(|| key.to_string())
+}
+
+/// Links written in `source` that satisfy `pred`, in document order.
+fn matching_links(graph: &Graph, source: &Key, pred: &LinkPredicate) -> Vec<Link> {
+    let index = BlockIndex::build(graph, source);
+    let mask = pred.within.as_ref().map(|scope| index.mask(scope));
+    index
+        .inline_links()
+        .into_iter()
+        .filter(|(i, _)| mask.as_ref().is_none_or(|m| m[*i]))
+        .map(|(i, reference)| {
+            let target_title = title_of(graph, &reference.key);
+            let text = if reference.text.is_empty() {
+                target_title.clone()
+            } else {
+                reference.text.clone()
+            };
+            Link {
+                target: reference.key.clone(),
+                target_title,
+                text,
+                link_type: reference.reference_type,
+                section_path: index.path(i).to_vec(),
+            }
+        })
+        .filter(|link| pred.accepts(link))
+        .collect()
+}
+
+fn linking_sources(graph: &Graph, target: &Key) -> Vec<Key> {
+    let mut sources: Vec<Key> = graph
+        .get_reference_edges_to(target)
+        .into_iter()
+        .map(|id| graph.key_of(id))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    sources.sort_by_key(|k| k.to_string());
+    sources
+}
+
+/// Links from `key` to other documents that satisfy `pred`, one entry per link.
+pub fn outbound_links(graph: &Graph, key: &Key, pred: &LinkPredicate) -> Vec<LinkEdge> {
+    matching_links(graph, key, pred)
+        .into_iter()
+        .map(|link| LinkEdge {
+            key: link.target.to_string(),
+            title: link.target_title,
+            text: link.text,
+            link_type: link_type_name(link.link_type),
+            section_path: link.section_path,
+        })
+        .collect()
+}
+
+/// Links from other documents to `key` that satisfy `pred`, one entry per link.
+pub fn inbound_links(graph: &Graph, key: &Key, pred: &LinkPredicate) -> Vec<LinkEdge> {
+    let mut out = Vec::new();
+    for source in linking_sources(graph, key) {
+        let title = title_of(graph, &source);
+        for link in matching_links(graph, &source, pred) {
+            if link.target != *key {
+                continue;
+            }
+            out.push(LinkEdge {
+                key: source.to_string(),
+                title: title.clone(),
+                text: link.text,
+                link_type: link_type_name(link.link_type),
+                section_path: link.section_path,
+            });
+        }
+    }
+    out
+}
+
+/// Reference walk that only follows links accepted by a [`LinkPredicate`].
+///
+/// Each source document's accepted targets are computed once and shared across walks, so a
+/// relation evaluated from many anchors or candidates indexes every document at most once.
+pub struct LinkWalk<'a> {
+    graph: &'a Graph,
+    predicate: &'a LinkPredicate,
+    targets: Mutex<HashMap<Key, Arc<Vec<Key>>>>,
+}
+
+impl<'a> LinkWalk<'a> {
+    pub fn new(graph: &'a Graph, predicate: &'a LinkPredicate) -> Self {
+        LinkWalk {
+            graph,
+            predicate,
+            targets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn targets(&self, source: &Key) -> Arc<Vec<Key>> {
+        if let Some(cached) = self.targets.lock().unwrap().get(source) {
+            return cached.clone();
+        }
+        let targets: Arc<Vec<Key>> = Arc::new(
+            matching_links(self.graph, source, self.predicate)
+                .into_iter()
+                .map(|link| link.target)
+                .collect(),
+        );
+        self.targets
+            .lock()
+            .unwrap()
+            .insert(source.clone(), targets.clone());
+        targets
+    }
+
+    fn neighbors(&self, key: &Key, outbound: bool) -> Vec<Key> {
+        if outbound {
+            self.targets(key).as_ref().clone()
+        } else {
+            linking_sources(self.graph, key)
+                .into_iter()
+                .filter(|source| self.targets(source).contains(key))
+                .collect()
+        }
+    }
+
+    /// Same traversal as [`crate::graph::walk::outbound_reference`] /
+    /// [`crate::graph::walk::inbound_reference`], restricted to accepted links.
+    pub fn walk(&self, anchor: &Key, max_distance: u32, outbound: bool) -> HashMap<Key, u32> {
+        let mut out: HashMap<Key, u32> = HashMap::new();
+        let mut visited: HashSet<Key> = HashSet::new();
+        visited.insert(anchor.clone());
+        let mut queue: VecDeque<(Key, u32)> = VecDeque::new();
+        queue.push_back((anchor.clone(), 0));
+        while let Some((current, distance)) = queue.pop_front() {
+            if distance >= max_distance {
+                continue;
+            }
+            for neighbor in self.neighbors(&current, outbound) {
+                if !visited.insert(neighbor.clone()) {
+                    continue;
+                }
+                out.insert(neighbor.clone(), distance + 1);
+                queue.push_back((neighbor, distance + 1));
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,586 @@
+//! Live query blocks: an `iwe-query` fenced block holding a find operation, followed by its
+//! results between generated markers. [`refresh`] rewrites the results from the current graph.
+
+use std::fmt;
+use std::ops::Range;
+
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+use serde_yaml::{Mapping, Value};
+
+use crate::graph::Graph;
+use crate::markdown::reader::PARSER_OPTIONS;
+use crate::model::config::{Format, LinkType};
+use crate::model::marker::Marker;
+use crate::model::Key;
+use crate::query::block_update::EvalError;
+use crate::query::builder::{parse_operation, ParseError};
+use crate::query::document::{Filter, KeyOp, Operation, OperationKind};
+use crate::query::execute::{execute, FindMatch, Outcome};
+
+pub const QUERY_BLOCK_LANG: &str = "iwe-query";
+
+/// How results are written below the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Render {
+    /// A bullet list of links.
+    #[default]
+    List,
+    /// A table of the projected fields, led by a link to each document.
+    Table,
+    /// An inclusion link per document.
+    Inclusion,
+}
+
+#[derive(Debug)]
+pub enum QueryBlockError {
+    Operation(ParseError),
+    Execute(EvalError),
+    InvalidRender(String),
+    InvalidEdges,
+}
+
+impl fmt::Display for QueryBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryBlockError::Operation(error) => write!(f, "{}", error),
+            QueryBlockError::Execute(error) => write!(f, "{}", error),
+            QueryBlockError::InvalidRender(render) => write!(
+                f,
+                "render must be list, table or inclusion, got '{}'",
+                render
+            ),
+            QueryBlockError::InvalidEdges => write!(f, "edges must be true or false"),
+        }
+    }
+}
+
+impl std::error::Error for QueryBlockError {}
+
+/// The content of an `iwe-query` block: a find operation plus the `render` and `edges` keys.
+#[derive(Debug, Clone)]
+pub struct QueryBlock {
+    pub operation: Operation,
+    pub render: Render,
+    pub edges: bool,
+}
+
+impl QueryBlock {
+    pub fn parse(yaml: &str) -> Result<QueryBlock, QueryBlockError> {
+        let mut mapping: Mapping = serde_yaml::from_str(yaml)
+            .map_err(|error| QueryBlockError::Operation(ParseError::Wire(error)))?;
+
+        let render = match mapping.remove("render") {
+            None => Render::List,
+            Some(Value::String(render)) => match render.as_str() {
+                "list" => Render::List,
+                "table" => Render::Table,
+                "inclusion" => Render::Inclusion,
+                _ => return Err(QueryBlockError::InvalidRender(render)),
+            },
+            Some(other) => {
+                return Err(QueryBlockError::InvalidRender(
+                    serde_yaml::to_string(&other)
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string(),
+                ))
+            }
+        };
+        let edges = match mapping.remove("edges") {
+            None => true,
+            Some(Value::Bool(edges)) => edges,
+            Some(_) => return Err(QueryBlockError::InvalidEdges),
+        };
+
+        let operation = if mapping.is_empty() {
+            "{}".to_string()
+        } else {
+            serde_yaml::to_string(&mapping).unwrap_or_default()
+        };
+        let operation =
+            parse_operation(&operation, OperationKind::Find).map_err(QueryBlockError::Operation)?;
+
+        Ok(QueryBlock {
+            operation,
+            render,
+            edges,
+        })
+    }
+
+    /// The results as markdown links relative to `key`, in the operation's order, which is by
+    /// key unless the operation sorts. `key` itself, the document holding the block, is never
+    /// among them.
+    pub fn render(
+        &self,
+        graph: &Graph,
+        key: &Key,
+        link_type: &LinkType,
+    ) -> Result<String, QueryBlockError> {
+        let mut operation = self.operation.clone();
+        if let Operation::Find(find) = &mut operation {
+            let host = Filter::Key(KeyOp::Ne(key.clone()));
+            find.filter = Some(match find.filter.take() {
+                Some(filter) => Filter::And(vec![filter, host]),
+                None => host,
+            });
+        }
+        let matches = match execute(&operation, graph).map_err(QueryBlockError::Execute)? {
+            Outcome::Find { matches } => matches,
+            _ => Vec::new(),
+        };
+        let link = |target: &Key| link(graph, key, target, link_type);
+
+        Ok(match self.render {
+            Render::List => matches
+                .iter()
+                .map(|found| format!("- {}", link(&found.key)))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Render::Inclusion => matches
+                .iter()
+                .map(|found| link(&found.key))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            Render::Table => table(&matches, link),
+        })
+    }
+}
+
+/// The outcome of [`refresh`].
+#[derive(Debug, Default)]
+pub struct Refreshed {
+    /// The document with fresh results; `None` when nothing changes.
+    pub content: Option<String>,
+    /// Errors of the blocks that did not evaluate, in document order. Those blocks keep their
+    /// old results.
+    pub errors: Vec<QueryBlockError>,
+}
+
+/// `key` with every query block's results rewritten. Blocks nested in lists or quotes are left
+/// alone, and so are all blocks of djot documents.
+pub fn refresh(graph: &Graph, key: &Key, link_type: &LinkType) -> Refreshed {
+    let mut refreshed = Refreshed::default();
+    if graph.format_options().format() != Format::Markdown {
+        return refreshed;
+    }
+    let Some(content) = graph.export_key(key) else {
+        return refreshed;
+    };
+
+    let blocks = top_level_blocks(&content);
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+
+    for (index, block) in blocks.iter().enumerate() {
+        let TopLevel::Query(yaml) = &block.kind else {
+            continue;
+        };
+        let rendered = QueryBlock::parse(yaml)
+            .and_then(|query| Ok((query.render(graph, key, link_type)?, query.edges)));
+        let (results, edges) = match rendered {
+            Ok(rendered) => rendered,
+            Err(error) => {
+                refreshed.errors.push(error);
+                continue;
+            }
+        };
+
+        let end = match blocks.get(index + 1) {
+            Some(next) if matches!(next.kind, TopLevel::Marker(Marker::Begin { .. })) => blocks
+                [index + 2..]
+                .iter()
+                .take_while(|block| !matches!(block.kind, TopLevel::Query(_)))
+                .find(|block| matches!(block.kind, TopLevel::Marker(Marker::End)))
+                .map(|end| end.range.end)
+                .unwrap_or(next.range.end),
+            _ => block.range.end,
+        };
+
+        let mut generated = format!("\n{}\n\n", Marker::Begin { edges }.text());
+        if !results.is_empty() {
+            generated.push_str(&results);
+            generated.push_str("\n\n");
+        }
+        generated.push_str(Marker::End.text());
+        generated.push('\n');
+
+        replacements.push((block.range.end..end, generated));
+    }
+
+    if replacements.is_empty() {
+        return refreshed;
+    }
+
+    let mut text = content.clone();
+    for (range, generated) in replacements.into_iter().rev() {
+        text.replace_range(range, &generated);
+    }
+
+    let mut patch = graph.new_patch();
+    patch.update_key(key.clone(), &text);
+    let text = patch.export_key(key).unwrap_or(text);
+
+    refreshed.content = Some(text).filter(|text| *text != content);
+    refreshed
+}
+
+enum TopLevel {
+    Query(String),
+    Marker(Marker),
+    Other,
+}
+
+struct Block {
+    range: Range<usize>,
+    kind: TopLevel,
+}
+
+fn top_level_blocks(content: &str) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut depth = 0usize;
+
+    for (event, range) in Parser::new_ext(content, PARSER_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(tag) => {
+                if depth == 0 {
+                    let kind = match &tag {
+                        Tag::CodeBlock(CodeBlockKind::Fenced(lang))
+                            if lang.as_ref() == QUERY_BLOCK_LANG =>
+                        {
+                            TopLevel::Query(String::new())
+                        }
+                        Tag::HtmlBlock => Marker::parse(&content[range.clone()])
+                            .map(TopLevel::Marker)
+                            .unwrap_or(TopLevel::Other),
+                        _ => TopLevel::Other,
+                    };
+                    blocks.push(Block { range, kind });
+                }
+                depth += 1;
+            }
+            Event::End(_) => depth = depth.saturating_sub(1),
+            Event::Text(text) if depth == 1 => {
+                if let Some(Block {
+                    kind: TopLevel::Query(yaml),
+                    ..
+                }) = blocks.last_mut()
+                {
+                    yaml.push_str(&text);
+                }
+            }
+            _ if depth == 0 => blocks.push(Block {
+                range,
+                kind: TopLevel::Other,
+            }),
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+fn link(graph: &Graph, key: &Key, target: &Key, link_type: &LinkType) -> String {
+    let options = graph.format_options();
+    match link_type {
+        LinkType::WikiLink => format!(
+            "[[{}]]",
+            graph
+                .key_index()
+                .wiki_target(target, options.markdown_options().wiki_link_path)
+        ),
+        LinkType::Markdown => format!(
+            "[{}]({}{})",
+            graph
+                .get_key_title(target)
+                .unwrap_or_else(|| target.to_string()),
+            target.link_url(&key.parent(), options.refs_path()),
+            options.refs_extension()
+        ),
+    }
+}
+
+fn table(matches: &[FindMatch], link: impl Fn(&Key) -> String) -> String {
+    if matches.is_empty() {
+        return String::new();
+    }
+
+    let mut columns: Vec<&Value> = Vec::new();
+    for found in matches {
+        for column in found.document.keys() {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+    }
+
+    let row = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let mut lines = vec![
+        row(std::iter::once("Document".to_string())
+            .chain(columns.iter().map(|column| cell(column)))
+            .collect()),
+        row(vec!["---".to_string(); columns.len() + 1]),
+    ];
+    for found in matches {
+        lines.push(row(std::iter::once(link(&found.key))
+            .chain(
+                columns
+                    .iter()
+                    .map(|column| found.document.get(*column).map(cell).unwrap_or_default()),
+            )
+            .collect()));
+    }
+    lines.join("\n")
+}
+
+fn cell(value: &Value) -> String {
+    let text = match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Bool(value) => value.to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::Sequence(values) => values.iter().map(cell).collect::<Vec<_>>().join(", "),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .replace('\n', " "),
+    };
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use indoc::indoc;
+
+    use crate::model::config::MarkdownOptions;
+
+    fn graph(index: &str) -> Graph {
+        let mut graph = Graph::new_with_options(MarkdownOptions::default());
+        graph.update_key(
+            Key::name("projects/alpha"),
+            "---\nstatus: active\nowner: Ada\n---\n\n# Alpha\n",
+        );
+        graph.update_key(
+            Key::name("projects/beta"),
+            "---\nstatus: active\nowner: Grace\n---\n\n# Beta\n",
+        );
+        graph.update_key(
+            Key::name("projects/gamma"),
+            "---\nstatus: done\n---\n\n# Gamma\n",
+        );
+        graph.update_key(Key::name("index"), index);
+        graph
+    }
+
+    fn refreshed(index: &str) -> Option<String> {
+        refresh(&graph(index), &Key::name("index"), &LinkType::Markdown).content
+    }
+
+    #[test]
+    fn writes_results_between_markers() {
+        let index = indoc! {"
+            # Index
+
+            ``` iwe-query
+            filter:
+              status: active
+            sort:
+              owner: -1
+            ```
+        "};
+
+        assert_eq!(
+            refreshed(index).unwrap(),
+            indoc! {"
+                # Index
+
+                ``` iwe-query
+                filter:
+                  status: active
+                sort:
+                  owner: -1
+                ```
+
+                <!-- iwe-query:begin -->
+
+                - [Beta](projects/beta)
+                - [Alpha](projects/alpha)
+
+                <!-- iwe-query:end -->
+            "}
+        );
+    }
+
+    #[test]
+    fn replaces_stale_results_and_keeps_fresh_ones() {
+        let stale = indoc! {"
+            # Index
+
+            ``` iwe-query
+            filter:
+              status: done
+            render: inclusion
+            ```
+
+            <!-- iwe-query:begin -->
+
+            - [Alpha](projects/alpha)
+
+            <!-- iwe-query:end -->
+
+            Trailing paragraph.
+        "};
+        let fresh = refreshed(stale).unwrap();
+
+        assert_eq!(
+            fresh,
+            indoc! {"
+                # Index
+
+                ``` iwe-query
+                filter:
+                  status: done
+                render: inclusion
+                ```
+
+                <!-- iwe-query:begin -->
+
+                [Gamma](projects/gamma)
+
+                <!-- iwe-query:end -->
+
+                Trailing paragraph.
+            "}
+        );
+        assert_eq!(refreshed(&fresh), None);
+    }
+
+    #[test]
+    fn renders_projected_fields_as_a_table() {
+        let index = indoc! {"
+            # Index
+
+            ``` iwe-query
+            filter:
+              status: active
+            project:
+              owner: 1
+            render: table
+            ```
+        "};
+
+        assert!(refreshed(index).unwrap().contains(indoc! {"
+            | Document                | owner |
+            | ----------------------- | ----- |
+            | [Alpha](projects/alpha) | Ada   |
+            | [Beta](projects/beta)   | Grace |
+        "}));
+    }
+
+    #[test]
+    fn results_without_edges_are_not_backlinks() {
+        let index = indoc! {"
+            # Index
+
+            ``` iwe-query
+            filter:
+              status: active
+            render: inclusion
+            edges: false
+            ```
+        "};
+        let mut graph = graph(index);
+        let fresh = refresh(&graph, &Key::name("index"), &LinkType::Markdown)
+            .content
+            .unwrap();
+        assert!(fresh.contains("<!-- iwe-query:begin no-edges -->"));
+
+        graph.update_key(Key::name("index"), &fresh);
+        assert!(graph
+            .get_inclusion_edges_to(&Key::name("projects/alpha"))
+            .is_empty());
+        assert!(graph.get_inclusion_edges_in(&Key::name("index")).is_empty());
+        assert_eq!(graph.export_key(&Key::name("index")).unwrap(), fresh);
+    }
+
+    #[test]
+    fn invalid_blocks_keep_their_results_and_others_refresh() {
+        let index = indoc! {"
+            # Index
+
+            ``` iwe-query
+            render: chart
+            ```
+
+            <!-- iwe-query:begin -->
+
+            - [Alpha](projects/alpha)
+
+            <!-- iwe-query:end -->
+
+            ``` iwe-query
+            filter:
+              status: done
+            ```
+        "};
+
+        let refreshed = refresh(&graph(index), &Key::name("index"), &LinkType::Markdown);
+
+        assert_eq!(
+            refreshed
+                .errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            vec!["render must be list, table or inclusion, got 'chart'"]
+        );
+        assert_eq!(
+            refreshed.content.unwrap(),
+            indoc! {"
+                # Index
+
+                ``` iwe-query
+                render: chart
+                ```
+
+                <!-- iwe-query:begin -->
+
+                - [Alpha](projects/alpha)
+
+                <!-- iwe-query:end -->
+
+                ``` iwe-query
+                filter:
+                  status: done
+                ```
+
+                <!-- iwe-query:begin -->
+
+                - [Gamma](projects/gamma)
+
+                <!-- iwe-query:end -->
+            "}
+        );
+    }
+
+    #[test]
+    fn results_leave_out_the_document_holding_the_block() {
+        let index = indoc! {"
+            ---
+            status: active
+            ---
+
+            # Index
+
+            ``` iwe-query
+            filter:
+              status: active
+            ```
+        "};
+
+        let fresh = refreshed(index).unwrap();
+
+        assert!(fresh.contains("- [Alpha](projects/alpha)\n- [Beta](projects/beta)\n"));
+        assert!(!fresh.contains("(index)"));
+    }
+}
@@ -1,26 +1,37 @@
 use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use serde_yaml::{Mapping, Value};
 
+use crate::graph::analytics::MetricCache;
+use crate::graph::walk::{
+    ancestors_inclusion, descendants_inclusion, inbound_reference, outbound_reference,
+};
 use crate::graph::Graph;
 use crate::model::Key;
 use crate::query::block_eval::BlockIndex;
+use crate::query::computed::frontmatter_for;
 use crate::query::document::{
-    FieldPath, Projection, ProjectionBase, ProjectionField, ProjectionSource, PseudoField,
+    FieldPath, Lookup, LookupFrom, Projection, ProjectionBase, ProjectionField, ProjectionSource,
+    PseudoField,
 };
 use crate::query::edges::EdgeRef;
+use crate::query::eval::eval;
 use crate::query::frontmatter::{is_reserved_segment, strip_reserved};
+use crate::query::links::{inbound_links, outbound_links};
 
 pub struct ProjectionContext<'a> {
     pub graph: &'a Graph,
+    pub metrics: &'a MetricCache<'a>,
     pub key: &'a Key,
     blocks: OnceCell<BlockIndex>,
 }
 
 impl<'a> ProjectionContext<'a> {
-    pub fn new(graph: &'a Graph, key: &'a Key) -> Self {
+    pub fn new(graph: &'a Graph, metrics: &'a MetricCache<'a>, key: &'a Key) -> Self {
         ProjectionContext {
             graph,
+            metrics,
             key,
             blocks: OnceCell::new(),
         }
@@ -83,7 +94,7 @@ fn merge_user_frontmatter(ctx: &ProjectionContext<'_>, out: &mut Mapping) {
 fn resolve_field(ctx: &ProjectionContext<'_>, field: &ProjectionField) -> Value {
     match &field.source {
         ProjectionSource::Pseudo(p) => resolve_pseudo(ctx, *p),
-        ProjectionSource::Frontmatter(path) => resolve_frontmatter(ctx, path),
+        ProjectionSource::Frontmatter(path) => resolve_frontmatter(ctx.graph, ctx.key, path),
         ProjectionSource::ContentBlocks(pred) => {
             Value::String(ctx.block_index().render_content(pred))
         }
@@ -94,6 +105,13 @@ fn resolve_field(ctx: &ProjectionContext<'_>, field: &ProjectionField) -> Value
                 .map(Value::Mapping)
                 .collect(),
         ),
+        ProjectionSource::Rows(pred) => Value::Sequence(
+            ctx.block_index()
+                .rows_entries(pred)
+                .into_iter()
+                .map(Value::Mapping)
+                .collect(),
+        ),
         ProjectionSource::Matches(source) => Value::Sequence(
             ctx.block_index()
                 .matches_entries(source)
@@ -101,9 +119,85 @@ fn resolve_field(ctx: &ProjectionContext<'_>, field: &ProjectionField) -> Value
                 .map(Value::Mapping)
                 .collect(),
         ),
+        ProjectionSource::Links {
+            outbound,
+            predicate,
+        } => {
+            let links = if *outbound {
+                outbound_links(ctx.graph, ctx.key, predicate)
+            } else {
+                inbound_links(ctx.graph, ctx.key, predicate)
+            };
+            serde_yaml::to_value(&links).unwrap_or(Value::Sequence(Vec::new()))
+        }
+        ProjectionSource::Lookup(lookup) => resolve_lookup(ctx, lookup),
     }
 }
 
+fn resolve_lookup(ctx: &ProjectionContext<'_>, lookup: &Lookup) -> Value {
+    let graph = ctx.graph;
+    let max = lookup.max_depth;
+    let walk = match &lookup.from {
+        LookupFrom::Includes => descendants_inclusion(graph, ctx.key, max),
+        LookupFrom::IncludedBy => ancestors_inclusion(graph, ctx.key, max),
+        LookupFrom::References => outbound_reference(graph, ctx.key, max),
+        LookupFrom::ReferencedBy => inbound_reference(graph, ctx.key, max),
+        LookupFrom::Field(path) => field_chain(graph, ctx.key, path, max),
+    };
+    let mut related: Vec<(Key, u32)> = match &lookup.match_filter {
+        Some(filter) => {
+            let scope: HashSet<Key> = walk.keys().cloned().collect();
+            let selected = eval(filter, graph, ctx.metrics, Some(&scope));
+            walk.into_iter()
+                .filter(|(k, _)| selected.contains(k))
+                .collect()
+        }
+        None => walk.into_iter().collect(),
+    };
+    related.sort_by_key(|(k, depth)| (*depth, k.to_string()));
+    Value::Sequence(
+        related
+            .iter()
+            .map(|(k, _)| {
+                Value::Mapping(apply_projection(
+                    &ProjectionContext::new(graph, ctx.metrics, k),
+                    &lookup.project,
+                ))
+            })
+            .collect(),
+    )
+}
+
+/// Keys named by the frontmatter field at `path`, followed from each reached document up to
+/// `max_depth` hops. Names without a document in the graph are skipped.
+fn field_chain(graph: &Graph, key: &Key, path: &FieldPath, max_depth: u32) -> HashMap<Key, u32> {
+    let mut out: HashMap<Key, u32> = HashMap::new();
+    let mut queue: VecDeque<(Key, u32)> = VecDeque::new();
+    queue.push_back((key.clone(), 0));
+    while let Some((current, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+        let named = match resolve_frontmatter(graph, &current, path) {
+            Value::String(s) => vec![s],
+            Value::Sequence(items) => items
+                .into_iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        for name in named {
+            let next = Key::name(&name);
+            if next == *key || out.contains_key(&next) || graph.maybe_key(&next).is_none() {
+                continue;
+            }
+            out.insert(next.clone(), depth + 1);
+            queue.push_back((next, depth + 1));
+        }
+    }
+    out
+}
+
 fn resolve_pseudo(ctx: &ProjectionContext<'_>, p: PseudoField) -> Value {
     match p {
         PseudoField::Key => Value::String(ctx.key.to_string()),
@@ -138,10 +232,8 @@ fn resolve_pseudo(ctx: &ProjectionContext<'_>, p: PseudoField) -> Value {
     }
 }
 
-fn resolve_frontmatter(ctx: &ProjectionContext<'_>, path: &FieldPath) -> Value {
-    let Some(mut fm) = ctx.graph.frontmatter(ctx.key).cloned() else {
-        return Value::Null;
-    };
+fn resolve_frontmatter(graph: &Graph, key: &Key, path: &FieldPath) -> Value {
+    let mut fm = frontmatter_for(graph, key, path);
     strip_reserved(&mut fm);
     let mut current = Value::Mapping(fm);
     for segment in &path.0 {
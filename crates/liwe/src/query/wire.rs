@@ -93,6 +93,22 @@ pub struct RawRelationalObj {
     pub min_distance: Option<i64>,
     #[serde(rename = "$size", default)]
     pub size: Option<Value>,
+    #[serde(default)]
+    pub link: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawLookup {
+    pub from: String,
+    #[serde(rename = "match", default)]
+    pub match_: Option<Mapping>,
+    #[serde(default)]
+    pub project: Option<RawProjection>,
+    #[serde(rename = "maxDepth", default)]
+    pub max_depth: Option<i64>,
+    #[serde(rename = "maxDistance", default)]
+    pub max_distance: Option<i64>,
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+mod derive;
 mod document;
 mod infer;
 
@@ -6,5 +7,8 @@ pub use schematter_validator::{
     Violation,
 };
 
+pub use derive::{
+    derive_schema, DeriveOptions, DerivedHeader, DerivedSchema, DerivedSection, SCHEMA_DIALECT,
+};
 pub use document::build_document;
 pub use infer::{infer_schema, Coverage, FieldSchema, TypeCount, ValueCount};
@@ -0,0 +1,420 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use schematter_validator::Section;
+
+use super::document::build_document;
+use super::infer::{infer_schema, FieldSchema};
+use crate::graph::Graph;
+use crate::model::Key;
+
+pub const SCHEMA_DIALECT: &str = "https://document-schema.org/draft/2026-06/schema";
+
+/// Thresholds that turn what the selected documents have in common into policy.
+#[derive(Debug, Clone)]
+pub struct DeriveOptions {
+    /// Coverage, in percent, at which a frontmatter field or a section becomes required.
+    pub required: f64,
+    /// Coverage, in percent, at which a section header is listed at all.
+    pub common: f64,
+    /// Percentile of the observed token counts used as a `maxTokens` budget.
+    pub percentile: f64,
+    /// Most distinct values a string field may take and still become an `enum`.
+    pub max_enum: usize,
+}
+
+impl Default for DeriveOptions {
+    fn default() -> Self {
+        Self {
+            required: 90.0,
+            common: 50.0,
+            percentile: 95.0,
+            max_enum: 10,
+        }
+    }
+}
+
+/// A document schema in the schema file dialect, ready to be written as YAML.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedSchema {
+    #[serde(rename = "$schema")]
+    pub dialect: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frontmatter: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<DerivedSection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedSection {
+    /// `None` for the wildcard entry standing in for a title section whose text varies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<DerivedHeader>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_contains: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_contains: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<DerivedSection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DerivedHeader {
+    #[serde(rename = "const")]
+    pub text: String,
+}
+
+/// A schema that the documents at `keys` mostly satisfy: the frontmatter fields and section
+/// headers they share, in the order they use them, with token budgets at the configured
+/// percentile of what they hold today.
+pub fn derive_schema(
+    graph: &Graph,
+    keys: &[Key],
+    count: impl Fn(&str) -> usize + Copy,
+    options: &DeriveOptions,
+) -> DerivedSchema {
+    let documents: Vec<_> = keys
+        .iter()
+        .map(|key| build_document(graph, key, count))
+        .collect();
+
+    let fields = infer_schema(graph, keys);
+    let frontmatter = object_schema(&fields, "", keys.len(), options);
+
+    let body_tokens: Vec<usize> = documents.iter().map(|d| d.body_tokens).collect();
+    let groups: Vec<&[Section]> = documents.iter().map(|d| d.sections.as_slice()).collect();
+
+    DerivedSchema {
+        dialect: SCHEMA_DIALECT.to_string(),
+        description: format!(
+            "inferred from {} document{}",
+            keys.len(),
+            if keys.len() == 1 { "" } else { "s" }
+        ),
+        frontmatter,
+        max_tokens: percentile(body_tokens, options.percentile),
+        sections: derive_sections(&groups, options),
+    }
+}
+
+fn object_schema(
+    fields: &[FieldSchema],
+    prefix: &str,
+    total: usize,
+    options: &DeriveOptions,
+) -> Option<Value> {
+    let children: Vec<&FieldSchema> = fields
+        .iter()
+        .filter(|field| match field.name.strip_prefix(prefix) {
+            Some(rest) => !rest.is_empty() && !rest.contains('.'),
+            None => false,
+        })
+        .collect();
+    if children.is_empty() || total == 0 {
+        return None;
+    }
+
+    let mut required = Vec::new();
+    let mut properties = Mapping::new();
+    for field in children {
+        let name = &field.name[prefix.len()..];
+        if field.coverage.count as f64 * 100.0 / total as f64 >= options.required {
+            required.push(Value::from(name));
+        }
+        let nested_prefix = format!("{}.", field.name);
+        let property = match object_schema(fields, &nested_prefix, field.coverage.count, options) {
+            Some(nested) if is_object_only(field) => nested,
+            _ => field_schema(field, options),
+        };
+        properties.insert(Value::from(name), property);
+    }
+
+    let mut schema = Mapping::new();
+    schema.insert(Value::from("type"), Value::from("object"));
+    if !required.is_empty() {
+        schema.insert(Value::from("required"), Value::Sequence(required));
+    }
+    schema.insert(Value::from("properties"), Value::Mapping(properties));
+    Some(Value::Mapping(schema))
+}
+
+fn is_object_only(field: &FieldSchema) -> bool {
+    field.types.len() == 1 && field.types[0].yaml_type == "object"
+}
+
+fn field_schema(field: &FieldSchema, options: &DeriveOptions) -> Value {
+    let mut kinds: Vec<&str> = Vec::new();
+    for observed in &field.types {
+        let kind = match observed.yaml_type.as_str() {
+            "date" | "datetime" => "string",
+            other => other,
+        };
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+
+    let mut schema = Mapping::new();
+    match kinds.as_slice() {
+        [] => {}
+        [kind] => {
+            schema.insert(Value::from("type"), Value::from(*kind));
+        }
+        several => {
+            schema.insert(
+                Value::from("type"),
+                Value::Sequence(several.iter().map(|kind| Value::from(*kind)).collect()),
+            );
+        }
+    }
+
+    let format = match field
+        .types
+        .iter()
+        .map(|t| t.yaml_type.as_str())
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["date"] => Some("date"),
+        ["datetime"] => Some("date-time"),
+        _ => None,
+    };
+    if let Some(format) = format {
+        schema.insert(Value::from("format"), Value::from(format));
+    }
+
+    let listed: usize = field.values.iter().map(|value| value.count).sum();
+    let enumerable = kinds == ["string"]
+        && format.is_none()
+        && !field.values.is_empty()
+        && listed == field.coverage.count
+        && field.distinct <= options.max_enum
+        && field.distinct < field.coverage.count;
+    if enumerable {
+        let mut values: Vec<&str> = field.values.iter().map(|v| v.value.as_str()).collect();
+        values.sort();
+        schema.insert(
+            Value::from("enum"),
+            Value::Sequence(values.into_iter().map(Value::from).collect()),
+        );
+    }
+
+    Value::Mapping(schema)
+}
+
+struct HeaderStats<'a> {
+    parents: usize,
+    max_per_parent: usize,
+    position: f64,
+    bound: Vec<&'a Section>,
+}
+
+fn derive_sections(groups: &[&[Section]], options: &DeriveOptions) -> Vec<DerivedSection> {
+    let total = groups.len();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let mut stats: HashMap<&str, HeaderStats> = HashMap::new();
+    for group in groups {
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for (index, section) in group.iter().enumerate() {
+            let entry = stats
+                .entry(section.header.as_str())
+                .or_insert_with(|| HeaderStats {
+                    parents: 0,
+                    max_per_parent: 0,
+                    position: 0.0,
+                    bound: Vec::new(),
+                });
+            entry.bound.push(section);
+            let occurrences = seen.entry(section.header.as_str()).or_insert(0);
+            *occurrences += 1;
+            entry.max_per_parent = entry.max_per_parent.max(*occurrences);
+            if *occurrences == 1 {
+                entry.parents += 1;
+                entry.position += index as f64 / group.len() as f64;
+            }
+        }
+    }
+
+    let covered = |parents: usize| parents as f64 * 100.0 / total as f64;
+
+    let mut common: Vec<(&str, HeaderStats)> = stats
+        .into_iter()
+        .filter(|(_, stats)| covered(stats.parents) >= options.common)
+        .collect();
+    common.sort_by(|(a, a_stats), (b, b_stats)| {
+        let a_position = a_stats.position / a_stats.parents as f64;
+        let b_position = b_stats.position / b_stats.parents as f64;
+        a_position.total_cmp(&b_position).then(a.cmp(b))
+    });
+
+    if common.is_empty() {
+        return title_section(groups, options).into_iter().collect();
+    }
+
+    common
+        .into_iter()
+        .map(|(header, stats)| DerivedSection {
+            header: Some(DerivedHeader {
+                text: header.to_string(),
+            }),
+            min_contains: (covered(stats.parents) < options.required).then_some(0),
+            max_contains: (stats.max_per_parent == 1).then_some(1),
+            max_tokens: percentile(
+                stats.bound.iter().map(|s| s.subtree_tokens).collect(),
+                options.percentile,
+            ),
+            sections: derive_sections(
+                &stats
+                    .bound
+                    .iter()
+                    .map(|s| s.sections.as_slice())
+                    .collect::<Vec<_>>(),
+                options,
+            ),
+        })
+        .collect()
+}
+
+/// A wildcard entry for the single section most documents open with, when its header differs
+/// from one document to the next, as a title does.
+fn title_section(groups: &[&[Section]], options: &DeriveOptions) -> Option<DerivedSection> {
+    let single = groups.iter().filter(|group| group.len() == 1).count();
+    let covered = single as f64 * 100.0 / groups.len() as f64;
+    if covered < options.common {
+        return None;
+    }
+
+    let bound: Vec<&Section> = groups.iter().flat_map(|group| group.iter()).collect();
+    Some(DerivedSection {
+        header: None,
+        min_contains: (covered < options.required).then_some(0),
+        max_contains: Some(1),
+        max_tokens: percentile(
+            bound.iter().map(|s| s.subtree_tokens).collect(),
+            options.percentile,
+        ),
+        sections: derive_sections(
+            &bound
+                .iter()
+                .map(|s| s.sections.as_slice())
+                .collect::<Vec<_>>(),
+            options,
+        ),
+    })
+}
+
+/// Nearest-rank percentile; `None` for no observations.
+fn percentile(mut values: Vec<usize>, percent: f64) -> Option<usize> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (percent / 100.0 * values.len() as f64).ceil() as usize;
+    Some(values[rank.clamp(1, values.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::MarkdownReader;
+
+    fn build_graph(docs: &[(&str, &str)]) -> Graph {
+        let mut graph = Graph::new();
+        for (key, content) in docs {
+            graph.from_markdown(Key::name(key), content, MarkdownReader::new());
+        }
+        graph
+    }
+
+    fn words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    fn derive(docs: &[(&str, &str)]) -> DerivedSchema {
+        let graph = build_graph(docs);
+        let mut keys = graph.keys();
+        keys.sort();
+        derive_schema(&graph, &keys, words, &DeriveOptions::default())
+    }
+
+    #[test]
+    fn shared_fields_become_required_and_repeated_strings_an_enum() {
+        let schema = derive(&[
+            (
+                "a",
+                "---\nstatus: draft\ndate: 2026-01-01\ntag: x\n---\n# A\n",
+            ),
+            ("b", "---\nstatus: published\ndate: 2026-01-02\n---\n# B\n"),
+            ("c", "---\nstatus: draft\ndate: 2026-01-03\n---\n# C\n"),
+            ("d", "---\nstatus: draft\ndate: 2026-01-04\n---\n# D\n"),
+        ]);
+
+        let frontmatter: Value = serde_yaml::from_str(
+            "type: object\n\
+             required: [date, status]\n\
+             properties:\n  \
+               date: { type: string, format: date }\n  \
+               status: { type: string, enum: [draft, published] }\n  \
+               tag: { type: string }\n",
+        )
+        .unwrap();
+        assert_eq!(schema.frontmatter, Some(frontmatter));
+    }
+
+    #[test]
+    fn common_headers_keep_their_order_below_a_varying_title() {
+        let schema = derive(&[
+            (
+                "a",
+                "# Alpha\n\n## Summary\n\none two\n\n## Tasks\n\n## Extra\n",
+            ),
+            ("b", "# Beta\n\n## Summary\n\none\n\n## Tasks\n"),
+            ("c", "# Gamma\n\n## Summary\n\n## Notes\n\n## Tasks\n"),
+        ]);
+
+        assert_eq!(schema.sections.len(), 1);
+        let title = &schema.sections[0];
+        assert_eq!(title.header, None);
+        assert_eq!(title.max_contains, Some(1));
+
+        let headers: Vec<(&str, Option<usize>)> = title
+            .sections
+            .iter()
+            .map(|s| (s.header.as_ref().unwrap().text.as_str(), s.min_contains))
+            .collect();
+        assert_eq!(headers, vec![("Summary", None), ("Tasks", None)]);
+        assert_eq!(title.sections[0].max_tokens, Some(4));
+    }
+
+    #[test]
+    fn budgets_come_from_the_percentile() {
+        assert_eq!(percentile(vec![5, 1, 3, 2, 4], 95.0), Some(5));
+        assert_eq!(percentile(vec![5, 1, 3, 2, 4], 50.0), Some(3));
+        assert_eq!(percentile(vec![], 95.0), None);
+    }
+
+    #[test]
+    fn derived_schema_compiles() {
+        let schema = derive(&[
+            ("a", "---\nstatus: draft\n---\n# Alpha\n\n## Summary\n"),
+            ("b", "---\nstatus: draft\n---\n# Beta\n\n## Summary\n"),
+        ]);
+        let yaml = serde_yaml::to_string(&schema).unwrap();
+        assert!(
+            schematter_validator::compile_schema(&yaml).is_ok(),
+            "{yaml}"
+        );
+    }
+}
@@ -124,10 +124,23 @@ pub fn infer_schema(graph: &Graph, keys: &[Key]) -> Vec<FieldSchema> {
     let total_documents = keys.len();
     let mut accumulators: BTreeMap<String, FieldAccumulator> = BTreeMap::new();
 
+    let computed = graph.computed_fields();
     for key in keys {
-        if let Some(mapping) = graph.frontmatter(key) {
-            walk_mapping(mapping, "", &mut accumulators);
+        if computed.is_empty() {
+            if let Some(mapping) = graph.frontmatter(key) {
+                walk_mapping(mapping, "", &mut accumulators);
+            }
+            continue;
+        }
+        let mut mapping = graph.frontmatter(key).cloned().unwrap_or_default();
+        for (name, value) in computed.values(graph, key) {
+            if value.is_null() {
+                mapping.remove(&name);
+            } else {
+                mapping.insert(name, value);
+            }
         }
+        walk_mapping(&mapping, "", &mut accumulators);
     }
 
     accumulators
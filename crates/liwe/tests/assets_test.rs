@@ -0,0 +1,76 @@
+use indoc::indoc;
+use pretty_assertions::assert_eq;
+
+use liwe::graph::assets::{asset_references, AssetReference};
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::model::{Key, State};
+use liwe::operations::rename;
+
+fn graph(documents: &[(&str, &str)]) -> Graph {
+    let state: State = documents
+        .iter()
+        .map(|(key, text)| (key.to_string(), text.to_string()))
+        .collect();
+    Graph::import(&state, MarkdownOptions::default(), None)
+}
+
+#[test]
+fn tracks_images_and_non_document_links() {
+    let graph = graph(&[(
+        "notes/a",
+        indoc! {"
+            # a
+
+            ![chart](img/chart.png) and [the spec](../papers/spec%20v2.pdf#page=3)
+
+            [b](b) and [site](https://example.com/logo.png)
+            "},
+    )]);
+
+    assert_eq!(
+        asset_references(&graph),
+        vec![
+            AssetReference {
+                key: Key::name("notes/a"),
+                path: "notes/img/chart.png".into(),
+                url: "img/chart.png".into(),
+            },
+            AssetReference {
+                key: Key::name("notes/a"),
+                path: "papers/spec v2.pdf".into(),
+                url: "../papers/spec%20v2.pdf#page=3".into(),
+            },
+        ]
+    );
+    assert_eq!(graph.reference_edge_target_keys().len(), 1);
+}
+
+#[test]
+fn asset_link_alone_in_paragraph_stays_a_link() {
+    let graph = graph(&[("a", "# a\n\n[spec](spec.pdf)\n")]);
+
+    assert_eq!(graph.inclusion_edge_target_keys().len(), 0);
+    assert_eq!(asset_references(&graph).len(), 1);
+}
+
+#[test]
+fn rename_rebases_asset_paths() {
+    let graph = graph(&[
+        (
+            "a",
+            "# a\n\n![chart](img/chart.png) see [spec](/papers/spec.pdf)\n",
+        ),
+        ("b", "# b\n\n[a](a)\n"),
+    ]);
+
+    let changes = rename(&graph, &Key::name("a"), &Key::name("archive/2024/a")).unwrap();
+
+    assert_eq!(
+        changes.creates,
+        vec![(
+            Key::name("archive/2024/a"),
+            "# a\n\n![chart](../../img/chart.png) see [spec](/papers/spec.pdf)\n".to_string()
+        )]
+    );
+}
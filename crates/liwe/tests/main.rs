@@ -1,3 +1,4 @@
+mod assets_test;
 #[allow(dead_code)]
 mod blocks;
 mod djot_roundtrip;
@@ -15,13 +16,18 @@ mod normalization_raw;
 mod queries;
 mod query_block_update;
 mod query_blocks;
+mod query_computed;
 mod query_content_filter;
 mod query_count;
 mod query_delete;
 mod query_deserialize;
+mod query_explain;
 mod query_filter_expression;
 mod query_find;
 mod query_graph;
+mod query_graph_metrics;
+mod query_links;
+mod query_lookup;
 mod query_reserved_prefix;
 mod query_scores;
 mod query_update;
@@ -315,6 +315,25 @@ fn table_with_alignment() {
     );
 }
 
+#[test]
+fn table_pads_wide_characters_by_display_width() {
+    setup();
+    compare(
+        indoc! {"
+        | name | status |
+        | ---- |:------:|
+        | 東京 |  open  |
+        | ab   |  done  |
+    "},
+        indoc! {"
+        | name | status |
+        |---|:-:|
+        | 東京 | open |
+        | ab | done |
+    "},
+    );
+}
+
 #[test]
 fn table_with_pipe_in_code() {
     setup();
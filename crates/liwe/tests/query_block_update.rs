@@ -924,3 +924,147 @@ fn document_expect_in_count_is_parse_error() {
         "'count' does not support the 'expect' field"
     );
 }
+
+const TASKS: &str = indoc! {"
+    # Doc
+
+    | Task  | Status |  Due |
+    | :---- | :----: | ---: |
+    | Draft |  open  |    1 |
+    | Print |  done  |   12 |
+"};
+
+#[test]
+fn append_row_fills_named_columns() {
+    assert_update(
+        TASKS,
+        indoc! {"
+            filter: {}
+            update:
+              $appendRow:
+                $table: {}
+                row: { Task: Publish the report, Due: 30 }
+        "},
+        indoc! {"
+            # Doc
+
+            | Task               | Status | Due |
+            |:-------------------|:------:|----:|
+            | Draft              |  open  |   1 |
+            | Print              |  done  |  12 |
+            | Publish the report |        |  30 |
+        "},
+    );
+}
+
+#[test]
+fn set_cells_in_rows_passing_row_tests() {
+    assert_update(
+        TASKS,
+        indoc! {"
+            filter: {}
+            update:
+              $setCells:
+                $row: { Task: draft }
+                set: { Status: in review }
+                expect: 1
+        "},
+        indoc! {"
+            # Doc
+
+            | Task  |  Status   | Due |
+            |:------|:---------:|----:|
+            | Draft | in review |   1 |
+            | Print |   done    |  12 |
+        "},
+    );
+}
+
+#[test]
+fn set_cells_in_rows_passing_nested_row_tests() {
+    assert_update(
+        TASKS,
+        indoc! {"
+            filter: {}
+            update:
+              $setCells:
+                $table:
+                  $or:
+                    - $row: { Task: draft }
+                    - $and: [{ $row: { Status: done } }, { $row: { Due: 1 } }]
+                set: { Status: in review }
+        "},
+        indoc! {"
+            # Doc
+
+            | Task  |  Status   | Due |
+            |:------|:---------:|----:|
+            | Draft | in review |   1 |
+            | Print |   done    |  12 |
+        "},
+    );
+}
+
+#[test]
+fn set_cells_ignores_blocks_other_than_tables() {
+    assert_update(
+        TASKS,
+        indoc! {"
+            filter: {}
+            update:
+              $setCells:
+                set: { Due: 0 }
+        "},
+        indoc! {"
+            # Doc
+
+            | Task  | Status | Due |
+            |:------|:------:|----:|
+            | Draft |  open  |   0 |
+            | Print |  done  |   0 |
+        "},
+    );
+}
+
+#[test]
+fn set_cells_unknown_column_is_error() {
+    assert_str_eq!(
+        update_err(
+            TASKS,
+            indoc! {"
+                filter: {}
+                update:
+                  $setCells:
+                    set: { Owner: Ann }
+            "},
+        ),
+        indoc! {"
+            $setCells column 'Owner' is not in the table
+              1 › \"| Task  | Status | Due |\""}
+    );
+}
+
+#[test]
+fn append_row_requires_row_payload() {
+    assert_str_eq!(
+        parse_err(indoc! {"
+            filter: {}
+            update:
+              $appendRow: { $table: {} }
+        "}),
+        "'$appendRow' requires the 'row' key"
+    );
+}
+
+#[test]
+fn append_row_rejects_nested_cell_values() {
+    assert_str_eq!(
+        parse_err(indoc! {"
+            filter: {}
+            update:
+              $appendRow:
+                row: { Task: [a, b] }
+        "}),
+        "'$appendRow' key 'row' expects a mapping of column names to scalar values"
+    );
+}
@@ -847,3 +847,121 @@ fn parse_error_invalid_regex() {
             error: unclosed character class"}
     );
 }
+
+const TASKS: &str = indoc! {"
+    # doc1
+
+    | Task  | Status | Owner        |
+    | ----- | ------ | ------------ |
+    | Draft | open   | [Ann](ann)   |
+    | Print | done   | Bob          |
+    | Ship  | Open   |              |
+    _
+    # ann
+"};
+
+#[test]
+fn row_predicate_selects_tables_by_cell() {
+    assert_yaml_results(
+        TASKS,
+        indoc! {"
+            filter:
+              $content: { $row: { Status: done, owner: Bob } }
+            project: { key: $key }
+        "},
+        indoc! {"
+            - key: '1'
+        "},
+    );
+}
+
+#[test]
+fn row_predicate_requires_known_column() {
+    assert_yaml_results(
+        TASKS,
+        indoc! {"
+            filter:
+              $content: { $row: { Priority: high } }
+            project: { key: $key }
+        "},
+        "[]",
+    );
+}
+
+#[test]
+fn rows_project_table_records() {
+    assert_yaml_results(
+        TASKS,
+        indoc! {"
+            filter: { $key: '1' }
+            project: { tasks: $rows }
+        "},
+        indoc! {"
+            - tasks:
+                - Task: Draft
+                  Status: open
+                  Owner: '[Ann](ann)'
+                - Task: Print
+                  Status: done
+                  Owner: Bob
+                - Task: Ship
+                  Status: Open
+                  Owner: ''
+        "},
+    );
+}
+
+#[test]
+fn rows_keep_rows_passing_row_tests() {
+    assert_yaml_results(
+        TASKS,
+        indoc! {"
+            filter: { $key: '1' }
+            project:
+              open:
+                $rows: { $row: { Status: open, Task: { $matches: '^[DS]' } } }
+        "},
+        indoc! {"
+            - open:
+                - Task: Draft
+                  Status: open
+                  Owner: '[Ann](ann)'
+                - Task: Ship
+                  Status: Open
+                  Owner: ''
+        "},
+    );
+}
+
+#[test]
+fn rows_keep_rows_passing_nested_row_tests() {
+    assert_yaml_results(
+        TASKS,
+        indoc! {"
+            filter: { $key: '1' }
+            project:
+              picked:
+                $rows: { $or: [{ $row: { Task: draft } }, { $row: { Owner: Bob } }] }
+        "},
+        indoc! {"
+            - picked:
+                - Task: Draft
+                  Status: open
+                  Owner: '[Ann](ann)'
+                - Task: Print
+                  Status: done
+                  Owner: Bob
+        "},
+    );
+}
+
+#[test]
+fn row_rejects_nested_cell_values() {
+    assert_eq!(
+        parse_err(indoc! {"
+            filter:
+              $content: { $row: { Status: [open] } }
+        "}),
+        "'$row' column 'Status' expects a scalar, or a mapping with $text or $matches"
+    );
+}
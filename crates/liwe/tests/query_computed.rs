@@ -0,0 +1,212 @@
+use chrono::NaiveDate;
+use indoc::indoc;
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::model::Key;
+use liwe::query::block::parse_block_predicate;
+use liwe::query::{
+    execute, parse_operation, ComputedField, ComputedFields, EdgeDirection, FieldPath,
+    OperationKind, Outcome,
+};
+use liwe::schema::infer_schema;
+use liwe::state::from_indoc;
+use pretty_assertions::assert_eq;
+use serde_yaml::Mapping;
+
+const CORPUS: &str = indoc! {"
+    ---
+    created: 2026-01-01
+    due: 2026-02-01
+    ---
+    # Launch
+
+    - TODO draft post
+    - pick date
+    - TODO book venue
+
+    ## Summary
+
+    Short one.
+    _
+    ---
+    created: 2026-01-20
+    meta:
+      due: 2026-03-01
+    ---
+    # Retro
+
+    Notes about the launch in [Launch](1).
+    _
+    # Ideas
+
+    [Launch](1)
+"};
+
+fn fields() -> ComputedFields {
+    let open = serde_yaml::from_str("$item: { $text: TODO }").unwrap();
+    let summary = serde_yaml::from_str("$header: Summary").unwrap();
+    ComputedFields::new()
+        .field("words", ComputedField::Words)
+        .field(
+            "open_tasks",
+            ComputedField::Count(parse_block_predicate(&open, "blocks").unwrap()),
+        )
+        .field(
+            "summaries",
+            ComputedField::Count(parse_block_predicate(&summary, "blocks").unwrap()),
+        )
+        .field(
+            "backlinks",
+            ComputedField::Edges(EdgeDirection::ReferencedBy),
+        )
+        .field(
+            "age",
+            ComputedField::Days {
+                from: FieldPath::from_dotted("created"),
+                to: None,
+            },
+        )
+        .field(
+            "lead",
+            ComputedField::Days {
+                from: FieldPath::from_dotted("created"),
+                to: Some(FieldPath::from_dotted("due")),
+            },
+        )
+        .field(
+            "due_any",
+            ComputedField::Coalesce(vec![
+                FieldPath::from_dotted("due"),
+                FieldPath::from_dotted("meta.due"),
+            ]),
+        )
+        .token_counter(|text| text.len())
+        .today(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap())
+}
+
+fn graph() -> Graph {
+    let mut graph = Graph::import(&from_indoc(CORPUS), MarkdownOptions::default(), None);
+    graph.set_computed_fields(fields());
+    graph
+}
+
+fn run(yaml: &str) -> Vec<Mapping> {
+    let op = parse_operation(yaml, OperationKind::Find).expect("operation parses");
+    match execute(&op, &graph()).expect("query succeeds") {
+        Outcome::Find { matches, .. } => matches.into_iter().map(|m| m.document).collect(),
+        other => panic!("expected Find, got {:?}", other),
+    }
+}
+
+fn assert_yaml(yaml: &str, expected: &str) {
+    let expected: Vec<Mapping> = serde_yaml::from_str(expected).expect("expected parses");
+    assert_eq!(run(yaml), expected);
+}
+
+#[test]
+fn computed_fields_filter_and_project() {
+    assert_yaml(
+        indoc! {"
+            filter: { open_tasks: { $gte: 1 }, summaries: 1 }
+            project: { key: $key, open_tasks: 1, backlinks: 1 }
+        "},
+        indoc! {"
+            - key: '1'
+              open_tasks: 2
+              backlinks: 1
+        "},
+    );
+}
+
+#[test]
+fn computed_fields_sort() {
+    assert_yaml(
+        indoc! {"
+            project: { key: $key, words: 1 }
+            sort: { words: -1 }
+        "},
+        indoc! {"
+            - key: '1'
+              words: 17
+            - key: '2'
+              words: 8
+            - key: '3'
+              words: 3
+        "},
+    );
+}
+
+#[test]
+fn computed_days_and_coalesce() {
+    assert_yaml(
+        indoc! {"
+            project: { key: $key, age: 1, lead: 1, due_any: 1 }
+            sort: { age: 1 }
+        "},
+        indoc! {"
+            - key: '3'
+              age: null
+              lead: null
+              due_any: null
+            - key: '2'
+              age: 12
+              lead: null
+              due_any: 2026-03-01
+            - key: '1'
+              age: 31
+              lead: 31
+              due_any: 2026-02-01
+        "},
+    );
+}
+
+#[test]
+fn computed_fields_stay_out_of_default_projection_and_updates() {
+    let docs = run("filter: { $key: '2' }\n");
+    assert!(!docs[0].contains_key("words"), "{:?}", docs[0]);
+
+    let op = parse_operation(
+        "filter: { words: { $gt: 10 } }\nupdate: { $set: { status: busy } }\n",
+        OperationKind::Update,
+    )
+    .expect("operation parses");
+    match execute(&op, &graph()).expect("update succeeds") {
+        Outcome::Update { changes } => {
+            assert_eq!(changes.len(), 1);
+            assert!(changes[0].1.contains("status: busy"), "{}", changes[0].1);
+            assert!(!changes[0].1.contains("words"), "{}", changes[0].1);
+        }
+        other => panic!("expected Update, got {:?}", other),
+    }
+}
+
+#[test]
+fn tokens_use_registered_counter() {
+    let mut graph = Graph::import(
+        &from_indoc("# A\n\nabc\n"),
+        MarkdownOptions::default(),
+        None,
+    );
+    let uncounted = ComputedFields::new().field("tokens", ComputedField::Tokens);
+    graph.set_computed_fields(uncounted.clone());
+    assert!(graph
+        .computed_fields()
+        .values(&graph, &Key::name("1"))
+        .get("tokens")
+        .unwrap()
+        .is_null());
+
+    graph.set_computed_fields(uncounted.token_counter(|text| text.len()));
+    let values = graph.computed_fields().values(&graph, &Key::name("1"));
+    assert_eq!(values.get("tokens").unwrap().as_u64(), Some(9));
+}
+
+#[test]
+fn schema_inference_reports_computed_fields() {
+    let graph = graph();
+    let schema = infer_schema(&graph, &graph.keys());
+    let words = schema.iter().find(|f| f.name == "words").expect("words");
+    assert_eq!(words.coverage.count, 3);
+    let age = schema.iter().find(|f| f.name == "age").expect("age");
+    assert_eq!(age.coverage.count, 2);
+}
@@ -0,0 +1,133 @@
+use indoc::indoc;
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::query::{
+    evaluate_explained, explain_with_candidates, explain_with_scores, parse_filter_expression,
+    ClauseRole, CountOp, Operation, Outcome, QueryScores,
+};
+use liwe::state::from_indoc;
+
+const CORPUS: &str = indoc! {"
+    ---
+    status: draft
+    ---
+    # Alpha
+
+    TODO write intro
+    _
+    ---
+    status: draft
+    ---
+    # Beta
+
+    nothing here
+    _
+    ---
+    status: published
+    ---
+    # Gamma
+
+    See [Alpha](1) first.
+"};
+
+fn graph() -> Graph {
+    Graph::import(&from_indoc(CORPUS), MarkdownOptions::default(), None)
+}
+
+#[test]
+fn explain_orders_generators_before_predicates() {
+    let g = graph();
+    let filter = parse_filter_expression(indoc! {"
+        $and:
+          - status: draft
+          - $content: { $text: TODO }
+          - $referencedBy: { match: { $key: '3' } }
+    "})
+    .expect("filter parses");
+
+    let (keys, trace) = evaluate_explained(&filter, &g);
+
+    assert_eq!(
+        keys.iter().map(|k| k.to_string()).collect::<Vec<_>>(),
+        ["1"]
+    );
+    assert_eq!(trace.clause, "$and");
+    assert_eq!(trace.role, ClauseRole::Root);
+    assert_eq!(trace.input, 3);
+    assert_eq!(trace.output, 1);
+
+    let roles: Vec<(ClauseRole, &str)> = trace
+        .children
+        .iter()
+        .map(|c| (c.role, c.clause.as_str()))
+        .collect();
+    assert_eq!(
+        roles,
+        vec![
+            (ClauseRole::Generator, "$referencedBy (min 1, max 1)"),
+            (ClauseRole::Predicate, "status: {$eq: \"draft\"}"),
+            (ClauseRole::Predicate, "$content"),
+        ]
+    );
+
+    let anchor = &trace.children[0].children[0];
+    assert_eq!(anchor.role, ClauseRole::Anchor);
+    assert_eq!(anchor.clause, "$key: {$eq: 3}");
+    assert_eq!(anchor.output, 1);
+}
+
+#[test]
+fn explain_counts_block_evaluations_on_surviving_candidates() {
+    let g = graph();
+    let filter = parse_filter_expression(indoc! {"
+        $and:
+          - status: draft
+          - $content: { $text: TODO }
+    "})
+    .expect("filter parses");
+
+    let (_, trace) = evaluate_explained(&filter, &g);
+
+    let content = &trace.children[1];
+    assert_eq!(content.input, 2);
+    assert_eq!(content.output, 1);
+    assert_eq!(content.block_evaluations, 2);
+    assert_eq!(trace.total_block_evaluations(), 2);
+}
+
+#[test]
+fn explain_with_scores_reports_totals() {
+    let g = graph();
+    let filter = parse_filter_expression("status: draft").expect("filter parses");
+    let op = Operation::Count(CountOp::new().filter(filter));
+
+    let (outcome, explain) =
+        explain_with_scores(&op, &g, &QueryScores::default()).expect("query succeeds");
+
+    assert!(matches!(outcome, Outcome::Count(2)));
+    assert_eq!(explain.candidates, 2);
+    assert_eq!(explain.matched, 2);
+    assert_eq!(explain.block_evaluations, 0);
+    assert!(explain.search.is_none());
+    assert_eq!(
+        explain.filter.expect("filter trace").clause,
+        "status: {$eq: \"draft\"}"
+    );
+}
+
+#[test]
+fn explain_with_candidates_reuses_the_callers_filter_pass() {
+    let g = graph();
+    let filter = parse_filter_expression("$content: { $text: TODO }").expect("filter parses");
+    let (keys, trace) = evaluate_explained(&filter, &g);
+    let op = Operation::Count(CountOp::new().filter(filter));
+
+    let (outcome, explain) =
+        explain_with_candidates(&op, &g, &QueryScores::default(), keys, Some(trace.clone()))
+            .expect("query succeeds");
+
+    assert!(matches!(outcome, Outcome::Count(1)));
+    assert_eq!(explain.candidates, 1);
+    assert_eq!(explain.block_evaluations, 3);
+    assert_eq!(explain.filter, Some(trace));
+}
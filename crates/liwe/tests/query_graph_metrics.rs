@@ -0,0 +1,132 @@
+use indoc::indoc;
+use liwe::graph::analytics::{GraphAnalytics, GraphMetric, MetricCache};
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::model::Key;
+use liwe::query::{execute, parse_operation, OperationKind, Outcome};
+use liwe::state::from_indoc;
+use pretty_assertions::assert_eq;
+
+const CORPUS: &str = indoc! {"
+    # Hub
+
+    [Bridge](3)
+    _
+    # Leaf
+
+    Points at [Bridge](3) inline.
+    _
+    # Bridge
+
+    [Hub](1)
+    _
+    # Island
+    _
+    # Left
+
+    See [Right](6) too.
+    _
+    # Right
+
+    See [Left](5) too.
+"};
+
+fn graph() -> Graph {
+    Graph::import(&from_indoc(CORPUS), MarkdownOptions::default(), None)
+}
+
+fn keys(group: &[Key]) -> Vec<String> {
+    group.iter().map(|k| k.to_string()).collect()
+}
+
+fn find(yaml: &str) -> Vec<String> {
+    let op = parse_operation(yaml, OperationKind::Find).expect("operation parses");
+    match execute(&op, &graph()).expect("query succeeds") {
+        Outcome::Find { matches, .. } => matches.into_iter().map(|m| m.key.to_string()).collect(),
+        other => panic!("expected Find, got {:?}", other),
+    }
+}
+
+#[test]
+fn components_and_communities_ordered_by_size() {
+    let analytics = GraphAnalytics::compute(&graph());
+    let components: Vec<Vec<String>> = analytics.components().iter().map(|g| keys(g)).collect();
+    assert_eq!(
+        components,
+        vec![vec!["1", "2", "3"], vec!["5", "6"], vec!["4"]]
+    );
+    let communities: Vec<Vec<String>> = analytics.communities().iter().map(|g| keys(g)).collect();
+    assert_eq!(communities, components);
+    assert_eq!(analytics.edge_count(), 5);
+}
+
+#[test]
+fn inclusion_cycles_ignore_references() {
+    let analytics = GraphAnalytics::compute(&graph());
+    let cycles: Vec<Vec<String>> = analytics
+        .inclusion_cycles()
+        .iter()
+        .map(|g| keys(g))
+        .collect();
+    assert_eq!(cycles, vec![vec!["1", "3"]]);
+}
+
+#[test]
+fn betweenness_marks_bridges() {
+    let analytics = GraphAnalytics::compute(&graph());
+    assert_eq!(analytics.betweenness(&Key::name("3")), Some(0.1));
+    assert_eq!(analytics.betweenness(&Key::name("1")), Some(0.0));
+    assert_eq!(analytics.betweenness(&Key::name("missing")), None);
+}
+
+#[test]
+fn pagerank_sums_to_one() {
+    let analytics = GraphAnalytics::compute(&graph());
+    let total: f64 = analytics
+        .keys()
+        .iter()
+        .filter_map(|k| analytics.pagerank(k))
+        .sum();
+    assert!((total - 1.0).abs() < 1e-4, "{}", total);
+    assert!(analytics.pagerank(&Key::name("3")) > analytics.pagerank(&Key::name("2")));
+}
+
+#[test]
+fn metric_filters() {
+    assert_eq!(find("filter: { $component: 0 }\n"), vec!["1", "2", "3"]);
+    assert_eq!(find("filter: { $betweenness: { $gt: 0 } }\n"), vec!["3"]);
+    assert_eq!(
+        find("filter: { $community: { $gte: 1 }, $key: { $ne: '4' } }\n"),
+        vec!["5", "6"]
+    );
+}
+
+#[test]
+fn metric_sort() {
+    let ranked = find("sort: { $pagerank: -1 }\nlimit: 2\n");
+    assert_eq!(ranked, vec!["3", "1"]);
+}
+
+#[test]
+fn metric_filter_and_sort_in_one_query() {
+    let ranked =
+        find("filter: { $or: [{ $component: 0 }, { $community: 1 }] }\nsort: { $pagerank: -1 }\n");
+    assert_eq!(ranked, vec!["3", "1", "5", "6", "2"]);
+}
+
+#[test]
+fn metric_cache_matches_analytics() {
+    let graph = graph();
+    let analytics = GraphAnalytics::compute(&graph);
+    let metrics = MetricCache::new(&graph);
+    for key in analytics.keys() {
+        for metric in [GraphMetric::Betweenness, GraphMetric::Community] {
+            assert_eq!(metrics.value(key, metric), analytics.value(key, metric));
+        }
+    }
+}
+
+#[test]
+fn unknown_metric_is_rejected() {
+    assert!(parse_operation("filter: { $rank: 1 }\n", OperationKind::Find).is_err());
+}
@@ -0,0 +1,219 @@
+use indoc::indoc;
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::query::{execute, parse_operation, OperationKind, Outcome};
+use liwe::state::from_indoc;
+use pretty_assertions::assert_eq;
+use serde_yaml::Mapping;
+
+const CORPUS: &str = indoc! {"
+    # Architecture
+
+    Background on [Storage](3).
+
+    ## Decisions
+
+    We adopted [the storage layer](3) and [[2]].
+    _
+    # Roadmap
+
+    Next up: [Storage](3).
+    _
+    # Storage
+
+    Plain notes.
+    _
+    # Meeting
+
+    ## Decisions
+
+    Revisit [Roadmap](2).
+"};
+
+fn run(yaml: &str) -> Vec<Mapping> {
+    let graph = Graph::import(&from_indoc(CORPUS), MarkdownOptions::default(), None);
+    let op = parse_operation(yaml, OperationKind::Find).expect("operation parses");
+    match execute(&op, &graph).expect("query succeeds") {
+        Outcome::Find { matches, .. } => matches.into_iter().map(|m| m.document).collect(),
+        other => panic!("expected Find, got {:?}", other),
+    }
+}
+
+fn keys(yaml: &str) -> Vec<String> {
+    run(yaml)
+        .into_iter()
+        .map(|m| m["key"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn parse_err(yaml: &str) -> String {
+    parse_operation(yaml, OperationKind::Find)
+        .expect_err("expected parse error")
+        .to_string()
+}
+
+#[test]
+fn references_without_link_predicate_follow_every_link() {
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references: { match: { $key: '3' } }
+            project: { key: $key }
+            sort: { key: 1 }
+        "}),
+        ["1", "2"]
+    );
+}
+
+#[test]
+fn link_within_section_restricts_source_block() {
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references:
+                match: { $key: '3' }
+                link: { within: Decisions }
+            project: { key: $key }
+        "}),
+        ["1"]
+    );
+}
+
+#[test]
+fn link_type_selects_wiki_links() {
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references:
+                match: { $key: '2' }
+                link: { type: wikiLink }
+            project: { key: $key }
+        "}),
+        ["1"]
+    );
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $referencedBy:
+                match: { $key: '1' }
+                link: { type: [regular, wikiLinkPiped] }
+            project: { key: $key }
+        "}),
+        ["3"]
+    );
+}
+
+#[test]
+fn link_title_differs_compares_text_with_target_title() {
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references:
+                match: { $key: '3' }
+                link: { titleDiffers: true }
+            project: { key: $key }
+        "}),
+        ["1"]
+    );
+}
+
+#[test]
+fn link_text_matches_substring_and_regex() {
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references:
+                match: { $key: '3' }
+                link: { text: layer }
+            project: { key: $key }
+        "}),
+        ["1"]
+    );
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references:
+                match: { $key: '3' }
+                link: { text: { $matches: '^Storage$' } }
+            project: { key: $key }
+            sort: { key: 1 }
+        "}),
+        ["1", "2"]
+    );
+}
+
+#[test]
+fn link_predicate_applies_to_size_counts() {
+    assert_eq!(
+        keys(indoc! {"
+            filter:
+              $references:
+                link: { within: Decisions }
+                $size: { $gte: 2 }
+            project: { key: $key }
+        "}),
+        ["1"]
+    );
+}
+
+#[test]
+fn projection_emits_link_attributes() {
+    let actual = run(indoc! {"
+        filter: { $key: '1' }
+        project:
+          links: { $references: {} }
+    "});
+    let expected: Vec<Mapping> = serde_yaml::from_str(indoc! {"
+        - links:
+            - key: '3'
+              title: Storage
+              text: Storage
+              linkType: regular
+              sectionPath: []
+            - key: '3'
+              title: Storage
+              text: the storage layer
+              linkType: regular
+              sectionPath: [Decisions]
+            - key: '2'
+              title: Roadmap
+              text: Roadmap
+              linkType: wikiLink
+              sectionPath: [Decisions]
+    "})
+    .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn projection_filters_inbound_links() {
+    let actual = run(indoc! {"
+        filter: { $key: '3' }
+        project:
+          mentions: { $referencedBy: { within: Decisions } }
+    "});
+    let expected: Vec<Mapping> = serde_yaml::from_str(indoc! {"
+        - mentions:
+            - key: '1'
+              title: Architecture
+              text: the storage layer
+              linkType: regular
+              sectionPath: [Decisions]
+    "})
+    .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn link_rejected_on_inclusion_operators() {
+    let err = parse_err("filter:\n  $includes: { match: { $key: '1' }, link: {} }\n");
+    assert!(err.contains("'link'"), "{}", err);
+}
+
+#[test]
+fn link_unknown_key_and_type_rejected() {
+    let err = parse_err("filter:\n  $references: { link: { kind: wikiLink } }\n");
+    assert!(err.contains("unknown key 'kind'"), "{}", err);
+    let err = parse_err("filter:\n  $references: { link: { type: markdown } }\n");
+    assert!(err.contains("unknown link type 'markdown'"), "{}", err);
+}
@@ -0,0 +1,199 @@
+use indoc::indoc;
+use liwe::graph::Graph;
+use liwe::model::config::MarkdownOptions;
+use liwe::query::{execute, parse_operation, OperationKind, Outcome};
+use liwe::state::from_indoc;
+use pretty_assertions::assert_eq;
+use serde_yaml::Mapping;
+
+const CORPUS: &str = indoc! {"
+    ---
+    type: area
+    owner: dana
+    ---
+    # Engineering
+
+    [Apollo](2)
+    _
+    ---
+    type: project
+    owner: ann
+    deadline: 2026-03-01
+    area: '1'
+    parent: '1'
+    ---
+    # Apollo
+
+    [Write spec](3)
+
+    [Ship it](4)
+    _
+    ---
+    type: task
+    project: '2'
+    parent: '2'
+    ---
+    # Write spec
+
+    Depends on [Ship it](4).
+    _
+    ---
+    type: task
+    project: '2'
+    ---
+    # Ship it
+"};
+
+fn run(yaml: &str) -> Vec<Mapping> {
+    let graph = Graph::import(&from_indoc(CORPUS), MarkdownOptions::default(), None);
+    let op = parse_operation(yaml, OperationKind::Find).expect("operation parses");
+    match execute(&op, &graph).expect("query succeeds") {
+        Outcome::Find { matches, .. } => matches.into_iter().map(|m| m.document).collect(),
+        other => panic!("expected Find, got {:?}", other),
+    }
+}
+
+fn assert_yaml(yaml: &str, expected: &str) {
+    let expected: Vec<Mapping> = serde_yaml::from_str(expected).expect("expected parses");
+    assert_eq!(run(yaml), expected);
+}
+
+fn parse_err(yaml: &str) -> String {
+    parse_operation(yaml, OperationKind::Find)
+        .expect_err("expected parse error")
+        .to_string()
+}
+
+#[test]
+fn lookup_edge_pulls_related_fields() {
+    assert_yaml(
+        indoc! {"
+            filter: { type: task }
+            project:
+              title: $title
+              parent:
+                $lookup:
+                  from: $includedBy
+                  project: { title: $title, owner: 1, deadline: 1 }
+        "},
+        indoc! {"
+            - title: Write spec
+              parent:
+                - title: Apollo
+                  owner: ann
+                  deadline: 2026-03-01
+            - title: Ship it
+              parent:
+                - title: Apollo
+                  owner: ann
+                  deadline: 2026-03-01
+        "},
+    );
+}
+
+#[test]
+fn lookup_defaults_to_key_and_title() {
+    assert_yaml(
+        indoc! {"
+            filter: { $key: '3' }
+            project:
+              deps: { $lookup: { from: $references } }
+        "},
+        indoc! {"
+            - deps:
+                - key: '4'
+                  title: Ship it
+        "},
+    );
+}
+
+#[test]
+fn lookup_depth_and_match_filter_related_side() {
+    assert_yaml(
+        indoc! {"
+            filter: { $key: '3' }
+            project:
+              areas:
+                $lookup:
+                  from: $includedBy
+                  maxDepth: 0
+                  match: { type: area }
+                  project: { key: $key, owner: 1 }
+        "},
+        indoc! {"
+            - areas:
+                - key: '1'
+                  owner: dana
+        "},
+    );
+}
+
+#[test]
+fn lookup_follows_frontmatter_key_field() {
+    assert_yaml(
+        indoc! {"
+            filter: { $key: '4' }
+            project:
+              chain:
+                $lookup:
+                  from: project
+                  project: { key: $key, owner: 1 }
+        "},
+        indoc! {"
+            - chain:
+                - key: '2'
+                  owner: ann
+        "},
+    );
+}
+
+#[test]
+fn lookup_field_chain_respects_depth() {
+    let keys = |depth: u32| -> Vec<String> {
+        let yaml = format!(
+            "filter: {{ $key: '3' }}\nproject:\n  up: {{ $lookup: {{ from: parent, maxDepth: {} }} }}\n",
+            depth
+        );
+        run(&yaml)[0]["up"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|m| m["key"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(keys(1), ["2"]);
+    assert_eq!(keys(2), ["2", "1"]);
+    assert_eq!(keys(0), ["2", "1"]);
+}
+
+#[test]
+fn lookup_nests() {
+    assert_yaml(
+        indoc! {"
+            filter: { $key: '3' }
+            project:
+              project:
+                $lookup:
+                  from: project
+                  project:
+                    title: $title
+                    area: { $lookup: { from: area, project: { owner: 1 } } }
+        "},
+        indoc! {"
+            - project:
+                - title: Apollo
+                  area:
+                    - owner: dana
+        "},
+    );
+}
+
+#[test]
+fn lookup_rejects_wrong_bound_family_and_unknown_source() {
+    let err = parse_err("project:\n  p: { $lookup: { from: $includedBy, maxDistance: 2 } }\n");
+    assert!(err.contains("'maxDistance'"), "{}", err);
+    let err = parse_err("project:\n  p: { $lookup: { from: $children } }\n");
+    assert!(err.contains("$children"), "{}", err);
+    let err = parse_err("project:\n  p: { $lookup: { match: {} } }\n");
+    assert!(err.contains("from"), "{}", err);
+}